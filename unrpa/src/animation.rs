@@ -0,0 +1,134 @@
+use std::io::Cursor;
+use std::time::Duration;
+use image::codecs::gif::GifDecoder;
+use image::codecs::png::PngDecoder;
+use image::codecs::webp::WebPDecoder;
+use image::{AnimationDecoder, Frames};
+use tracing::warn;
+use crate::rpa::RpaEditor;
+
+/// Frames kept per animation, so a long GIF can't exhaust memory.
+const MAX_ANIMATION_FRAMES: usize = 600;
+
+/// Decoded RGBA kept per animation; large frames hit this long before the frame cap.
+const MAX_ANIMATION_BYTES: usize = 256 * 1024 * 1024;
+
+/// Browsers play frames with a (near) zero delay at this rate.
+const DEFAULT_FRAME_DELAY: Duration = Duration::from_millis(100);
+
+pub struct AnimationFrame {
+    pub image: egui::ColorImage,
+    pub delay: Duration,
+}
+
+fn animation_frames<'a>(filename: &str, data: &'a [u8]) -> Option<Frames<'a>> {
+    let lower = filename.to_lowercase();
+    let cursor = Cursor::new(data);
+    if lower.ends_with(".gif") {
+        GifDecoder::new(cursor).ok().map(|decoder| decoder.into_frames())
+    } else if lower.ends_with(".webp") {
+        let decoder = WebPDecoder::new(cursor).ok()?;
+        decoder.has_animation().then(|| decoder.into_frames())
+    } else if lower.ends_with(".png") || lower.ends_with(".apng") {
+        let decoder = PngDecoder::new(cursor).ok()?;
+        if !decoder.is_apng().ok()? {
+            return None;
+        }
+        decoder.apng().ok().map(|decoder| decoder.into_frames())
+    } else {
+        None
+    }
+}
+
+/// Decodes an animated GIF, APNG or WebP; `None` for still images.
+pub fn decode_animation(filename: &str, data: &[u8]) -> Option<Vec<AnimationFrame>> {
+    let mut frames = Vec::new();
+    let mut total_bytes = 0usize;
+    for frame in animation_frames(filename, data)?.take(MAX_ANIMATION_FRAMES) {
+        let frame = match frame {
+            Ok(frame) => frame,
+            Err(e) => {
+                warn!("⚠️ Animation decoding stopped at frame {}: {}", frames.len(), e);
+                break;
+            }
+        };
+        let delay = Duration::from(frame.delay());
+        let delay = if delay < Duration::from_millis(20) { DEFAULT_FRAME_DELAY } else { delay };
+        let buffer = frame.into_buffer();
+        let size = [buffer.width() as usize, buffer.height() as usize];
+        total_bytes = total_bytes.saturating_add(size[0] * size[1] * 4);
+        if total_bytes > MAX_ANIMATION_BYTES {
+            warn!("⚠️ Animation cut at frame {}: over {} MiB decoded", frames.len(), MAX_ANIMATION_BYTES >> 20);
+            break;
+        }
+        frames.push(AnimationFrame {
+            image: egui::ColorImage::from_rgba_unmultiplied(size, &buffer),
+            delay,
+        });
+    }
+    (frames.len() > 1).then_some(frames)
+}
+
+impl RpaEditor {
+    fn show_animation_frame(&mut self, index: usize) {
+        if let Some(frame) = self.preview_animation.get(index) {
+            self.animation_frame = index;
+            self.animation_elapsed = Duration::ZERO;
+            self.set_preview_image(Some(frame.image.clone()));
+        }
+    }
+
+    /// Moves the playing animation forward by `dt`, skipping frames if the UI lags.
+    pub(crate) fn advance_animation(&mut self, dt: Duration) {
+        if !self.animation_playing || self.preview_animation.is_empty() {
+            return;
+        }
+        self.animation_elapsed += dt;
+        let mut index = self.animation_frame;
+        let mut elapsed = self.animation_elapsed;
+        while elapsed >= self.preview_animation[index].delay {
+            elapsed -= self.preview_animation[index].delay;
+            index = (index + 1) % self.preview_animation.len();
+        }
+        if index != self.animation_frame {
+            self.show_animation_frame(index);
+        }
+        self.animation_elapsed = elapsed;
+    }
+
+    pub(crate) fn step_animation(&mut self, forward: bool) {
+        let count = self.preview_animation.len();
+        if count == 0 {
+            return;
+        }
+        self.animation_playing = false;
+        let index = if forward {
+            (self.animation_frame + 1) % count
+        } else {
+            (self.animation_frame + count - 1) % count
+        };
+        self.show_animation_frame(index);
+    }
+
+    pub(crate) fn show_animation_controls(&mut self, ui: &mut egui::Ui) {
+        if self.preview_animation.is_empty() {
+            return;
+        }
+        if ui.button("⏮").on_hover_text("Previous frame").clicked() {
+            self.step_animation(false);
+        }
+        let label = if self.animation_playing { "⏸" } else { "▶" };
+        if ui.button(label).clicked() {
+            self.animation_playing = !self.animation_playing;
+        }
+        if ui.button("⏭").on_hover_text("Next frame").clicked() {
+            self.step_animation(true);
+        }
+        ui.label(format!(
+            "Frame {}/{} ({} ms)",
+            self.animation_frame + 1,
+            self.preview_animation.len(),
+            self.preview_animation[self.animation_frame].delay.as_millis()
+        ));
+    }
+}
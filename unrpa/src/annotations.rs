@@ -0,0 +1,87 @@
+use crate::rpa::RpaEditor;
+
+impl RpaEditor {
+    pub(crate) fn is_bookmarked(&self, filename: &str) -> bool {
+        self.bookmarks.contains(filename)
+    }
+
+    pub(crate) fn toggle_bookmark(&mut self, filename: &str) {
+        if !self.bookmarks.remove(filename) {
+            self.bookmarks.insert(filename.to_string());
+        }
+    }
+
+    /// Adds `tag` to an entry unless it's empty or already there.
+    pub(crate) fn add_entry_tag(&mut self, filename: &str, tag: &str) {
+        let tag = tag.trim();
+        if tag.is_empty() {
+            return;
+        }
+        let tags = self.entry_tags.entry(filename.to_string()).or_default();
+        if !tags.iter().any(|existing| existing == tag) {
+            tags.push(tag.to_string());
+        }
+    }
+
+    pub(crate) fn remove_entry_tag(&mut self, filename: &str, tag: &str) {
+        if let Some(tags) = self.entry_tags.get_mut(filename) {
+            tags.retain(|existing| existing != tag);
+            if tags.is_empty() {
+                self.entry_tags.remove(filename);
+            }
+        }
+    }
+
+    /// Hover text for the file list: the entry's tags and note, if it has any.
+    pub(crate) fn entry_annotation_summary(&self, filename: &str) -> Option<String> {
+        let tags = self.entry_tags.get(filename).map(|tags| format!("🏷 {}", tags.join(", ")));
+        let note = self.entry_notes.get(filename).cloned();
+        match (tags, note) {
+            (None, None) => None,
+            (Some(text), None) | (None, Some(text)) => Some(text),
+            (Some(tags), Some(note)) => Some(format!("{}\n{}", tags, note)),
+        }
+    }
+
+    /// Bookmark toggle, tags and note of the selected entry. They belong to the session
+    /// and travel with exported workspaces; the archive itself is untouched.
+    pub(crate) fn show_entry_annotations(&mut self, ui: &mut egui::Ui, filename: &str) {
+        ui.horizontal_wrapped(|ui| {
+            let bookmarked = self.is_bookmarked(filename);
+            if ui
+                .selectable_label(bookmarked, if bookmarked { "⭐ Bookmarked" } else { "☆ Bookmark" })
+                .clicked()
+            {
+                self.toggle_bookmark(filename);
+            }
+            ui.separator();
+
+            let mut removed = None;
+            for tag in self.entry_tags.get(filename).into_iter().flatten() {
+                if ui.small_button(format!("🏷 {} ✖", tag)).on_hover_text("Remove tag").clicked() {
+                    removed = Some(tag.clone());
+                }
+            }
+            if let Some(tag) = removed {
+                self.remove_entry_tag(filename, &tag);
+            }
+            let response = ui.add(egui::TextEdit::singleline(&mut self.new_tag).desired_width(100.0).hint_text("Add tag"));
+            if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                let tag = std::mem::take(&mut self.new_tag);
+                self.add_entry_tag(filename, &tag);
+            }
+        });
+
+        let mut note = self.entry_notes.get(filename).cloned().unwrap_or_default();
+        if ui
+            .add(egui::TextEdit::multiline(&mut note).desired_rows(2).desired_width(f32::INFINITY).hint_text("Note"))
+            .changed()
+        {
+            if note.trim().is_empty() {
+                self.entry_notes.remove(filename);
+            } else {
+                self.entry_notes.insert(filename.to_string(), note);
+            }
+        }
+    }
+}
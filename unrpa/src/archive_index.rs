@@ -0,0 +1,349 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use flate2::read::ZlibDecoder;
+use serde_pickle::{DeOptions, Value};
+use tracing::warn;
+use crate::archive_reader::map_archive;
+use crate::header::{HeaderFormat, ParsedHeader};
+use crate::rpa::{RpaFileEntry, RpaSegment};
+
+/// Header and entries of an archive on disk.
+pub struct ArchiveIndex {
+    pub header: ParsedHeader,
+    pub header_line: String,
+    pub entries: HashMap<String, RpaFileEntry>,
+}
+
+fn parse_index_pickle(data: &[u8], key: u32) -> anyhow::Result<HashMap<String, RpaFileEntry>> {
+    let value: Value = serde_pickle::value_from_slice(data, DeOptions::new().decode_strings())?;
+
+    let mut indexes = HashMap::new();
+
+    if let Value::Dict(dict) = value {
+        for (name, val) in dict {
+            let filename = name.to_string().replace("\"", "");
+
+            if let Value::List(list) = val {
+                let segments: Option<Vec<RpaSegment>> =
+                    list.iter().map(|item| parse_index_segment(item, key)).collect();
+
+                match segments {
+                    Some(segments) if !segments.is_empty() => {
+                        indexes.insert(filename, RpaFileEntry::from_segments(segments));
+                    }
+                    _ => warn!("⚠️ Unrecognized index entry: {filename}"),
+                }
+            }
+        }
+
+        Ok(indexes)
+    } else {
+        Err(anyhow::anyhow!("Pickle root is not a dict"))
+    }
+}
+
+fn parse_index_segment(item: &Value, key: u32) -> Option<RpaSegment> {
+    let (Value::Tuple(tuple) | Value::List(tuple)) = item else {
+        return None;
+    };
+
+    let (offset, length) = match (tuple.first()?, tuple.get(1)?) {
+        (Value::I64(offset), Value::I64(length)) => (*offset as u64, *length as u64),
+        _ => return None,
+    };
+
+    let prefix = match tuple.get(2) {
+        None => Vec::new(),
+        Some(Value::Bytes(prefix)) => prefix.clone(),
+        // Python 2 archives store the prefix as a str, decoded as latin-1 by Ren'Py.
+        Some(Value::String(prefix)) => prefix.chars().map(|c| c as u8).collect(),
+        Some(_) => return None,
+    };
+
+    Some(RpaSegment {
+        offset: offset ^ key as u64,
+        length: length ^ key as u64,
+        prefix,
+    })
+}
+
+/// Reads the header and index of the archive at `path`, without opening it in an editor.
+pub fn read_archive_index(path: &str, use_mmap: bool) -> anyhow::Result<ArchiveIndex> {
+    let mut file = File::open(path)?;
+    let mut line = Vec::new();
+    let mut byte = [0u8; 1];
+    while file.read_exact(&mut byte).is_ok() && byte[0] != b'\n' {
+        line.push(byte[0]);
+    }
+
+    let header_line = String::from_utf8_lossy(&line).to_string();
+    let header = HeaderFormat::parse(&header_line)?;
+    let offset = header.offset;
+
+    let mut decompressed = Vec::new();
+    if use_mmap {
+        // Decoded straight from the mapping, without copying the compressed index first.
+        let map = map_archive(&file)?;
+        let compressed_data = map
+            .get(offset as usize..)
+            .ok_or_else(|| anyhow::anyhow!("Index offset 0x{:x} is past the end of the file", offset))?;
+        ZlibDecoder::new(compressed_data).read_to_end(&mut decompressed)?;
+    } else {
+        file.seek(SeekFrom::Start(offset))?;
+        let mut compressed_data = Vec::new();
+        file.read_to_end(&mut compressed_data)?;
+        ZlibDecoder::new(&compressed_data[..]).read_to_end(&mut decompressed)?;
+    }
+
+    let entries = match parse_index_pickle(&decompressed, header.key) {
+        Ok(entries) => entries,
+        Err(e) => {
+            warn!("⚠️ Erreur pickle: {e}, on tente l'extraction heuristique...");
+            parse_binary_dict(&decompressed, header.key)?
+        }
+    };
+    Ok(ArchiveIndex {
+        header,
+        header_line,
+        entries,
+    })
+}
+
+fn parse_binary_dict(data: &[u8], key: u32) -> anyhow::Result<HashMap<String, RpaFileEntry>> {
+    let mut indexes = HashMap::new();
+    let mut pos = 0;
+
+    while pos < data.len() {
+        if let Some((filename, filename_end)) = extract_filename_at_pos(data, pos) {
+            if let Some(entry) = find_entry_data_after_filename(data, filename_end, key) {
+                indexes.insert(filename, entry);
+                pos = filename_end + 50;
+            } else {
+                pos = filename_end;
+            }
+        } else {
+            pos += 1;
+        }
+    }
+
+    Ok(indexes)
+}
+
+fn extract_filename_at_pos(data: &[u8], start_pos: usize) -> Option<(String, usize)> {
+    let mut pos = start_pos;
+
+    while pos < data.len() {
+        if data[pos].is_ascii_graphic() || data[pos] == b'/' {
+            break;
+        }
+        pos += 1;
+    }
+
+    if pos >= data.len() {
+        return None;
+    }
+
+    let filename_start = pos;
+
+    let is_valid_char = |c: u8| {
+        c.is_ascii() && (c as char).is_ascii_graphic() && !"\"\\:*?<>|".contains(c as char)
+    };
+
+    while pos < data.len() && is_valid_char(data[pos]) {
+        pos += 1;
+    }
+
+    let slice = &data[filename_start..pos];
+
+    if let Ok(filename) = std::str::from_utf8(slice)
+        && is_valid_filename(filename)
+    {
+        return Some((filename.to_string(), pos));
+    }
+
+    None
+}
+
+fn find_entry_data_after_filename(data: &[u8], start_pos: usize, key: u32) -> Option<RpaFileEntry> {
+    let search_end = std::cmp::min(start_pos + 100, data.len());
+
+    for pos in start_pos..search_end {
+        if pos + 10 < data.len()
+            && data[pos] == b'J'
+            && let Some((offset, length, prefix)) = extract_j_values_at(data, pos, key)
+            && is_reasonable_entry(offset, length)
+        {
+            return Some(RpaFileEntry::from_segments(vec![RpaSegment {
+                offset,
+                length,
+                prefix,
+            }]));
+        }
+    }
+
+    None
+}
+
+fn extract_j_values_at(data: &[u8], pos: usize, key: u32) -> Option<(u64, u64, Vec<u8>)> {
+    if pos + 9 < data.len() && data[pos] == b'J' {
+        let val1_bytes = [data[pos + 1], data[pos + 2], data[pos + 3], data[pos + 4]];
+        let val1 = u32::from_le_bytes(val1_bytes);
+
+        for next_pos in (pos + 5)..(pos + 15) {
+            if next_pos + 4 < data.len() && data[next_pos] == b'J' {
+                let val2_bytes = [
+                    data[next_pos + 1],
+                    data[next_pos + 2],
+                    data[next_pos + 3],
+                    data[next_pos + 4],
+                ];
+                let val2 = u32::from_le_bytes(val2_bytes);
+
+                let offset = (val1 ^ key) as u64;
+                let length = (val2 ^ key) as u64;
+
+                if is_reasonable_entry(offset, length) {
+                    return Some((offset, length, Vec::new()));
+                }
+            }
+        }
+    }
+
+    None
+}
+
+fn is_valid_filename(filename: &str) -> bool {
+    if filename.len() < 2 || filename.len() > 200 {
+        return false;
+    }
+
+    let extensions = [
+        ".png", ".jpg", ".jpeg", ".webp", ".webm", ".avi", ".mp4", ".mov", ".ogg", ".wav",
+        ".mp3", ".flac", ".rpy", ".rpyc",
+    ];
+
+    extensions.iter().any(|&ext| filename.ends_with(ext))
+}
+
+fn is_reasonable_entry(offset: u64, length: u64) -> bool {
+    offset > 50
+        && offset < 2_000_000_000
+        && length > 0
+        && length < 500_000_000
+        && offset + length < 2_000_000_000
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_pickle::HashableValue;
+
+    fn index_pickle(entries: Vec<(&str, Vec<Value>)>) -> Vec<u8> {
+        let dict = entries
+            .into_iter()
+            .map(|(name, segments)| (HashableValue::String(name.to_string()), Value::List(segments)))
+            .collect();
+        serde_pickle::value_to_vec(&Value::Dict(dict), Default::default()).unwrap()
+    }
+
+    fn segment(fields: Vec<Value>) -> Value {
+        Value::Tuple(fields)
+    }
+
+    #[test]
+    fn two_tuple() {
+        let data = index_pickle(vec![("a.png", vec![segment(vec![Value::I64(10), Value::I64(5)])])]);
+        let entries = parse_index_pickle(&data, 0).unwrap();
+        let entry = &entries["a.png"];
+        assert_eq!(entry.length, 5);
+        assert_eq!(entry.segments.len(), 1);
+        assert_eq!((entry.segments[0].offset, entry.segments[0].length), (10, 5));
+        assert!(entry.segments[0].prefix.is_empty());
+    }
+
+    #[test]
+    fn three_tuple_with_prefix() {
+        let key = 0x42;
+        let data = index_pickle(vec![
+            (
+                "a.png",
+                vec![segment(vec![Value::I64(100 ^ key), Value::I64(8 ^ key), Value::Bytes(b"abc".to_vec())])],
+            ),
+            (
+                "b.png",
+                vec![segment(vec![Value::I64(200 ^ key), Value::I64(4 ^ key), Value::String("\u{e9}".to_string())])],
+            ),
+        ]);
+        let entries = parse_index_pickle(&data, key as u32).unwrap();
+        let a = &entries["a.png"].segments[0];
+        assert_eq!((a.offset, a.length, a.prefix.as_slice()), (100, 8, &b"abc"[..]));
+        assert_eq!(entries["a.png"].length, 8);
+        // Python 2 str prefixes are latin-1.
+        assert_eq!(entries["b.png"].segments[0].prefix, vec![0xe9]);
+    }
+
+    #[test]
+    fn multi_segment_entry() {
+        let data = index_pickle(vec![(
+            "a.png",
+            vec![
+                segment(vec![Value::I64(10), Value::I64(4)]),
+                segment(vec![Value::I64(50), Value::I64(6), Value::Bytes(b"xy".to_vec())]),
+            ],
+        )]);
+        let entry = &parse_index_pickle(&data, 0).unwrap()["a.png"];
+        assert_eq!(entry.segments.len(), 2);
+        assert_eq!((entry.segments[1].offset, entry.segments[1].length), (50, 6));
+        assert_eq!(entry.length, 10);
+    }
+
+    #[test]
+    fn malformed_tuples_are_skipped() {
+        let data = index_pickle(vec![
+            ("ok.png", vec![segment(vec![Value::I64(10), Value::I64(5)])]),
+            ("short.png", vec![segment(vec![Value::I64(10)])]),
+            ("text.png", vec![segment(vec![Value::String("10".to_string()), Value::I64(5)])]),
+            ("prefix.png", vec![segment(vec![Value::I64(10), Value::I64(5), Value::I64(7)])]),
+            ("scalar.png", vec![Value::I64(10)]),
+            ("empty.png", vec![]),
+            (
+                "partial.png",
+                vec![segment(vec![Value::I64(10), Value::I64(5)]), segment(vec![Value::I64(20)])],
+            ),
+        ]);
+        let entries = parse_index_pickle(&data, 0).unwrap();
+        assert_eq!(entries.keys().collect::<Vec<_>>(), vec!["ok.png"]);
+
+        let list = serde_pickle::value_to_vec(&Value::List(vec![]), Default::default()).unwrap();
+        assert!(parse_index_pickle(&list, 0).is_err());
+    }
+
+    /// Saving with regenerated prefixes stores the first bytes in the index; the stored
+    /// length still counts them, as Ren'Py expects.
+    #[test]
+    fn saved_prefix_is_part_of_length() {
+        let dir = crate::test_dir::TestDir::new("index");
+        let path = dir.file("prefix.rpa");
+        let data: Vec<u8> = (0..40).collect();
+
+        let mut editor = crate::rpa::RpaEditor {
+            auto_backup: false,
+            archive_bak_count: 0,
+            regenerate_prefixes: true,
+            ..crate::rpa::RpaEditor::default()
+        };
+        editor.add_data("a.bin", data.clone()).unwrap();
+        editor.save_rpa(&path).unwrap();
+
+        let index = read_archive_index(&path, false).unwrap();
+        let entry = &index.entries["a.bin"];
+        assert_eq!(entry.length, 40);
+        assert_eq!(entry.segments[0].length, 40);
+        assert_eq!(entry.segments[0].prefix, data[..16]);
+
+        let mut reopened = crate::rpa::RpaEditor::default();
+        reopened.load_rpa(&path).unwrap();
+        assert_eq!(reopened.load_file_data("a.bin").unwrap(), data);
+    }
+}
@@ -0,0 +1,187 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Sender;
+use tracing::{debug, info};
+use crate::archive_index::{read_archive_index, ArchiveIndex};
+use crate::jobs::JobEvent;
+use crate::rpa::RpaEditor;
+
+/// Archive a "load" job is reading the index of, for the tab holding this.
+pub struct ArchiveLoad {
+    pub job: usize,
+    pub path: String,
+}
+
+/// Reads the header and index off the UI thread; the editor drops the result if the
+/// job was cancelled meanwhile.
+fn run_load(id: usize, path: String, use_mmap: bool, cancel: Arc<AtomicBool>, sender: Sender<JobEvent>) {
+    let index = read_archive_index(&path, use_mmap);
+    let message = match &index {
+        Ok(index) if !cancel.load(Ordering::Relaxed) => format!("Read {} entries from {}", index.entries.len(), path),
+        Ok(_) => String::new(),
+        Err(e) => format!("Could not read {}: {}", path, e),
+    };
+    let failed = usize::from(index.is_err());
+    let _ = sender.send(JobEvent::Progress { id, processed: 1, failed });
+    let _ = sender.send(JobEvent::Loaded { id, index });
+    let _ = sender.send(JobEvent::Finished { id, message });
+}
+
+impl RpaEditor {
+    /// Starts a job reading `path` for the active tab, which keeps showing what it has
+    /// until the index is in.
+    pub(crate) fn start_archive_load(&mut self, path: &str) {
+        // The archive itself is the job's one entry.
+        self.push_job("load", vec![path.to_string()], None);
+        let Some(job) = self.jobs.last_mut() else {
+            return;
+        };
+        job.archive = Some(path.to_string());
+        job.status = "running".to_string();
+        let (id, cancel) = (job.id, job.cancel.clone());
+
+        self.archive_loading = Some(ArchiveLoad {
+            job: id,
+            path: path.to_string(),
+        });
+        self.status_message = format!("Opening {}...", path);
+        info!("📂 Reading the index of {} (job #{})", path, id);
+        let (path, use_mmap, sender) = (path.to_string(), self.use_mmap, self.job_sender.clone());
+        std::thread::spawn(move || run_load(id, path, use_mmap, cancel, sender));
+    }
+
+    /// Ends the active tab's load, showing `index` unless the load was cancelled (`None`).
+    fn end_archive_load(&mut self, index: Option<anyhow::Result<ArchiveIndex>>) {
+        let Some(load) = self.archive_loading.take() else {
+            return;
+        };
+        match index {
+            Some(index) => self.finish_open_archive(&load.path, index),
+            None => {
+                self.status_message = format!("Stopped opening {}", load.path);
+                info!("⏹ {}", self.status_message);
+            }
+        }
+    }
+
+    /// Hands the index read by job `id` to the tab waiting for it, wherever that is now.
+    /// A tab opened only for the archive goes away again if it couldn't be read.
+    pub(crate) fn finish_archive_load(&mut self, id: usize, index: anyhow::Result<ArchiveIndex>) {
+        let cancelled = self.jobs.iter().any(|job| job.id == id && job.cancel.load(Ordering::Relaxed));
+        let index = (!cancelled).then_some(index);
+        let tab = if self.archive_loading.as_ref().is_some_and(|load| load.job == id) {
+            self.end_archive_load(index);
+            self.active_tab
+        } else if let Some(tab) = self.background_tab_loading(id) {
+            self.with_background_tab(tab, |editor| editor.end_archive_load(index));
+            tab
+        } else {
+            debug!("⏭️ Dropping the index read by job #{}: its tab moved on", id);
+            return;
+        };
+        self.close_tab_if_empty(tab);
+    }
+
+    /// Gives up on the active tab's load; the job's index is dropped when it arrives.
+    pub(crate) fn cancel_archive_load(&mut self) {
+        if let Some(load) = &self.archive_loading
+            && let Some(job) = self.jobs.iter().find(|job| job.id == load.job)
+        {
+            job.cancel.store(true, Ordering::Relaxed);
+        }
+        self.end_archive_load(None);
+        self.close_tab_if_empty(self.active_tab);
+    }
+
+    pub(crate) fn show_archive_loading(&mut self, ui: &mut egui::Ui) {
+        let Some(load) = &self.archive_loading else {
+            return;
+        };
+        ui.horizontal(|ui| {
+            ui.spinner();
+            ui.label(format!("Reading the index of {}", load.path));
+        });
+        ui.label("Other tabs stay usable meanwhile.");
+        if ui.button("⏹ Cancel").clicked() {
+            self.cancel_archive_load();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TestDir;
+
+    /// Feeds job events to the editor until every job has finished; a load's index
+    /// comes in before its job finishes.
+    fn wait_for_loads(editor: &mut RpaEditor) {
+        let ctx = egui::Context::default();
+        for _ in 0..500 {
+            editor.process_jobs(&ctx);
+            if editor.jobs.iter().all(|job| job.status != "running") {
+                return;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        panic!("the load job never finished");
+    }
+
+    /// Opening an archive also starts a content sniffing job, so the load is looked up.
+    fn load_job_status(editor: &RpaEditor) -> &str {
+        editor.jobs.iter().find(|job| job.kind == "load").map_or("", |job| job.status.as_str())
+    }
+
+    fn write_archive(dir: &TestDir, name: &str, entries: &[&str]) -> String {
+        let path = dir.file(name);
+        let mut editor = RpaEditor::default();
+        for entry in entries {
+            editor.add_data(entry, entry.as_bytes().to_vec()).unwrap();
+        }
+        editor.save_rpa(&path).unwrap();
+        path
+    }
+
+    #[test]
+    fn load_job_fills_the_tab_that_asked() {
+        let dir = TestDir::new("load");
+        let path_a = write_archive(&dir, "a.rpa", &["a.txt"]);
+        let path_b = write_archive(&dir, "b.rpa", &["b1.txt", "b2.txt"]);
+        let mut editor = RpaEditor::default();
+        editor.load_rpa(&path_a).unwrap();
+
+        editor.open_in_new_tab(&path_b);
+        assert_eq!(editor.active_tab, 1);
+        assert!(editor.archive_path.is_none());
+        assert!(editor.is_archive_open(&path_b));
+
+        // The index lands in tab 1 even though tab 0 is shown by then.
+        editor.switch_tab(0);
+        wait_for_loads(&mut editor);
+        assert_eq!(editor.archive_path.as_deref(), Some(path_a.as_str()));
+        assert_eq!(editor.indexes.len(), 1);
+        editor.switch_tab(1);
+        assert_eq!(editor.archive_path.as_deref(), Some(path_b.as_str()));
+        assert_eq!(editor.indexes.len(), 2);
+        assert_eq!(load_job_status(&editor), "done");
+    }
+
+    #[test]
+    fn cancelled_load_closes_its_tab() {
+        let dir = TestDir::new("load_cancel");
+        let path_a = write_archive(&dir, "c.rpa", &["c.txt"]);
+        let path_b = write_archive(&dir, "d.rpa", &["d.txt"]);
+        let mut editor = RpaEditor::default();
+        editor.load_rpa(&path_a).unwrap();
+
+        editor.open_in_new_tab(&path_b);
+        editor.cancel_archive_load();
+        assert_eq!(editor.archive_tabs.len(), 1);
+        assert_eq!(editor.archive_path.as_deref(), Some(path_a.as_str()));
+
+        wait_for_loads(&mut editor);
+        assert_eq!(editor.archive_path.as_deref(), Some(path_a.as_str()));
+        assert_eq!(load_job_status(&editor), "cancelled");
+        assert!(!editor.is_archive_open(&path_b));
+    }
+}
@@ -0,0 +1,244 @@
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::sync::{Arc, PoisonError};
+use memmap2::Mmap;
+use tracing::warn;
+use crate::rpa::{RpaEditor, RpaSegment};
+
+/// Read-ahead kept between entry reads; small neighbouring entries come out of one fill.
+const READ_BUFFER_SIZE: usize = 256 * 1024;
+
+/// Maps `file` into memory.
+pub fn map_archive(file: &File) -> anyhow::Result<Arc<Mmap>> {
+    // SAFETY: the mapping is only read. Another program truncating the archive while it's
+    // open would fault; that's the trade-off the "Memory-map archives" setting opts into.
+    let map = unsafe { Mmap::map(file)? };
+    Ok(Arc::new(map))
+}
+
+/// Appends the bytes of an entry stored as `segments` in a mapped archive to `out`.
+pub fn copy_mapped_segments(map: &[u8], segments: &[RpaSegment], out: &mut Vec<u8>) -> anyhow::Result<()> {
+    for segment in segments {
+        out.extend_from_slice(&segment.prefix);
+        let stored = segment.length.saturating_sub(segment.prefix.len() as u64);
+        out.extend_from_slice(mapped_range(map, segment.offset, stored)?);
+    }
+    Ok(())
+}
+
+/// The entry as a slice of the mapping, when it is stored in one piece without a prefix.
+pub fn mapped_entry<'a>(map: &'a [u8], segments: &[RpaSegment]) -> Option<&'a [u8]> {
+    match segments {
+        [segment] if segment.prefix.is_empty() => mapped_range(map, segment.offset, segment.length).ok(),
+        _ => None,
+    }
+}
+
+fn mapped_range(map: &[u8], offset: u64, length: u64) -> anyhow::Result<&[u8]> {
+    offset
+        .checked_add(length)
+        .and_then(|end| map.get(offset as usize..end as usize))
+        .ok_or_else(|| anyhow::anyhow!("Data 0x{:x}+{} is past the end of the archive", offset, length))
+}
+
+enum Backend {
+    Buffered {
+        reader: BufReader<File>,
+        /// Position of `reader`, or `None` after a failed read.
+        pos: Option<u64>,
+    },
+    Mapped(Arc<Mmap>),
+}
+
+/// Handle on the active archive shared by entry reads, so they don't reopen the file.
+pub struct ArchiveReader {
+    path: String,
+    /// File size when opened; segments past it come from a broken index.
+    len: u64,
+    backend: Backend,
+}
+
+impl ArchiveReader {
+    fn open(path: &str, mapped: bool) -> anyhow::Result<Self> {
+        let file = File::open(path)?;
+        let len = file.metadata()?.len();
+        let backend = if mapped {
+            Backend::Mapped(map_archive(&file)?)
+        } else {
+            Backend::Buffered {
+                reader: BufReader::with_capacity(READ_BUFFER_SIZE, file),
+                pos: Some(0),
+            }
+        };
+        Ok(Self {
+            path: path.to_string(),
+            len,
+            backend,
+        })
+    }
+
+    fn is_mapped(&self) -> bool {
+        matches!(self.backend, Backend::Mapped(_))
+    }
+
+    /// Fails for a segment reaching past the end of the archive, before anything is
+    /// allocated for it.
+    fn check_segments(&self, segments: &[RpaSegment]) -> anyhow::Result<()> {
+        for segment in segments {
+            let stored = segment.length.saturating_sub(segment.prefix.len() as u64);
+            if segment.offset.checked_add(stored).is_none_or(|end| end > self.len) {
+                return Err(anyhow::anyhow!(
+                    "Data 0x{:x}+{} is past the end of the archive",
+                    segment.offset,
+                    stored
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Appends the bytes of an entry stored as `segments` to `out`.
+    fn read_segments(&mut self, segments: &[RpaSegment], out: &mut Vec<u8>) -> anyhow::Result<()> {
+        self.check_segments(segments)?;
+        let (reader, pos) = match &mut self.backend {
+            Backend::Mapped(map) => return copy_mapped_segments(map, segments, out),
+            Backend::Buffered { reader, pos } => (reader, pos),
+        };
+        for segment in segments {
+            out.extend_from_slice(&segment.prefix);
+            let stored = segment.length.saturating_sub(segment.prefix.len() as u64);
+            // Stay inside the buffered bytes when the segment is just ahead.
+            match *pos {
+                Some(current) if segment.offset >= current && segment.offset - current <= reader.buffer().len() as u64 => {
+                    reader.seek_relative((segment.offset - current) as i64)?;
+                }
+                _ => {
+                    reader.seek(SeekFrom::Start(segment.offset))?;
+                }
+            }
+
+            let start = out.len();
+            out.resize(start + stored as usize, 0);
+            if let Err(e) = reader.read_exact(&mut out[start..]) {
+                *pos = None;
+                return Err(e.into());
+            }
+            *pos = Some(segment.offset + stored);
+        }
+        Ok(())
+    }
+}
+
+impl RpaEditor {
+    fn with_archive_reader<T>(
+        &self,
+        read: impl FnOnce(&mut ArchiveReader) -> anyhow::Result<T>,
+    ) -> anyhow::Result<T> {
+        let path = self
+            .archive_path
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("No archive loaded"))?;
+        let mut cached = self.archive_reader.lock().unwrap_or_else(PoisonError::into_inner);
+        // Tabs share the editor, so the handle may belong to another archive.
+        if cached
+            .as_ref()
+            .is_none_or(|reader| reader.path != *path || reader.is_mapped() != self.use_mmap)
+        {
+            *cached = Some(ArchiveReader::open(path, self.use_mmap)?);
+        }
+        match cached.as_mut() {
+            Some(reader) => read(reader),
+            None => Err(anyhow::anyhow!("Archive is not open")),
+        }
+    }
+
+    /// Drops the shared handle; the next read reopens the archive. Needed whenever the
+    /// file on disk is replaced, since the handle would keep reading the old one.
+    pub(crate) fn close_archive_reader(&self) {
+        *self.archive_reader.lock().unwrap_or_else(PoisonError::into_inner) = None;
+    }
+
+    /// The mapping of the active archive when "Memory-map archives" is on.
+    pub(crate) fn archive_map(&self) -> Option<Arc<Mmap>> {
+        if !self.use_mmap {
+            return None;
+        }
+        self.with_archive_reader(|reader| match &reader.backend {
+            Backend::Mapped(map) => Ok(Some(map.clone())),
+            Backend::Buffered { .. } => Ok(None),
+        })
+        .unwrap_or_else(|e| {
+            warn!("⚠️ Could not map the archive: {}", e);
+            None
+        })
+    }
+
+    /// Reads an entry into `out`, replacing its contents. Reusing `out` across entries
+    /// avoids an allocation per read.
+    pub(crate) fn read_entry_into(&self, filename: &str, out: &mut Vec<u8>) -> anyhow::Result<()> {
+        let entry = self
+            .indexes
+            .get(filename)
+            .ok_or_else(|| anyhow::anyhow!("File not found"))?;
+        out.clear();
+        if let Some(data) = &entry.data {
+            out.extend_from_slice(data);
+            return Ok(());
+        }
+        if let Some(source) = &entry.merged {
+            source.open()?.read_to_end(out)?;
+            return Ok(());
+        }
+        self.with_archive_reader(|reader| {
+            // The index may be corrupt, so its lengths are checked before reserving.
+            reader.check_segments(&entry.segments)?;
+            out.reserve(entry.segments.iter().map(|segment| segment.length).sum::<u64>() as usize);
+            reader.read_segments(&entry.segments, out)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpa::RpaFileEntry;
+    use crate::test_dir::TestDir;
+
+    #[test]
+    fn entry_past_the_end_is_an_error() {
+        let dir = TestDir::new("reader");
+        let path = dir.path().join("short.rpa");
+        std::fs::write(&path, b"0123456789").unwrap();
+
+        for use_mmap in [false, true] {
+            let mut editor = RpaEditor {
+                archive_path: Some(path.to_string_lossy().to_string()),
+                use_mmap,
+                ..RpaEditor::default()
+            };
+            let segment = |offset, length| RpaSegment {
+                offset,
+                length,
+                prefix: Vec::new(),
+            };
+            editor
+                .indexes
+                .insert("ok.txt".to_string(), RpaFileEntry::from_segments(vec![segment(2, 4)]));
+            editor.indexes.insert(
+                "huge.txt".to_string(),
+                RpaFileEntry::from_segments(vec![segment(2, 0xFFFF_FFFF_FFFF)]),
+            );
+            editor
+                .indexes
+                .insert("wrapped.txt".to_string(), RpaFileEntry::from_segments(vec![segment(u64::MAX, 2)]));
+
+            let mut out = Vec::new();
+            editor.read_entry_into("ok.txt", &mut out).unwrap();
+            assert_eq!(out, b"2345");
+            for name in ["huge.txt", "wrapped.txt"] {
+                let error = editor.read_entry_into(name, &mut out).unwrap_err();
+                assert!(error.to_string().contains("past the end of the archive"));
+            }
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use std::time::{Duration, Instant, SystemTime};
+use tracing::{info, warn};
+use crate::rpa::{RpaEditor, RpaFileEntry};
+
+/// How often the open archive's modification time is checked.
+const CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Modification time and size of an archive file, to notice when something rewrites it.
+pub type ArchiveStamp = (SystemTime, u64);
+
+fn archive_stamp(path: &str) -> Option<ArchiveStamp> {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+impl RpaEditor {
+    /// Records the open archive as it is on disk now; called after loading and saving it.
+    pub(crate) fn remember_archive_stamp(&mut self) {
+        self.archive_stamp = self.archive_path.as_deref().and_then(archive_stamp);
+    }
+
+    /// Notices another program rewriting the open archive. Without pending edits it is
+    /// reloaded right away; otherwise the user is asked.
+    pub(crate) fn poll_archive_on_disk(&mut self) {
+        if self.archive_changed_on_disk || self.archive_checked_at.elapsed() < CHECK_INTERVAL {
+            return;
+        }
+        self.archive_checked_at = Instant::now();
+        let (Some(path), Some(stamp)) = (self.archive_path.clone(), self.archive_stamp) else {
+            return;
+        };
+        // A missing file is most likely being replaced; look again next time.
+        let Some(current) = archive_stamp(&path) else {
+            return;
+        };
+        if current == stamp {
+            return;
+        }
+
+        warn!("⚠️ {} changed on disk", path);
+        if self.modified {
+            self.archive_changed_on_disk = true;
+        } else {
+            match self.reload_archive(false) {
+                Ok(()) => self.add_toast("Archive changed on disk and was reloaded"),
+                Err(e) => {
+                    self.add_error(format!("Archive changed on disk, reload failed: {}", e));
+                    self.archive_stamp = Some(current);
+                }
+            }
+        }
+    }
+
+    /// Reads the open archive again. With `keep_edits`, added and replaced entries and
+    /// pending deletions are put back on top of the new index; changes that only
+    /// pointed into the old file (an unmodified entry that was renamed) can't be kept.
+    pub(crate) fn reload_archive(&mut self, keep_edits: bool) -> anyhow::Result<()> {
+        let Some(path) = self.archive_path.clone() else {
+            return Ok(());
+        };
+        let kept: Vec<(String, RpaFileEntry)> = if keep_edits {
+            self.indexes
+                .iter()
+                .filter(|(_, entry)| entry.modified || entry.to_delete)
+                .map(|(name, entry)| (name.clone(), entry.clone()))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let selected = self.selected_file.clone();
+
+        self.load_rpa(&path)?;
+        self.checksums.clear();
+        self.thumbnails.clear();
+
+        let mut lost = Vec::new();
+        for (name, mut entry) in kept {
+            if entry.in_archive() {
+                match self.indexes.get_mut(&name) {
+                    Some(current) if entry.to_delete => current.to_delete = true,
+                    _ => lost.push(name),
+                }
+                continue;
+            }
+            entry.segments = self
+                .indexes
+                .get(&name)
+                .map(|current| current.segments.clone())
+                .unwrap_or_default();
+            self.indexes.insert(name, entry);
+        }
+        self.set_modified(!self.changed_entries().is_empty());
+
+        if let Some(name) = selected.filter(|name| self.indexes.contains_key(name)) {
+            self.file_to_preview = Some(name);
+        }
+        self.archive_changed_on_disk = false;
+        if !lost.is_empty() {
+            self.add_warning(format!("{} edits referred to the old file and were dropped", lost.len()));
+            warn!("⚠️ Dropped after reload: {}", lost.join(", "));
+        }
+        info!("🔄 Reloaded {}", path);
+        Ok(())
+    }
+
+    pub(crate) fn show_archive_changed_dialog(&mut self, ui: &mut egui::Ui) {
+        ui.label(format!(
+            "{} was changed by another program.",
+            self.archive_path.as_deref().unwrap_or_default()
+        ));
+        ui.label("Entries not edited here are read from the file, so they may show the wrong data until it is reloaded.");
+        ui.separator();
+
+        let mut result = None;
+        ui.horizontal(|ui| {
+            if ui.button("🔄 Reload, keep my edits").clicked() {
+                result = Some(self.reload_archive(true));
+            }
+            if ui.button("Reload, discard edits").clicked() {
+                result = Some(self.reload_archive(false));
+            }
+            if ui.button("Ignore").on_hover_text("Keep working with the loaded index").clicked() {
+                self.archive_changed_on_disk = false;
+                self.remember_archive_stamp();
+            }
+        });
+        if let Some(Err(e)) = result {
+            self.archive_changed_on_disk = false;
+            self.remember_archive_stamp();
+            self.add_error(format!("Reload failed: {}", e));
+        }
+    }
+}
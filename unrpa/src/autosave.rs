@@ -0,0 +1,179 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+use crate::rpa::RpaEditor;
+use crate::workspace::{WorkspaceManifest, WorkspaceSnapshot};
+
+/// Appended to the archive path for its autosave sidecar, a workspace file.
+const AUTOSAVE_SUFFIX: &str = ".autosave.rpaws";
+
+pub fn autosave_path(archive: &str) -> PathBuf {
+    PathBuf::from(format!("{}{}", archive, AUTOSAVE_SUFFIX))
+}
+
+/// Sidecar found when an archive was opened, waiting for the user to restore or discard it.
+pub struct AutosaveOffer {
+    pub archive: String,
+    pub path: PathBuf,
+    pub manifest: WorkspaceManifest,
+}
+
+/// Writes a snapshot aside first so a power cut mid-write leaves the previous one intact.
+fn write_autosave(path: &Path, snapshot: &WorkspaceSnapshot) {
+    let partial = path.with_extension("partial");
+    match snapshot.write(&partial).and_then(|count| {
+        std::fs::rename(&partial, path)?;
+        Ok(count)
+    }) {
+        Ok(count) => info!("💾 Autosaved {} unsaved changes to {}", count, path.display()),
+        Err(e) => {
+            let _ = std::fs::remove_file(&partial);
+            warn!("⚠️ Autosave to {} failed: {}", path.display(), e);
+        }
+    }
+}
+
+impl RpaEditor {
+    /// Snapshots the unsaved changes of every open archive every `autosave_minutes`,
+    /// skipping those unchanged since their last snapshot. Files are written on a
+    /// background thread. Untitled archives have nowhere to put a sidecar; crash
+    /// recovery still covers them.
+    pub(crate) fn poll_autosave(&mut self) {
+        let interval = Duration::from_secs(u64::from(self.autosave_minutes.max(1)) * 60);
+        if !self.autosave_enabled || self.autosaved_at.elapsed() < interval {
+            return;
+        }
+        self.autosaved_at = Instant::now();
+        // A slow disk may still be busy with the previous round.
+        if self.autosave_thread.as_ref().is_some_and(|thread| !thread.is_finished()) {
+            return;
+        }
+
+        let mut snapshots: Vec<(PathBuf, WorkspaceSnapshot)> = self.autosave_snapshot().into_iter().collect();
+        for index in self.modified_background_tabs() {
+            if let Some(Some(snapshot)) = self.with_background_tab(index, |editor| editor.autosave_snapshot()) {
+                snapshots.push(snapshot);
+            }
+        }
+        if snapshots.is_empty() {
+            return;
+        }
+        self.autosave_files.extend(snapshots.iter().map(|(path, _)| path.clone()));
+        self.autosave_thread = Some(std::thread::spawn(move || {
+            for (path, snapshot) in &snapshots {
+                write_autosave(path, snapshot);
+            }
+        }));
+    }
+
+    /// The active archive's sidecar path and changes, when they changed since the last snapshot.
+    fn autosave_snapshot(&mut self) -> Option<(PathBuf, WorkspaceSnapshot)> {
+        if !self.modified || self.edit_generation == self.autosaved_generation {
+            return None;
+        }
+        let path = autosave_path(self.archive_path.as_ref()?);
+        // Don't overwrite a sidecar the user hasn't decided about yet.
+        if self.autosave_offer.as_ref().is_some_and(|offer| offer.path == path) {
+            return None;
+        }
+        self.autosaved_generation = self.edit_generation;
+        Some((path, self.workspace_snapshot()))
+    }
+
+    /// Waits for the autosave round in progress, so its files can be removed.
+    fn finish_autosave(&mut self) {
+        if let Some(thread) = self.autosave_thread.take() {
+            let _ = thread.join();
+        }
+    }
+
+    /// Deletes the active archive's sidecar once its changes are saved or discarded.
+    pub(crate) fn remove_autosave(&mut self) {
+        let Some(archive) = &self.archive_path else {
+            return;
+        };
+        let path = autosave_path(archive);
+        if !self.autosave_files.remove(&path) {
+            return;
+        }
+        self.finish_autosave();
+        if std::fs::remove_file(&path).is_ok() {
+            info!("🗑️ Removed autosave {}", path.display());
+        }
+    }
+
+    /// Quitting discards unsaved changes, so their snapshots go too.
+    pub(crate) fn remove_session_autosaves(&mut self) {
+        self.finish_autosave();
+        for path in &self.autosave_files {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+
+    /// Offers the changes autosaved for `archive` by an earlier session; called after opening it.
+    pub(crate) fn check_autosave(&mut self, archive: &str) {
+        let path = autosave_path(archive);
+        if !path.is_file() || self.autosave_files.contains(&path) {
+            return;
+        }
+        match Self::read_workspace_manifest(&path) {
+            Ok(manifest) => {
+                info!("💾 Found autosaved changes in {}", path.display());
+                self.autosave_offer = Some(AutosaveOffer {
+                    archive: archive.to_string(),
+                    path,
+                    manifest,
+                });
+            }
+            Err(e) => warn!("⚠️ Unreadable autosave {}: {}", path.display(), e),
+        }
+    }
+
+    fn restore_autosave(&mut self) -> anyhow::Result<usize> {
+        let Some(offer) = self.autosave_offer.take() else {
+            return Ok(0);
+        };
+        if self.archive_path.as_deref() != Some(offer.archive.as_str()) {
+            let archive = offer.archive.clone();
+            self.autosave_offer = Some(offer);
+            return Err(anyhow::anyhow!("Switch back to {} first", archive));
+        }
+        let count = self.import_workspace(&offer.path, &offer.archive)?;
+        // Kept until the restored changes are saved, as if this session had written it.
+        self.autosave_files.insert(offer.path);
+        Ok(count)
+    }
+
+    fn discard_autosave(&mut self) {
+        if let Some(offer) = self.autosave_offer.take() {
+            let _ = std::fs::remove_file(&offer.path);
+            info!("🗑️ Discarded autosave {}", offer.path.display());
+        }
+    }
+
+    pub(crate) fn show_autosave_dialog(&mut self, ui: &mut egui::Ui) {
+        let Some(offer) = &self.autosave_offer else {
+            return;
+        };
+        ui.label(format!("{} has changes that were never saved:", offer.archive));
+        ui.label(format!(
+            "{} changed and {} deleted entries, autosaved {}",
+            offer.manifest.pending.len(),
+            offer.manifest.deleted.len(),
+            offer.manifest.created.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M")
+        ));
+        ui.separator();
+
+        ui.horizontal(|ui| {
+            if ui.add_enabled(!self.read_only, egui::Button::new("💾 Restore")).clicked() {
+                match self.restore_autosave() {
+                    Ok(count) => self.add_toast(format!("Restored {} autosaved changes", count)),
+                    Err(e) => self.add_error(format!("Restore failed: {}", e)),
+                }
+            }
+            if ui.button("🗑️ Discard").clicked() {
+                self.discard_autosave();
+            }
+        });
+    }
+}
@@ -0,0 +1,226 @@
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+use crate::health::config_dir;
+use crate::rpa::{BackupEntry, RpaEditor};
+
+/// Sidecar written next to each compressed backup; `path` is the sidecar itself.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiskBackup {
+    pub archive: String,
+    pub filename: String,
+    pub original_size: u64,
+    pub compressed_size: u64,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    #[serde(skip)]
+    pub path: PathBuf,
+}
+
+impl DiskBackup {
+    fn data_path(&self) -> PathBuf {
+        self.path.with_extension("zlib")
+    }
+
+    fn restore_data(&self) -> anyhow::Result<Vec<u8>> {
+        let backup = BackupEntry {
+            filename: self.filename.clone(),
+            data: std::fs::read(self.data_path())?,
+            original_size: self.original_size,
+            timestamp: self.timestamp,
+        };
+        backup.restore_data()
+    }
+}
+
+pub fn default_backup_dir() -> PathBuf {
+    config_dir().join("backups")
+}
+
+/// Entry names become part of a file name, so path separators are flattened.
+pub fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_alphanumeric() || matches!(c, '.' | '-' | '_') { c } else { '_' })
+        .collect()
+}
+
+fn read_backup(path: &Path) -> anyhow::Result<DiskBackup> {
+    Ok(serde_json::from_slice(&std::fs::read(path)?)?)
+}
+
+fn read_backups(folder: &Path, backups: &mut Vec<DiskBackup>) -> anyhow::Result<()> {
+    for item in std::fs::read_dir(folder)? {
+        let path = item?.path();
+        if path.is_dir() {
+            read_backups(&path, backups)?;
+        } else if path.extension().is_some_and(|ext| ext == "json") {
+            match read_backup(&path) {
+                Ok(mut backup) => {
+                    backup.path = path;
+                    backups.push(backup);
+                }
+                Err(e) => warn!("⚠️ Skipping backup {}: {}", path.display(), e),
+            }
+        }
+    }
+    Ok(())
+}
+
+impl RpaEditor {
+    /// File name of the open archive, used to group its backups.
+    pub(crate) fn archive_backup_name(&self) -> String {
+        self.archive_path
+            .as_deref()
+            .and_then(|path| Path::new(path).file_name())
+            .map_or("untitled".to_string(), |name| name.to_string_lossy().to_string())
+    }
+
+    /// Saves a snapshot to `<backup_dir>/<archive>/<timestamp>_<entry>.zlib` plus its sidecar.
+    pub(crate) fn write_disk_backup(&self, backup: &BackupEntry) -> anyhow::Result<PathBuf> {
+        let archive = self.archive_backup_name();
+        let folder = self.backup_dir.join(sanitize(&archive));
+        std::fs::create_dir_all(&folder)?;
+
+        let stem = format!(
+            "{}_{}",
+            backup.timestamp.format("%Y%m%d-%H%M%S%.3f"),
+            sanitize(&backup.filename)
+        );
+        let meta = DiskBackup {
+            archive,
+            filename: backup.filename.clone(),
+            original_size: backup.original_size,
+            compressed_size: backup.data.len() as u64,
+            timestamp: backup.timestamp,
+            path: folder.join(format!("{}.json", stem)),
+        };
+        std::fs::write(meta.data_path(), &backup.data)?;
+        std::fs::write(&meta.path, serde_json::to_vec_pretty(&meta)?)?;
+        Ok(meta.path)
+    }
+
+    /// Reloads the list of backups in the backup folder, newest first.
+    pub(crate) fn scan_disk_backups(&mut self) {
+        let mut backups = Vec::new();
+        if self.backup_dir.is_dir()
+            && let Err(e) = read_backups(&self.backup_dir, &mut backups)
+        {
+            self.status_message = format!("Could not read backups: {}", e);
+        }
+        backups.sort_by_key(|backup| std::cmp::Reverse(backup.timestamp));
+        self.disk_backups = backups;
+    }
+
+    /// Puts backed-up bytes back as the entry's current content.
+    pub(crate) fn restore_entry_from_backup(
+        &mut self,
+        filename: &str,
+        data: Vec<u8>,
+        timestamp: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<()> {
+        self.insert_data(filename, data)?;
+        self.set_provenance(filename, format!("backup from {}", timestamp.format("%Y-%m-%d %H:%M")));
+        self.status_message = format!("Restored backup of {}", filename);
+        Ok(())
+    }
+
+    fn restore_disk_backup(&mut self, index: usize) -> anyhow::Result<()> {
+        let backup = self.disk_backups[index].clone();
+        let data = backup.restore_data()?;
+        self.restore_entry_from_backup(&backup.filename, data, backup.timestamp)
+    }
+
+    fn delete_disk_backup(&mut self, index: usize) -> anyhow::Result<()> {
+        let backup = self.disk_backups.remove(index);
+        std::fs::remove_file(backup.data_path())?;
+        std::fs::remove_file(&backup.path)?;
+        Ok(())
+    }
+
+    /// Deletes backups older than `days`; returns how many were removed.
+    pub(crate) fn prune_disk_backups(&mut self, days: u32) -> usize {
+        let cutoff = chrono::Utc::now() - chrono::Duration::days(days as i64);
+        let mut removed = 0;
+        let mut index = 0;
+        while index < self.disk_backups.len() {
+            if self.disk_backups[index].timestamp >= cutoff {
+                index += 1;
+                continue;
+            }
+            match self.delete_disk_backup(index) {
+                Ok(()) => removed += 1,
+                Err(e) => {
+                    warn!("⚠️ Could not delete backup: {}", e);
+                    index += 1;
+                }
+            }
+        }
+        info!("🧹 Pruned {} backups older than {} days", removed, days);
+        removed
+    }
+
+    /// Browser for backups kept on disk from this and earlier sessions.
+    pub(crate) fn show_disk_backups(&mut self, ui: &mut egui::Ui) {
+        let archive = self.archive_backup_name();
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.backup_only_this_archive, format!("Only {}", archive));
+            if ui.button("🔄 Refresh").clicked() {
+                self.scan_disk_backups();
+            }
+        });
+
+        let visible: Vec<usize> = (0..self.disk_backups.len())
+            .filter(|&i| !self.backup_only_this_archive || self.disk_backups[i].archive == archive)
+            .collect();
+        if visible.is_empty() {
+            ui.label("No backups on disk");
+        }
+
+        let mut restore = None;
+        let mut delete = None;
+        egui::ScrollArea::vertical().max_height(260.0).show(ui, |ui| {
+            for &i in &visible {
+                let backup = &self.disk_backups[i];
+                ui.horizontal(|ui| {
+                    ui.label(format!("📄 {}", backup.filename));
+                    if !self.backup_only_this_archive {
+                        ui.label(egui::RichText::new(&backup.archive).small().weak());
+                    }
+                    ui.label(format!(
+                        "({} → {})",
+                        Self::format_bytes(backup.original_size),
+                        Self::format_bytes(backup.compressed_size)
+                    ));
+                    ui.label(format!("📅 {}", backup.timestamp.format("%Y-%m-%d %H:%M")));
+                    if ui.button("📤 Restore").clicked() {
+                        restore = Some(i);
+                    }
+                    if ui.small_button("🗑").on_hover_text("Delete this backup").clicked() {
+                        delete = Some(i);
+                    }
+                });
+            }
+        });
+
+        if let Some(i) = restore
+            && let Err(e) = self.restore_disk_backup(i)
+        {
+            self.status_message = format!("Restore error: {}", e);
+        }
+        if let Some(i) = delete
+            && let Err(e) = self.delete_disk_backup(i)
+        {
+            self.status_message = format!("Could not delete backup: {}", e);
+        }
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label("Delete backups older than");
+            ui.add(egui::DragValue::new(&mut self.backup_prune_days).range(1..=3650));
+            ui.label("days");
+            if ui.button("🧹 Prune").clicked() {
+                let removed = self.prune_disk_backups(self.backup_prune_days);
+                self.add_toast(format!("Deleted {} old backups", removed));
+            }
+        });
+    }
+}
@@ -0,0 +1,285 @@
+use std::collections::{HashMap, HashSet};
+use regex::Regex;
+use tracing::info;
+use crate::rpa::{RpaEditor, RpaFileEntry};
+use crate::selection_export::SELECTION_SCOPES;
+
+/// Case conversions applied last, to the whole new name.
+pub const RENAME_CASES: &[(&str, &str)] = &[
+    ("keep", "Keep"),
+    ("lower", "lowercase"),
+    ("upper", "UPPERCASE"),
+];
+
+/// Options of the Batch Rename dialog. Find/replace runs on the full path; prefix and
+/// suffix go around the file name, the suffix before its extension.
+pub struct BatchRename {
+    pub find: String,
+    pub replace: String,
+    pub use_regex: bool,
+    pub prefix: String,
+    pub suffix: String,
+    /// One of `RENAME_CASES`.
+    pub case: String,
+    /// One of `SELECTION_SCOPES`.
+    pub scope: String,
+}
+
+impl Default for BatchRename {
+    fn default() -> Self {
+        Self {
+            find: String::new(),
+            replace: String::new(),
+            use_regex: false,
+            prefix: String::new(),
+            suffix: String::new(),
+            case: "keep".to_string(),
+            scope: "visible".to_string(),
+        }
+    }
+}
+
+pub struct RenamePlanItem {
+    pub old_name: String,
+    pub new_name: String,
+    /// Why this rename can't be applied.
+    pub problem: Option<String>,
+}
+
+impl BatchRename {
+    fn new_name(&self, name: &str, pattern: Option<&Regex>) -> String {
+        let mut name = match pattern {
+            Some(regex) => regex.replace_all(name, self.replace.as_str()).into_owned(),
+            None if !self.find.is_empty() => name.replace(&self.find, &self.replace),
+            None => name.to_string(),
+        };
+
+        if !self.prefix.is_empty() || !self.suffix.is_empty() {
+            let file_start = name.rfind('/').map_or(0, |slash| slash + 1);
+            let stem_end = name[file_start..]
+                .rfind('.')
+                .filter(|&dot| dot > 0)
+                .map_or(name.len(), |dot| file_start + dot);
+            name.insert_str(stem_end, &self.suffix);
+            name.insert_str(file_start, &self.prefix);
+        }
+
+        match self.case.as_str() {
+            "lower" => name.to_lowercase(),
+            "upper" => name.to_uppercase(),
+            _ => name,
+        }
+    }
+}
+
+impl RpaEditor {
+    fn batch_rename_sources(&self) -> Vec<String> {
+        let mut names: Vec<String> = match self.batch_rename.scope.as_str() {
+            "selected" => self.selected_file.iter().cloned().collect(),
+            _ => self
+                .get_filtered_sorted_files()
+                .into_iter()
+                .map(|(name, _)| name.clone())
+                .collect(),
+        };
+        names.retain(|name| self.indexes.get(name).is_some_and(|entry| !entry.to_delete));
+        names
+    }
+
+    /// Resulting names for the entries in scope, with collisions flagged. Entries whose
+    /// name doesn't change are left out.
+    pub(crate) fn batch_rename_plan(&self) -> anyhow::Result<Vec<RenamePlanItem>> {
+        let options = &self.batch_rename;
+        let pattern = if options.use_regex && !options.find.is_empty() {
+            Some(Regex::new(&options.find)?)
+        } else {
+            None
+        };
+
+        let mut plan: Vec<RenamePlanItem> = self
+            .batch_rename_sources()
+            .into_iter()
+            .map(|old_name| RenamePlanItem {
+                new_name: options.new_name(&old_name, pattern.as_ref()),
+                old_name,
+                problem: None,
+            })
+            .filter(|item| item.new_name != item.old_name)
+            .collect();
+
+        let renamed_away: HashSet<&str> = plan.iter().map(|item| item.old_name.as_str()).collect();
+        let mut targets: HashMap<&str, usize> = HashMap::new();
+        for item in &plan {
+            *targets.entry(item.new_name.as_str()).or_default() += 1;
+        }
+        let problems: Vec<Option<String>> = plan
+            .iter()
+            .map(|item| {
+                let name = item.new_name.as_str();
+                if name.is_empty() || name.ends_with('/') {
+                    Some("empty file name".to_string())
+                } else if targets[name] > 1 {
+                    Some(format!("{} other entries get this name", targets[name] - 1))
+                } else if !renamed_away.contains(name)
+                    && self.indexes.get(name).is_some_and(|entry| !entry.to_delete)
+                {
+                    Some("an entry with this name exists".to_string())
+                } else {
+                    None
+                }
+            })
+            .collect();
+        for (item, problem) in plan.iter_mut().zip(problems) {
+            item.problem = problem;
+        }
+        Ok(plan)
+    }
+
+    /// Renames every entry in the plan at once, so names can be swapped or shifted along
+    /// a chain. Refuses the whole batch if any rename collides.
+    pub(crate) fn apply_batch_rename(&mut self) -> anyhow::Result<usize> {
+        self.ensure_writable()?;
+        let plan = self.batch_rename_plan()?;
+        if let Some(item) = plan.iter().find(|item| item.problem.is_some()) {
+            return Err(anyhow::anyhow!(
+                "{} → {}: {}",
+                item.old_name,
+                item.new_name,
+                item.problem.as_deref().unwrap_or_default()
+            ));
+        }
+
+        let mut renamed = Vec::with_capacity(plan.len());
+        for item in &plan {
+            let loaded = self.loaded_names.contains(&item.old_name);
+            let Some(old) = self.indexes.get_mut(&item.old_name) else {
+                continue;
+            };
+            let entry = RpaFileEntry {
+                segments: old.segments.clone(),
+                length: old.length,
+                data: old.data.take(),
+                merged: old.merged.take(),
+                modified: true,
+                to_delete: false,
+                provenance: format!("renamed from {}", item.old_name),
+            };
+            // Like rename_entry, a loaded entry leaves a deletion behind so it can be reverted.
+            if loaded {
+                old.to_delete = true;
+            } else {
+                self.indexes.remove(&item.old_name);
+            }
+            renamed.push((item, entry, self.checksums.remove(&item.old_name)));
+        }
+
+        for (item, entry, checksum) in renamed {
+            self.indexes.insert(item.new_name.clone(), entry);
+            if let Some(hash) = checksum {
+                self.checksums.insert(item.new_name.clone(), hash);
+            }
+            self.thumbnails.forget(&item.new_name);
+            if self.selected_file.as_deref() == Some(item.old_name.as_str()) {
+                self.selected_file = Some(item.new_name.clone());
+            }
+        }
+
+        if !plan.is_empty() {
+            self.set_modified(true);
+        }
+        self.status_message = format!("Renamed {} entries", plan.len());
+        info!("✏️ {}", self.status_message);
+        Ok(plan.len())
+    }
+
+    pub(crate) fn show_batch_rename_dialog(&mut self, ui: &mut egui::Ui) {
+        let options = &mut self.batch_rename;
+        egui::Grid::new("batch_rename_options").num_columns(2).show(ui, |ui| {
+            ui.label("Find:");
+            ui.horizontal(|ui| {
+                ui.text_edit_singleline(&mut options.find);
+                ui.checkbox(&mut options.use_regex, "Regex")
+                    .on_hover_text("Use $1, $2 or ${name} in the replacement for capture groups");
+            });
+            ui.end_row();
+            ui.label("Replace with:");
+            ui.text_edit_singleline(&mut options.replace);
+            ui.end_row();
+            ui.label("Prefix:");
+            ui.text_edit_singleline(&mut options.prefix);
+            ui.end_row();
+            ui.label("Suffix:");
+            ui.text_edit_singleline(&mut options.suffix);
+            ui.end_row();
+            ui.label("Case:");
+            ui.horizontal(|ui| {
+                for (case, label) in RENAME_CASES {
+                    ui.radio_value(&mut options.case, case.to_string(), *label);
+                }
+            });
+            ui.end_row();
+            ui.label("Entries:");
+            ui.vertical(|ui| {
+                for (scope, label) in SELECTION_SCOPES {
+                    ui.radio_value(&mut options.scope, scope.to_string(), *label);
+                }
+            });
+            ui.end_row();
+        });
+        ui.separator();
+
+        let plan = match self.batch_rename_plan() {
+            Ok(plan) => plan,
+            Err(e) => {
+                ui.colored_label(egui::Color32::LIGHT_RED, format!("Invalid pattern: {}", e));
+                Vec::new()
+            }
+        };
+        let collisions = plan.iter().filter(|item| item.problem.is_some()).count();
+        ui.label(format!("{} entries renamed", plan.len()));
+        if collisions > 0 {
+            ui.colored_label(egui::Color32::ORANGE, format!("⚠ {} collisions", collisions));
+        }
+
+        let row_height = ui.spacing().interact_size.y;
+        egui::ScrollArea::vertical()
+            .max_height(300.0)
+            .auto_shrink([false, true])
+            .show_rows(ui, row_height, plan.len(), |ui, rows| {
+                for item in &plan[rows] {
+                    ui.horizontal(|ui| {
+                        ui.label(egui::RichText::new(&item.old_name).weak());
+                        ui.label("→");
+                        match &item.problem {
+                            Some(problem) => {
+                                ui.colored_label(egui::Color32::ORANGE, format!("⚠ {}", item.new_name))
+                                    .on_hover_text(problem);
+                            }
+                            None => {
+                                ui.label(&item.new_name);
+                            }
+                        }
+                    });
+                }
+            });
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(!plan.is_empty() && collisions == 0, egui::Button::new("✏️ Rename"))
+                .clicked()
+            {
+                match self.apply_batch_rename() {
+                    Ok(count) => {
+                        self.add_toast(format!("Renamed {} entries", count));
+                        self.show_batch_rename_dialog = false;
+                    }
+                    Err(e) => self.add_error(format!("Rename error: {}", e)),
+                }
+            }
+            if ui.button("❌ Cancel").clicked() {
+                self.show_batch_rename_dialog = false;
+            }
+        });
+    }
+}
@@ -0,0 +1,283 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use tracing::error;
+use crate::file_tree::collect_files;
+use crate::rpa::RpaEditor;
+
+pub struct BatchReplaceMatch {
+    /// Path of the disk file relative to the folder.
+    pub file: String,
+    pub path: PathBuf,
+    pub entry: String,
+    /// Matched by file name alone, the only entry with that name.
+    pub by_name: bool,
+}
+
+/// What a batch replace from `folder` would do, worked out without touching the archive.
+pub struct BatchReplacePlan {
+    pub folder: String,
+    pub matches: Vec<BatchReplaceMatch>,
+    /// Disk files whose name doesn't single out one entry, with the candidates; left alone.
+    pub ambiguous: Vec<(String, Vec<String>)>,
+    pub unmatched: Vec<String>,
+}
+
+pub struct BatchReplaceResult {
+    pub entry: String,
+    pub file: String,
+    pub error: Option<String>,
+}
+
+pub struct BatchReplaceReport {
+    pub folder: String,
+    pub results: Vec<BatchReplaceResult>,
+    pub ambiguous: Vec<(String, Vec<String>)>,
+    pub unmatched: Vec<String>,
+}
+
+impl BatchReplaceReport {
+    pub fn replaced(&self) -> usize {
+        self.results.iter().filter(|result| result.error.is_none()).count()
+    }
+}
+
+fn show_skipped(ui: &mut egui::Ui, ambiguous: &[(String, Vec<String>)], unmatched: &[String]) {
+    if !ambiguous.is_empty() {
+        egui::CollapsingHeader::new(format!("⚠ {} ambiguous", ambiguous.len()))
+            .id_salt("batch_replace_ambiguous")
+            .show(ui, |ui| {
+                ui.label("Their name doesn't single out one entry. Put them at the entry's path inside the folder to replace them.");
+                for (file, candidates) in ambiguous {
+                    ui.label(egui::RichText::new(file).strong());
+                    for candidate in candidates {
+                        ui.label(format!("    {}", candidate));
+                    }
+                }
+            });
+    }
+    if !unmatched.is_empty() {
+        egui::CollapsingHeader::new(format!("{} files without a matching entry", unmatched.len()))
+            .id_salt("batch_replace_unmatched")
+            .show(ui, |ui| {
+                for file in unmatched {
+                    ui.label(egui::RichText::new(file).weak());
+                }
+            });
+    }
+}
+
+impl RpaEditor {
+    /// Pairs the files below `folder_path` with entries. `images/bg.png` on disk goes to
+    /// the entry `images/bg.png`; files without such an entry fall back to the one entry
+    /// sharing their file name, if there is exactly one.
+    pub(crate) fn plan_batch_replace(&self, folder_path: &str) -> anyhow::Result<BatchReplacePlan> {
+        let folder = Path::new(folder_path);
+        let mut files = Vec::new();
+        collect_files(folder, folder, &mut files)?;
+        files.sort();
+
+        let mut by_basename: HashMap<&str, Vec<&String>> = HashMap::new();
+        for (name, entry) in &self.indexes {
+            if !entry.to_delete {
+                let basename = name.rsplit('/').next().unwrap_or(name);
+                by_basename.entry(basename).or_default().push(name);
+            }
+        }
+
+        let mut plan = BatchReplacePlan {
+            folder: folder_path.to_string(),
+            matches: Vec::new(),
+            ambiguous: Vec::new(),
+            unmatched: Vec::new(),
+        };
+        let mut fallback = Vec::new();
+        for (relative, path) in files {
+            if self.indexes.get(&relative).is_some_and(|entry| !entry.to_delete) {
+                plan.matches.push(BatchReplaceMatch {
+                    entry: relative.clone(),
+                    file: relative,
+                    path,
+                    by_name: false,
+                });
+                continue;
+            }
+            let basename = relative.rsplit('/').next().unwrap_or(&relative);
+            match by_basename.get(basename).map(Vec::as_slice) {
+                Some([name]) => fallback.push(BatchReplaceMatch {
+                    entry: (*name).clone(),
+                    file: relative,
+                    path,
+                    by_name: true,
+                }),
+                Some(candidates) => {
+                    let mut candidates: Vec<String> = candidates.iter().map(|name| (*name).clone()).collect();
+                    candidates.sort();
+                    plan.ambiguous.push((relative, candidates));
+                }
+                None => plan.unmatched.push(relative),
+            }
+        }
+
+        // An exact match beats a same-named file from another folder.
+        let claimed: HashSet<String> = plan.matches.iter().map(|item| item.entry.clone()).collect();
+        let mut fallback_counts: HashMap<String, usize> = HashMap::new();
+        for item in &fallback {
+            *fallback_counts.entry(item.entry.clone()).or_default() += 1;
+        }
+        for item in fallback {
+            if claimed.contains(&item.entry) {
+                plan.unmatched.push(item.file);
+            } else if fallback_counts[&item.entry] > 1 {
+                plan.ambiguous.push((item.file, vec![item.entry]));
+            } else {
+                plan.matches.push(item);
+            }
+        }
+        Ok(plan)
+    }
+
+    pub(crate) fn batch_replace_from_folder(&mut self, folder_path: &str) -> anyhow::Result<BatchReplaceReport> {
+        self.ensure_writable()?;
+        let plan = self.plan_batch_replace(folder_path)?;
+        let mut results = Vec::with_capacity(plan.matches.len());
+        for item in plan.matches {
+            let error = self
+                .replace_entry(&item.entry, &item.path.to_string_lossy(), false)
+                .err()
+                .map(|e| e.to_string());
+            if let Some(error) = &error {
+                error!("❌ Failed to replace {}: {}", item.entry, error);
+            }
+            results.push(BatchReplaceResult {
+                entry: item.entry,
+                file: item.file,
+                error,
+            });
+        }
+
+        let report = BatchReplaceReport {
+            folder: plan.folder,
+            results,
+            ambiguous: plan.ambiguous,
+            unmatched: plan.unmatched,
+        };
+        self.status_message = format!(
+            "Batch replaced {} of {} files, {} ambiguous, {} unmatched",
+            report.replaced(),
+            report.results.len(),
+            report.ambiguous.len(),
+            report.unmatched.len()
+        );
+        Ok(report)
+    }
+
+    pub(crate) fn show_batch_replace_dialog(&mut self, ui: &mut egui::Ui) {
+        ui.set_width(500.0);
+        ui.label("Replace multiple files from a folder");
+        ui.label("Files are matched by their path inside the folder, then by file name when only one entry has it");
+        ui.separator();
+
+        let mut refresh = false;
+        ui.horizontal(|ui| {
+            ui.label("📂 Folder:");
+            refresh |= ui.text_edit_singleline(&mut self.batch_replace_folder).lost_focus();
+            if ui.button("Browse...").clicked()
+                && let Some(folder) = rfd::FileDialog::new().pick_folder()
+            {
+                self.batch_replace_folder = folder.to_string_lossy().to_string();
+                refresh = true;
+            }
+            refresh |= ui.button("🔍 Preview").clicked();
+        });
+        if refresh && !self.batch_replace_folder.is_empty() {
+            match self.plan_batch_replace(&self.batch_replace_folder) {
+                Ok(plan) => self.batch_replace_plan = Some(plan),
+                Err(e) => {
+                    self.batch_replace_plan = None;
+                    self.add_error(format!("Batch replace error: {}", e));
+                }
+            }
+        }
+
+        if let Some(plan) = &self.batch_replace_plan
+            && plan.folder == self.batch_replace_folder
+        {
+            ui.separator();
+            ui.label(format!("{} entries will be replaced", plan.matches.len()));
+            let row_height = ui.spacing().interact_size.y;
+            egui::ScrollArea::vertical()
+                .max_height(250.0)
+                .auto_shrink([false, true])
+                .show_rows(ui, row_height, plan.matches.len(), |ui, rows| {
+                    for item in &plan.matches[rows] {
+                        ui.horizontal(|ui| {
+                            ui.label(&item.entry);
+                            if item.by_name {
+                                ui.label(egui::RichText::new(format!("← {} (by name)", item.file)).weak());
+                            }
+                        });
+                    }
+                });
+            show_skipped(ui, &plan.ambiguous, &plan.unmatched);
+        }
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            if ui.button("🔄 Replace All").clicked() && !self.batch_replace_folder.is_empty() {
+                self.batch_replace_to_execute = Some(self.batch_replace_folder.clone());
+                self.batch_replace_plan = None;
+                self.show_batch_replace_dialog = false;
+            }
+
+            if ui.button("❌ Cancel").clicked() {
+                self.show_batch_replace_dialog = false;
+                self.batch_replace_folder.clear();
+                self.batch_replace_plan = None;
+            }
+        });
+    }
+
+    pub(crate) fn show_batch_replace_report(&mut self, ui: &mut egui::Ui) {
+        let Some(report) = &self.batch_replace_report else {
+            return;
+        };
+        let failed = report.results.len() - report.replaced();
+        ui.label(format!("📂 {}", report.folder));
+        ui.label(format!("✅ {} replaced", report.replaced()));
+        if failed > 0 {
+            ui.colored_label(egui::Color32::LIGHT_RED, format!("❌ {} failed", failed));
+        }
+        ui.separator();
+
+        egui::ScrollArea::vertical()
+            .id_salt("batch_replace_results")
+            .max_height(300.0)
+            .show(ui, |ui| {
+                egui::Grid::new("batch_replace_results_grid").num_columns(3).striped(true).show(ui, |ui| {
+                    for result in &report.results {
+                        match &result.error {
+                            Some(error) => {
+                                ui.colored_label(egui::Color32::LIGHT_RED, "❌").on_hover_text(error);
+                            }
+                            None => {
+                                ui.label("✅");
+                            }
+                        }
+                        ui.label(&result.entry);
+                        match &result.error {
+                            Some(error) => ui.colored_label(egui::Color32::LIGHT_RED, error),
+                            None if result.file != result.entry => ui.weak(format!("← {}", result.file)),
+                            None => ui.label(""),
+                        };
+                        ui.end_row();
+                    }
+                });
+            });
+        show_skipped(ui, &report.ambiguous, &report.unmatched);
+
+        ui.separator();
+        if ui.button("Close").clicked() {
+            self.batch_replace_report = None;
+        }
+    }
+}
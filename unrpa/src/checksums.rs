@@ -0,0 +1,224 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use tracing::{debug, info};
+use crate::rpa::RpaEditor;
+use crate::sha256::Sha256;
+
+/// Outcome of comparing the archive with a checksum file.
+#[derive(Debug, Default)]
+pub struct ChecksumReport {
+    pub source: PathBuf,
+    pub matched: usize,
+    pub mismatched: Vec<String>,
+    /// Listed in the file but not in the archive.
+    pub missing: Vec<String>,
+    /// In the archive but not listed in the file.
+    pub extra: Vec<String>,
+}
+
+impl ChecksumReport {
+    pub fn is_clean(&self) -> bool {
+        self.mismatched.is_empty() && self.missing.is_empty() && self.extra.is_empty()
+    }
+}
+
+/// Reads `sha256sum` output: `<hex>  <name>`, with `*` marking binary mode.
+pub fn parse_checksum_file(text: &str) -> anyhow::Result<BTreeMap<String, String>> {
+    let mut checksums = BTreeMap::new();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim_end();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (hash, name) = line
+            .split_once(' ')
+            .filter(|(hash, _)| hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit()))
+            .ok_or_else(|| anyhow::anyhow!("Line {} is not a SHA-256 checksum", number + 1))?;
+        let name = name.strip_prefix([' ', '*']).unwrap_or(name);
+        checksums.insert(name.to_string(), hash.to_lowercase());
+    }
+    Ok(checksums)
+}
+
+impl RpaEditor {
+    fn live_entries(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .indexes
+            .iter()
+            .filter(|(_, entry)| !entry.to_delete)
+            .map(|(name, _)| name.clone())
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// Hashes the entries without a known checksum in a background job.
+    pub(crate) fn compute_checksums(&mut self) {
+        let missing: Vec<String> = self
+            .live_entries()
+            .into_iter()
+            .filter(|name| !self.checksums.contains_key(name))
+            .collect();
+        if missing.is_empty() {
+            self.finish_checksum_verify();
+            return;
+        }
+        self.add_toast(format!("Hashing {} entries...", missing.len()));
+        self.push_job("checksum", missing, None);
+        self.jobs_running = true;
+    }
+
+    /// Stores hashes from a finished job and completes a pending verification.
+    /// `generation` is the `edit_generation` the job read the entries at; when the
+    /// archive was edited since, the hashes may be of old data, so they are dropped
+    /// and whatever is still missing is hashed again.
+    pub(crate) fn apply_checksums(&mut self, hashes: Vec<(String, String)>, generation: u64) {
+        if generation != self.edit_generation {
+            debug!("⏭️ Dropping {} checksums: the archive was edited while hashing", hashes.len());
+            self.compute_checksums();
+            return;
+        }
+        for (name, hash) in hashes {
+            if self.indexes.contains_key(&name) {
+                self.checksums.insert(name, hash);
+            }
+        }
+        self.finish_checksum_verify();
+    }
+
+    fn checksum(&self, filename: &str) -> anyhow::Result<String> {
+        match self.checksums.get(filename) {
+            Some(hash) => Ok(hash.clone()),
+            None => {
+                let mut hasher = Sha256::default();
+                std::io::copy(&mut self.open_entry_stream(filename)?, &mut hasher)?;
+                Ok(hasher.finish())
+            }
+        }
+    }
+
+    /// Writes a `sha256sum`-compatible file; entries not hashed yet are hashed here.
+    pub(crate) fn export_checksums(&self, path: &Path) -> anyhow::Result<usize> {
+        let names = self.live_entries();
+        let mut out = BufWriter::new(File::create(path)?);
+        for name in &names {
+            writeln!(out, "{}  {}", self.checksum(name)?, name)?;
+        }
+        out.flush()?;
+        info!("🔐 Wrote {} checksums to {}", names.len(), path.display());
+        Ok(names.len())
+    }
+
+    /// Loads a checksum file and compares it once every entry has been hashed.
+    pub(crate) fn verify_checksum_file(&mut self, path: &Path) -> anyhow::Result<()> {
+        let expected = parse_checksum_file(&std::fs::read_to_string(path)?)?;
+        self.checksum_verify = Some((path.to_path_buf(), expected));
+        self.compute_checksums();
+        Ok(())
+    }
+
+    fn finish_checksum_verify(&mut self) {
+        let Some((source, expected)) = &self.checksum_verify else {
+            return;
+        };
+        let names = self.live_entries();
+        if names.iter().any(|name| !self.checksums.contains_key(name)) {
+            return;
+        }
+
+        let mut report = ChecksumReport {
+            source: source.clone(),
+            ..Default::default()
+        };
+        for name in &names {
+            match expected.get(name) {
+                Some(hash) if *hash == self.checksums[name] => report.matched += 1,
+                Some(_) => report.mismatched.push(name.clone()),
+                None => report.extra.push(name.clone()),
+            }
+        }
+        report.missing = expected
+            .keys()
+            .filter(|name| self.indexes.get(*name).is_none_or(|entry| entry.to_delete))
+            .cloned()
+            .collect();
+
+        info!(
+            "🔐 Checksums: {} match, {} differ, {} missing, {} extra",
+            report.matched,
+            report.mismatched.len(),
+            report.missing.len(),
+            report.extra.len()
+        );
+        self.checksum_verify = None;
+        self.checksum_report = Some(report);
+    }
+
+    pub(crate) fn show_checksum_report(&mut self, ui: &mut egui::Ui) {
+        let Some(report) = &self.checksum_report else {
+            return;
+        };
+        ui.label(format!("Checked against {}", report.source.display()));
+        if report.is_clean() {
+            ui.colored_label(
+                egui::Color32::GREEN,
+                format!("✅ All {} entries match", report.matched),
+            );
+        } else {
+            ui.label(format!("✅ {} entries match", report.matched));
+        }
+
+        egui::ScrollArea::vertical().max_height(350.0).show(ui, |ui| {
+            for (title, names) in [
+                ("❌ Content differs", &report.mismatched),
+                ("➖ Missing from the archive", &report.missing),
+                ("➕ Not in the checksum file", &report.extra),
+            ] {
+                if names.is_empty() {
+                    continue;
+                }
+                egui::CollapsingHeader::new(format!("{} ({})", title, names.len()))
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        for name in names {
+                            ui.label(name);
+                        }
+                    });
+            }
+        });
+
+        ui.separator();
+        if ui.button("❌ Close").clicked() {
+            self.checksum_report = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sha256::sha256_hex;
+
+    #[test]
+    fn hashes_of_entries_edited_while_hashing_are_dropped() {
+        let mut editor = RpaEditor::default();
+        editor.add_data("a.txt", b"old".to_vec()).unwrap();
+        let ctx = egui::Context::default();
+
+        editor.compute_checksums();
+        // Starts the job on the old bytes, then replaces them before its hashes come in.
+        editor.process_jobs(&ctx);
+        editor.add_data("a.txt", b"new".to_vec()).unwrap();
+
+        for _ in 0..500 {
+            editor.process_jobs(&ctx);
+            if editor.jobs.iter().all(|job| job.status != "running" && job.status != "queued") {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert_eq!(editor.checksums.get("a.txt"), Some(&sha256_hex(b"new")));
+    }
+}
@@ -0,0 +1,75 @@
+use tracing::info;
+use crate::orphans::HEADER_PADDING_MAX;
+use crate::rpa::RpaEditor;
+
+impl RpaEditor {
+    /// Bytes of the data area no live entry reads: orphaned gaps plus the old data of
+    /// removed and replaced entries. Saving drops them.
+    pub(crate) fn slack_bytes(&self) -> u64 {
+        if self.archive_path.is_none() {
+            return 0;
+        }
+        let data_start = self.header_line.len() as u64 + 1;
+        let mut used: Vec<(u64, u64)> = self
+            .indexes
+            .values()
+            .filter(|entry| entry.in_archive() && !entry.to_delete)
+            .flat_map(|entry| entry.segments.iter())
+            .map(|segment| {
+                let stored = segment.length.saturating_sub(segment.prefix.len() as u64);
+                let start = segment.offset.max(data_start).min(self.index_offset);
+                (start, segment.offset.saturating_add(stored).max(start).min(self.index_offset))
+            })
+            .collect();
+        used.sort();
+        // Padding after the header isn't slack; every save writes it again.
+        let data_start = match used.first() {
+            Some(&(start, _)) if start - data_start <= HEADER_PADDING_MAX => start,
+            _ => data_start,
+        };
+
+        let mut live = 0;
+        let mut cursor = data_start;
+        for (start, end) in used {
+            let start = start.max(cursor);
+            if end > start {
+                live += end - start;
+                cursor = end;
+            }
+        }
+        self.index_offset.saturating_sub(data_start).saturating_sub(live)
+    }
+
+    /// Rewrites the archive with only its live entries and reloads it. Returns the bytes
+    /// reclaimed, which is zero if the file didn't shrink. Pending changes have to be
+    /// saved first, through the review, so compacting never saves them unseen.
+    pub(crate) fn compact_archive(&mut self) -> anyhow::Result<u64> {
+        self.ensure_writable()?;
+        let path = self
+            .archive_path
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("No archive loaded"))?;
+        if self.modified {
+            return Err(anyhow::anyhow!("Save or discard the pending changes first"));
+        }
+        if self.jobs_running {
+            return Err(anyhow::anyhow!("Wait for the background jobs to finish"));
+        }
+        let before = std::fs::metadata(&path)?.len();
+        self.save_rpa(&path)?;
+        let after = std::fs::metadata(&path)?.len();
+        self.reload_after_save()?;
+        self.remove_autosave();
+
+        let reclaimed = before.saturating_sub(after);
+        self.status_message = format!(
+            "Compacted {}: {} → {} ({} reclaimed)",
+            path,
+            Self::format_bytes(before),
+            Self::format_bytes(after),
+            Self::format_bytes(reclaimed)
+        );
+        info!("🧹 {}", self.status_message);
+        Ok(reclaimed)
+    }
+}
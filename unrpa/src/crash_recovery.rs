@@ -0,0 +1,220 @@
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::{error, info, warn};
+use crate::backup_store::sanitize;
+use crate::health::config_dir;
+use crate::rpa::RpaEditor;
+use crate::workspace::WorkspaceManifest;
+
+/// Recovery files are workspaces, so they can also be imported by hand.
+const RECOVERY_EXTENSION: &str = "rpaws";
+
+/// What the last panic said, written next to the recovery files.
+const PANIC_NOTE: &str = "panic.txt";
+
+static LAST_PANIC: Mutex<Option<String>> = Mutex::new(None);
+
+pub fn recovery_dir() -> PathBuf {
+    config_dir().join("recovery")
+}
+
+/// Logs panics (they'd otherwise only reach stderr) and remembers the message for
+/// the recovery files. The previous hook still prints the usual report.
+pub fn install_panic_hook() {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |panic| {
+        let message = panic
+            .payload()
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| panic.payload().downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string());
+        let location = panic
+            .location()
+            .map(|location| format!(" at {}:{}", location.file(), location.line()))
+            .unwrap_or_default();
+        let thread = std::thread::current().name().unwrap_or("unnamed").to_string();
+        let report = format!("{}{} (thread {})", message, location, thread);
+        error!("💥 Panic: {}", report);
+        if let Ok(mut last) = LAST_PANIC.lock() {
+            *last = Some(report);
+        }
+        previous(panic);
+    }));
+}
+
+/// A recovery file found at startup.
+pub struct CrashRecovery {
+    pub path: PathBuf,
+    pub manifest: WorkspaceManifest,
+}
+
+impl CrashRecovery {
+    fn title(&self) -> String {
+        if self.manifest.archive.is_empty() {
+            "Untitled archive".to_string()
+        } else {
+            self.manifest.archive.clone()
+        }
+    }
+}
+
+impl RpaEditor {
+    /// Writes the pending edits of the active archive to the recovery folder.
+    fn write_recovery_file(&self, tab: usize) -> anyhow::Result<PathBuf> {
+        let dir = recovery_dir();
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join(format!(
+            "{}-{}-{}.{}",
+            chrono::Local::now().format("%Y%m%d-%H%M%S"),
+            tab,
+            sanitize(&self.archive_backup_name()),
+            RECOVERY_EXTENSION
+        ));
+        self.write_workspace(&path)?;
+        Ok(path)
+    }
+
+    /// Saves the unsaved changes of every open archive after a panic in the UI, so the
+    /// next launch can offer them back. Tabs without changes are skipped.
+    pub(crate) fn write_crash_recovery(&mut self) {
+        let mut written = Vec::new();
+        if self.modified {
+            written.push(self.write_recovery_file(self.active_tab));
+        }
+        for index in self.modified_background_tabs() {
+            if let Some(result) = self.with_background_tab(index, |editor| editor.write_recovery_file(index)) {
+                written.push(result);
+            }
+        }
+        if written.is_empty() {
+            return;
+        }
+
+        let panic = LAST_PANIC.lock().ok().and_then(|last| last.clone()).unwrap_or_default();
+        let _ = std::fs::write(recovery_dir().join(PANIC_NOTE), panic);
+        for result in written {
+            match result {
+                Ok(path) => error!("🛟 Saved unsaved changes to {}", path.display()),
+                Err(e) => error!("❌ Could not save unsaved changes: {}", e),
+            }
+        }
+    }
+
+    /// Looks for changes left behind by a crash; called at startup.
+    pub(crate) fn find_crash_recovery(&mut self) {
+        let Ok(files) = std::fs::read_dir(recovery_dir()) else {
+            return;
+        };
+        let mut found: Vec<CrashRecovery> = files
+            .flatten()
+            .map(|file| file.path())
+            .filter(|path| path.extension().is_some_and(|extension| extension == RECOVERY_EXTENSION))
+            .filter_map(|path| match Self::read_workspace_manifest(&path) {
+                Ok(manifest) => Some(CrashRecovery { path, manifest }),
+                Err(e) => {
+                    warn!("⚠️ Unreadable recovery file {}: {}", path.display(), e);
+                    None
+                }
+            })
+            .collect();
+        found.sort_by(|a, b| a.path.cmp(&b.path));
+        if !found.is_empty() {
+            self.crash_panic_message = std::fs::read_to_string(recovery_dir().join(PANIC_NOTE)).unwrap_or_default();
+            info!("🛟 Found {} archives with changes from a crash", found.len());
+        }
+        self.crash_recoveries = found;
+    }
+
+    /// Opens the recovery's archive in a new tab with its changes applied.
+    fn restore_crash_recovery(&mut self, index: usize) -> anyhow::Result<()> {
+        let recovery = self.crash_recoveries.remove(index);
+        let archive = recovery.manifest.archive.clone();
+        if !archive.is_empty() && !Path::new(&archive).is_file() {
+            self.crash_recoveries.insert(index, recovery);
+            return Err(anyhow::anyhow!("{} no longer exists", archive));
+        }
+
+        let mut result = Ok(0);
+        self.open_in_new_tab_with(&archive, |editor, archive| {
+            result = editor.import_workspace(&recovery.path, archive);
+        });
+        match result {
+            Ok(count) => {
+                let _ = std::fs::remove_file(&recovery.path);
+                self.status_message = format!("Restored {} unsaved changes to {}", count, recovery.title());
+                info!("🛟 {}", self.status_message);
+                self.finish_crash_recovery();
+                Ok(())
+            }
+            Err(e) => {
+                self.crash_recoveries.insert(index, recovery);
+                Err(e)
+            }
+        }
+    }
+
+    fn discard_crash_recovery(&mut self, index: usize) {
+        let recovery = self.crash_recoveries.remove(index);
+        let _ = std::fs::remove_file(&recovery.path);
+        info!("🗑️ Discarded recovered changes to {}", recovery.title());
+        self.finish_crash_recovery();
+    }
+
+    /// Removes the panic note once every recovery is dealt with.
+    fn finish_crash_recovery(&mut self) {
+        if self.crash_recoveries.is_empty() {
+            let _ = std::fs::remove_file(recovery_dir().join(PANIC_NOTE));
+            self.crash_panic_message.clear();
+        }
+    }
+
+    pub(crate) fn show_crash_recovery_dialog(&mut self, ui: &mut egui::Ui) {
+        ui.label("The editor closed unexpectedly last time. These unsaved changes were kept:");
+        if !self.crash_panic_message.is_empty() {
+            ui.label(egui::RichText::new(&self.crash_panic_message).small().weak());
+        }
+        ui.separator();
+
+        let mut action = None;
+        egui::Grid::new("crash_recoveries").num_columns(3).striped(true).show(ui, |ui| {
+            for (index, recovery) in self.crash_recoveries.iter().enumerate() {
+                ui.label(recovery.title());
+                ui.label(format!(
+                    "{} changed, {} deleted · {}",
+                    recovery.manifest.pending.len(),
+                    recovery.manifest.deleted.len(),
+                    recovery
+                        .manifest
+                        .created
+                        .with_timezone(&chrono::Local)
+                        .format("%Y-%m-%d %H:%M")
+                ));
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(!self.read_only, egui::Button::new("🛟 Restore")).clicked() {
+                        action = Some(("restore", index));
+                    }
+                    if ui.button("🗑️ Discard").clicked() {
+                        action = Some(("discard", index));
+                    }
+                });
+                ui.end_row();
+            }
+        });
+
+        match action {
+            Some(("restore", index)) => {
+                if let Err(e) = self.restore_crash_recovery(index) {
+                    self.add_error(format!("Restore failed: {}", e));
+                }
+            }
+            Some(("discard", index)) => self.discard_crash_recovery(index),
+            _ => {}
+        }
+
+        ui.separator();
+        if ui.button("Decide Later").on_hover_text("Keep the changes and ask again next launch").clicked() {
+            self.crash_recoveries.clear();
+        }
+    }
+}
@@ -0,0 +1,645 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use tracing::warn;
+use crate::rpa::RpaEditor;
+use crate::rpyc::{self, PyValue};
+
+const CSV_HEADER: &[&str] = &[
+    "file", "line", "label", "character", "kind", "identifier", "text", "translation",
+];
+
+/// Statements whose quoted strings aren't dialogue.
+const NON_DIALOGUE_KEYWORDS: &[&str] = &[
+    "define", "default", "image", "show", "scene", "hide", "with", "jump", "call", "return",
+    "play", "queue", "stop", "voice", "translate", "old", "new", "style", "text", "textbutton",
+    "add", "imagebutton", "key", "action", "renpy", "$", "window", "pause", "nvl", "if", "elif",
+    "while", "config", "init", "transform", "font", "size", "color",
+];
+
+/// Block headers whose bodies are skipped entirely.
+const SKIPPED_BLOCKS: &[&str] = &["python", "init python", "screen", "style", "transform"];
+
+#[derive(Debug, Clone)]
+pub struct DialogueLine {
+    pub file: String,
+    pub line: usize,
+    pub label: String,
+    pub who: String,
+    pub kind: String,
+    /// Id of the implicit translate block around the line, known for `.rpyc` sources.
+    pub identifier: String,
+    pub text: String,
+    pub translation: String,
+}
+
+/// Reads a Ren'Py string literal starting at the opening quote, returning it and the rest.
+fn parse_string(input: &str) -> Option<(String, &str)> {
+    let quote = input.chars().next().filter(|c| matches!(c, '"' | '\'' | '`'))?;
+    let mut text = String::new();
+    let mut chars = input.char_indices().skip(1);
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some((_, 'n')) => text.push('\n'),
+                Some((_, other)) => text.push(other),
+                None => return None,
+            },
+            c if c == quote => return Some((text, &input[i + 1..])),
+            c => text.push(c),
+        }
+    }
+    None
+}
+
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+/// Line-based extraction from `.rpy` source.
+fn extract_from_source(file: &str, source: &str) -> Vec<DialogueLine> {
+    let mut lines = Vec::new();
+    let mut label = String::new();
+    let mut skip_below: Option<usize> = None;
+
+    for (number, raw) in source.lines().enumerate() {
+        let trimmed = raw.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let indent = indentation(raw);
+        if let Some(level) = skip_below {
+            if indent > level {
+                continue;
+            }
+            skip_below = None;
+        }
+
+        let first_word = trimmed.split_whitespace().next().unwrap_or_default();
+        let header = trimmed.trim_end_matches(':');
+        if trimmed.ends_with(':')
+            && SKIPPED_BLOCKS.iter().any(|block| {
+                header.starts_with(block)
+                    || (header.starts_with("init ") && header.contains(" python"))
+            })
+        {
+            skip_below = Some(indent);
+            continue;
+        }
+
+        if first_word == "label" {
+            label = trimmed["label".len()..]
+                .trim()
+                .split(['(', ':', ' '])
+                .next()
+                .unwrap_or_default()
+                .to_string();
+            continue;
+        }
+        if NON_DIALOGUE_KEYWORDS.contains(&first_word) {
+            continue;
+        }
+
+        let Some(quote_at) = trimmed.find(['"', '\'', '`']) else {
+            continue;
+        };
+        let prefix = trimmed[..quote_at].trim();
+        let valid_prefix = prefix
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '.' || c == ' ' || c == '@' || c == '-');
+        if !valid_prefix {
+            continue;
+        }
+        let Some((text, rest)) = parse_string(&trimmed[quote_at..]) else {
+            continue;
+        };
+
+        let rest = rest.trim();
+        let kind = if prefix.is_empty() && rest.ends_with(':') {
+            "menu"
+        } else {
+            "dialogue"
+        };
+        lines.push(DialogueLine {
+            file: file.to_string(),
+            line: number + 1,
+            label: label.clone(),
+            who: prefix.split_whitespace().next().unwrap_or_default().to_string(),
+            kind: kind.to_string(),
+            identifier: String::new(),
+            text,
+            translation: String::new(),
+        });
+    }
+
+    lines
+}
+
+fn walk_statements(file: &str, nodes: &[PyValue], label: &mut String, out: &mut Vec<DialogueLine>) {
+    for node in nodes {
+        let class = node.class_name().unwrap_or_default();
+        let class = class.rsplit('.').next().unwrap_or_default();
+        let line = match node.attr("linenumber") {
+            PyValue::Int(n) => n as usize,
+            _ => 0,
+        };
+        match class {
+            "Label" => {
+                *label = node.attr("name").as_str().unwrap_or_default();
+                walk_statements(file, &node.attr("block").items(), label, out);
+            }
+            "Say" | "TranslateSay" => out.push(DialogueLine {
+                file: file.to_string(),
+                line,
+                label: label.clone(),
+                who: node.attr("who").as_str().unwrap_or_default(),
+                kind: "dialogue".to_string(),
+                identifier: String::new(),
+                text: node.attr("what").as_str().unwrap_or_default(),
+                translation: String::new(),
+            }),
+            "Menu" => {
+                for item in node.attr("items").items() {
+                    let parts = item.items();
+                    if let Some(caption) = parts.first().and_then(PyValue::as_str) {
+                        out.push(DialogueLine {
+                            file: file.to_string(),
+                            line,
+                            label: label.clone(),
+                            who: String::new(),
+                            kind: "menu".to_string(),
+                            identifier: String::new(),
+                            text: caption,
+                            translation: String::new(),
+                        });
+                    }
+                    if let Some(block) = parts.get(2) {
+                        walk_statements(file, &block.items(), label, out);
+                    }
+                }
+            }
+            "If" => {
+                for entry in node.attr("entries").items() {
+                    if let Some(block) = entry.items().get(1) {
+                        walk_statements(file, &block.items(), label, out);
+                    }
+                }
+            }
+            "While" | "Init" => walk_statements(file, &node.attr("block").items(), label, out),
+            "Translate" if node.attr("language").is_none() => {
+                let start = out.len();
+                walk_statements(file, &node.attr("block").items(), label, out);
+                let identifier = node.attr("identifier").as_str().unwrap_or_default();
+                for line in &mut out[start..] {
+                    if line.kind == "dialogue" {
+                        line.identifier = identifier.clone();
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+pub(crate) fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn write_csv(path: &Path, lines: &[DialogueLine]) -> anyhow::Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    writeln!(out, "{}", CSV_HEADER.join(","))?;
+    for line in lines {
+        writeln!(
+            out,
+            "{},{},{},{},{},{},{},{}",
+            csv_field(&line.file),
+            line.line,
+            csv_field(&line.label),
+            csv_field(&line.who),
+            line.kind,
+            csv_field(&line.identifier),
+            csv_field(&line.text),
+            csv_field(&line.translation)
+        )?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+fn po_string(value: &str) -> String {
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\t', "\\t");
+    format!("\"{}\"", escaped)
+}
+
+/// gettext rejects duplicate msgctxt/msgid pairs, so repeated lines are merged.
+fn write_po(path: &Path, lines: &[DialogueLine]) -> anyhow::Result<()> {
+    let mut order: Vec<(String, String)> = Vec::new();
+    let mut merged: HashMap<(String, String), Vec<&DialogueLine>> = HashMap::new();
+    for line in lines {
+        let key = (line.label.clone(), line.text.clone());
+        if !merged.contains_key(&key) {
+            order.push(key.clone());
+        }
+        merged.entry(key).or_default().push(line);
+    }
+
+    let mut out = BufWriter::new(File::create(path)?);
+    writeln!(out, "msgid \"\"")?;
+    writeln!(out, "msgstr \"\"")?;
+    writeln!(out, "\"Content-Type: text/plain; charset=UTF-8\\n\"")?;
+
+    for key in order {
+        let occurrences = &merged[&key];
+        writeln!(out)?;
+        let who: Vec<&str> = occurrences
+            .iter()
+            .map(|l| l.who.as_str())
+            .filter(|w| !w.is_empty())
+            .collect();
+        if let Some(who) = who.first() {
+            writeln!(out, "#. {}", who)?;
+        } else if occurrences[0].kind == "menu" {
+            writeln!(out, "#. menu choice")?;
+        }
+        for identifier in occurrences.iter().filter(|l| !l.identifier.is_empty()) {
+            writeln!(out, "#. id: {}", identifier.identifier)?;
+        }
+        let references: Vec<String> = occurrences
+            .iter()
+            .map(|l| format!("{}:{}", l.file, l.line))
+            .collect();
+        writeln!(out, "#: {}", references.join(" "))?;
+        if !key.0.is_empty() {
+            writeln!(out, "msgctxt {}", po_string(&key.0))?;
+        }
+        writeln!(out, "msgid {}", po_string(&key.1))?;
+        writeln!(out, "msgstr {}", po_string(&occurrences[0].translation))?;
+    }
+
+    out.flush()?;
+    Ok(())
+}
+
+impl RpaEditor {
+    /// Dialogue and menu strings from every script, preferring `.rpy` over its `.rpyc`.
+    pub(crate) fn collect_dialogue(&self) -> Vec<DialogueLine> {
+        let mut files: Vec<&String> = self
+            .indexes
+            .iter()
+            .filter(|(name, entry)| {
+                let lower = name.to_lowercase();
+                !entry.to_delete
+                    && !name.starts_with("tl/")
+                    && (lower.ends_with(".rpy") || lower.ends_with(".rpyc"))
+            })
+            .map(|(name, _)| name)
+            .collect();
+        files.sort();
+
+        let mut lines = Vec::new();
+        for filename in files {
+            let is_compiled = filename.to_lowercase().ends_with(".rpyc");
+            if is_compiled && self.indexes.contains_key(&filename[..filename.len() - 1]) {
+                continue;
+            }
+            let Ok(data) = self.load_file_data(filename) else {
+                continue;
+            };
+
+            if is_compiled {
+                match rpyc::load_statements(&data) {
+                    Ok(statements) => {
+                        walk_statements(filename, &statements, &mut String::new(), &mut lines)
+                    }
+                    Err(e) => warn!("⚠️ Could not read {}: {}", filename, e),
+                }
+            } else {
+                lines.extend(extract_from_source(filename, &String::from_utf8_lossy(&data)));
+            }
+        }
+        lines
+    }
+
+    /// Writes CSV or gettext `.po` depending on the extension of `path`.
+    pub(crate) fn export_dialogue(&self, path: &Path) -> anyhow::Result<usize> {
+        let lines = self.collect_dialogue();
+        if lines.is_empty() {
+            return Err(anyhow::anyhow!("No dialogue found in .rpy/.rpyc scripts"));
+        }
+
+        let is_po = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("po") || ext.eq_ignore_ascii_case("pot"));
+        if is_po {
+            write_po(path, &lines)?;
+        } else {
+            write_csv(path, &lines)?;
+        }
+        Ok(lines.len())
+    }
+}
+
+/// Splits CSV text into records, honouring quoted fields with embedded newlines.
+pub(crate) fn parse_csv(text: &str) -> Vec<Vec<String>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => record.push(std::mem::take(&mut field)),
+            '\r' if !in_quotes => {}
+            '\n' if !in_quotes => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            c => field.push(c),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    records
+}
+
+fn read_csv(text: &str) -> anyhow::Result<Vec<DialogueLine>> {
+    let mut records = parse_csv(text).into_iter();
+    let header = records.next().ok_or_else(|| anyhow::anyhow!("Empty CSV"))?;
+    let column = |name: &str| header.iter().position(|h| h.trim() == name);
+    let text_column = column("text").ok_or_else(|| anyhow::anyhow!("CSV has no text column"))?;
+    let translation_column =
+        column("translation").ok_or_else(|| anyhow::anyhow!("CSV has no translation column"))?;
+
+    let get = |record: &[String], index: Option<usize>| {
+        index.and_then(|i| record.get(i)).cloned().unwrap_or_default()
+    };
+    Ok(records
+        .map(|record| DialogueLine {
+            file: get(&record, column("file")),
+            line: get(&record, column("line")).parse().unwrap_or(0),
+            label: get(&record, column("label")),
+            who: get(&record, column("character")),
+            kind: get(&record, column("kind")),
+            identifier: get(&record, column("identifier")),
+            text: get(&record, Some(text_column)),
+            translation: get(&record, Some(translation_column)),
+        })
+        .collect())
+}
+
+fn po_unquote(value: &str) -> String {
+    let inner = value.trim().trim_start_matches('"').trim_end_matches('"');
+    let mut text = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => text.push('\n'),
+            Some('t') => text.push('\t'),
+            Some(other) => text.push(other),
+            None => {}
+        }
+    }
+    text
+}
+
+#[derive(Default)]
+struct PoEntry {
+    references: Vec<(String, usize)>,
+    identifiers: Vec<String>,
+    who: String,
+    menu: bool,
+    context: String,
+    msgid: String,
+    msgstr: String,
+}
+
+impl PoEntry {
+    /// One line per reference, plus one per translate-block id.
+    fn into_lines(self, out: &mut Vec<DialogueLine>) {
+        if self.msgid.is_empty() {
+            return;
+        }
+        let kind = if self.menu { "menu" } else { "dialogue" };
+        let line = |file: String, line: usize, identifier: String| DialogueLine {
+            file,
+            line,
+            label: self.context.clone(),
+            who: self.who.clone(),
+            kind: kind.to_string(),
+            identifier,
+            text: self.msgid.clone(),
+            translation: self.msgstr.clone(),
+        };
+        for (file, number) in &self.references {
+            out.push(line(file.clone(), *number, String::new()));
+        }
+        for identifier in &self.identifiers {
+            out.push(line(String::new(), 0, identifier.clone()));
+        }
+    }
+}
+
+fn read_po(text: &str) -> Vec<DialogueLine> {
+    let mut lines = Vec::new();
+    let mut entry = PoEntry::default();
+    let mut field: Option<&str> = None;
+
+    for raw in text.lines() {
+        let line = raw.trim();
+        if line.is_empty() {
+            std::mem::take(&mut entry).into_lines(&mut lines);
+            field = None;
+        } else if let Some(refs) = line.strip_prefix("#:") {
+            for reference in refs.split_whitespace() {
+                if let Some((file, number)) = reference.rsplit_once(':') {
+                    entry.references.push((file.to_string(), number.parse().unwrap_or(0)));
+                }
+            }
+        } else if let Some(comment) = line.strip_prefix("#.") {
+            let comment = comment.trim();
+            if let Some(identifier) = comment.strip_prefix("id:") {
+                entry.identifiers.push(identifier.trim().to_string());
+            } else if comment == "menu choice" {
+                entry.menu = true;
+            } else {
+                entry.who = comment.to_string();
+            }
+        } else if line.starts_with('#') {
+            continue;
+        } else if let Some(value) = line.strip_prefix("msgctxt ") {
+            entry.context = po_unquote(value);
+            field = Some("msgctxt");
+        } else if let Some(value) = line.strip_prefix("msgid ") {
+            entry.msgid = po_unquote(value);
+            field = Some("msgid");
+        } else if let Some(value) = line.strip_prefix("msgstr ") {
+            entry.msgstr = po_unquote(value);
+            field = Some("msgstr");
+        } else if line.starts_with('"') {
+            let value = po_unquote(line);
+            match field {
+                Some("msgctxt") => entry.context.push_str(&value),
+                Some("msgid") => entry.msgid.push_str(&value),
+                Some("msgstr") => entry.msgstr.push_str(&value),
+                _ => {}
+            }
+        }
+    }
+    entry.into_lines(&mut lines);
+    lines
+}
+
+/// Replaces the string literal equal to `original` on one source line.
+fn patch_line(line: &str, original: &str, translation: &str) -> Option<String> {
+    for (start, c) in line.char_indices() {
+        if !matches!(c, '"' | '\'' | '`') {
+            continue;
+        }
+        if let Some((text, rest)) = parse_string(&line[start..])
+            && text == original
+        {
+            let end = line.len() - rest.len();
+            return Some(format!("{}{}{}", &line[..start], rpyc::quote(translation), &line[end..]));
+        }
+    }
+    None
+}
+
+impl RpaEditor {
+    pub(crate) fn read_dialogue_translations(path: &Path) -> anyhow::Result<Vec<DialogueLine>> {
+        let text = std::fs::read_to_string(path)?;
+        let is_po = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("po") || ext.eq_ignore_ascii_case("pot"));
+        let lines = if is_po { read_po(&text) } else { read_csv(&text)? };
+        Ok(lines
+            .into_iter()
+            .filter(|line| !line.translation.is_empty() && line.translation != line.text)
+            .collect())
+    }
+
+    /// Rewrites translated strings in place inside `.rpy` entries; returns (patched, missed).
+    pub(crate) fn patch_dialogue_translations(&mut self, lines: &[DialogueLine]) -> anyhow::Result<(usize, usize)> {
+        self.ensure_writable()?;
+        let mut by_file: HashMap<&str, Vec<&DialogueLine>> = HashMap::new();
+        for line in lines.iter().filter(|l| l.file.to_lowercase().ends_with(".rpy") && l.line > 0) {
+            by_file.entry(line.file.as_str()).or_default().push(line);
+        }
+
+        let mut patched = 0;
+        let mut missed = 0;
+        for (file, translations) in by_file {
+            let Ok(data) = self.load_file_data(file) else {
+                missed += translations.len();
+                continue;
+            };
+            let source = String::from_utf8_lossy(&data).to_string();
+            let mut source_lines: Vec<String> =
+                source.split_inclusive('\n').map(str::to_string).collect();
+
+            let mut changed = false;
+            for translation in translations {
+                let patched_line = source_lines
+                    .get(translation.line - 1)
+                    .and_then(|l| patch_line(l, &translation.text, &translation.translation));
+                match patched_line {
+                    Some(new_line) => {
+                        source_lines[translation.line - 1] = new_line;
+                        patched += 1;
+                        changed = true;
+                    }
+                    None => {
+                        warn!("⚠️ {}:{} no longer matches the exported text", file, translation.line);
+                        missed += 1;
+                    }
+                }
+            }
+            if changed {
+                self.backup_before(file, "edit");
+                self.insert_data(file, source_lines.concat().into_bytes())?;
+                self.set_provenance(file, "dialogue translation import");
+            }
+        }
+
+        self.status_message = format!("Patched {} strings ({} not found)", patched, missed);
+        Ok((patched, missed))
+    }
+
+    /// Writes `tl/<language>/rpa_editor_import.rpy`; dialogue needs a translate-block id.
+    pub(crate) fn generate_tl_translations(&mut self, lines: &[DialogueLine], language: &str) -> anyhow::Result<(usize, usize)> {
+        self.ensure_writable()?;
+        if language.is_empty() || !language.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            return Err(anyhow::anyhow!("Language must be an identifier, e.g. french"));
+        }
+
+        let mut blocks = String::new();
+        let mut strings = String::new();
+        let mut seen_ids = std::collections::HashSet::new();
+        let mut seen_strings = std::collections::HashSet::new();
+        // PO entries list the source reference and the id as separate lines.
+        let texts_with_id: std::collections::HashSet<&str> = lines
+            .iter()
+            .filter(|l| !l.identifier.is_empty())
+            .map(|l| l.text.as_str())
+            .collect();
+        let mut written = 0;
+        let mut skipped = 0;
+
+        for line in lines {
+            if line.kind == "dialogue" {
+                if line.identifier.is_empty() {
+                    if !texts_with_id.contains(line.text.as_str()) {
+                        skipped += 1;
+                    }
+                    continue;
+                }
+                if !seen_ids.insert(line.identifier.clone()) {
+                    continue;
+                }
+                blocks.push_str(&format!("translate {} {}:\n", language, line.identifier));
+                let who = if line.who.is_empty() { String::new() } else { format!("{} ", line.who) };
+                blocks.push_str(&format!("    {}{}\n\n", who, rpyc::quote(&line.translation)));
+                written += 1;
+            } else if seen_strings.insert(line.text.clone()) {
+                strings.push_str(&format!("    old {}\n", rpyc::quote(&line.text)));
+                strings.push_str(&format!("    new {}\n\n", rpyc::quote(&line.translation)));
+                written += 1;
+            }
+        }
+
+        let mut output = format!("# Translations imported by RPA Editor\n\n{}", blocks);
+        if !strings.is_empty() {
+            output.push_str(&format!("translate {} strings:\n\n{}", language, strings));
+        }
+
+        let archive_name = format!("tl/{}/rpa_editor_import.rpy", language);
+        self.add_data(&archive_name, output.into_bytes())?;
+        self.set_provenance(&archive_name, "dialogue translation import");
+        self.status_message = format!(
+            "Wrote {} translations to {} ({} dialogue lines without a translate id)",
+            written, archive_name, skipped
+        );
+        Ok((written, skipped))
+    }
+}
@@ -0,0 +1,265 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use tracing::info;
+use crate::archive_index::read_archive_index;
+use crate::entry_stream::EntryStream;
+use crate::rpa::{fnv1a, RpaEditor, RpaFileEntry, FNV_OFFSET};
+use crate::rpyc;
+
+/// Largest `old lines × new lines` table the line diff builds; bigger changes are
+/// shown as one removed block followed by one added block.
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// The open archive compared with a base archive on disk.
+pub struct ArchiveDiff {
+    pub base_path: String,
+    pub base_indexes: HashMap<String, RpaFileEntry>,
+    /// Only in the open archive.
+    pub added: Vec<String>,
+    /// Only in the base archive.
+    pub removed: Vec<String>,
+    pub changed: Vec<String>,
+    pub unchanged: usize,
+}
+
+pub enum DiffLine {
+    Same(String),
+    Removed(String),
+    Added(String),
+}
+
+/// Line diff from the longest common subsequence, after trimming the shared ends.
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let old_middle = &old[prefix..old.len() - suffix];
+    let new_middle = &new[prefix..new.len() - suffix];
+
+    let mut lines: Vec<DiffLine> = old[..prefix].iter().map(|line| DiffLine::Same(line.to_string())).collect();
+    if old_middle.len() * new_middle.len() > MAX_DIFF_CELLS {
+        lines.extend(old_middle.iter().map(|line| DiffLine::Removed(line.to_string())));
+        lines.extend(new_middle.iter().map(|line| DiffLine::Added(line.to_string())));
+    } else {
+        // lcs[i][j]: common lines between old_middle[i..] and new_middle[j..].
+        let width = new_middle.len() + 1;
+        let mut lcs = vec![0u32; (old_middle.len() + 1) * width];
+        for i in (0..old_middle.len()).rev() {
+            for j in (0..new_middle.len()).rev() {
+                lcs[i * width + j] = if old_middle[i] == new_middle[j] {
+                    lcs[(i + 1) * width + j + 1] + 1
+                } else {
+                    lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < old_middle.len() || j < new_middle.len() {
+            if i < old_middle.len() && j < new_middle.len() && old_middle[i] == new_middle[j] {
+                lines.push(DiffLine::Same(old_middle[i].to_string()));
+                i += 1;
+                j += 1;
+            } else if j == new_middle.len()
+                || (i < old_middle.len() && lcs[(i + 1) * width + j] >= lcs[i * width + j + 1])
+            {
+                lines.push(DiffLine::Removed(old_middle[i].to_string()));
+                i += 1;
+            } else {
+                lines.push(DiffLine::Added(new_middle[j].to_string()));
+                j += 1;
+            }
+        }
+    }
+    lines.extend(old[old.len() - suffix..].iter().map(|line| DiffLine::Same(line.to_string())));
+    lines
+}
+
+impl ArchiveDiff {
+    fn base_data(&self, filename: &str) -> anyhow::Result<Vec<u8>> {
+        let entry = self
+            .base_indexes
+            .get(filename)
+            .ok_or_else(|| anyhow::anyhow!("{} isn't in the base archive", filename))?;
+        let mut stream = EntryStream::from_archive(File::open(&self.base_path)?, entry.segments.clone());
+        let mut data = Vec::new();
+        stream.read_to_end(&mut data)?;
+        Ok(data)
+    }
+}
+
+impl RpaEditor {
+    /// Compares every entry of the open archive with `base_path`, by size and then by hash.
+    pub(crate) fn diff_with_archive(&mut self, base_path: &str) -> anyhow::Result<()> {
+        let base = read_archive_index(base_path, false)?;
+        let mut diff = ArchiveDiff {
+            base_path: base_path.to_string(),
+            base_indexes: base.entries,
+            added: Vec::new(),
+            removed: Vec::new(),
+            changed: Vec::new(),
+            unchanged: 0,
+        };
+
+        for (name, entry) in self.indexes.iter().filter(|(_, entry)| !entry.to_delete) {
+            let Some(base_entry) = diff.base_indexes.get(name) else {
+                diff.added.push(name.clone());
+                continue;
+            };
+            let same = base_entry.length == entry.length
+                && fnv1a(FNV_OFFSET, &self.load_file_data(name)?) == fnv1a(FNV_OFFSET, &diff.base_data(name)?);
+            if same {
+                diff.unchanged += 1;
+            } else {
+                diff.changed.push(name.clone());
+            }
+        }
+        diff.removed = diff
+            .base_indexes
+            .keys()
+            .filter(|name| self.indexes.get(*name).is_none_or(|entry| entry.to_delete))
+            .cloned()
+            .collect();
+        diff.added.sort();
+        diff.removed.sort();
+        diff.changed.sort();
+
+        self.status_message = format!(
+            "{} added, {} removed, {} changed, {} unchanged compared with {}",
+            diff.added.len(),
+            diff.removed.len(),
+            diff.changed.len(),
+            diff.unchanged,
+            base_path
+        );
+        info!("🔀 {}", self.status_message);
+        self.archive_diff = Some(diff);
+        Ok(())
+    }
+
+    /// Script text for the line diff; `.rpyc` files are decompiled first.
+    fn diff_text(&self, filename: &str, data: &[u8]) -> Option<String> {
+        match self.get_preview_mode(filename) {
+            "rpyc" => rpyc::decompile(data).ok().or_else(|| self.decompile_rpyc(data)),
+            "text" => Some(String::from_utf8_lossy(data).to_string()),
+            _ => None,
+        }
+    }
+
+    fn can_drill_down(&self, filename: &str) -> bool {
+        matches!(self.get_preview_mode(filename), "rpyc" | "text" | "image")
+    }
+
+    /// Opens the text diff or the image comparison for a changed entry.
+    fn show_entry_diff(&mut self, filename: &str) -> anyhow::Result<()> {
+        let diff = self.archive_diff.as_ref().ok_or_else(|| anyhow::anyhow!("No diff"))?;
+        let before = diff.base_data(filename)?;
+        let after = self.load_file_data(filename)?;
+        if self.get_preview_mode(filename) == "image" {
+            return self.compare_images(filename, &before, &after);
+        }
+        let (Some(old), Some(new)) = (self.diff_text(filename, &before), self.diff_text(filename, &after)) else {
+            return Err(anyhow::anyhow!("{} can't be shown as text", filename));
+        };
+        self.entry_diff = Some((filename.to_string(), diff_lines(&old, &new)));
+        Ok(())
+    }
+
+    pub(crate) fn show_archive_diff(&mut self, ui: &mut egui::Ui) {
+        let Some(diff) = &self.archive_diff else {
+            return;
+        };
+        ui.label(format!("Base: {}", diff.base_path));
+        ui.label(format!(
+            "➕ {} added   ➖ {} removed   ✏️ {} changed   {} unchanged",
+            diff.added.len(),
+            diff.removed.len(),
+            diff.changed.len(),
+            diff.unchanged
+        ));
+        ui.separator();
+
+        let mut open = None;
+        egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+            let sections = [
+                ("✏️ Changed", &diff.changed, egui::Color32::YELLOW, true),
+                ("➕ Only in this archive", &diff.added, egui::Color32::LIGHT_GREEN, false),
+                ("➖ Only in the base archive", &diff.removed, egui::Color32::LIGHT_RED, false),
+            ];
+            for (title, names, color, comparable) in sections {
+                egui::CollapsingHeader::new(format!("{} ({})", title, names.len()))
+                    .default_open(!names.is_empty() && names.len() <= 50)
+                    .show(ui, |ui| {
+                        for name in names {
+                            ui.horizontal(|ui| {
+                                ui.colored_label(color, name);
+                                if comparable
+                                    && self.can_drill_down(name)
+                                    && ui.small_button("🔍 Show").clicked()
+                                {
+                                    open = Some(name.clone());
+                                }
+                            });
+                        }
+                    });
+            }
+        });
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            if ui.button("🩹 Export Patch...").on_hover_text("Save added and changed entries as a patch RPA").clicked()
+                && let Some(path) = rfd::FileDialog::new()
+                    .add_filter("RPA files", &["rpa"])
+                    .set_file_name("patch.rpa")
+                    .save_file()
+            {
+                match self.export_patch(&path) {
+                    Ok((count, 0)) => self.add_toast(format!("Patch written with {} entries", count)),
+                    Ok((count, removed)) => self.add_error(format!(
+                        "Patch written with {} entries; {} removed entries can't be expressed in a patch",
+                        count, removed
+                    )),
+                    Err(e) => self.add_error(format!("Patch error: {}", e)),
+                }
+            }
+            if ui.button("❌ Close").clicked() {
+                self.archive_diff = None;
+                self.entry_diff = None;
+            }
+        });
+        if let Some(name) = open
+            && let Err(e) = self.show_entry_diff(&name)
+        {
+            self.add_error(format!("Diff error: {}", e));
+        }
+    }
+
+    /// Unified view of a script's line changes.
+    pub(crate) fn show_entry_text_diff(&mut self, ui: &mut egui::Ui) {
+        let Some((filename, lines)) = &self.entry_diff else {
+            return;
+        };
+        ui.label(format!("📜 {}", filename));
+        ui.separator();
+        let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+        egui::ScrollArea::both().max_height(500.0).show_rows(ui, row_height, lines.len(), |ui, rows| {
+            for line in &lines[rows] {
+                let (text, color) = match line {
+                    DiffLine::Same(text) => (format!("  {}", text), ui.visuals().text_color()),
+                    DiffLine::Removed(text) => (format!("- {}", text), egui::Color32::LIGHT_RED),
+                    DiffLine::Added(text) => (format!("+ {}", text), egui::Color32::LIGHT_GREEN),
+                };
+                ui.label(egui::RichText::new(text).monospace().color(color));
+            }
+        });
+        if ui.button("❌ Close").clicked() {
+            self.entry_diff = None;
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+use crate::rpa::RpaEditor;
+
+/// Entries with byte-identical content; the first name is the one kept.
+#[derive(Debug, Clone)]
+pub struct DuplicateGroup {
+    pub sha256: String,
+    pub size: u64,
+    pub names: Vec<String>,
+}
+
+impl DuplicateGroup {
+    /// Bytes the archive would save by keeping a single copy.
+    pub fn wasted(&self) -> u64 {
+        self.size * (self.names.len() as u64 - 1)
+    }
+}
+
+/// Groups `(name, size, sha256)` triples by content, largest savings first.
+pub fn duplicate_groups(hashes: Vec<(String, u64, String)>) -> Vec<DuplicateGroup> {
+    let mut by_content: HashMap<(u64, String), Vec<String>> = HashMap::new();
+    for (name, size, sha256) in hashes {
+        by_content.entry((size, sha256)).or_default().push(name);
+    }
+    let mut groups: Vec<DuplicateGroup> = by_content
+        .into_iter()
+        .filter(|(_, names)| names.len() > 1)
+        .map(|((size, sha256), mut names)| {
+            names.sort();
+            DuplicateGroup { sha256, size, names }
+        })
+        .collect();
+    groups.sort_by(|a, b| b.wasted().cmp(&a.wasted()).then_with(|| a.names.cmp(&b.names)));
+    groups
+}
+
+impl RpaEditor {
+    /// Hashes every entry in a background job; the groups show up when it finishes.
+    pub(crate) fn find_duplicates(&mut self) {
+        let mut entries: Vec<String> = self
+            .indexes
+            .iter()
+            .filter(|(_, entry)| !entry.to_delete)
+            .map(|(name, _)| name.clone())
+            .collect();
+        entries.sort();
+        let count = entries.len();
+        self.push_job("duplicates", entries, None);
+        self.jobs_running = true;
+        self.add_toast(format!("Hashing {} entries to find duplicates...", count));
+    }
+
+    /// Marks every copy but the first of group `index` for deletion.
+    pub(crate) fn remove_duplicates(&mut self, index: usize) -> usize {
+        if self.blocked_by_read_only() {
+            return 0;
+        }
+        let Some(groups) = &mut self.duplicate_groups else {
+            return 0;
+        };
+        let group = groups.remove(index);
+        for name in &group.names[1..] {
+            self.remove_file(name);
+        }
+        group.names.len() - 1
+    }
+
+    pub(crate) fn show_duplicates(&mut self, ui: &mut egui::Ui) {
+        let Some(groups) = &self.duplicate_groups else {
+            return;
+        };
+        let wasted: u64 = groups.iter().map(DuplicateGroup::wasted).sum();
+        ui.label(format!(
+            "{} groups of identical files, {} could be saved",
+            groups.len(),
+            Self::format_bytes(wasted)
+        ));
+        ui.weak("Removing a copy breaks scripts that refer to it by name.");
+        ui.separator();
+
+        let mut preview = None;
+        let mut extract = None;
+        let mut remove = None;
+        egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+            for (index, group) in groups.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.strong(format!(
+                        "{} copies of {}",
+                        group.names.len(),
+                        Self::format_bytes(group.size)
+                    ))
+                    .on_hover_text(format!("SHA-256 {}", group.sha256));
+                    if ui.button("📤 Extract Once").clicked() {
+                        extract = Some(group.names[0].clone());
+                    }
+                    if ui.button("🗑 Remove Duplicates").on_hover_text("Keeps the first name").clicked() {
+                        remove = Some(index);
+                    }
+                });
+                for (position, name) in group.names.iter().enumerate() {
+                    let icon = if position == 0 { "✅" } else { "♊" };
+                    if ui.selectable_label(false, format!("  {} {}", icon, name)).clicked() {
+                        preview = Some(name.clone());
+                    }
+                }
+                ui.separator();
+            }
+        });
+
+        ui.horizontal(|ui| {
+            if ui.button("❌ Close").clicked() {
+                self.duplicate_groups = None;
+            }
+        });
+
+        if let Some(name) = preview {
+            self.selected_file = Some(name.clone());
+            self.file_to_preview = Some(name);
+        }
+        if let Some(name) = extract
+            && let Some(path) = rfd::FileDialog::new()
+                .set_file_name(name.rsplit('/').next().unwrap_or(&name))
+                .save_file()
+        {
+            let outcome = self.write_entry(&name, path, "overwrite");
+            if outcome.result == "failed" {
+                self.add_error(format!("Extract Error: {}", outcome.detail));
+            } else {
+                self.add_toast(format!("Extracted {}", outcome.detail));
+            }
+        }
+        if let Some(index) = remove {
+            let removed = self.remove_duplicates(index);
+            self.add_toast(format!("Marked {} duplicates for deletion", removed));
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use std::fs::File;
+use std::io::{Cursor, Read, Seek, SeekFrom};
+use crate::rpa::RpaSegment;
+
+/// Read/Seek view of a single archive entry.
+///
+/// Unmodified entries are read straight from the archive file, so media can be
+/// streamed without copying the whole entry into memory first.
+pub enum EntryStream {
+    Archive {
+        file: File,
+        segments: Vec<RpaSegment>,
+        pos: u64,
+    },
+    Memory(Cursor<Vec<u8>>),
+}
+
+impl EntryStream {
+    pub fn from_archive(file: File, segments: Vec<RpaSegment>) -> Self {
+        EntryStream::Archive {
+            file,
+            segments,
+            pos: 0,
+        }
+    }
+
+    pub fn from_memory(data: Vec<u8>) -> Self {
+        EntryStream::Memory(Cursor::new(data))
+    }
+
+    pub fn total_len(&self) -> u64 {
+        match self {
+            EntryStream::Archive { segments, .. } => segments.iter().map(|s| s.length).sum(),
+            EntryStream::Memory(cursor) => cursor.get_ref().len() as u64,
+        }
+    }
+}
+
+impl Read for EntryStream {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            EntryStream::Memory(cursor) => cursor.read(buf),
+            EntryStream::Archive {
+                file,
+                segments,
+                pos,
+            } => {
+                if buf.is_empty() {
+                    return Ok(0);
+                }
+
+                let mut segment_start = 0;
+                for segment in segments.iter() {
+                    let segment_end = segment_start + segment.length;
+                    if *pos >= segment_end {
+                        segment_start = segment_end;
+                        continue;
+                    }
+
+                    let within = *pos - segment_start;
+                    let prefix_len = segment.prefix.len() as u64;
+
+                    if within < prefix_len {
+                        let from = within as usize;
+                        let n = std::cmp::min(buf.len(), segment.prefix.len() - from);
+                        buf[..n].copy_from_slice(&segment.prefix[from..from + n]);
+                        *pos += n as u64;
+                        return Ok(n);
+                    }
+
+                    let remaining = (segment_end - *pos) as usize;
+                    let want = std::cmp::min(buf.len(), remaining);
+                    file.seek(SeekFrom::Start(segment.offset + (within - prefix_len)))?;
+                    let n = file.read(&mut buf[..want])?;
+                    *pos += n as u64;
+                    return Ok(n);
+                }
+
+                Ok(0)
+            }
+        }
+    }
+}
+
+impl Seek for EntryStream {
+    fn seek(&mut self, target: SeekFrom) -> std::io::Result<u64> {
+        let total = self.total_len();
+        match self {
+            EntryStream::Memory(cursor) => cursor.seek(target),
+            EntryStream::Archive { pos, .. } => {
+                let new_pos = match target {
+                    SeekFrom::Start(n) => n as i64,
+                    SeekFrom::End(n) => total as i64 + n,
+                    SeekFrom::Current(n) => *pos as i64 + n,
+                };
+                if new_pos < 0 {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidInput,
+                        "Seek before start of entry",
+                    ));
+                }
+                *pos = new_pos as u64;
+                Ok(*pos)
+            }
+        }
+    }
+}
@@ -0,0 +1,190 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant, SystemTime};
+use tracing::info;
+use crate::rpa::RpaEditor;
+use crate::toast::Toast;
+
+/// How often the extracted copies are checked for saves.
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// An entry extracted for editing in another application.
+pub struct ExternalEdit {
+    pub entry: String,
+    pub path: PathBuf,
+    /// Modification time of the copy when it was extracted or last reimported.
+    pub synced_at: Option<SystemTime>,
+    /// Saved in the other application since, waiting to be reimported or ignored.
+    pub changed: bool,
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+/// Opens `path` with whatever the system associates with it, a folder in the file manager.
+pub fn open_with_default_app(path: &Path) -> std::io::Result<()> {
+    #[cfg(target_os = "windows")]
+    let mut command = {
+        let mut command = Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    };
+    #[cfg(target_os = "macos")]
+    let mut command = Command::new("open");
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let mut command = Command::new("xdg-open");
+
+    command.arg(path).spawn().map(|_| ())
+}
+
+impl RpaEditor {
+    /// Extracts `filename` and opens it in its associated application. Saves made there
+    /// are picked up by `poll_external_edits`.
+    pub(crate) fn open_in_external_editor(&mut self, filename: &str) -> anyhow::Result<()> {
+        let index = match self.external_edits.iter().position(|edit| edit.entry == filename) {
+            // Unimported changes stay on disk; otherwise start again from the archive.
+            Some(index) if self.external_edits[index].changed => index,
+            found => {
+                let path = self.stream_entry_to_dir(filename, &self.archive_temp_dir("edit"))?;
+                let edit = ExternalEdit {
+                    entry: filename.to_string(),
+                    synced_at: modified_time(&path),
+                    path,
+                    changed: false,
+                };
+                match found {
+                    Some(index) => {
+                        self.external_edits[index] = edit;
+                        index
+                    }
+                    None => {
+                        self.external_edits.push(edit);
+                        self.external_edits.len() - 1
+                    }
+                }
+            }
+        };
+
+        let path = &self.external_edits[index].path;
+        open_with_default_app(path)
+            .map_err(|e| anyhow::anyhow!("Failed to open {}: {}", path.display(), e))?;
+        self.status_message = format!("Editing {} externally, save it there to reimport", filename);
+        info!("✏️ Opened {} externally at {}", filename, path.display());
+        Ok(())
+    }
+
+    /// Flags edited copies whose file changed on disk. Cheap to call every frame.
+    pub(crate) fn poll_external_edits(&mut self) {
+        if self.external_edits.is_empty() || self.external_edits_polled.elapsed() < WATCH_INTERVAL {
+            return;
+        }
+        self.external_edits_polled = Instant::now();
+
+        let mut changed = Vec::new();
+        for edit in self.external_edits.iter_mut().filter(|edit| !edit.changed) {
+            let modified = modified_time(&edit.path);
+            if modified.is_some() && modified != edit.synced_at {
+                edit.changed = true;
+                changed.push(edit.entry.clone());
+            }
+        }
+        for entry in changed {
+            self.push_toast(Toast::new(format!("{} was changed in another app", entry)).with_action("external_edits"));
+            self.show_external_edits = true;
+        }
+    }
+
+    /// Replaces the entry with the edited copy.
+    pub(crate) fn reimport_external_edit(&mut self, index: usize) -> anyhow::Result<()> {
+        self.ensure_writable()?;
+        let Some(edit) = self.external_edits.get(index) else {
+            return Ok(());
+        };
+        let (entry, path) = (edit.entry.clone(), edit.path.clone());
+        let synced_at = modified_time(&path);
+        self.replace_entry(&entry, &path.to_string_lossy(), true)?;
+        self.set_provenance(&entry, format!("edited externally: {}", path.display()));
+        if let Some(edit) = self.external_edits.get_mut(index) {
+            edit.synced_at = synced_at;
+            edit.changed = false;
+        }
+        if self.selected_file.as_deref() == Some(entry.as_str()) {
+            self.preview_file(&entry);
+        }
+        Ok(())
+    }
+
+    /// Keeps watching without taking the current save.
+    fn ignore_external_edit(&mut self, index: usize) {
+        if let Some(edit) = self.external_edits.get_mut(index) {
+            edit.synced_at = modified_time(&edit.path);
+            edit.changed = false;
+        }
+    }
+
+    /// Stops watching the copy and deletes it.
+    fn stop_external_edit(&mut self, index: usize) {
+        if index < self.external_edits.len() {
+            let edit = self.external_edits.remove(index);
+            let _ = std::fs::remove_file(&edit.path);
+            info!("✏️ Stopped watching {}", edit.entry);
+        }
+    }
+
+    pub(crate) fn show_external_edits(&mut self, ui: &mut egui::Ui) {
+        ui.label("Entries open in other applications. Saves there can be brought back into the archive.");
+        ui.separator();
+
+        let mut action = None;
+        egui::Grid::new("external_edits").num_columns(3).striped(true).show(ui, |ui| {
+            for (index, edit) in self.external_edits.iter().enumerate() {
+                ui.label(&edit.entry).on_hover_text(edit.path.display().to_string());
+                if edit.changed {
+                    ui.colored_label(egui::Color32::YELLOW, "● Changed");
+                } else {
+                    ui.label(egui::RichText::new("Watching").weak());
+                }
+                ui.horizontal(|ui| {
+                    if edit.changed {
+                        if ui.button("📥 Reimport").clicked() {
+                            action = Some(("reimport", index));
+                        }
+                        if ui.button("Ignore").clicked() {
+                            action = Some(("ignore", index));
+                        }
+                    }
+                    if ui.button("↗ Open").clicked() {
+                        action = Some(("open", index));
+                    }
+                    if ui.button("⏹ Stop").on_hover_text("Stop watching and delete the copy").clicked() {
+                        action = Some(("stop", index));
+                    }
+                });
+                ui.end_row();
+            }
+        });
+
+        match action {
+            Some(("reimport", index)) => {
+                if let Err(e) = self.reimport_external_edit(index) {
+                    self.add_error(format!("Reimport error: {}", e));
+                }
+            }
+            Some(("ignore", index)) => self.ignore_external_edit(index),
+            Some(("open", index)) => {
+                let entry = self.external_edits[index].entry.clone();
+                if let Err(e) = self.open_in_external_editor(&entry) {
+                    self.add_error(format!("Can't open {}: {}", entry, e));
+                }
+            }
+            Some(("stop", index)) => self.stop_external_edit(index),
+            _ => {}
+        }
+
+        ui.separator();
+        if ui.button("Close").clicked() {
+            self.show_external_edits = false;
+        }
+    }
+}
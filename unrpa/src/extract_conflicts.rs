@@ -0,0 +1,263 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use tracing::info;
+use crate::glob::glob_match;
+use crate::rpa::RpaEditor;
+use crate::toast::Toast;
+
+/// What to do when an extracted file already exists, stored as the first element.
+pub const EXTRACT_POLICIES: &[(&str, &str)] = &[
+    ("skip", "Skip existing files"),
+    ("overwrite", "Overwrite existing files"),
+    ("rename", "Keep both, adding a number to the new file"),
+];
+
+/// How bulk extraction lays out files, stored as the first element. "archive" keeps the
+/// entry paths, so the output can be dropped into a game's `game/` folder as is.
+pub const EXTRACT_LAYOUTS: &[(&str, &str)] = &[
+    ("type", "Sort into folders by type"),
+    ("archive", "Keep archive paths"),
+];
+
+/// Result of extracting one entry.
+#[derive(Debug)]
+pub struct ExtractOutcome {
+    pub name: String,
+    /// "written", "overwritten", "renamed", "skipped" or "failed".
+    pub result: &'static str,
+    /// Path written, or the reason nothing was.
+    pub detail: String,
+}
+
+/// Per-file results of one extraction.
+#[derive(Debug, Default)]
+pub struct ExtractReport {
+    pub target: PathBuf,
+    pub outcomes: Vec<ExtractOutcome>,
+}
+
+impl ExtractReport {
+    pub fn count(&self, result: &str) -> usize {
+        self.outcomes.iter().filter(|outcome| outcome.result == result).count()
+    }
+
+    pub fn extracted(&self) -> usize {
+        self.count("written") + self.count("overwritten") + self.count("renamed")
+    }
+
+    /// Anything other than a plain write is worth showing the report for.
+    pub fn has_conflicts(&self) -> bool {
+        self.outcomes.iter().any(|outcome| outcome.result != "written")
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "Extracted {} files to {} ({} overwritten, {} renamed, {} skipped, {} failed)",
+            self.extracted(),
+            self.target.display(),
+            self.count("overwritten"),
+            self.count("renamed"),
+            self.count("skipped"),
+            self.count("failed")
+        )
+    }
+}
+
+/// A bulk extraction waiting for a policy: `kind` is "type" (a file type or "all")
+/// or "folder" (an entry prefix from the tree).
+pub struct PendingExtract {
+    pub kind: String,
+    pub value: String,
+    pub target: PathBuf,
+    /// Glob the entries must match, empty for all of them.
+    pub pattern: String,
+    /// Only take entries the file list currently shows.
+    pub respect_filter: bool,
+}
+
+/// `path` with " (n)" before the extension, for the first n that isn't taken.
+fn numbered_path(path: &Path) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = path.extension().map(|ext| ext.to_string_lossy());
+    (1..)
+        .map(|n| {
+            path.with_file_name(match &extension {
+                Some(ext) => format!("{} ({}).{}", stem, n, ext),
+                None => format!("{} ({})", stem, n),
+            })
+        })
+        .find(|candidate| !candidate.exists())
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+/// Where to write `path` under `policy` and how the outcome is reported; `None` skips it.
+pub fn resolve_conflict(path: PathBuf, policy: &str) -> Option<(PathBuf, &'static str)> {
+    if !path.exists() {
+        return Some((path, "written"));
+    }
+    match policy {
+        "skip" => None,
+        "rename" => Some((numbered_path(&path), "renamed")),
+        _ => Some((path, "overwritten")),
+    }
+}
+
+pub fn folder_has_files(folder: &Path) -> bool {
+    std::fs::read_dir(folder).is_ok_and(|mut items| items.next().is_some())
+}
+
+fn outcome_icon(result: &str) -> &'static str {
+    match result {
+        "written" => "✅",
+        "overwritten" => "♻️",
+        "renamed" => "🔢",
+        "skipped" => "⏭️",
+        _ => "❌",
+    }
+}
+
+impl RpaEditor {
+    /// Writes one entry to `path`, settling an existing file by `policy`.
+    pub(crate) fn write_entry(&self, filename: &str, path: PathBuf, policy: &str) -> ExtractOutcome {
+        let outcome = |result, detail: String| ExtractOutcome {
+            name: filename.to_string(),
+            result,
+            detail,
+        };
+        let Some((path, result)) = resolve_conflict(path.clone(), policy) else {
+            return outcome("skipped", format!("{} exists", path.display()));
+        };
+        match self.stream_entry_to_file(filename, &path) {
+            Ok(()) => outcome(result, path.display().to_string()),
+            Err(e) => outcome("failed", e.to_string()),
+        }
+    }
+
+
+    pub(crate) fn request_extract(&mut self, kind: &str, value: &str, target: PathBuf) {
+        self.submit_extract(PendingExtract {
+            kind: kind.to_string(),
+            value: value.to_string(),
+            target,
+            pattern: String::new(),
+            respect_filter: false,
+        });
+    }
+
+    /// Extraction from the Bulk Extract dialog, narrowed by its pattern and filter toggle.
+    pub(crate) fn request_bulk_extract(&mut self, file_type: &str, target: PathBuf) {
+        self.submit_extract(PendingExtract {
+            kind: "type".to_string(),
+            value: file_type.to_string(),
+            target,
+            pattern: self.extract_pattern.trim().to_string(),
+            respect_filter: self.extract_respect_filter,
+        });
+    }
+
+    /// Extracts right away into an empty folder, otherwise asks what to do with existing files.
+    fn submit_extract(&mut self, pending: PendingExtract) {
+        if folder_has_files(&pending.target) {
+            self.pending_extract = Some(pending);
+        } else {
+            self.run_extract(pending);
+        }
+    }
+
+    /// Queues the extraction as a job; the report comes back when it finishes.
+    pub(crate) fn run_extract(&mut self, pending: PendingExtract) {
+        let (entries, layout) = match pending.kind.as_str() {
+            "folder" => (self.entries_in_folder(&pending.value), "archive".to_string()),
+            _ => {
+                let include = self.extract_selector(&pending.pattern, pending.respect_filter);
+                (self.entries_of_type(&pending.value, include), self.extract_layout.clone())
+            }
+        };
+        let count = self.push_job("extract", entries, Some(pending.target));
+        if let Some(job) = self.jobs.last_mut() {
+            job.policy = self.extract_policy.clone();
+            job.layout = layout;
+        }
+        self.jobs_running = true;
+        info!("📤 Extracting {} entries", count);
+        self.push_toast(Toast::new(format!("Extracting {} entries in the background", count)).with_action("jobs"));
+    }
+
+    /// Predicate for entries matching `pattern` and, with `respect_filter`, the file list's
+    /// current search and type filter.
+    pub(crate) fn extract_selector<'a>(&'a self, pattern: &'a str, respect_filter: bool) -> impl Fn(&str) -> bool + 'a {
+        let visible: Option<HashSet<&str>> = respect_filter.then(|| {
+            self.get_filtered_sorted_files()
+                .into_iter()
+                .map(|(name, _)| name.as_str())
+                .collect()
+        });
+        move |name| {
+            (pattern.is_empty() || glob_match(pattern, name))
+                && visible.as_ref().is_none_or(|visible| visible.contains(name))
+        }
+    }
+
+    /// Policy choice shown before extracting into a folder that already has files.
+    pub(crate) fn show_extract_conflict_dialog(&mut self, ui: &mut egui::Ui) {
+        let Some(pending) = &self.pending_extract else {
+            return;
+        };
+        ui.label(format!("{} already contains files.", pending.target.display()));
+        ui.label("When a file with the same name exists:");
+        for (policy, label) in EXTRACT_POLICIES {
+            ui.radio_value(&mut self.extract_policy, policy.to_string(), *label);
+        }
+        ui.separator();
+        ui.horizontal(|ui| {
+            if ui.button("📤 Extract").clicked()
+                && let Some(pending) = self.pending_extract.take()
+            {
+                self.run_extract(pending);
+            }
+            if ui.button("Cancel").clicked() {
+                self.pending_extract = None;
+            }
+        });
+    }
+
+    pub(crate) fn show_extract_report(&mut self, ui: &mut egui::Ui) {
+        let Some(report) = &self.extract_report else {
+            return;
+        };
+        let mut close = false;
+        ui.label(report.summary());
+        ui.separator();
+
+        let row_height = ui.text_style_height(&egui::TextStyle::Body);
+        egui::ScrollArea::vertical()
+            .max_height(400.0)
+            .auto_shrink([false, true])
+            .show_rows(ui, row_height, report.outcomes.len(), |ui, rows| {
+                for outcome in &report.outcomes[rows] {
+                    ui.horizontal(|ui| {
+                        ui.label(format!("{} {}", outcome_icon(outcome.result), outcome.name));
+                        ui.weak(&outcome.detail);
+                    });
+                }
+            });
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            if ui.button("📋 Copy Report").clicked() {
+                let lines: Vec<String> = report
+                    .outcomes
+                    .iter()
+                    .map(|outcome| format!("{}\t{}\t{}", outcome.result, outcome.name, outcome.detail))
+                    .collect();
+                ui.ctx().copy_text(lines.join("\n"));
+            }
+            if ui.button("❌ Close").clicked() {
+                close = true;
+            }
+        });
+        if close {
+            self.extract_report = None;
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use egui::Color32;
+use serde::{Deserialize, Serialize};
+use crate::rpa::RpaEditor;
+
+/// Built-in icon and colour per group of extensions, also shown in the legend.
+const BUILTIN_STYLES: &[(&str, &[&str], &str, [u8; 3])] = &[
+    ("Images", &[".png", ".jpg", ".jpeg", ".webp"], "🖼️", [100, 200, 100]),
+    ("Videos", &[".webm", ".mp4", ".avi"], "🎬", [200, 100, 100]),
+    ("Audio", &[".ogg", ".wav", ".mp3"], "🎵", [100, 100, 200]),
+    ("Scripts", &[".rpy", ".rpyc"], "📜", [200, 200, 100]),
+];
+const DEFAULT_ICON: &str = "📄";
+const DEFAULT_COLOR: [u8; 3] = [160, 160, 160];
+
+/// User rule: `pattern` is an extension (".ogg") or a path prefix ("gui/").
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileStyle {
+    pub pattern: String,
+    pub icon: String,
+    pub color: [u8; 3],
+}
+
+impl FileStyle {
+    fn is_extension(&self) -> bool {
+        self.pattern.starts_with('.')
+    }
+}
+
+fn builtin_style(filename: &str) -> (&'static str, [u8; 3]) {
+    let lower = filename.to_lowercase();
+    BUILTIN_STYLES
+        .iter()
+        .find(|(_, extensions, _, _)| extensions.iter().any(|ext| lower.ends_with(ext)))
+        .map_or((DEFAULT_ICON, DEFAULT_COLOR), |&(_, _, icon, color)| (icon, color))
+}
+
+/// Built-in style of a file type detected from content, e.g. "images".
+fn type_style(file_type: &str) -> Option<(&'static str, [u8; 3])> {
+    BUILTIN_STYLES
+        .iter()
+        .find(|(group, _, _, _)| group.eq_ignore_ascii_case(file_type))
+        .map(|&(_, _, icon, color)| (icon, color))
+}
+
+impl RpaEditor {
+    /// Built-in style, going by content for entries whose extension is misleading.
+    fn entry_style(&self, filename: &str) -> (&'static str, [u8; 3]) {
+        self.sniffed_types
+            .get(filename)
+            .and_then(|file_type| type_style(file_type))
+            .unwrap_or_else(|| builtin_style(filename))
+    }
+
+    pub(crate) fn get_file_icon(filename: &str) -> &'static str {
+        builtin_style(filename).0
+    }
+
+    pub(crate) fn get_file_type_color(filename: &str) -> Color32 {
+        let [r, g, b] = builtin_style(filename).1;
+        Color32::from_rgb(r, g, b)
+    }
+
+    /// Longest matching path prefix wins, then extension rules, then the built-in table.
+    fn custom_style(&self, filename: &str) -> Option<&FileStyle> {
+        let lower = filename.to_lowercase();
+        self.file_styles
+            .iter()
+            .filter(|style| !style.is_extension() && lower.starts_with(&style.pattern))
+            .max_by_key(|style| style.pattern.len())
+            .or_else(|| {
+                self.file_styles
+                    .iter()
+                    .find(|style| style.is_extension() && lower.ends_with(&style.pattern))
+            })
+    }
+
+    pub(crate) fn file_icon(&self, filename: &str) -> &str {
+        match self.custom_style(filename) {
+            Some(style) => &style.icon,
+            None => self.entry_style(filename).0,
+        }
+    }
+
+    pub(crate) fn file_color(&self, filename: &str) -> Color32 {
+        match self.custom_style(filename) {
+            Some(style) => Color32::from_rgb(style.color[0], style.color[1], style.color[2]),
+            None => {
+                let [r, g, b] = self.entry_style(filename).1;
+                Color32::from_rgb(r, g, b)
+            }
+        }
+    }
+
+    /// Adds or replaces the rule for `pattern`; a leading "." means an extension.
+    pub(crate) fn set_file_style(&mut self, pattern: &str, icon: &str, color: [u8; 3]) {
+        let pattern = pattern.trim().to_lowercase().replace('\\', "/");
+        if pattern.is_empty() || icon.trim().is_empty() {
+            return;
+        }
+        self.file_styles.retain(|style| style.pattern != pattern);
+        self.status_message = format!("{} entries now show as {}", pattern, icon.trim());
+        self.file_styles.push(FileStyle {
+            pattern,
+            icon: icon.trim().to_string(),
+            color,
+        });
+    }
+
+    pub(crate) fn show_icon_legend(&mut self, ui: &mut egui::Ui) {
+        ui.label("Built-in");
+        egui::Grid::new("builtin_styles").num_columns(3).show(ui, |ui| {
+            for (label, extensions, icon, [r, g, b]) in BUILTIN_STYLES {
+                ui.label(*icon);
+                ui.colored_label(Color32::from_rgb(*r, *g, *b), *label);
+                ui.label(extensions.join(" "));
+                ui.end_row();
+            }
+            ui.label(DEFAULT_ICON);
+            ui.colored_label(Self::get_file_type_color(""), "Other");
+            ui.label("everything else");
+            ui.end_row();
+        });
+
+        ui.separator();
+        ui.label("Custom (path prefixes override extensions)");
+        let mut remove = None;
+        if self.file_styles.is_empty() {
+            ui.label("No custom icons");
+        }
+        for (i, style) in self.file_styles.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.text_edit_singleline(&mut style.icon);
+                ui.color_edit_button_srgb(&mut style.color);
+                ui.colored_label(
+                    Color32::from_rgb(style.color[0], style.color[1], style.color[2]),
+                    &style.pattern,
+                );
+                if ui.button("🗑️").clicked() {
+                    remove = Some(i);
+                }
+            });
+        }
+        if let Some(i) = remove {
+            self.file_styles.remove(i);
+        }
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label("Extension or prefix:");
+            ui.add(
+                egui::TextEdit::singleline(&mut self.new_style_pattern)
+                    .desired_width(100.0)
+                    .hint_text(".ogg or gui/"),
+            );
+            ui.add(egui::TextEdit::singleline(&mut self.new_style_icon).desired_width(30.0));
+            ui.color_edit_button_srgb(&mut self.new_style_color);
+            if ui.button("➕ Add").clicked() {
+                let (pattern, icon, color) = (
+                    self.new_style_pattern.clone(),
+                    self.new_style_icon.clone(),
+                    self.new_style_color,
+                );
+                self.set_file_style(&pattern, &icon, color);
+                self.new_style_pattern.clear();
+            }
+        });
+    }
+}
@@ -0,0 +1,150 @@
+use egui_extras::{Column, TableBuilder};
+use crate::rpa::{RpaEditor, RpaFileEntry, CHANGE_FILTERS};
+
+/// Sortable columns of the list view: (sort key, header).
+pub const FILE_TABLE_COLUMNS: &[(&str, &str)] = &[
+    ("name", "Name"),
+    ("type", "Type"),
+    ("size", "Size"),
+    ("offset", "Offset"),
+    ("modified", "Modified"),
+];
+
+fn entry_state(entry: &RpaFileEntry) -> &'static str {
+    entry
+        .change_kind()
+        .and_then(|kind| CHANGE_FILTERS.iter().find(|(key, _)| *key == kind))
+        .map_or("", |(_, label)| label)
+}
+
+impl RpaEditor {
+    /// Clicking the sorted column flips the direction; another column sorts ascending.
+    fn toggle_sort(&mut self, key: &str) {
+        if self.sort_by == key {
+            self.sort_ascending = !self.sort_ascending;
+        } else {
+            self.sort_by = key.to_string();
+            self.sort_ascending = true;
+        }
+    }
+
+    /// List view: one table row per entry passing the filter, with click-to-sort headers
+    /// and resizable columns. Only visible rows are laid out.
+    pub(crate) fn show_file_table(&mut self, ui: &mut egui::Ui) {
+        let mut clicked = None;
+        let mut sort_clicked = None;
+
+        {
+            let files = self.get_filtered_sorted_files();
+            let row_height = ui.spacing().interact_size.y;
+            let mut table = TableBuilder::new(ui)
+                .striped(true)
+                .resizable(true)
+                .sense(egui::Sense::click())
+                .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+                .auto_shrink([false, false])
+                .column(Column::remainder().at_least(120.0).clip(true))
+                .column(Column::initial(70.0).at_least(40.0))
+                .column(Column::initial(70.0).at_least(40.0))
+                .column(Column::initial(80.0).at_least(40.0))
+                .column(Column::initial(90.0).at_least(40.0));
+            if self.show_checksum_column {
+                table = table.column(Column::initial(100.0).at_least(40.0).clip(true));
+            }
+
+            table
+                .header(row_height, |mut header| {
+                    for (key, label) in FILE_TABLE_COLUMNS {
+                        header.col(|ui| {
+                            let text = match (self.sort_by == *key, self.sort_ascending) {
+                                (true, true) => format!("{} ⏶", label),
+                                (true, false) => format!("{} ⏷", label),
+                                (false, _) => label.to_string(),
+                            };
+                            if ui
+                                .add(egui::Button::new(egui::RichText::new(text).strong()).frame(false))
+                                .clicked()
+                            {
+                                sort_clicked = Some(*key);
+                            }
+                        });
+                    }
+                    if self.show_checksum_column {
+                        header.col(|ui| {
+                            ui.strong("SHA-256");
+                        });
+                    }
+                })
+                .body(|body| {
+                    body.rows(row_height, files.len(), |mut row| {
+                        let (filename, entry) = files[row.index()];
+                        row.set_selected(Some(filename.as_str()) == self.selected_file.as_deref());
+
+                        row.col(|ui| {
+                            ui.label(self.file_icon(filename));
+                            let annotation = self.entry_annotation_summary(filename);
+                            if self.is_bookmarked(filename) || annotation.is_some() {
+                                let marker = if self.is_bookmarked(filename) { "⭐" } else { "🏷" };
+                                let response = ui.label(marker);
+                                if let Some(annotation) = annotation {
+                                    response.on_hover_text(annotation);
+                                }
+                            }
+                            if let Some(warning) = self.entry_warnings.get(filename) {
+                                ui.colored_label(egui::Color32::ORANGE, "⚠").on_hover_text(warning);
+                            }
+                            let text = egui::RichText::new(filename);
+                            let text = if entry.to_delete {
+                                text.strikethrough().color(egui::Color32::RED)
+                            } else if entry.modified {
+                                text.color(egui::Color32::YELLOW)
+                            } else {
+                                text.color(self.file_color(filename))
+                            };
+                            ui.add(egui::Label::new(text).truncate().selectable(false));
+                        });
+                        row.col(|ui| {
+                            let response = ui.weak(self.get_file_type(filename));
+                            if let Some(video) = self.video_info.get(filename) {
+                                response.on_hover_text(video.summary());
+                            }
+                        });
+                        row.col(|ui| {
+                            ui.label(Self::format_bytes(entry.length));
+                        });
+                        row.col(|ui| {
+                            match entry.archive_offset() {
+                                Some(offset) => ui.monospace(format!("0x{:x}", offset)),
+                                None => ui.weak("—").on_hover_text("In memory, written on save"),
+                            };
+                        });
+                        row.col(|ui| {
+                            let response = ui.label(entry_state(entry));
+                            if entry.modified {
+                                response.on_hover_text(&entry.provenance);
+                            }
+                        });
+                        if self.show_checksum_column {
+                            row.col(|ui| {
+                                if let Some(hash) = self.checksums.get(filename) {
+                                    ui.monospace(&hash[..12]).on_hover_text(hash);
+                                }
+                            });
+                        }
+
+                        if row.response().clicked() {
+                            clicked = Some(filename.clone());
+                        }
+                    });
+                });
+        }
+
+        if let Some(key) = sort_clicked {
+            self.toggle_sort(key);
+        }
+        if let Some(name) = clicked {
+            self.selected_file = Some(name.clone());
+            self.file_to_preview = Some(name);
+        }
+    }
+}
@@ -0,0 +1,227 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tracing::info;
+use crate::rpa::{RpaEditor, RpaFileEntry};
+
+/// Folder node built from the `/`-separated entry paths.
+#[derive(Default)]
+pub struct FileTreeNode {
+    pub folders: BTreeMap<String, FileTreeNode>,
+    pub files: Vec<String>,
+    pub count: usize,
+    pub size: u64,
+}
+
+impl FileTreeNode {
+    pub fn build(files: &[(&String, &RpaFileEntry)]) -> Self {
+        let mut root = FileTreeNode::default();
+
+        for (filename, entry) in files {
+            let mut node = &mut root;
+            node.count += 1;
+            node.size += entry.length;
+
+            let mut parts: Vec<&str> = filename.split('/').collect();
+            parts.pop();
+            for part in parts {
+                node = node.folders.entry(part.to_string()).or_default();
+                node.count += 1;
+                node.size += entry.length;
+            }
+            node.files.push((*filename).clone());
+        }
+
+        root
+    }
+}
+
+#[derive(Default)]
+pub struct TreeActions {
+    pub file_to_preview: Option<String>,
+    pub folder_to_extract: Option<String>,
+    pub folder_to_replace: Option<String>,
+    pub folder_to_copy_paths: Option<String>,
+    pub folder_to_remove: Option<String>,
+}
+
+impl RpaEditor {
+    /// Draws one file row and returns true when it was clicked.
+    pub(crate) fn show_file_row(&self, ui: &mut egui::Ui, filename: &str, entry: &RpaFileEntry, display_name: &str) -> bool {
+        let is_selected = Some(filename) == self.selected_file.as_deref();
+        let mut clicked = false;
+
+        ui.horizontal(|ui| {
+            ui.set_min_height(25.0);
+
+            ui.label(self.file_icon(filename));
+
+            if let Some(warning) = self.entry_warnings.get(filename) {
+                ui.colored_label(egui::Color32::ORANGE, "⚠").on_hover_text(warning);
+            }
+
+            let mut text = egui::RichText::new(display_name);
+
+            if entry.to_delete {
+                text = text.strikethrough().color(egui::Color32::RED);
+            } else if entry.modified {
+                text = text.color(egui::Color32::YELLOW);
+            } else {
+                text = text.color(self.file_color(filename));
+            }
+
+            clicked = ui.selectable_label(is_selected, text).clicked();
+
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                ui.label(
+                    egui::RichText::new(Self::format_bytes(entry.length))
+                        .small()
+                        .weak(),
+                );
+                if let Some(video) = self.video_info.get(filename) {
+                    ui.label(egui::RichText::new(video.summary()).small().weak());
+                }
+                if self.show_checksum_column
+                    && let Some(hash) = self.checksums.get(filename)
+                {
+                    ui.label(egui::RichText::new(&hash[..12]).small().weak().monospace())
+                        .on_hover_text(hash);
+                }
+            });
+        });
+
+        clicked
+    }
+
+    /// Single-line row for the virtualized list, without the size column.
+    pub(crate) fn show_compact_file_row(&self, ui: &mut egui::Ui, filename: &str, entry: &RpaFileEntry) -> bool {
+        let is_selected = Some(filename) == self.selected_file.as_deref();
+        let text = egui::RichText::new(filename);
+        let text = if entry.to_delete {
+            text.strikethrough().color(egui::Color32::RED)
+        } else if entry.modified {
+            text.color(egui::Color32::YELLOW)
+        } else {
+            text.color(self.file_color(filename))
+        };
+
+        ui.selectable_label(is_selected, text).clicked()
+    }
+
+    pub(crate) fn show_tree_node(&self, ui: &mut egui::Ui, node: &FileTreeNode, path: &str, actions: &mut TreeActions) {
+        for (name, child) in &node.folders {
+            let child_path = format!("{}{}/", path, name);
+
+            let response = egui::CollapsingHeader::new(format!(
+                "📁 {} ({}, {})",
+                name,
+                child.count,
+                Self::format_bytes(child.size)
+            ))
+            .id_salt(&child_path)
+            .default_open(!self.search_filter.is_empty())
+            .show(ui, |ui| {
+                self.show_tree_node(ui, child, &child_path, actions);
+            });
+
+            response.header_response.context_menu(|ui| {
+                if ui.button("📤 Extract folder...").clicked() {
+                    actions.folder_to_extract = Some(child_path.clone());
+                    ui.close_menu();
+                }
+                if ui.button("📋 Extract & copy paths").clicked() {
+                    actions.folder_to_copy_paths = Some(child_path.clone());
+                    ui.close_menu();
+                }
+                if ui.button("🔄 Replace folder from disk...").clicked() {
+                    actions.folder_to_replace = Some(child_path.clone());
+                    ui.close_menu();
+                }
+                if ui.button("🗑️ Remove folder").clicked() {
+                    actions.folder_to_remove = Some(child_path.clone());
+                    ui.close_menu();
+                }
+            });
+        }
+
+        for filename in &node.files {
+            if let Some(entry) = self.indexes.get(filename) {
+                let display_name = filename.rsplit('/').next().unwrap_or(filename);
+                if self.show_file_row(ui, filename, entry, display_name) {
+                    actions.file_to_preview = Some(filename.clone());
+                }
+            }
+        }
+    }
+
+    /// Entries below `folder`, sorted, for extracting the folder.
+    pub(crate) fn entries_in_folder(&self, folder: &str) -> Vec<String> {
+        let mut filenames: Vec<String> = self
+            .indexes
+            .iter()
+            .filter(|(filename, entry)| !entry.to_delete && filename.starts_with(folder))
+            .map(|(filename, _)| filename.clone())
+            .collect();
+        filenames.sort();
+        filenames
+    }
+
+    /// Replaces entries under `folder` with same-relative-path files found below `base_path`.
+    pub(crate) fn replace_folder(&mut self, folder: &str, base_path: &Path) -> anyhow::Result<(usize, usize)> {
+        self.ensure_writable()?;
+        let mut disk_files = Vec::new();
+        collect_files(base_path, base_path, &mut disk_files)?;
+
+        let mut replaced = 0;
+        let mut skipped = 0;
+        for (relative, path) in disk_files {
+            let archive_name = format!("{}{}", folder, relative);
+            if !self.indexes.contains_key(&archive_name) {
+                skipped += 1;
+                continue;
+            }
+            let data = std::fs::read(&path)?;
+            self.backup_before(&archive_name, "replace");
+            self.insert_data(&archive_name, data)?;
+            self.set_provenance(&archive_name, format!("disk: {}", path.display()));
+            info!("🔄 Replaced: {}", archive_name);
+            replaced += 1;
+        }
+
+        self.status_message = format!(
+            "Replaced {} files in {} ({} not in archive)",
+            replaced, folder, skipped
+        );
+        Ok((replaced, skipped))
+    }
+
+    pub(crate) fn remove_folder(&mut self, folder: &str) -> usize {
+        if self.blocked_by_read_only() {
+            return 0;
+        }
+        let mut count = 0;
+        for (filename, entry) in self.indexes.iter_mut() {
+            if filename.starts_with(folder) && !entry.to_delete {
+                entry.to_delete = true;
+                count += 1;
+            }
+        }
+
+        if count > 0 {
+            self.set_modified(true);
+        }
+        self.status_message = format!("Marked {} files in {} for deletion", count, folder);
+        count
+    }
+}
+
+pub(crate) fn collect_files(root: &Path, dir: &Path, out: &mut Vec<(String, PathBuf)>) -> anyhow::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(root, &path, out)?;
+        } else if let Ok(relative) = path.strip_prefix(root) {
+            out.push((relative.to_string_lossy().replace('\\', "/"), path.clone()));
+        }
+    }
+    Ok(())
+}
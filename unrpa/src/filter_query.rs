@@ -0,0 +1,275 @@
+use crate::glob::glob_match;
+use crate::rpa::{RpaEditor, RpaFileEntry};
+
+/// Shown on the search box.
+pub const FILTER_QUERY_HELP: &str = "Words match anywhere in the path. Terms narrow the list further:\n\
+    ext:png,jpg   extension\n\
+    path:images/   path prefix (or a glob like path:**/bg_*)\n\
+    type:audio   file type\n\
+    size>1mb  size<=200kb   size\n\
+    modified:true  deleted:false   entry state\n\
+    -term   excludes matches\n\
+    \"two words\"  path:\"my folder/\"   quotes keep spaces";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SizeOp {
+    Less,
+    LessOrEqual,
+    Equal,
+    GreaterOrEqual,
+    Greater,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Predicate {
+    Text(String),
+    Ext(Vec<String>),
+    Path(String),
+    Type(String),
+    Size(SizeOp, u64),
+    Modified(bool),
+    Deleted(bool),
+    Not(Box<Predicate>),
+}
+
+impl Predicate {
+    fn matches(&self, name: &str, entry: &RpaFileEntry, file_type: &str) -> bool {
+        match self {
+            Predicate::Text(text) => name.to_lowercase().contains(text),
+            Predicate::Ext(extensions) => {
+                let lower = name.to_lowercase();
+                extensions.iter().any(|ext| lower.ends_with(&format!(".{}", ext)))
+            }
+            Predicate::Path(pattern) if pattern.contains(['*', '?']) => glob_match(pattern, name),
+            Predicate::Path(prefix) => name.to_lowercase().starts_with(prefix),
+            Predicate::Type(wanted) => file_type.starts_with(wanted.as_str()),
+            Predicate::Size(op, size) => match op {
+                SizeOp::Less => entry.length < *size,
+                SizeOp::LessOrEqual => entry.length <= *size,
+                SizeOp::Equal => entry.length == *size,
+                SizeOp::GreaterOrEqual => entry.length >= *size,
+                SizeOp::Greater => entry.length > *size,
+            },
+            Predicate::Modified(wanted) => entry.modified == *wanted,
+            Predicate::Deleted(wanted) => entry.to_delete == *wanted,
+            Predicate::Not(inner) => !inner.matches(name, entry, file_type),
+        }
+    }
+}
+
+/// `1mb`, `200k`, `1.5GB`, `512`; units are binary like the sizes shown in the list.
+fn parse_size(text: &str) -> Result<u64, String> {
+    let lower = text.to_lowercase();
+    let split = lower
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(lower.len());
+    let (number, unit) = lower.split_at(split);
+    let multiplier: u64 = match unit {
+        "" | "b" => 1,
+        "k" | "kb" => 1024,
+        "m" | "mb" => 1024 * 1024,
+        "g" | "gb" => 1024 * 1024 * 1024,
+        _ => return Err(format!("Unknown size unit '{}'", unit)),
+    };
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("'{}' is not a size", text))?;
+    Ok((number * multiplier as f64) as u64)
+}
+
+fn parse_bool(key: &str, value: &str) -> Result<bool, String> {
+    match value.to_lowercase().as_str() {
+        "true" | "yes" | "1" => Ok(true),
+        "false" | "no" | "0" => Ok(false),
+        _ => Err(format!("{}: expects true or false", key)),
+    }
+}
+
+fn parse_term(term: &str) -> Result<Predicate, String> {
+    if let Some(rest) = term.strip_prefix('-')
+        && !rest.is_empty()
+    {
+        return Ok(Predicate::Not(Box::new(parse_term(rest)?)));
+    }
+
+    let lower = term.to_lowercase();
+    if let Some(rest) = lower.strip_prefix("size") {
+        let (op, value) = if let Some(value) = rest.strip_prefix(">=") {
+            (SizeOp::GreaterOrEqual, value)
+        } else if let Some(value) = rest.strip_prefix("<=") {
+            (SizeOp::LessOrEqual, value)
+        } else if let Some(value) = rest.strip_prefix('>') {
+            (SizeOp::Greater, value)
+        } else if let Some(value) = rest.strip_prefix('<') {
+            (SizeOp::Less, value)
+        } else if let Some(value) = rest.strip_prefix('=').or_else(|| rest.strip_prefix(':')) {
+            (SizeOp::Equal, value)
+        } else {
+            return Ok(Predicate::Text(lower));
+        };
+        return Ok(Predicate::Size(op, parse_size(value)?));
+    }
+
+    let Some((key, value)) = lower.split_once(':') else {
+        return Ok(Predicate::Text(lower));
+    };
+    match key {
+        "ext" => Ok(Predicate::Ext(
+            value
+                .split(',')
+                .map(|ext| ext.trim_start_matches('.').to_string())
+                .filter(|ext| !ext.is_empty())
+                .collect(),
+        )),
+        "path" => Ok(Predicate::Path(value.to_string())),
+        "type" => Ok(Predicate::Type(value.to_string())),
+        "modified" => Ok(Predicate::Modified(parse_bool(key, value)?)),
+        "deleted" => Ok(Predicate::Deleted(parse_bool(key, value)?)),
+        // Not a filter key, e.g. a name containing ':'.
+        _ => Ok(Predicate::Text(lower)),
+    }
+}
+
+/// Splits on whitespace outside double quotes, dropping the quotes.
+fn split_terms(query: &str) -> Result<Vec<String>, String> {
+    let mut terms = Vec::new();
+    let mut term = String::new();
+    let mut quoted = false;
+    for c in query.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !term.is_empty() {
+                    terms.push(std::mem::take(&mut term));
+                }
+            }
+            c => term.push(c),
+        }
+    }
+    if quoted {
+        return Err("Unclosed quote".to_string());
+    }
+    if !term.is_empty() {
+        terms.push(term);
+    }
+    Ok(terms)
+}
+
+/// Parsed search box contents; every term has to match.
+#[derive(Debug, Default)]
+pub struct FilterQuery {
+    predicates: Vec<Predicate>,
+}
+
+impl FilterQuery {
+    pub fn parse(query: &str) -> Result<Self, String> {
+        let predicates = split_terms(query)?
+            .iter()
+            .map(|term| parse_term(term))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { predicates })
+    }
+
+    pub fn matches(&self, name: &str, entry: &RpaFileEntry, file_type: &str) -> bool {
+        self.predicates
+            .iter()
+            .all(|predicate| predicate.matches(name, entry, file_type))
+    }
+}
+
+impl RpaEditor {
+    /// The search box as a query. A query that doesn't parse falls back to plain
+    /// substring matching, so half-typed terms don't empty the list.
+    pub(crate) fn search_query(&self) -> FilterQuery {
+        FilterQuery::parse(&self.search_filter).unwrap_or_else(|_| FilterQuery {
+            predicates: vec![Predicate::Text(self.search_filter.to_lowercase())],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpa::RpaSegment;
+
+    fn archived(length: u64) -> RpaFileEntry {
+        RpaFileEntry::from_segments(vec![RpaSegment {
+            offset: 0x34,
+            length,
+            prefix: Vec::new(),
+        }])
+    }
+
+    fn matches(query: &str, name: &str, entry: &RpaFileEntry) -> bool {
+        FilterQuery::parse(query).unwrap().matches(name, entry, "images")
+    }
+
+    #[test]
+    fn combined_terms() {
+        let query = "ext:png size>1mb path:images/ modified:true";
+        let mut big = archived(2 * 1024 * 1024);
+        big.modified = true;
+        assert!(matches(query, "images/bg.png", &big));
+        assert!(matches(query, "Images/BG.PNG", &big));
+        assert!(!matches(query, "images/bg.jpg", &big));
+        assert!(!matches(query, "gui/bg.png", &big));
+        assert!(!matches(query, "images/bg.png", &archived(2 * 1024 * 1024)));
+        let mut small = archived(1024 * 1024);
+        small.modified = true;
+        assert!(!matches(query, "images/bg.png", &small));
+
+        assert!(matches("ext:.jpg,png -path:gui/", "images/bg.png", &big));
+        assert!(!matches("-ext:png", "images/bg.png", &big));
+        assert!(matches("path:**/bg_* deleted:no", "images/sub/bg_1.png", &big));
+    }
+
+    #[test]
+    fn size_units() {
+        assert_eq!(parse_size("512"), Ok(512));
+        assert_eq!(parse_size("512b"), Ok(512));
+        assert_eq!(parse_size("200k"), Ok(200 * 1024));
+        assert_eq!(parse_size("200KB"), Ok(200 * 1024));
+        assert_eq!(parse_size("1mb"), Ok(1024 * 1024));
+        assert_eq!(parse_size("1.5GB"), Ok(3 * 512 * 1024 * 1024));
+        assert_eq!(parse_term("size<=2k"), Ok(Predicate::Size(SizeOp::LessOrEqual, 2048)));
+        assert_eq!(parse_term("size:10"), Ok(Predicate::Size(SizeOp::Equal, 10)));
+    }
+
+    #[test]
+    fn malformed_terms() {
+        assert!(FilterQuery::parse("size>1tb").unwrap_err().contains("Unknown size unit"));
+        assert!(FilterQuery::parse("size>mb").unwrap_err().contains("is not a size"));
+        assert!(FilterQuery::parse("modified:maybe").unwrap_err().contains("expects true or false"));
+        assert!(FilterQuery::parse("path:\"images").unwrap_err().contains("Unclosed quote"));
+        // Unknown keys and bare operators are searched for as text.
+        assert_eq!(parse_term("chapter:1"), Ok(Predicate::Text("chapter:1".to_string())));
+        assert_eq!(parse_term("sizes"), Ok(Predicate::Text("sizes".to_string())));
+        assert_eq!(parse_term("-"), Ok(Predicate::Text("-".to_string())));
+    }
+
+    #[test]
+    fn quoted_values() {
+        let entry = archived(10);
+        assert!(matches("path:\"my images/\"", "my images/bg.png", &entry));
+        assert!(!matches("path:\"my images/\"", "my/images/bg.png", &entry));
+        assert!(matches("\"bg 01\" ext:png", "images/bg 01.png", &entry));
+        assert!(!matches("\"bg 01\"", "images/bg_01.png", &entry));
+        assert!(!matches("-\"bg 01\"", "images/bg 01.png", &entry));
+        assert_eq!(
+            split_terms("  a  \"b c\" d:\"e f\" ").unwrap(),
+            vec!["a", "b c", "d:e f"]
+        );
+    }
+
+    /// A query that doesn't parse still narrows the list by plain text.
+    #[test]
+    fn invalid_query_falls_back_to_text() {
+        let editor = RpaEditor {
+            search_filter: "size>1tb".to_string(),
+            ..RpaEditor::default()
+        };
+        let query = editor.search_query();
+        assert!(query.matches("size>1tb.txt", &archived(1), "files"));
+        assert!(!query.matches("other.txt", &archived(1), "files"));
+    }
+}
@@ -0,0 +1,200 @@
+use std::path::{Path, PathBuf};
+use tracing::info;
+use crate::file_tree::collect_files;
+use crate::rpa::{PendingAdd, RpaEditor};
+
+/// What to do with a file whose archive path is already taken.
+pub const FOLDER_ADD_RESOLUTIONS: &[(&str, &str)] = &[
+    ("skip", "⏭️ Skip"),
+    ("overwrite", "🔄 Overwrite"),
+    ("rename", "📑 Keep both"),
+];
+
+pub struct FolderAddItem {
+    pub path: PathBuf,
+    /// Path below the picked folder, `/`-separated.
+    pub relative: String,
+    pub size: u64,
+    pub include: bool,
+    /// One of `FOLDER_ADD_RESOLUTIONS`, used when the archive path exists.
+    pub resolution: String,
+}
+
+/// A folder picked for "Add Folder...", waiting for its preview to be confirmed.
+pub struct FolderAdd {
+    pub folder: PathBuf,
+    /// Prepended to every relative path, e.g. `images/`.
+    pub prefix: String,
+    pub items: Vec<FolderAddItem>,
+}
+
+impl FolderAdd {
+    pub fn archive_name(&self, item: &FolderAddItem) -> String {
+        let prefix = self.prefix.trim().trim_start_matches('/').replace('\\', "/");
+        if prefix.is_empty() || prefix.ends_with('/') {
+            format!("{}{}", prefix, item.relative)
+        } else {
+            format!("{}/{}", prefix, item.relative)
+        }
+    }
+}
+
+impl RpaEditor {
+    /// Walks `folder` and opens the Add Folder preview for it.
+    pub(crate) fn open_folder_add(&mut self, folder: &Path) -> anyhow::Result<usize> {
+        self.ensure_writable()?;
+        let mut files = Vec::new();
+        collect_files(folder, folder, &mut files)?;
+        files.sort();
+        let items: Vec<FolderAddItem> = files
+            .into_iter()
+            .map(|(relative, path)| FolderAddItem {
+                size: std::fs::metadata(&path).map_or(0, |metadata| metadata.len()),
+                path,
+                relative,
+                include: true,
+                resolution: "skip".to_string(),
+            })
+            .collect();
+        let count = items.len();
+        self.folder_add = Some(FolderAdd {
+            folder: folder.to_path_buf(),
+            prefix: String::new(),
+            items,
+        });
+        Ok(count)
+    }
+
+    fn entry_exists(&self, archive_name: &str) -> bool {
+        self.indexes.get(archive_name).is_some_and(|entry| !entry.to_delete)
+    }
+
+    /// Adds the checked files under their previewed names. Returns (added, skipped).
+    pub(crate) fn apply_folder_add(&mut self) -> (usize, usize) {
+        let Some(folder_add) = self.folder_add.take() else {
+            return (0, 0);
+        };
+        let mut added = 0;
+        let mut skipped = 0;
+        for item in &folder_add.items {
+            let archive_name = folder_add.archive_name(item);
+            let resolution = if self.entry_exists(&archive_name) {
+                item.resolution.as_str()
+            } else {
+                "overwrite"
+            };
+            if !item.include || resolution == "skip" {
+                skipped += 1;
+                continue;
+            }
+            self.apply_pending_add(
+                PendingAdd {
+                    path: item.path.to_string_lossy().to_string(),
+                    archive_name,
+                    merged: None,
+                },
+                resolution,
+            );
+            added += 1;
+        }
+        self.status_message = format!(
+            "Added {} files from {} ({} skipped)",
+            added,
+            folder_add.folder.display(),
+            skipped
+        );
+        info!("📁 {}", self.status_message);
+        (added, skipped)
+    }
+
+    pub(crate) fn show_folder_add_dialog(&mut self, ui: &mut egui::Ui) {
+        let existing: Vec<bool> = match &self.folder_add {
+            Some(folder_add) => folder_add
+                .items
+                .iter()
+                .map(|item| self.entry_exists(&folder_add.archive_name(item)))
+                .collect(),
+            None => return,
+        };
+        let Some(folder_add) = &mut self.folder_add else {
+            return;
+        };
+
+        ui.label(format!("📂 {}", folder_add.folder.display()));
+        ui.horizontal(|ui| {
+            ui.label("Archive path prefix:");
+            ui.add(egui::TextEdit::singleline(&mut folder_add.prefix).hint_text("images/"));
+        });
+
+        let conflicts = existing.iter().filter(|exists| **exists).count();
+        let included = folder_add.items.iter().filter(|item| item.include).count();
+        ui.horizontal(|ui| {
+            ui.label(format!("{} of {} files", included, folder_add.items.len()));
+            if ui.button("All").clicked() {
+                folder_add.items.iter_mut().for_each(|item| item.include = true);
+            }
+            if ui.button("None").clicked() {
+                folder_add.items.iter_mut().for_each(|item| item.include = false);
+            }
+        });
+        if conflicts > 0 {
+            ui.horizontal(|ui| {
+                ui.colored_label(egui::Color32::ORANGE, format!("⚠ {} already in the archive, set all:", conflicts));
+                for (resolution, label) in FOLDER_ADD_RESOLUTIONS {
+                    if ui.small_button(*label).clicked() {
+                        folder_add
+                            .items
+                            .iter_mut()
+                            .for_each(|item| item.resolution = resolution.to_string());
+                    }
+                }
+            });
+        }
+        ui.separator();
+
+        let row_height = ui.spacing().interact_size.y;
+        egui::ScrollArea::vertical()
+            .max_height(350.0)
+            .auto_shrink([false, true])
+            .show_rows(ui, row_height, folder_add.items.len(), |ui, rows| {
+                for index in rows {
+                    let archive_name = folder_add.archive_name(&folder_add.items[index]);
+                    let item = &mut folder_add.items[index];
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut item.include, "");
+                        let text = egui::RichText::new(&archive_name);
+                        if existing[index] {
+                            ui.label(text.color(egui::Color32::ORANGE))
+                                .on_hover_text(item.path.display().to_string());
+                            egui::ComboBox::from_id_salt(("folder_add_resolution", index))
+                                .selected_text(
+                                    FOLDER_ADD_RESOLUTIONS
+                                        .iter()
+                                        .find(|(resolution, _)| *resolution == item.resolution)
+                                        .map_or("", |(_, label)| label),
+                                )
+                                .show_ui(ui, |ui| {
+                                    for (resolution, label) in FOLDER_ADD_RESOLUTIONS {
+                                        ui.selectable_value(&mut item.resolution, resolution.to_string(), *label);
+                                    }
+                                });
+                        } else {
+                            ui.label(text).on_hover_text(item.path.display().to_string());
+                        }
+                        ui.label(egui::RichText::new(Self::format_bytes(item.size)).small().weak());
+                    });
+                }
+            });
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            if ui.add_enabled(included > 0, egui::Button::new(format!("✅ Add {} files", included))).clicked() {
+                let (added, skipped) = self.apply_folder_add();
+                self.add_toast(format!("Added {} files ({} skipped)", added, skipped));
+            }
+            if ui.button("❌ Cancel").clicked() {
+                self.folder_add = None;
+            }
+        });
+    }
+}
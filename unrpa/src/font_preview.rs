@@ -0,0 +1,328 @@
+use std::io::Read;
+use flate2::read::ZlibDecoder;
+use ttf_parser::{Face, OutlineBuilder};
+use crate::rpa::RpaEditor;
+
+const MARGIN: f32 = 16.0;
+const LINE_GAP: f32 = 8.0;
+const SAMPLE_SENTENCE: &str = "The quick brown fox jumps over the lazy dog.";
+const SAMPLE_SIZES: &[f32] = &[12.0, 18.0, 24.0, 36.0, 48.0, 72.0];
+
+/// Widest specimen sheet produced, so huge fonts don't make a giant texture.
+const MAX_SHEET_WIDTH: f32 = 4096.0;
+
+/// Largest unpacked WOFF font accepted. The table lengths come from the file itself, so
+/// they're checked against this before anything is allocated.
+const MAX_WOFF_SIZE: usize = 64 * 1024 * 1024;
+
+/// Unpacks a WOFF 1.0 font into a plain sfnt that `ttf-parser` can read.
+fn woff_to_sfnt(data: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let u16_at = |pos: usize| data.get(pos..pos + 2).map(|b| u16::from_be_bytes([b[0], b[1]]));
+    let u32_at = |pos: usize| {
+        data.get(pos..pos + 4)
+            .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    };
+    let truncated = || anyhow::anyhow!("Truncated WOFF file");
+
+    let flavor = u32_at(4).ok_or_else(truncated)?;
+    let num_tables = u16_at(12).ok_or_else(truncated)? as usize;
+
+    let mut tables = Vec::with_capacity(num_tables);
+    let mut total = 0;
+    for i in 0..num_tables {
+        let record = 44 + i * 20;
+        let tag = data.get(record..record + 4).ok_or_else(truncated)?;
+        let offset = u32_at(record + 4).ok_or_else(truncated)? as usize;
+        let compressed_length = u32_at(record + 8).ok_or_else(truncated)? as usize;
+        let length = u32_at(record + 12).ok_or_else(truncated)? as usize;
+        let stored = offset
+            .checked_add(compressed_length)
+            .and_then(|end| data.get(offset..end))
+            .ok_or_else(truncated)?;
+        total += length;
+        if total > MAX_WOFF_SIZE {
+            return Err(anyhow::anyhow!("WOFF font unpacks to more than {} bytes", MAX_WOFF_SIZE));
+        }
+
+        let table = if compressed_length < length {
+            // One byte past `length` is read so an overlong stream is caught too.
+            let mut table = Vec::new();
+            ZlibDecoder::new(stored).take(length as u64 + 1).read_to_end(&mut table)?;
+            if table.len() != length {
+                return Err(anyhow::anyhow!(
+                    "WOFF table {} unpacks to {} bytes instead of {}",
+                    String::from_utf8_lossy(tag),
+                    table.len(),
+                    length
+                ));
+            }
+            table
+        } else {
+            stored.to_vec()
+        };
+        tables.push((tag.to_vec(), table));
+    }
+
+    // Checksums are left at zero; they aren't needed for reading.
+    let mut out = Vec::new();
+    out.extend_from_slice(&flavor.to_be_bytes());
+    out.extend_from_slice(&(num_tables as u16).to_be_bytes());
+    out.extend_from_slice(&[0; 6]);
+    let mut offset = 12 + num_tables * 16;
+    for (tag, table) in &tables {
+        out.extend_from_slice(tag);
+        out.extend_from_slice(&0u32.to_be_bytes());
+        out.extend_from_slice(&(offset as u32).to_be_bytes());
+        out.extend_from_slice(&(table.len() as u32).to_be_bytes());
+        offset += table.len().div_ceil(4) * 4;
+    }
+    for (_, table) in &tables {
+        out.extend_from_slice(table);
+        out.resize(out.len().div_ceil(4) * 4, 0);
+    }
+    Ok(out)
+}
+
+/// Anti-aliased coverage rasterizer: signed area is accumulated per pixel and
+/// summed along each row, so overlapping contours fill with the nonzero rule.
+struct Rasterizer {
+    width: usize,
+    height: usize,
+    area: Vec<f32>,
+}
+
+impl Rasterizer {
+    fn new(width: usize, height: usize) -> Self {
+        Self { width, height, area: vec![0.0; width * height + 4] }
+    }
+
+    fn line(&mut self, (x0, y0): (f32, f32), (x1, y1): (f32, f32)) {
+        if (y0 - y1).abs() <= f32::EPSILON {
+            return;
+        }
+        let max_x = self.width as f32 - 1.001;
+        let (dir, (x0, y0), (x1, y1)) = if y0 < y1 {
+            (1.0, (x0.clamp(0.0, max_x), y0), (x1.clamp(0.0, max_x), y1))
+        } else {
+            (-1.0, (x1.clamp(0.0, max_x), y1), (x0.clamp(0.0, max_x), y0))
+        };
+        let dxdy = (x1 - x0) / (y1 - y0);
+        let mut x = x0;
+        if y0 < 0.0 {
+            x -= y0 * dxdy;
+        }
+
+        let first_row = y0.max(0.0) as usize;
+        let last_row = (y1.ceil().max(0.0) as usize).min(self.height);
+        for row in first_row..last_row {
+            let start = row * self.width;
+            let dy = ((row + 1) as f32).min(y1) - (row as f32).max(y0);
+            let x_next = x + dxdy * dy;
+            let d = dy * dir;
+            let (left, right) = if x < x_next { (x, x_next) } else { (x_next, x) };
+            let left_floor = left.floor();
+            let left_i = left_floor as usize;
+            let right_ceil = right.ceil();
+            let right_i = right_ceil as usize;
+
+            if right_i <= left_i + 1 {
+                // The segment stays inside one pixel column.
+                let middle = 0.5 * (x + x_next) - left_floor;
+                self.area[start + left_i] += d - d * middle;
+                self.area[start + left_i + 1] += d * middle;
+            } else {
+                let slope = (right - left).recip();
+                let left_fraction = left - left_floor;
+                let a0 = 0.5 * slope * (1.0 - left_fraction) * (1.0 - left_fraction);
+                let right_fraction = right - right_ceil + 1.0;
+                let am = 0.5 * slope * right_fraction * right_fraction;
+                self.area[start + left_i] += d * a0;
+                if right_i == left_i + 2 {
+                    self.area[start + left_i + 1] += d * (1.0 - a0 - am);
+                } else {
+                    let a1 = slope * (1.5 - left_fraction);
+                    self.area[start + left_i + 1] += d * (a1 - a0);
+                    for column in left_i + 2..right_i - 1 {
+                        self.area[start + column] += d * slope;
+                    }
+                    let a2 = a1 + (right_i - left_i - 3) as f32 * slope;
+                    self.area[start + right_i - 1] += d * (1.0 - a2 - am);
+                }
+                self.area[start + right_i] += d * am;
+            }
+            x = x_next;
+        }
+    }
+
+    /// Dark glyphs on a white sheet.
+    fn into_image(self) -> egui::ColorImage {
+        let mut coverage = 0.0f32;
+        let pixels = self.area[..self.width * self.height]
+            .iter()
+            .map(|&area| {
+                coverage += area;
+                let shade = 255 - (coverage.abs().min(1.0) * 255.0) as u8;
+                egui::Color32::from_gray(shade)
+            })
+            .collect();
+        egui::ColorImage { size: [self.width, self.height], pixels }
+    }
+}
+
+/// Feeds glyph outlines into the rasterizer, mapped from font units to sheet pixels.
+struct GlyphPen<'a> {
+    raster: &'a mut Rasterizer,
+    scale: f32,
+    origin: (f32, f32),
+    start: (f32, f32),
+    current: (f32, f32),
+}
+
+impl GlyphPen<'_> {
+    fn point(&self, x: f32, y: f32) -> (f32, f32) {
+        (self.origin.0 + x * self.scale, self.origin.1 - y * self.scale)
+    }
+
+    fn segments(from: (f32, f32), to: (f32, f32)) -> usize {
+        let length = ((to.0 - from.0).powi(2) + (to.1 - from.1).powi(2)).sqrt();
+        ((length / 2.0).ceil() as usize).clamp(1, 32)
+    }
+}
+
+impl OutlineBuilder for GlyphPen<'_> {
+    fn move_to(&mut self, x: f32, y: f32) {
+        // CFF outlines may start a new contour without closing the last one.
+        self.close();
+        self.start = self.point(x, y);
+        self.current = self.start;
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        let to = self.point(x, y);
+        self.raster.line(self.current, to);
+        self.current = to;
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        let (from, control, to) = (self.current, self.point(x1, y1), self.point(x, y));
+        let steps = Self::segments(from, to);
+        for step in 1..=steps {
+            let t = step as f32 / steps as f32;
+            let u = 1.0 - t;
+            let next = (
+                u * u * from.0 + 2.0 * u * t * control.0 + t * t * to.0,
+                u * u * from.1 + 2.0 * u * t * control.1 + t * t * to.1,
+            );
+            self.raster.line(self.current, next);
+            self.current = next;
+        }
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        let (from, c1, c2, to) = (self.current, self.point(x1, y1), self.point(x2, y2), self.point(x, y));
+        let steps = Self::segments(from, to);
+        for step in 1..=steps {
+            let t = step as f32 / steps as f32;
+            let u = 1.0 - t;
+            let next = (
+                u * u * u * from.0 + 3.0 * u * u * t * c1.0 + 3.0 * u * t * t * c2.0 + t * t * t * to.0,
+                u * u * u * from.1 + 3.0 * u * u * t * c1.1 + 3.0 * u * t * t * c2.1 + t * t * t * to.1,
+            );
+            self.raster.line(self.current, next);
+            self.current = next;
+        }
+    }
+
+    fn close(&mut self) {
+        self.raster.line(self.current, self.start);
+        self.current = self.start;
+    }
+}
+
+fn line_width(face: &Face, text: &str, size: f32) -> f32 {
+    let scale = size / face.units_per_em() as f32;
+    text.chars()
+        .map(|c| {
+            let glyph = face.glyph_index(c).unwrap_or_default();
+            face.glyph_hor_advance(glyph).unwrap_or(0) as f32 * scale
+        })
+        .sum()
+}
+
+/// Family name from the `name` table, if it has a readable one.
+fn family_name(face: &Face) -> Option<String> {
+    face.names()
+        .into_iter()
+        .filter(|name| name.name_id == ttf_parser::name_id::FAMILY)
+        .find_map(|name| name.to_string())
+}
+
+/// Renders an alphabet, digits and a sample sentence at several sizes.
+pub fn render_specimen(data: &[u8]) -> anyhow::Result<egui::ColorImage> {
+    let sfnt;
+    let data = match data.get(0..4) {
+        Some(b"wOFF") => {
+            sfnt = woff_to_sfnt(data)?;
+            &sfnt[..]
+        }
+        Some(b"wOF2") => return Err(anyhow::anyhow!("WOFF2 fonts can't be previewed")),
+        _ => data,
+    };
+    let face = Face::parse(data, 0)?;
+
+    let title = family_name(&face).unwrap_or_else(|| "Font specimen".to_string());
+    let mut lines: Vec<(String, f32)> = vec![
+        (title, 28.0),
+        ("ABCDEFGHIJKLMNOPQRSTUVWXYZ".to_string(), 32.0),
+        ("abcdefghijklmnopqrstuvwxyz".to_string(), 32.0),
+        ("0123456789 !?.,:;'\"()[]&%$#@".to_string(), 32.0),
+    ];
+    lines.extend(SAMPLE_SIZES.iter().map(|&size| (SAMPLE_SENTENCE.to_string(), size)));
+
+    let units = face.units_per_em() as f32;
+    let line_height = |size: f32| (face.ascender() - face.descender()) as f32 * size / units + LINE_GAP;
+    let width = lines
+        .iter()
+        .map(|(text, size)| line_width(&face, text, *size))
+        .fold(0.0, f32::max)
+        .min(MAX_SHEET_WIDTH)
+        + 2.0 * MARGIN;
+    let height = lines.iter().map(|(_, size)| line_height(*size)).sum::<f32>() + 2.0 * MARGIN;
+
+    let mut raster = Rasterizer::new(width.ceil() as usize, height.ceil() as usize);
+    let mut top = MARGIN;
+    for (text, size) in &lines {
+        let scale = size / units;
+        let baseline = top + face.ascender() as f32 * scale;
+        let mut pen_x = MARGIN;
+        for c in text.chars() {
+            let glyph = face.glyph_index(c).unwrap_or_default();
+            let mut pen = GlyphPen {
+                raster: &mut raster,
+                scale,
+                origin: (pen_x, baseline),
+                start: (0.0, 0.0),
+                current: (0.0, 0.0),
+            };
+            face.outline_glyph(glyph, &mut pen);
+            pen.close();
+            pen_x += face.glyph_hor_advance(glyph).unwrap_or(0) as f32 * scale;
+        }
+        top += line_height(*size);
+    }
+    Ok(raster.into_image())
+}
+
+impl RpaEditor {
+    pub(crate) fn preview_font(&mut self, filename: &str, data: &[u8]) {
+        match render_specimen(data) {
+            Ok(image) => {
+                self.status_message = format!("Font specimen of {}", filename);
+                self.set_preview_image(Some(image));
+            }
+            Err(e) => {
+                self.status_message = format!("Could not render {}: {}", filename, e);
+            }
+        }
+    }
+}
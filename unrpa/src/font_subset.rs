@@ -0,0 +1,208 @@
+use std::collections::BTreeSet;
+use subsetter::GlyphRemapper;
+use tracing::warn;
+use crate::rpa::RpaEditor;
+use crate::rpyc;
+
+/// Printable ASCII is always kept so menus and UI labels keep rendering.
+const ALWAYS_KEPT: std::ops::RangeInclusive<char> = ' '..='~';
+
+/// Subsets a TrueType/OpenType font to the given characters.
+///
+/// `subsetter` strips the `cmap` table (it targets PDF embedding), so a format 12
+/// `cmap` for the kept characters is written back, along with the original `OS/2`.
+pub fn subset_font(data: &[u8], chars: &BTreeSet<char>) -> anyhow::Result<Vec<u8>> {
+    let face = ttf_parser::Face::parse(data, 0)?;
+
+    let mut mapping = Vec::new();
+    let mut remapper = GlyphRemapper::new();
+    for &c in chars.iter().chain(ALWAYS_KEPT.collect::<Vec<_>>().iter()) {
+        if let Some(glyph) = face.glyph_index(c) {
+            mapping.push((c as u32, glyph.0));
+        }
+    }
+    mapping.sort();
+    mapping.dedup();
+    for (_, glyph) in &mapping {
+        remapper.remap(*glyph);
+    }
+
+    let subset = subsetter::subset(data, 0, &remapper)
+        .map_err(|e| anyhow::anyhow!("Font subsetting failed: {}", e))?;
+
+    let cmap_mapping: Vec<(u32, u16)> = mapping
+        .iter()
+        .filter_map(|&(c, glyph)| remapper.get(glyph).map(|new_glyph| (c, new_glyph)))
+        .collect();
+
+    let mut extra_tables = vec![(*b"cmap", build_cmap(&cmap_mapping))];
+    if let Some(os2) = find_table(data, b"OS/2") {
+        extra_tables.push((*b"OS/2", os2.to_vec()));
+    }
+
+    rebuild_sfnt(&subset, extra_tables)
+}
+
+fn read_u16(data: &[u8], pos: usize) -> Option<u16> {
+    data.get(pos..pos + 2).map(|b| u16::from_be_bytes([b[0], b[1]]))
+}
+
+fn read_u32(data: &[u8], pos: usize) -> Option<u32> {
+    data.get(pos..pos + 4)
+        .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+fn read_tables(data: &[u8]) -> Option<Vec<([u8; 4], &[u8])>> {
+    let num_tables = read_u16(data, 4)? as usize;
+    let mut tables = Vec::with_capacity(num_tables);
+    for i in 0..num_tables {
+        let record = 12 + i * 16;
+        let tag: [u8; 4] = data.get(record..record + 4)?.try_into().ok()?;
+        let offset = read_u32(data, record + 8)? as usize;
+        let length = read_u32(data, record + 12)? as usize;
+        tables.push((tag, data.get(offset..offset + length)?));
+    }
+    Some(tables)
+}
+
+fn find_table<'a>(data: &'a [u8], tag: &[u8; 4]) -> Option<&'a [u8]> {
+    read_tables(data)?
+        .into_iter()
+        .find(|(t, _)| t == tag)
+        .map(|(_, table)| table)
+}
+
+fn build_cmap(mapping: &[(u32, u16)]) -> Vec<u8> {
+    let mut cmap = Vec::new();
+    cmap.extend_from_slice(&0u16.to_be_bytes());
+    cmap.extend_from_slice(&1u16.to_be_bytes());
+    // Windows, Unicode full repertoire
+    cmap.extend_from_slice(&3u16.to_be_bytes());
+    cmap.extend_from_slice(&10u16.to_be_bytes());
+    cmap.extend_from_slice(&12u32.to_be_bytes());
+
+    let mut groups: Vec<(u32, u32, u32)> = Vec::new();
+    for &(c, glyph) in mapping {
+        match groups.last_mut() {
+            Some((start, end, start_glyph))
+                if *end + 1 == c && *start_glyph + (c - *start) == glyph as u32 =>
+            {
+                *end = c;
+            }
+            _ => groups.push((c, c, glyph as u32)),
+        }
+    }
+
+    let length = 16 + groups.len() * 12;
+    cmap.extend_from_slice(&12u16.to_be_bytes());
+    cmap.extend_from_slice(&0u16.to_be_bytes());
+    cmap.extend_from_slice(&(length as u32).to_be_bytes());
+    cmap.extend_from_slice(&0u32.to_be_bytes());
+    cmap.extend_from_slice(&(groups.len() as u32).to_be_bytes());
+    for (start, end, start_glyph) in groups {
+        cmap.extend_from_slice(&start.to_be_bytes());
+        cmap.extend_from_slice(&end.to_be_bytes());
+        cmap.extend_from_slice(&start_glyph.to_be_bytes());
+    }
+    cmap
+}
+
+fn table_checksum(table: &[u8]) -> u32 {
+    table.chunks(4).fold(0u32, |sum, chunk| {
+        let mut word = [0u8; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(word))
+    })
+}
+
+fn rebuild_sfnt(data: &[u8], extra_tables: Vec<([u8; 4], Vec<u8>)>) -> anyhow::Result<Vec<u8>> {
+    let flavor = read_u32(data, 0).ok_or_else(|| anyhow::anyhow!("Truncated font header"))?;
+    let mut tables: Vec<([u8; 4], Vec<u8>)> = read_tables(data)
+        .ok_or_else(|| anyhow::anyhow!("Malformed font table directory"))?
+        .into_iter()
+        .map(|(tag, table)| (tag, table.to_vec()))
+        .collect();
+
+    for (tag, table) in extra_tables {
+        if !tables.iter().any(|(t, _)| *t == tag) {
+            tables.push((tag, table));
+        }
+    }
+    tables.sort_by_key(|(tag, _)| *tag);
+
+    // checkSumAdjustment must be zero while the table checksums are computed.
+    for (tag, table) in &mut tables {
+        if tag == b"head" && table.len() >= 12 {
+            table[8..12].copy_from_slice(&0u32.to_be_bytes());
+        }
+    }
+
+    let num_tables = tables.len() as u16;
+    let entry_selector = 15 - num_tables.leading_zeros() as u16;
+    let search_range = (1u16 << entry_selector) * 16;
+    let range_shift = num_tables * 16 - search_range;
+
+    let mut out = Vec::new();
+    out.extend_from_slice(&flavor.to_be_bytes());
+    out.extend_from_slice(&num_tables.to_be_bytes());
+    out.extend_from_slice(&search_range.to_be_bytes());
+    out.extend_from_slice(&entry_selector.to_be_bytes());
+    out.extend_from_slice(&range_shift.to_be_bytes());
+
+    let mut offset = 12 + tables.len() * 16;
+    let mut head_offset = None;
+    for (tag, table) in &tables {
+        if tag == b"head" {
+            head_offset = Some(offset);
+        }
+        out.extend_from_slice(tag);
+        out.extend_from_slice(&table_checksum(table).to_be_bytes());
+        out.extend_from_slice(&(offset as u32).to_be_bytes());
+        out.extend_from_slice(&(table.len() as u32).to_be_bytes());
+        offset += table.len().div_ceil(4) * 4;
+    }
+
+    for (_, table) in &tables {
+        out.extend_from_slice(table);
+        out.resize(out.len().div_ceil(4) * 4, 0);
+    }
+
+    if let Some(head) = head_offset {
+        let adjustment = 0xB1B0AFBAu32.wrapping_sub(table_checksum(&out));
+        out[head + 8..head + 12].copy_from_slice(&adjustment.to_be_bytes());
+    }
+
+    Ok(out)
+}
+
+impl RpaEditor {
+    /// Every character appearing in the archive's text scripts. A compiled `.rpyc`
+    /// is decompiled unless its `.rpy` source is in the archive too.
+    pub(crate) fn collect_script_characters(&self) -> BTreeSet<char> {
+        let mut chars = BTreeSet::new();
+        for (filename, entry) in &self.indexes {
+            if entry.to_delete {
+                continue;
+            }
+            let is_compiled = filename.to_lowercase().ends_with(".rpyc");
+            if is_compiled && self.indexes.contains_key(&filename[..filename.len() - 1]) {
+                continue;
+            }
+            if !is_compiled && !matches!(self.get_file_type(filename), "scripts" | "files") {
+                continue;
+            }
+            let Ok(data) = self.load_file_data(filename) else {
+                continue;
+            };
+            if is_compiled {
+                match rpyc::decompile(&data) {
+                    Ok(source) => chars.extend(source.chars().filter(|c| !c.is_control())),
+                    Err(e) => warn!("⚠️ Could not read the text of {}: {}", filename, e),
+                }
+            } else if let Ok(text) = std::str::from_utf8(&data) {
+                chars.extend(text.chars().filter(|c| !c.is_control()));
+            }
+        }
+        chars
+    }
+}
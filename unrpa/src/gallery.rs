@@ -0,0 +1,199 @@
+use std::collections::HashMap;
+use std::fs::{create_dir_all, File};
+use std::io::Read;
+use std::path::Path;
+use std::process::Command;
+use tracing::warn;
+use crate::archive_index::read_archive_index;
+use crate::entry_stream::EntryStream;
+use crate::rpa::{RpaEditor, RpaFileEntry};
+use crate::safe_path::{entry_output_path, sanitize_file_name};
+use crate::temp_files::default_temp_dir;
+
+const THUMBNAIL_SIZE: u32 = 256;
+
+struct GalleryItem {
+    filename: String,
+    thumbnail: Option<String>,
+    details: String,
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Renders thumbnails for every image and video entry plus an `index.html`.
+///
+/// Video frames are grabbed with the `ffmpeg` command line tool when it is installed.
+pub fn run_gallery(archive_path: &str, out_dir: &Path) -> anyhow::Result<usize> {
+    let index = read_archive_index(archive_path, false)?;
+    let gallery = Gallery {
+        archive_path: archive_path.to_string(),
+        indexes: index.entries,
+    };
+    gallery.render(out_dir)
+}
+
+/// The archive as the command line gallery reads it: just the index and the file.
+struct Gallery {
+    archive_path: String,
+    indexes: HashMap<String, RpaFileEntry>,
+}
+
+impl Gallery {
+    fn open_entry_stream(&self, filename: &str) -> anyhow::Result<EntryStream> {
+        let entry = self
+            .indexes
+            .get(filename)
+            .ok_or_else(|| anyhow::anyhow!("File not found"))?;
+        Ok(EntryStream::from_archive(File::open(&self.archive_path)?, entry.segments.clone()))
+    }
+
+    fn render(&self, out_dir: &Path) -> anyhow::Result<usize> {
+        let thumbs_dir = out_dir.join("thumbs");
+        create_dir_all(&thumbs_dir)?;
+
+        let mut files: Vec<_> = self
+            .indexes
+            .iter()
+            .filter(|(filename, _)| matches!(RpaEditor::file_type_by_extension(filename), "images" | "videos"))
+            .map(|(filename, _)| filename.clone())
+            .collect();
+        files.sort();
+
+        let mut items = Vec::new();
+        let mut rendered = 0;
+
+        for filename in files {
+            let thumb_name = format!("{}.png", sanitize_file_name(&filename.replace(['/', '\\'], "__")));
+            let thumb_path = thumbs_dir.join(&thumb_name);
+
+            let result = if RpaEditor::file_type_by_extension(&filename) == "images" {
+                self.render_image_thumbnail(&filename, &thumb_path)
+            } else {
+                self.render_video_thumbnail(&filename, &thumb_path)
+            };
+
+            match result {
+                Ok(details) => {
+                    rendered += 1;
+                    items.push(GalleryItem {
+                        filename,
+                        thumbnail: Some(format!("thumbs/{}", thumb_name)),
+                        details,
+                    });
+                }
+                Err(e) => {
+                    warn!("⚠️ No thumbnail for {}: {}", filename, e);
+                    items.push(GalleryItem {
+                        filename,
+                        thumbnail: None,
+                        details: e.to_string(),
+                    });
+                }
+            }
+        }
+
+        std::fs::write(out_dir.join("index.html"), self.gallery_html(&items))?;
+        Ok(rendered)
+    }
+
+    fn render_image_thumbnail(&self, filename: &str, thumb_path: &Path) -> anyhow::Result<String> {
+        let mut data = Vec::new();
+        self.open_entry_stream(filename)?.read_to_end(&mut data)?;
+        let img = image::load_from_memory(&data)?;
+        img.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE).save(thumb_path)?;
+        Ok(format!(
+            "{}×{} · {}",
+            img.width(),
+            img.height(),
+            RpaEditor::format_bytes(data.len() as u64)
+        ))
+    }
+
+    fn render_video_thumbnail(&self, filename: &str, thumb_path: &Path) -> anyhow::Result<String> {
+        let video_path = entry_output_path(&default_temp_dir().join("gallery"), filename)?;
+        if let Some(parent) = video_path.parent() {
+            create_dir_all(parent)?;
+        }
+        std::io::copy(&mut self.open_entry_stream(filename)?, &mut File::create(&video_path)?)?;
+        let output = Command::new("ffmpeg")
+            .arg("-y")
+            .arg("-loglevel")
+            .arg("error")
+            .arg("-i")
+            .arg(&video_path)
+            .arg("-frames:v")
+            .arg("1")
+            .arg("-vf")
+            .arg(format!(
+                "scale={0}:{0}:force_original_aspect_ratio=decrease",
+                THUMBNAIL_SIZE
+            ))
+            .arg(thumb_path)
+            .output();
+        let _ = std::fs::remove_file(&video_path);
+
+        let output = output.map_err(|e| anyhow::anyhow!("ffmpeg is not available: {}", e))?;
+        if !output.status.success() {
+            return Err(anyhow::anyhow!(
+                "ffmpeg failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+
+        let size = self.indexes.get(filename).map(|e| e.length).unwrap_or(0);
+        Ok(format!("video · {}", RpaEditor::format_bytes(size)))
+    }
+
+    fn gallery_html(&self, items: &[GalleryItem]) -> String {
+        let title = Path::new(&self.archive_path)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        let mut html = String::new();
+        html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+        html.push_str(&format!("<title>{} - Asset Gallery</title>\n", escape_html(&title)));
+        html.push_str(
+            "<style>\n\
+             body { font-family: sans-serif; background: #1e1e1e; color: #ddd; }\n\
+             .grid { display: flex; flex-wrap: wrap; gap: 12px; }\n\
+             .item { width: 270px; background: #2a2a2a; padding: 6px; border-radius: 4px; }\n\
+             .item img { max-width: 256px; max-height: 256px; display: block; margin: auto; }\n\
+             .name { font-size: 12px; word-break: break-all; }\n\
+             .details { font-size: 11px; color: #999; }\n\
+             </style>\n",
+        );
+        html.push_str("</head>\n<body>\n");
+        html.push_str(&format!(
+            "<h1>{}</h1>\n<p>{} assets</p>\n<div class=\"grid\">\n",
+            escape_html(&title),
+            items.len()
+        ));
+
+        for item in items {
+            html.push_str("<div class=\"item\">\n");
+            if let Some(ref thumb) = item.thumbnail {
+                html.push_str(&format!(
+                    "<img src=\"{}\" alt=\"{}\" loading=\"lazy\">\n",
+                    escape_html(thumb),
+                    escape_html(&item.filename)
+                ));
+            }
+            html.push_str(&format!(
+                "<div class=\"name\">{} {}</div>\n<div class=\"details\">{}</div>\n",
+                RpaEditor::get_file_icon(&item.filename),
+                escape_html(&item.filename),
+                escape_html(&item.details)
+            ));
+            html.push_str("</div>\n");
+        }
+
+        html.push_str("</div>\n</body>\n</html>\n");
+        html
+    }
+}
@@ -0,0 +1,161 @@
+use std::path::{Path, PathBuf};
+use tracing::info;
+use crate::header::HeaderFormat;
+use crate::rpa::RpaEditor;
+
+/// A Ren'Py game directory and the archives found under its `game/` folder.
+pub struct GameFolder {
+    pub root: PathBuf,
+    pub archives: Vec<(PathBuf, u64)>,
+    pub renpy_version: Option<String>,
+}
+
+impl GameFolder {
+    /// Accepts either the game's root or its `game/` folder.
+    pub fn scan(path: &Path) -> anyhow::Result<Self> {
+        let (root, game_dir) = if path.join("game").is_dir() {
+            (path.to_path_buf(), path.join("game"))
+        } else if path.file_name().is_some_and(|name| name == "game") {
+            (path.parent().unwrap_or(path).to_path_buf(), path.to_path_buf())
+        } else {
+            return Err(anyhow::anyhow!("{} has no game/ folder", path.display()));
+        };
+
+        let mut archives = Vec::new();
+        find_archives(&game_dir, &mut archives)?;
+        archives.sort();
+        Ok(Self {
+            renpy_version: detect_renpy_version(&root),
+            root,
+            archives,
+        })
+    }
+
+    /// Major Ren'Py version, when it could be detected.
+    fn major_version(&self) -> Option<u32> {
+        self.renpy_version.as_ref()?.split('.').next()?.parse().ok()
+    }
+
+    /// Archive format the game's engine reads; RPA-3.0 is understood by every Ren'Py since 6.
+    pub fn preferred_format(&self) -> &'static HeaderFormat {
+        match self.major_version() {
+            Some(major) if major < 6 => HeaderFormat::standard(2.0),
+            _ => HeaderFormat::standard(3.0),
+        }
+    }
+}
+
+fn find_archives(folder: &Path, archives: &mut Vec<(PathBuf, u64)>) -> anyhow::Result<()> {
+    for item in std::fs::read_dir(folder)? {
+        let item = item?;
+        let path = item.path();
+        if path.is_dir() {
+            find_archives(&path, archives)?;
+        } else if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("rpa")) {
+            archives.push((path, item.metadata()?.len()));
+        }
+    }
+    Ok(())
+}
+
+/// Digits of a version written as `8.1.3.23091805`, `(7, 4, 11)` or `version_tuple = (6, 99, 12, 4)`.
+fn parse_version(text: &str) -> Option<String> {
+    let numbers: Vec<&str> = text
+        .split(|c: char| !c.is_ascii_digit())
+        .filter(|part| !part.is_empty())
+        .take(3)
+        .collect();
+    (numbers.len() >= 2).then(|| numbers.join("."))
+}
+
+/// Reads the engine version from the files a Ren'Py distribution ships.
+fn detect_renpy_version(root: &Path) -> Option<String> {
+    if let Ok(text) = std::fs::read_to_string(root.join("game/script_version.txt"))
+        && let Some(version) = parse_version(&text)
+    {
+        return Some(version);
+    }
+    // Ren'Py 7.4 and later keep the version in vc_version.py, older ones in __init__.py.
+    for (file, marker) in [("renpy/vc_version.py", "version ="), ("renpy/__init__.py", "version_tuple =")] {
+        let Ok(text) = std::fs::read_to_string(root.join(file)) else {
+            continue;
+        };
+        if let Some(version) = text
+            .lines()
+            .find(|line| line.trim_start().starts_with(marker))
+            .and_then(|line| parse_version(line.split_once('=')?.1))
+        {
+            return Some(version);
+        }
+    }
+    None
+}
+
+impl RpaEditor {
+    pub(crate) fn open_game_folder(&mut self, path: &Path) {
+        match GameFolder::scan(path) {
+            Ok(folder) => {
+                self.status_message = format!(
+                    "Found {} archives in {} (Ren'Py {})",
+                    folder.archives.len(),
+                    folder.root.display(),
+                    folder.renpy_version.as_deref().unwrap_or("unknown")
+                );
+                info!("🎮 {}", self.status_message);
+                self.game_folder = Some(folder);
+                self.show_game_folder_dialog = true;
+            }
+            Err(e) => self.add_toast(format!("Not a Ren'Py game: {}", e)),
+        }
+    }
+
+    /// Archive list of the opened game, loaded into tabs on demand.
+    pub(crate) fn show_game_folder(&mut self, ui: &mut egui::Ui) {
+        let Some(folder) = &self.game_folder else {
+            return;
+        };
+        ui.label(format!("📁 {}", folder.root.display()));
+        ui.label(format!(
+            "Ren'Py version: {}",
+            folder.renpy_version.as_deref().unwrap_or("not detected")
+        ));
+        ui.label(format!("New archives are saved as {}", folder.preferred_format().magic));
+        ui.separator();
+
+        if folder.archives.is_empty() {
+            ui.label("No .rpa files in game/");
+        }
+        let game_dir = folder.root.join("game");
+        let mut open = Vec::new();
+        egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+            for (path, size) in &folder.archives {
+                ui.horizontal(|ui| {
+                    let name = path.strip_prefix(&game_dir).unwrap_or(path);
+                    ui.label(format!("📦 {}", name.display()));
+                    ui.label(Self::format_bytes(*size));
+                    let already_open = self.is_archive_open(&path.to_string_lossy());
+                    if ui.add_enabled(!already_open, egui::Button::new("📂 Open")).clicked() {
+                        open.push(path.clone());
+                    }
+                });
+            }
+        });
+        ui.separator();
+        ui.horizontal(|ui| {
+            if ui.button("📂 Open All").clicked() {
+                open = folder.archives.iter().map(|(path, _)| path.clone()).collect();
+                self.show_game_folder_dialog = false;
+            }
+            if ui.button("❌ Close").clicked() {
+                self.show_game_folder_dialog = false;
+            }
+        });
+
+        for path in open {
+            let path = path.to_string_lossy().to_string();
+            if !self.is_archive_open(&path) {
+                self.open_in_new_tab(&path);
+            }
+        }
+    }
+}
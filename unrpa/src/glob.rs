@@ -0,0 +1,90 @@
+/// Case-insensitive shell-style match: `*` stays within a path segment, `**` crosses
+/// segments and `?` is one character. A pattern without `/` only looks at the file name,
+/// so `*.ogg` finds every `.ogg` entry wherever it is.
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let name = name.to_lowercase();
+    let name = if pattern.contains(&'/') {
+        name.as_str()
+    } else {
+        name.rsplit('/').next().unwrap_or_default()
+    };
+    let name: Vec<char> = name.chars().collect();
+    let mut matcher = Matcher {
+        pattern: &pattern,
+        name: &name,
+        tried: vec![None; (pattern.len() + 1) * (name.len() + 1)],
+    };
+    matcher.matches(0, 0)
+}
+
+/// Backtracking over the stars, remembering the outcome at every (pattern, name)
+/// position so patterns like `*a*a*a*b` don't go exponential.
+struct Matcher<'a> {
+    pattern: &'a [char],
+    name: &'a [char],
+    tried: Vec<Option<bool>>,
+}
+
+impl Matcher<'_> {
+    fn matches(&mut self, p: usize, n: usize) -> bool {
+        let slot = p * (self.name.len() + 1) + n;
+        if let Some(matched) = self.tried[slot] {
+            return matched;
+        }
+        let (pattern, name) = (self.pattern, self.name);
+        let matched = match pattern[p..] {
+            [] => n == name.len(),
+            // "a/**/b" also matches "a/b".
+            ['*', '*', '/', ..] => (n..=name.len())
+                .filter(|&i| i == n || name[i - 1] == '/')
+                .any(|i| self.matches(p + 3, i)),
+            ['*', '*', ..] => (n..=name.len()).any(|i| self.matches(p + 2, i)),
+            ['*', ..] => (n..=name.len())
+                .take_while(|&i| i == n || name[i - 1] != '/')
+                .any(|i| self.matches(p + 1, i)),
+            ['?', ..] => name.get(n).is_some_and(|&c| c != '/') && self.matches(p + 1, n + 1),
+            [c, ..] => name.get(n) == Some(&c) && self.matches(p + 1, n + 1),
+        };
+        self.tried[slot] = Some(matched);
+        matched
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn star_stays_in_one_segment() {
+        assert!(glob_match("images/*.png", "images/bg.png"));
+        assert!(!glob_match("images/*.png", "images/ui/bg.png"));
+        assert!(glob_match("images/**.png", "images/ui/bg.png"));
+    }
+
+    #[test]
+    fn double_star_slash_matches_zero_segments() {
+        assert!(glob_match("images/**/bg.png", "images/bg.png"));
+        assert!(glob_match("images/**/bg.png", "images/ui/menu/bg.png"));
+        assert!(!glob_match("images/**/bg.png", "images/xbg.png"));
+    }
+
+    #[test]
+    fn question_mark_is_not_a_slash() {
+        assert!(glob_match("audio/a?b.ogg", "audio/a-b.ogg"));
+        assert!(!glob_match("audio/a?b.ogg", "audio/a/b.ogg"));
+    }
+
+    #[test]
+    fn name_only_pattern_ignores_case_and_folders() {
+        assert!(glob_match("*.OGG", "music/Theme.ogg"));
+        assert!(!glob_match("*.ogg", "music.ogg/theme.png"));
+    }
+
+    #[test]
+    fn many_stars_fail_quickly() {
+        let name = "a".repeat(200);
+        assert!(!glob_match("*a*a*a*a*a*a*a*a*a*a*b", &name));
+        assert!(glob_match("**a**a**a**a**a", &name));
+    }
+}
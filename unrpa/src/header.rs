@@ -0,0 +1,131 @@
+use std::io::Write;
+
+/// Layout of an archive header line: `<magic> <offset> [<subkey>...]`.
+///
+/// A renamed variant that only differs in its magic and in how the key is derived
+/// from the header is supported by adding a row to [`HEADER_FORMATS`].
+#[derive(Debug)]
+pub struct HeaderFormat {
+    pub magic: &'static str,
+    /// Standard version whose index layout the variant uses.
+    pub version: f32,
+    /// Position of the first subkey in the split header line, 0 when unkeyed.
+    pub key_start: usize,
+    pub derive_key: fn(&[u32]) -> u32,
+}
+
+fn xor_subkeys(subkeys: &[u32]) -> u32 {
+    subkeys.iter().fold(0, |key, subkey| key ^ subkey)
+}
+
+pub static HEADER_FORMATS: &[HeaderFormat] = &[
+    HeaderFormat { magic: "RPA-3.2", version: 3.2, key_start: 3, derive_key: xor_subkeys },
+    HeaderFormat { magic: "RPA-3.0", version: 3.0, key_start: 2, derive_key: xor_subkeys },
+    HeaderFormat { magic: "RPA-2.0", version: 2.0, key_start: 0, derive_key: xor_subkeys },
+];
+
+/// Header parsed from the first line of an archive.
+#[derive(Debug)]
+pub struct ParsedHeader {
+    pub format: &'static HeaderFormat,
+    pub offset: u64,
+    pub subkeys: Vec<u32>,
+    pub key: u32,
+}
+
+impl HeaderFormat {
+    pub fn detect(head: &[u8]) -> Option<&'static HeaderFormat> {
+        HEADER_FORMATS.iter().find(|format| {
+            head.starts_with(format.magic.as_bytes())
+                && head.get(format.magic.len()) == Some(&b' ')
+        })
+    }
+
+    /// Standard format written when converting to `version`.
+    pub fn standard(version: f32) -> &'static HeaderFormat {
+        HEADER_FORMATS
+            .iter()
+            .find(|format| format.magic.starts_with("RPA-") && format.version == version)
+            .unwrap_or(&HEADER_FORMATS[1])
+    }
+
+    pub fn parse(line: &str) -> anyhow::Result<ParsedHeader> {
+        let format = Self::detect(line.as_bytes())
+            .ok_or_else(|| anyhow::anyhow!("Unsupported RPA version"))?;
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        let offset = parts
+            .get(1)
+            .ok_or_else(|| anyhow::anyhow!("Header is missing the index offset"))?;
+        let offset = u64::from_str_radix(offset, 16)?;
+
+        let mut subkeys = Vec::new();
+        if format.key_start > 0 {
+            for part in parts.iter().skip(format.key_start) {
+                subkeys.push(u32::from_str_radix(part, 16)?);
+            }
+        }
+
+        Ok(ParsedHeader {
+            format,
+            offset,
+            key: if format.key_start > 0 { (format.derive_key)(&subkeys) } else { 0 },
+            subkeys,
+        })
+    }
+
+    pub fn is_keyed(&self) -> bool {
+        self.key_start > 0
+    }
+
+    /// Length of the header line written by [`HeaderFormat::write`], newline included.
+    pub fn header_len(&self) -> u64 {
+        let keys = if self.is_keyed() { self.key_start - 1 } else { 0 };
+        (self.magic.len() + 1 + 16 + keys * 9 + 1) as u64
+    }
+
+    /// Writes the header with `key` as the last subkey (placeholder subkeys are zero).
+    pub fn write(&self, out: &mut impl Write, offset: u64, key: u32) -> std::io::Result<()> {
+        write!(out, "{} {:016x}", self.magic, offset)?;
+        if self.is_keyed() {
+            for _ in 2..self.key_start {
+                write!(out, " {:08x}", 0)?;
+            }
+            write!(out, " {:08x}", key)?;
+        }
+        writeln!(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(format: &'static HeaderFormat, key: u32) -> ParsedHeader {
+        let mut line = Vec::new();
+        format.write(&mut line, 0x1234_5678, key).unwrap();
+        assert_eq!(line.len() as u64, format.header_len());
+        HeaderFormat::parse(std::str::from_utf8(&line).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn standard_headers_round_trip() {
+        let header = round_trip(HeaderFormat::standard(3.0), 0xDEADBEEF);
+        assert_eq!(header.format.magic, "RPA-3.0");
+        assert_eq!((header.offset, header.key), (0x1234_5678, 0xDEADBEEF));
+
+        let header = round_trip(HeaderFormat::standard(3.2), 0x42);
+        assert_eq!(header.format.magic, "RPA-3.2");
+        assert_eq!(header.subkeys, vec![0x42]);
+        assert_eq!(header.key, 0x42);
+
+        let header = round_trip(HeaderFormat::standard(2.0), 0xDEADBEEF);
+        assert_eq!((header.offset, header.key), (0x1234_5678, 0));
+    }
+
+    #[test]
+    fn subkeys_are_xored() {
+        let header = HeaderFormat::parse("RPA-3.0 0000000000000100 0000ff00 000000ff\n").unwrap();
+        assert_eq!(header.offset, 0x100);
+        assert_eq!(header.key, 0xffff);
+    }
+}
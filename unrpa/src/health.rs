@@ -0,0 +1,155 @@
+use std::path::PathBuf;
+use std::process::Command;
+use tracing::{info, warn};
+use crate::rpa::RpaEditor;
+
+/// Result of one startup check, with what to do about a failure.
+pub struct HealthCheck {
+    pub name: &'static str,
+    pub ok: bool,
+    pub detail: String,
+    pub hint: &'static str,
+}
+
+impl HealthCheck {
+    fn new(name: &'static str, result: Result<String, String>, hint: &'static str) -> Self {
+        let ok = result.is_ok();
+        let detail = result.unwrap_or_else(|e| e);
+        Self { name, ok, detail, hint }
+    }
+}
+
+/// Per-user folder for editor settings.
+pub fn config_dir() -> PathBuf {
+    let base = if cfg!(target_os = "windows") {
+        std::env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        std::env::var_os("HOME").map(|home| PathBuf::from(home).join("Library/Application Support"))
+    } else {
+        std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+    };
+    base.unwrap_or_else(std::env::temp_dir).join("rpa_editor")
+}
+
+/// Creates `dir` and round-trips a small file through it.
+fn check_writable(dir: PathBuf) -> Result<String, String> {
+    let probe = dir.join(format!(".rpa_editor_probe_{}", std::process::id()));
+    std::fs::create_dir_all(&dir)
+        .and_then(|_| std::fs::write(&probe, b"ok"))
+        .and_then(|_| std::fs::remove_file(&probe))
+        .map(|_| format!("{} is writable", dir.display()))
+        .map_err(|e| format!("{}: {}", dir.display(), e))
+}
+
+fn check_ffmpeg() -> Result<String, String> {
+    let output = Command::new("ffmpeg")
+        .arg("-version")
+        .output()
+        .map_err(|e| format!("ffmpeg not found: {}", e))?;
+    if !output.status.success() {
+        return Err("ffmpeg -version failed".to_string());
+    }
+    let version = String::from_utf8_lossy(&output.stdout);
+    Ok(version.lines().next().unwrap_or("ffmpeg").to_string())
+}
+
+impl RpaEditor {
+    /// Probes what optional features depend on, so failures show up at launch.
+    pub(crate) fn run_health_check(&mut self) {
+        let audio = if self.audio_player.has_output() {
+            Ok("Default output device opened".to_string())
+        } else {
+            Err("No audio output device".to_string())
+        };
+        let video_audio = if self.audio_device.is_some() {
+            Ok("SDL audio device opened".to_string())
+        } else {
+            Err("SDL audio device unavailable".to_string())
+        };
+
+        self.health_checks = vec![
+            HealthCheck::new(
+                "Audio output",
+                audio,
+                "Connect or enable an output device, then restart; audio previews stay silent until then.",
+            ),
+            HealthCheck::new(
+                "Video audio",
+                video_audio,
+                "Videos will play without sound. Check that SDL can open your sound card.",
+            ),
+            HealthCheck::new(
+                "Graphics",
+                self.graphics_backend.clone().ok_or_else(|| "No OpenGL context".to_string()),
+                "Update your graphics driver; image and video previews need GPU textures.",
+            ),
+            HealthCheck::new(
+                "ffmpeg",
+                check_ffmpeg(),
+                "Install ffmpeg and add it to PATH to transcode audio and video in the job queue.",
+            ),
+            HealthCheck::new(
+                "Temp folder",
+                check_writable(self.temp_dir.clone()),
+                "Pick a writable temp folder in Tools; videos are extracted there for playback.",
+            ),
+            HealthCheck::new(
+                "Config folder",
+                check_writable(config_dir()),
+                "Fix the folder's permissions so settings can be saved.",
+            ),
+        ];
+
+        let failed = self.health_checks.iter().filter(|check| !check.ok).count();
+        for check in &self.health_checks {
+            if check.ok {
+                info!("✅ {}: {}", check.name, check.detail);
+            } else {
+                warn!("⚠️ {}: {}", check.name, check.detail);
+            }
+        }
+        if failed > 0 {
+            self.show_health_dialog = true;
+        }
+    }
+
+    /// OpenGL version string from the renderer, if eframe created a GL context.
+    pub(crate) fn detect_graphics(cc: &eframe::CreationContext<'_>) -> Option<String> {
+        use eframe::glow::HasContext;
+        let gl = cc.gl.as_ref()?;
+        // SAFETY: the context is current while the app is being created.
+        let version = unsafe { gl.get_parameter_string(eframe::glow::VERSION) };
+        Some(format!("OpenGL {}", version))
+    }
+
+    pub(crate) fn show_health_report(&mut self, ui: &mut egui::Ui) {
+        let failed = self.health_checks.iter().filter(|check| !check.ok).count();
+        if failed == 0 {
+            ui.label("Everything the editor depends on is available.");
+        } else {
+            ui.label(format!("{} check(s) need attention; the related features are limited until fixed.", failed));
+        }
+        ui.separator();
+
+        egui::Grid::new("health_checks").num_columns(3).striped(true).show(ui, |ui| {
+            for check in &self.health_checks {
+                ui.label(if check.ok { "✅" } else { "⚠️" });
+                ui.strong(check.name);
+                ui.vertical(|ui| {
+                    ui.label(&check.detail);
+                    if !check.ok {
+                        ui.label(egui::RichText::new(check.hint).small().color(egui::Color32::YELLOW));
+                    }
+                });
+                ui.end_row();
+            }
+        });
+
+        ui.separator();
+        if ui.button("🔄 Run again").clicked() {
+            self.run_health_check();
+        }
+    }
+}
@@ -0,0 +1,260 @@
+use tracing::info;
+use crate::rpa::RpaEditor;
+
+pub const BYTES_PER_ROW: usize = 16;
+pub const PAGE_SIZE: usize = 512;
+
+/// Parses "DE AD be ef" or "deadbeef" into bytes.
+pub fn parse_hex_bytes(text: &str) -> anyhow::Result<Vec<u8>> {
+    let digits: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    if !digits.len().is_multiple_of(2) {
+        return Err(anyhow::anyhow!("Odd number of hex digits"));
+    }
+    (0..digits.len())
+        .step_by(2)
+        .map(|i| {
+            u8::from_str_radix(&digits[i..i + 2], 16)
+                .map_err(|_| anyhow::anyhow!("Invalid hex byte: {}", &digits[i..i + 2]))
+        })
+        .collect()
+}
+
+impl RpaEditor {
+    pub(crate) fn start_hex_edit(&mut self, filename: &str) {
+        if self.blocked_by_read_only() {
+            return;
+        }
+        // The paged hex view doesn't keep the entry in memory.
+        if self.preview_data.is_none() {
+            match self.load_file_data(filename) {
+                Ok(data) => self.preview_data = Some(data),
+                Err(e) => {
+                    self.status_message = format!("Read error: {}", e);
+                    return;
+                }
+            }
+        }
+        self.hex_buffer = self.preview_data.clone().unwrap_or_default();
+        self.hex_editing_file = Some(filename.to_string());
+        self.hex_cursor = 0;
+        self.hex_anchor = None;
+        self.hex_nibble = None;
+        self.hex_view_offset = 0;
+    }
+
+    /// True when the buffer differs from the entry's current bytes.
+    pub(crate) fn hex_edit_dirty(&self) -> bool {
+        self.hex_editing_file.is_some() && self.preview_data.as_ref() != Some(&self.hex_buffer)
+    }
+
+    /// Selected byte range; without a selection it's the byte under the cursor.
+    fn hex_selection(&self) -> std::ops::Range<usize> {
+        let (start, end) = match self.hex_anchor {
+            Some(anchor) => (anchor.min(self.hex_cursor), anchor.max(self.hex_cursor) + 1),
+            None => (self.hex_cursor, self.hex_cursor + 1),
+        };
+        start.min(self.hex_buffer.len())..end.min(self.hex_buffer.len())
+    }
+
+    fn move_hex_cursor(&mut self, position: usize, extend: bool) {
+        if extend {
+            self.hex_anchor.get_or_insert(self.hex_cursor);
+        } else {
+            self.hex_anchor = None;
+        }
+        self.hex_cursor = position.min(self.hex_buffer.len().saturating_sub(1));
+        self.hex_nibble = None;
+
+        // Keep the cursor on the visible page.
+        if self.hex_cursor < self.hex_view_offset || self.hex_cursor >= self.hex_view_offset + PAGE_SIZE {
+            self.hex_view_offset = self.hex_cursor / BYTES_PER_ROW * BYTES_PER_ROW;
+        }
+    }
+
+    /// Overwrites the byte under the cursor, one hex digit at a time.
+    fn type_hex_digit(&mut self, digit: u8) {
+        if self.hex_buffer.is_empty() {
+            self.hex_buffer.push(0);
+        }
+        let cursor = self.hex_cursor;
+        match self.hex_nibble.take() {
+            None => {
+                self.hex_buffer[cursor] = digit << 4 | (self.hex_buffer[cursor] & 0x0F);
+                self.hex_nibble = Some(digit);
+            }
+            Some(high) => {
+                self.hex_buffer[cursor] = high << 4 | digit;
+                if cursor + 1 < self.hex_buffer.len() {
+                    self.hex_cursor += 1;
+                }
+            }
+        }
+        self.hex_anchor = None;
+    }
+
+    pub(crate) fn hex_insert(&mut self, bytes: &[u8]) {
+        let at = self.hex_cursor.min(self.hex_buffer.len());
+        self.hex_buffer.splice(at..at, bytes.iter().copied());
+        self.hex_anchor = None;
+        self.hex_nibble = None;
+    }
+
+    pub(crate) fn hex_overwrite(&mut self, bytes: &[u8]) {
+        let at = self.hex_cursor.min(self.hex_buffer.len());
+        let end = (at + bytes.len()).min(self.hex_buffer.len());
+        self.hex_buffer.splice(at..end, bytes.iter().copied());
+        self.hex_anchor = None;
+        self.hex_nibble = None;
+    }
+
+    pub(crate) fn hex_delete_selection(&mut self) {
+        let range = self.hex_selection();
+        let start = range.start;
+        self.hex_buffer.drain(range);
+        self.move_hex_cursor(start, false);
+    }
+
+    /// Writes the edited buffer back into the entry, which marks it modified.
+    pub(crate) fn apply_hex_edit(&mut self) {
+        if self.blocked_by_read_only() {
+            return;
+        }
+        let Some(filename) = self.hex_editing_file.clone() else {
+            return;
+        };
+
+        self.backup_before(&filename, "edit");
+        if let Err(e) = self.insert_data(&filename, self.hex_buffer.clone()) {
+            self.add_error(format!("Can't apply the hex edit: {}", e));
+            return;
+        }
+        self.set_provenance(&filename, "edited in hex editor");
+
+        self.preview_data = Some(self.hex_buffer.clone());
+        self.status_message = format!("Applied {} bytes to {}", self.hex_buffer.len(), filename);
+    }
+
+    pub(crate) fn close_hex_edit(&mut self) {
+        if self.hex_edit_dirty()
+            && let Some(filename) = &self.hex_editing_file
+        {
+            info!("↩️ Discarded unapplied hex edits to {}", filename);
+        }
+        self.hex_editing_file = None;
+        self.hex_buffer = Vec::new();
+        self.hex_anchor = None;
+        self.hex_nibble = None;
+    }
+
+    fn handle_hex_keys(&mut self, ui: &egui::Ui) {
+        if ui.ctx().wants_keyboard_input() {
+            return;
+        }
+        let events = ui.input(|input| input.events.clone());
+        for event in events {
+            match event {
+                egui::Event::Text(text) => {
+                    for c in text.chars() {
+                        if let Some(digit) = c.to_digit(16) {
+                            self.type_hex_digit(digit as u8);
+                        }
+                    }
+                }
+                egui::Event::Key { key, pressed: true, modifiers, .. } => {
+                    let cursor = self.hex_cursor;
+                    match key {
+                        egui::Key::ArrowLeft => self.move_hex_cursor(cursor.saturating_sub(1), modifiers.shift),
+                        egui::Key::ArrowRight => self.move_hex_cursor(cursor + 1, modifiers.shift),
+                        egui::Key::ArrowUp => self.move_hex_cursor(cursor.saturating_sub(BYTES_PER_ROW), modifiers.shift),
+                        egui::Key::ArrowDown => self.move_hex_cursor(cursor + BYTES_PER_ROW, modifiers.shift),
+                        egui::Key::Delete | egui::Key::Backspace => self.hex_delete_selection(),
+                        _ => {}
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    pub(crate) fn show_hex_editor(&mut self, ui: &mut egui::Ui) {
+        self.handle_hex_keys(ui);
+
+        ui.horizontal(|ui| {
+            ui.label(format!("📊 {} bytes", self.hex_buffer.len()));
+            let selection = self.hex_selection();
+            ui.label(format!(
+                "Cursor 0x{:X}, selected {} bytes",
+                self.hex_cursor,
+                selection.len()
+            ));
+            ui.separator();
+            if ui.button("⬆️ Prev").clicked() {
+                self.hex_view_offset = self.hex_view_offset.saturating_sub(PAGE_SIZE);
+            }
+            if ui.button("⬇️ Next").clicked() && self.hex_view_offset + PAGE_SIZE < self.hex_buffer.len() {
+                self.hex_view_offset += PAGE_SIZE;
+            }
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Bytes:");
+            ui.add(egui::TextEdit::singleline(&mut self.hex_input).desired_width(160.0).hint_text("DE AD BE EF"));
+            let parsed = parse_hex_bytes(&self.hex_input);
+            ui.add_enabled_ui(parsed.is_ok() && !self.hex_input.trim().is_empty(), |ui| {
+                if ui.button("Insert at cursor").clicked()
+                    && let Ok(bytes) = &parsed
+                {
+                    self.hex_insert(bytes);
+                }
+                if ui.button("Overwrite at cursor").clicked()
+                    && let Ok(bytes) = &parsed
+                {
+                    self.hex_overwrite(bytes);
+                }
+            });
+            if ui.button("🗑️ Delete selection").clicked() {
+                self.hex_delete_selection();
+            }
+        });
+        ui.label(
+            egui::RichText::new("Type hex digits to overwrite, arrows move, Shift extends the selection, Delete removes it")
+                .small()
+                .weak(),
+        );
+
+        ui.separator();
+
+        let selection = self.hex_selection();
+        let end = (self.hex_view_offset + PAGE_SIZE).min(self.hex_buffer.len());
+        let mut clicked = None;
+        for row_start in (self.hex_view_offset..end).step_by(BYTES_PER_ROW) {
+            let row_end = (row_start + BYTES_PER_ROW).min(end);
+            ui.horizontal(|ui| {
+                ui.spacing_mut().item_spacing.x = 4.0;
+                ui.monospace(format!("{:08X}:", row_start));
+                for offset in row_start..row_end {
+                    let text = match self.hex_nibble {
+                        Some(high) if offset == self.hex_cursor => format!("{:X}_", high),
+                        _ => format!("{:02X}", self.hex_buffer[offset]),
+                    };
+                    let is_selected = selection.contains(&offset);
+                    if ui
+                        .selectable_label(is_selected, egui::RichText::new(text).monospace())
+                        .clicked()
+                    {
+                        clicked = Some(offset);
+                    }
+                }
+                let ascii: String = self.hex_buffer[row_start..row_end]
+                    .iter()
+                    .map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' })
+                    .collect();
+                ui.monospace(ascii);
+            });
+        }
+        if let Some(offset) = clicked {
+            let extend = ui.input(|input| input.modifiers.shift);
+            self.move_hex_cursor(offset, extend);
+        }
+    }
+}
@@ -0,0 +1,287 @@
+use std::io::{Read, Seek, SeekFrom};
+use crate::archive_reader::mapped_entry;
+use crate::entry_stream::EntryStream;
+use crate::hex_editor::{parse_hex_bytes, BYTES_PER_ROW, PAGE_SIZE};
+use crate::rpa::RpaEditor;
+
+/// Bytes read per step while searching; matches spanning two chunks are still found.
+const SEARCH_CHUNK: usize = 1 << 20;
+
+/// Chunks searched per frame, so the UI stays responsive on big entries.
+const SEARCH_CHUNKS_PER_FRAME: usize = 8;
+
+/// A "Find Next" in progress, advanced a few chunks every frame.
+pub struct HexSearch {
+    filename: String,
+    pattern: Vec<u8>,
+    stream: EntryStream,
+    buffer: Vec<u8>,
+    /// Bytes kept from the previous chunk for matches spanning two.
+    carried: usize,
+    /// Entry offset of `buffer[0]`.
+    base: usize,
+    /// Where the search began; it wraps around to the start once and stops there.
+    from: usize,
+    wrapped: bool,
+    found: Option<usize>,
+}
+
+impl HexSearch {
+    fn new(mut stream: EntryStream, filename: &str, pattern: Vec<u8>, from: usize) -> anyhow::Result<Self> {
+        stream.seek(SeekFrom::Start(from as u64))?;
+        Ok(Self {
+            filename: filename.to_string(),
+            buffer: vec![0u8; SEARCH_CHUNK + pattern.len() - 1],
+            pattern,
+            stream,
+            carried: 0,
+            base: from,
+            from,
+            wrapped: false,
+            found: None,
+        })
+    }
+
+    /// Searches the next chunk; true once the search is over, with `found` set on a match.
+    fn step(&mut self) -> anyhow::Result<bool> {
+        let read = self.stream.read(&mut self.buffer[self.carried..])?;
+        if read == 0 || (self.wrapped && self.base >= self.from) {
+            if self.wrapped || self.from == 0 {
+                return Ok(true);
+            }
+            self.stream.seek(SeekFrom::Start(0))?;
+            self.wrapped = true;
+            self.base = 0;
+            self.carried = 0;
+            return Ok(false);
+        }
+
+        let filled = self.carried + read;
+        if let Some(pos) = self.buffer[..filled].windows(self.pattern.len()).position(|w| w == self.pattern) {
+            self.found = Some(self.base + pos);
+            return Ok(true);
+        }
+        let keep = (self.pattern.len() - 1).min(filled);
+        self.buffer.copy_within(filled - keep..filled, 0);
+        self.base += filled - keep;
+        self.carried = keep;
+        Ok(false)
+    }
+}
+
+impl RpaEditor {
+    fn hex_entry_len(&self, filename: &str) -> usize {
+        match &self.preview_data {
+            Some(data) => data.len(),
+            None => self.indexes.get(filename).map_or(0, |entry| entry.length as usize),
+        }
+    }
+
+    /// Reads `length` bytes at `offset` straight from the entry, without loading the rest.
+    pub(crate) fn read_entry_range(&self, filename: &str, offset: u64, length: usize) -> anyhow::Result<Vec<u8>> {
+        if let Some(map) = self.archive_map()
+            && let Some(entry) = self.indexes.get(filename)
+            && entry.in_archive()
+            && let Some(data) = mapped_entry(&map, &entry.segments)
+        {
+            let start = (offset as usize).min(data.len());
+            return Ok(data[start..(start + length).min(data.len())].to_vec());
+        }
+        let mut stream = self.open_entry_stream(filename)?;
+        stream.seek(SeekFrom::Start(offset))?;
+        let mut data = Vec::with_capacity(length);
+        stream.take(length as u64).read_to_end(&mut data)?;
+        Ok(data)
+    }
+
+    /// Bytes of the visible page, from `preview_data` when loaded or from the archive otherwise.
+    fn hex_page(&mut self, filename: &str) -> Vec<u8> {
+        let start = self.hex_view_offset;
+        if let Some(data) = &self.preview_data {
+            let end = (start + PAGE_SIZE).min(data.len());
+            return data.get(start..end).unwrap_or_default().to_vec();
+        }
+
+        let key = (filename.to_string(), start);
+        if self.hex_page_key.as_ref() != Some(&key) {
+            self.hex_page = self
+                .read_entry_range(filename, start as u64, PAGE_SIZE)
+                .unwrap_or_else(|e| {
+                    self.status_message = format!("Read error: {}", e);
+                    Vec::new()
+                });
+            self.hex_page_key = Some(key);
+        }
+        self.hex_page.clone()
+    }
+
+    fn hex_search_pattern(&self) -> anyhow::Result<Vec<u8>> {
+        let pattern = if self.hex_search_mode == "ascii" {
+            self.hex_search.as_bytes().to_vec()
+        } else {
+            parse_hex_bytes(&self.hex_search)?
+        };
+        if pattern.is_empty() {
+            return Err(anyhow::anyhow!("Nothing to search for"));
+        }
+        Ok(pattern)
+    }
+
+    /// Starts looking for the next match after the current one, wrapping around to the
+    /// start once. `poll_hex_search` carries it on over the next frames.
+    pub(crate) fn hex_find_next(&mut self, filename: &str) -> anyhow::Result<()> {
+        let pattern = self.hex_search_pattern()?;
+        let from = self.hex_match.map_or(self.hex_view_offset, |m| m + 1);
+        let stream = self.open_entry_stream(filename)?;
+        self.hex_search_task = Some(HexSearch::new(stream, filename, pattern, from)?);
+        Ok(())
+    }
+
+    /// Advances the running search and jumps to its match once it's found.
+    fn poll_hex_search(&mut self, ctx: &egui::Context, filename: &str) {
+        let Some(search) = &mut self.hex_search_task else {
+            return;
+        };
+        if search.filename != filename {
+            self.hex_search_task = None;
+            return;
+        }
+        let mut result = Ok(false);
+        for _ in 0..SEARCH_CHUNKS_PER_FRAME {
+            result = search.step();
+            if !matches!(result, Ok(false)) {
+                break;
+            }
+        }
+        let found = search.found;
+        match result {
+            Ok(false) => {
+                ctx.request_repaint();
+                return;
+            }
+            Ok(true) => {
+                self.hex_match = found;
+                if let Some(offset) = found {
+                    self.hex_view_offset = offset / BYTES_PER_ROW * BYTES_PER_ROW;
+                    self.status_message = format!("Found at 0x{:X}", offset);
+                } else {
+                    self.status_message = "Pattern not found".to_string();
+                }
+            }
+            Err(e) => self.status_message = format!("Search error: {}", e),
+        }
+        self.hex_search_task = None;
+    }
+
+    pub(crate) fn hex_goto(&mut self, filename: &str) {
+        let text = self.hex_goto_input.trim();
+        let parsed = match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+            Some(hex) => usize::from_str_radix(hex, 16),
+            None => text.parse(),
+        };
+        match parsed {
+            Ok(offset) if offset < self.hex_entry_len(filename) => {
+                self.hex_view_offset = offset / BYTES_PER_ROW * BYTES_PER_ROW;
+                self.hex_match = Some(offset);
+            }
+            Ok(_) => self.status_message = "Offset is past the end of the entry".to_string(),
+            Err(_) => self.status_message = format!("Invalid offset: {}", text),
+        }
+    }
+
+    /// Paged hex dump with search and go-to; only the visible page is read from disk.
+    pub(crate) fn show_hex_view(&mut self, ui: &mut egui::Ui, filename: &str) {
+        let total = self.hex_entry_len(filename);
+        let last_page = total.saturating_sub(1) / PAGE_SIZE * PAGE_SIZE;
+
+        ui.horizontal(|ui| {
+            ui.label("📊 File size:");
+            ui.strong(Self::format_bytes(total as u64));
+            ui.separator();
+
+            if ui.button("⏮ Top").clicked() {
+                self.hex_view_offset = 0;
+            }
+            if ui.button("⬆️ Prev").clicked() {
+                self.hex_view_offset = self.hex_view_offset.saturating_sub(PAGE_SIZE);
+            }
+            if ui.button("⬇️ Next").clicked() {
+                self.hex_view_offset = (self.hex_view_offset + PAGE_SIZE).min(last_page);
+            }
+            if ui.button("⏭ End").clicked() {
+                self.hex_view_offset = last_page;
+            }
+        });
+
+        ui.horizontal(|ui| {
+            ui.label("Go to:");
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut self.hex_goto_input)
+                    .desired_width(100.0)
+                    .hint_text("0x1A2B"),
+            );
+            if (response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)))
+                || ui.button("➡").clicked()
+            {
+                self.hex_goto(filename);
+            }
+
+            ui.separator();
+            ui.label("🔍");
+            let response = ui.add(egui::TextEdit::singleline(&mut self.hex_search).desired_width(160.0));
+            egui::ComboBox::from_id_salt("hex_search_mode")
+                .selected_text(if self.hex_search_mode == "ascii" { "Text" } else { "Hex" })
+                .width(60.0)
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut self.hex_search_mode, "hex".to_string(), "Hex");
+                    ui.selectable_value(&mut self.hex_search_mode, "ascii".to_string(), "Text");
+                });
+            if let Some(search) = &self.hex_search_task {
+                ui.spinner();
+                ui.label(format!("0x{:X}", search.base));
+                if ui.button("⏹ Stop").clicked() {
+                    self.hex_search_task = None;
+                    self.status_message = "Search stopped".to_string();
+                }
+            } else {
+                let find = (response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)))
+                    || ui.button("Find Next").clicked();
+                if find && let Err(e) = self.hex_find_next(filename) {
+                    self.status_message = format!("Search error: {}", e);
+                }
+            }
+        });
+        self.poll_hex_search(ui.ctx(), filename);
+
+        ui.separator();
+        ui.heading("🔍 Hex Preview");
+
+        let page = self.hex_page(filename);
+        let start_offset = self.hex_view_offset;
+        for (i, chunk) in page.chunks(BYTES_PER_ROW).enumerate() {
+            let addr = start_offset + i * BYTES_PER_ROW;
+            let hex: String = chunk.iter().map(|b| format!("{:02X}", b)).collect::<Vec<_>>().join(" ");
+            let ascii: String = chunk
+                .iter()
+                .map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' })
+                .collect();
+            let line = format!("{:08X}: {:<48} {}", addr, hex, ascii);
+
+            let has_match = self
+                .hex_match
+                .is_some_and(|m| m >= addr && m < addr + BYTES_PER_ROW);
+            if has_match {
+                ui.label(egui::RichText::new(line).monospace().color(egui::Color32::YELLOW));
+            } else {
+                ui.monospace(line);
+            }
+        }
+
+        if start_offset + page.len() < total {
+            ui.label(format!(
+                "... and {} more bytes",
+                total - start_offset - page.len()
+            ));
+        }
+    }
+}
@@ -0,0 +1,250 @@
+use egui::text::{LayoutJob, TextFormat};
+use egui::{Color32, FontId};
+use crate::rpa::RpaEditor;
+
+const FONT_SIZE: f32 = 13.0;
+
+/// Ren'Py script statements and clauses.
+const RENPY_KEYWORDS: &[&str] = &[
+    "label", "menu", "jump", "call", "return", "scene", "show", "hide", "with", "play", "stop",
+    "queue", "voice", "pause", "define", "default", "image", "init", "python", "early", "hide",
+    "if", "elif", "else", "while", "pass", "translate", "strings", "old", "new", "screen",
+    "transform", "style", "at", "as", "behind", "onlayer", "zorder", "from", "expression",
+    "nvl", "window", "extend", "centered", "layeredimage", "group", "attribute", "testcase",
+    "camera", "audio", "music", "sound", "fadein", "fadeout", "loop", "noloop", "in",
+];
+
+const PYTHON_KEYWORDS: &[&str] = &[
+    "def", "class", "return", "if", "elif", "else", "for", "while", "in", "not", "and", "or",
+    "is", "import", "from", "as", "try", "except", "finally", "raise", "with", "lambda",
+    "yield", "global", "nonlocal", "pass", "break", "continue", "del", "assert", "True",
+    "False", "None", "self", "renpy", "store", "persistent", "config",
+];
+
+/// Screen language statements plus the most common properties.
+const SCREEN_KEYWORDS: &[&str] = &[
+    "screen", "use", "has", "vbox", "hbox", "frame", "fixed", "grid", "side", "viewport",
+    "vpgrid", "window", "text", "textbutton", "imagebutton", "button", "add", "bar", "vbar",
+    "input", "key", "timer", "null", "label", "mousearea", "drag", "draggroup", "showif",
+    "transclude", "on", "action", "hovered", "unhovered", "style", "style_prefix", "xalign",
+    "yalign", "xpos", "ypos", "xsize", "ysize", "spacing", "modal", "zorder", "tag", "if",
+    "elif", "else", "for", "in", "default", "python", "at", "as", "id", "idle", "hover",
+];
+
+#[derive(Clone, Copy, PartialEq)]
+enum Token {
+    Plain,
+    Keyword,
+    String,
+    Comment,
+    Number,
+}
+
+/// Which language a line is written in, from the block that contains it.
+#[derive(Clone, Copy, PartialEq)]
+enum Block {
+    Script,
+    Python,
+    Screen,
+}
+
+fn color(token: Token, block: Block) -> Color32 {
+    match (token, block) {
+        (Token::Keyword, Block::Python) => Color32::from_rgb(198, 120, 221),
+        (Token::Keyword, Block::Screen) => Color32::from_rgb(86, 182, 194),
+        (Token::Keyword, Block::Script) => Color32::LIGHT_BLUE,
+        (Token::String, _) => Color32::from_rgb(152, 195, 121),
+        (Token::Comment, _) => Color32::GRAY,
+        (Token::Number, _) => Color32::from_rgb(209, 154, 102),
+        (Token::Plain, _) => Color32::from_gray(220),
+    }
+}
+
+fn keywords(block: Block) -> &'static [&'static str] {
+    match block {
+        Block::Script => RENPY_KEYWORDS,
+        Block::Python => PYTHON_KEYWORDS,
+        Block::Screen => SCREEN_KEYWORDS,
+    }
+}
+
+fn push(job: &mut LayoutJob, text: &str, token: Token, block: Block) {
+    if !text.is_empty() {
+        job.append(
+            text,
+            0.0,
+            TextFormat::simple(FontId::monospace(FONT_SIZE), color(token, block)),
+        );
+    }
+}
+
+/// Byte index just past the closing `quote`, honouring backslash escapes.
+fn string_end(text: &str, quote: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in text.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if text[i..].starts_with(quote) {
+            return Some(i + quote.len());
+        }
+    }
+    None
+}
+
+/// Highlights one line; `open_string` carries a triple-quoted string across lines.
+fn highlight_line(job: &mut LayoutJob, line: &str, block: Block, open_string: &mut Option<&'static str>) {
+    let mut rest = line;
+
+    if let Some(quote) = *open_string {
+        match string_end(rest, quote) {
+            Some(end) => {
+                push(job, &rest[..end], Token::String, block);
+                rest = &rest[end..];
+                *open_string = None;
+            }
+            None => return push(job, rest, Token::String, block),
+        }
+    }
+
+    let mut block = block;
+    if block == Block::Script
+        && let Some(after) = rest.trim_start().strip_prefix('$')
+    {
+        let dollar = rest.len() - after.len();
+        push(job, &rest[..dollar], Token::Keyword, block);
+        rest = after;
+        block = Block::Python;
+    }
+
+    let mut plain = 0;
+    let mut i = 0;
+    while i < rest.len() {
+        let tail = &rest[i..];
+        let c = tail.chars().next().unwrap_or(' ');
+
+        let token = if c == '#' {
+            Some((tail.len(), Token::Comment))
+        } else if c == '"' || c == '\'' || c == '`' {
+            let quote = match c {
+                '"' if tail.starts_with("\"\"\"") => "\"\"\"",
+                '\'' if tail.starts_with("'''") => "'''",
+                '"' => "\"",
+                '\'' => "'",
+                _ => "`",
+            };
+            match string_end(&tail[quote.len()..], quote) {
+                Some(end) => Some((quote.len() + end, Token::String)),
+                None => {
+                    if quote.len() == 3 {
+                        *open_string = Some(quote);
+                    }
+                    Some((tail.len(), Token::String))
+                }
+            }
+        } else if c.is_alphanumeric() || c == '_' {
+            let len = tail
+                .find(|ch: char| !(ch.is_alphanumeric() || ch == '_' || (c.is_ascii_digit() && ch == '.')))
+                .unwrap_or(tail.len());
+            let word = &tail[..len];
+            let preceded_by_dot = rest[..i].ends_with('.');
+            if c.is_ascii_digit() {
+                Some((len, Token::Number))
+            } else if !preceded_by_dot && keywords(block).contains(&word) {
+                Some((len, Token::Keyword))
+            } else {
+                // Skip the whole identifier so keywords inside names aren't matched.
+                i += len;
+                continue;
+            }
+        } else {
+            None
+        };
+
+        match token {
+            Some((len, token)) => {
+                push(job, &rest[plain..i], Token::Plain, block);
+                push(job, &tail[..len], token, block);
+                i += len;
+                plain = i;
+            }
+            None => i += c.len_utf8(),
+        }
+    }
+    push(job, &rest[plain..], Token::Plain, block);
+}
+
+/// Kind of block opened by a line ending in `:`, if it switches language.
+fn opened_block(code: &str) -> Option<Block> {
+    let mut words = code.split_whitespace();
+    let first = words.next()?;
+    if first == "screen" {
+        return Some(Block::Screen);
+    }
+    let head = code.trim_end_matches(':');
+    if first == "python" || (first == "init" && head.split_whitespace().any(|w| w == "python")) {
+        return Some(Block::Python);
+    }
+    None
+}
+
+/// Colours a Ren'Py script: statements, strings, comments, and python and screen blocks.
+pub fn highlight_renpy(text: &str) -> LayoutJob {
+    highlight(text, Block::Script)
+}
+
+pub fn highlight_python(text: &str) -> LayoutJob {
+    highlight(text, Block::Python)
+}
+
+fn highlight(text: &str, outer: Block) -> LayoutJob {
+    let mut job = LayoutJob::default();
+    let mut blocks: Vec<(usize, Block)> = Vec::new();
+    let mut open_string = None;
+
+    for line in text.split_inclusive('\n') {
+        let code = line.trim();
+        let indent = line.len() - line.trim_start().len();
+        if !code.is_empty() && open_string.is_none() {
+            while blocks.last().is_some_and(|&(block_indent, _)| indent <= block_indent) {
+                blocks.pop();
+            }
+        }
+
+        let block = blocks.last().map_or(outer, |&(_, block)| block);
+        let starts_inside_string = open_string.is_some();
+        highlight_line(&mut job, line, block, &mut open_string);
+
+        let code = code.split(" #").next().unwrap_or(code).trim_end();
+        if !starts_inside_string && code.ends_with(':') {
+            let opened = if block == Block::Script { opened_block(code) } else { Some(block) };
+            if let Some(opened) = opened {
+                blocks.push((indent, opened));
+            }
+        }
+    }
+    job
+}
+
+impl RpaEditor {
+    /// Highlighting for script entries; other text keeps the plain line view.
+    pub(crate) fn highlight_for(filename: &str, text: &str) -> Option<LayoutJob> {
+        let lower = filename.to_lowercase();
+        if lower.ends_with(".rpy") || lower.ends_with(".rpyc") {
+            Some(highlight_renpy(text))
+        } else if lower.ends_with(".py") {
+            Some(highlight_python(text))
+        } else {
+            None
+        }
+    }
+
+    /// Rebuilds the cached highlighting after `preview_text` changes.
+    pub(crate) fn refresh_highlight(&mut self, filename: &str) {
+        self.preview_highlight = self
+            .preview_text
+            .as_deref()
+            .and_then(|text| Self::highlight_for(filename, text));
+    }
+}
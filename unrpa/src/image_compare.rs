@@ -0,0 +1,164 @@
+use std::fs::File;
+use std::io::Read;
+use crate::entry_stream::EntryStream;
+use crate::rpa::RpaEditor;
+
+/// Two versions of an image entry shown in the comparison window.
+pub struct ImageComparison {
+    pub entry: String,
+    /// Replacement waiting for confirmation; `None` when comparing an applied change.
+    pub new_path: Option<String>,
+    pub before: egui::ColorImage,
+    pub after: egui::ColorImage,
+}
+
+impl ImageComparison {
+    pub fn size_mismatch(&self) -> bool {
+        self.before.size != self.after.size
+    }
+}
+
+fn decode(data: &[u8]) -> anyhow::Result<egui::ColorImage> {
+    let rgba = image::load_from_memory(data)?.to_rgba8();
+    let size = [rgba.width() as usize, rgba.height() as usize];
+    Ok(egui::ColorImage::from_rgba_unmultiplied(size, &rgba))
+}
+
+/// Draws `image` from the top-left of `rect` at `scale`, so differing sizes stay visible.
+fn paint_scaled(painter: &egui::Painter, texture: &egui::TextureHandle, rect: egui::Rect, image: &egui::ColorImage, scale: f32) {
+    let size = egui::Vec2::new(image.width() as f32, image.height() as f32) * scale;
+    painter.image(
+        texture.id(),
+        egui::Rect::from_min_size(rect.min, size),
+        egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
+        egui::Color32::WHITE,
+    );
+}
+
+impl RpaEditor {
+    /// Entry bytes as stored in the archive on disk, ignoring in-memory changes.
+    fn original_entry_data(&self, filename: &str) -> anyhow::Result<Vec<u8>> {
+        let entry = self
+            .indexes
+            .get(filename)
+            .ok_or_else(|| anyhow::anyhow!("File not found"))?;
+        let archive_path = self
+            .archive_path
+            .as_ref()
+            .filter(|_| !entry.segments.is_empty())
+            .ok_or_else(|| anyhow::anyhow!("{} isn't in the archive on disk", filename))?;
+        let mut stream = EntryStream::from_archive(File::open(archive_path)?, entry.segments.clone());
+        let mut data = Vec::new();
+        stream.read_to_end(&mut data)?;
+        Ok(data)
+    }
+
+    /// Opens the comparison before `new_path` replaces the image entry.
+    pub(crate) fn stage_image_replacement(&mut self, filename: &str, new_path: &str) -> anyhow::Result<()> {
+        let before = decode(&self.load_file_data(filename)?)?;
+        let after = decode(&std::fs::read(new_path)?)
+            .map_err(|e| anyhow::anyhow!("{} is not a readable image: {}", new_path, e))?;
+        self.image_comparison = Some(ImageComparison {
+            entry: filename.to_string(),
+            new_path: Some(new_path.to_string()),
+            before,
+            after,
+        });
+        Ok(())
+    }
+
+    /// Compares a modified image entry with the version in the archive.
+    pub(crate) fn compare_with_original(&mut self, filename: &str) -> anyhow::Result<()> {
+        let before = self.original_entry_data(filename)?;
+        let after = self.load_file_data(filename)?;
+        self.compare_images(filename, &before, &after)
+    }
+
+    /// Shows two already applied versions of an image side by side.
+    pub(crate) fn compare_images(&mut self, filename: &str, before: &[u8], after: &[u8]) -> anyhow::Result<()> {
+        self.image_comparison = Some(ImageComparison {
+            entry: filename.to_string(),
+            new_path: None,
+            before: decode(before)?,
+            after: decode(after)?,
+        });
+        Ok(())
+    }
+
+    pub(crate) fn show_image_comparison(&mut self, ui: &mut egui::Ui) {
+        let Some(comparison) = &self.image_comparison else {
+            return;
+        };
+        let (before_size, after_size) = (comparison.before.size, comparison.after.size);
+
+        ui.label(format!("Entry: {}", comparison.entry));
+        ui.horizontal(|ui| {
+            ui.label(format!("Before: {}×{}", before_size[0], before_size[1]));
+            ui.label(format!("After: {}×{}", after_size[0], after_size[1]));
+        });
+        if comparison.size_mismatch() {
+            ui.colored_label(
+                egui::Color32::ORANGE,
+                "⚠ The dimensions differ; sprites may be misplaced or scaled in game.",
+            );
+        }
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut self.image_compare_mode, "side".to_string(), "Side by side");
+            ui.radio_value(&mut self.image_compare_mode, "slider".to_string(), "Slider");
+            if self.image_compare_mode == "slider" {
+                ui.add(egui::Slider::new(&mut self.image_compare_split, 0.0..=1.0).show_value(false));
+            }
+        });
+        ui.separator();
+
+        let Some(comparison) = &self.image_comparison else {
+            return;
+        };
+        let before = ui.ctx().load_texture("compare_before", comparison.before.clone(), Default::default());
+        let after = ui.ctx().load_texture("compare_after", comparison.after.clone(), Default::default());
+        let widest = before_size[0].max(after_size[0]) as f32;
+        let tallest = before_size[1].max(after_size[1]) as f32;
+        let available = ui.available_size() - egui::Vec2::new(0.0, 40.0);
+
+        if self.image_compare_mode == "slider" {
+            let scale = (available.x / widest).min(available.y / tallest).min(1.0);
+            let (rect, response) =
+                ui.allocate_exact_size(egui::Vec2::new(widest, tallest) * scale, egui::Sense::drag());
+            if let Some(pointer) = response.interact_pointer_pos() {
+                self.image_compare_split = ((pointer.x - rect.left()) / rect.width()).clamp(0.0, 1.0);
+            }
+            let split_x = rect.left() + rect.width() * self.image_compare_split;
+            let left = egui::Rect::from_min_max(rect.min, egui::pos2(split_x, rect.bottom()));
+            let right = egui::Rect::from_min_max(egui::pos2(split_x, rect.top()), rect.max);
+            paint_scaled(&ui.painter_at(left), &before, rect, &comparison.before, scale);
+            paint_scaled(&ui.painter_at(right), &after, rect, &comparison.after, scale);
+            ui.painter().vline(split_x, rect.y_range(), egui::Stroke::new(2.0, egui::Color32::YELLOW));
+        } else {
+            let half = egui::Vec2::new((available.x - 8.0) / 2.0, available.y);
+            let scale = (half.x / widest).min(half.y / tallest).min(1.0);
+            ui.horizontal(|ui| {
+                for (texture, image) in [(&before, &comparison.before), (&after, &comparison.after)] {
+                    let (rect, _) = ui.allocate_exact_size(egui::Vec2::new(widest, tallest) * scale, egui::Sense::hover());
+                    ui.painter().rect_stroke(rect, 0.0, egui::Stroke::new(1.0, egui::Color32::DARK_GRAY));
+                    paint_scaled(&ui.painter_at(rect), texture, rect, image, scale);
+                }
+            });
+        }
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            let pending = self.image_comparison.as_ref().and_then(|c| c.new_path.clone().map(|path| (c.entry.clone(), path)));
+            if let Some(replace) = pending {
+                if ui.button("✅ Replace").clicked() {
+                    self.file_to_replace = Some(replace);
+                    self.image_comparison = None;
+                }
+                if ui.button("❌ Cancel").clicked() {
+                    self.image_comparison = None;
+                }
+            } else if ui.button("❌ Close").clicked() {
+                self.image_comparison = None;
+            }
+        });
+    }
+}
@@ -0,0 +1,171 @@
+use crate::rpa::RpaEditor;
+
+pub const MIN_ZOOM: f32 = 0.1;
+pub const MAX_ZOOM: f32 = 8.0;
+
+/// Room left under the image for the size line.
+const INFO_HEIGHT: f32 = 24.0;
+
+/// Side of one checkerboard square, in screen points.
+const CHECKER_SIZE: f32 = 8.0;
+
+/// Alpha as a grey ramp: transparent is black, opaque is white.
+fn alpha_only(image: &egui::ColorImage) -> egui::ColorImage {
+    egui::ColorImage {
+        size: image.size,
+        pixels: image.pixels.iter().map(|pixel| egui::Color32::from_gray(pixel.a())).collect(),
+    }
+}
+
+/// Light/dark squares behind `area`, aligned to `origin` so they move with the image.
+fn paint_checkerboard(painter: &egui::Painter, area: egui::Rect, origin: egui::Pos2) {
+    painter.rect_filled(area, 0.0, egui::Color32::from_gray(200));
+    let first_column = ((area.min.x - origin.x) / CHECKER_SIZE).floor() as i64;
+    let first_row = ((area.min.y - origin.y) / CHECKER_SIZE).floor() as i64;
+    let columns = (area.width() / CHECKER_SIZE).ceil() as i64 + 1;
+    let rows = (area.height() / CHECKER_SIZE).ceil() as i64 + 1;
+    for row in first_row..first_row + rows {
+        for column in first_column..first_column + columns {
+            if (row + column) % 2 == 0 {
+                continue;
+            }
+            let min = origin + egui::Vec2::new(column as f32, row as f32) * CHECKER_SIZE;
+            let square = egui::Rect::from_min_size(min, egui::Vec2::splat(CHECKER_SIZE)).intersect(area);
+            painter.rect_filled(square, 0.0, egui::Color32::from_gray(150));
+        }
+    }
+}
+
+impl RpaEditor {
+    /// Scale before `image_zoom`: shrink to the viewport in "fit" mode, 1:1 in "actual".
+    fn image_base_scale(&self, viewport: egui::Vec2, image: egui::Vec2) -> f32 {
+        if self.image_fit_mode == "fit" {
+            (viewport.x / image.x).min(viewport.y / image.y).min(1.0)
+        } else {
+            1.0
+        }
+    }
+
+    /// Replaces the previewed image; its texture is uploaded on the next frame that shows it.
+    pub(crate) fn set_preview_image(&mut self, image: Option<egui::ColorImage>) {
+        self.preview_image = image;
+        self.preview_texture = None;
+    }
+
+    /// The preview texture, rebuilt only when the image or the alpha switch changed.
+    fn preview_texture(&mut self, ctx: &egui::Context) -> Option<egui::TextureHandle> {
+        if let Some((alpha_only, texture)) = &self.preview_texture
+            && *alpha_only == self.image_alpha_only
+        {
+            return Some(texture.clone());
+        }
+        let image = self.preview_image.as_ref()?;
+        let shown = if self.image_alpha_only { alpha_only(image) } else { image.clone() };
+        let texture = ctx.load_texture("preview", shown, Default::default());
+        self.preview_texture = Some((self.image_alpha_only, texture.clone()));
+        Some(texture)
+    }
+
+    pub(crate) fn set_image_fit_mode(&mut self, mode: &str) {
+        self.image_fit_mode = mode.to_string();
+        self.image_zoom = 1.0;
+        self.image_pan = egui::Vec2::ZERO;
+    }
+
+    /// Fit / 100% toggle and zoom slider for the preview header.
+    pub(crate) fn show_image_zoom_controls(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            if self.image_fit_mode == "fit" {
+                if ui.button("1:1").on_hover_text("Show at 100%").clicked() {
+                    self.set_image_fit_mode("actual");
+                }
+            } else if ui.button("⛶ Fit").on_hover_text("Fit to window").clicked() {
+                self.set_image_fit_mode("fit");
+            }
+            ui.label("🔍");
+            ui.add(egui::Slider::new(&mut self.image_zoom, MIN_ZOOM..=MAX_ZOOM).logarithmic(true).text("Zoom"));
+        });
+    }
+
+    /// Background behind transparent pixels and the alpha-only switch.
+    fn show_image_background_controls(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Background:");
+            ui.radio_value(&mut self.image_background, "checker".to_string(), "▦ Checkerboard");
+            ui.radio_value(&mut self.image_background, "color".to_string(), "Colour");
+            if self.image_background == "color" {
+                ui.color_edit_button_srgb(&mut self.image_background_color);
+            }
+            ui.separator();
+            ui.checkbox(&mut self.image_alpha_only, "Show alpha only");
+        });
+    }
+
+    /// Image preview that pans by dragging and zooms around the cursor with the wheel.
+    pub(crate) fn show_image_preview(&mut self, ui: &mut egui::Ui) {
+        let Some([width, height]) = self.preview_image.as_ref().map(|image| image.size) else {
+            return;
+        };
+        self.show_image_background_controls(ui);
+
+        let Some(texture) = self.preview_texture(ui.ctx()) else {
+            return;
+        };
+        let image_size = egui::Vec2::new(width as f32, height as f32);
+
+        let viewport = (ui.available_size() - egui::Vec2::new(0.0, INFO_HEIGHT)).max(egui::Vec2::splat(64.0));
+        let (rect, response) = ui.allocate_exact_size(viewport, egui::Sense::drag());
+        let base_scale = self.image_base_scale(viewport, image_size);
+
+        if response.dragged() {
+            self.image_pan += response.drag_delta();
+        }
+        if response.double_clicked() {
+            self.image_pan = egui::Vec2::ZERO;
+        }
+        if let Some(cursor) = response.hover_pos() {
+            let scroll = ui.input(|input| input.smooth_scroll_delta.y);
+            if scroll != 0.0 {
+                let old_zoom = self.image_zoom;
+                self.image_zoom = (old_zoom * (scroll * 0.002).exp()).clamp(MIN_ZOOM, MAX_ZOOM);
+                // Keep the pixel under the cursor in place.
+                let from_center = cursor - rect.center();
+                self.image_pan = from_center - (from_center - self.image_pan) * (self.image_zoom / old_zoom);
+            }
+        }
+
+        let display_size = image_size * base_scale * self.image_zoom;
+        let image_rect = egui::Rect::from_center_size(rect.center() + self.image_pan, display_size);
+        let painter = ui.painter_at(rect);
+        let visible = image_rect.intersect(rect);
+        // The alpha map is opaque, so it needs no background.
+        if visible.is_positive() && !self.image_alpha_only {
+            if self.image_background == "checker" {
+                paint_checkerboard(&painter, visible, image_rect.min);
+            } else {
+                let [r, g, b] = self.image_background_color;
+                painter.rect_filled(visible, 0.0, egui::Color32::from_rgb(r, g, b));
+            }
+        }
+        painter.image(
+            texture.id(),
+            image_rect,
+            egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
+            egui::Color32::WHITE,
+        );
+        if response.dragged() {
+            ui.ctx().set_cursor_icon(egui::CursorIcon::Grabbing);
+        } else if response.hovered() {
+            ui.ctx().set_cursor_icon(egui::CursorIcon::Grab);
+        }
+
+        ui.label(format!(
+            "Original: {}×{} | Display: {:.0}×{:.0} | Zoom: {:.1}% | Drag to pan, scroll to zoom, double-click to recenter",
+            width,
+            height,
+            display_size.x,
+            display_size.y,
+            base_scale * self.image_zoom * 100.0
+        ));
+    }
+}
@@ -0,0 +1,252 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use serde::Serialize;
+use tracing::info;
+use crate::rpa::RpaEditor;
+
+/// One finding of "Verify Archive". `severity` is "error", "warning" or "info".
+#[derive(Debug, Clone, Serialize)]
+pub struct IntegrityIssue {
+    pub severity: &'static str,
+    pub kind: &'static str,
+    pub entry: Option<String>,
+    pub detail: String,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct IntegrityReport {
+    pub archive_path: String,
+    pub file_length: u64,
+    pub index_offset: u64,
+    pub entries_checked: usize,
+    pub issues: Vec<IntegrityIssue>,
+}
+
+impl IntegrityReport {
+    pub fn count(&self, severity: &str) -> usize {
+        self.issues.iter().filter(|issue| issue.severity == severity).count()
+    }
+
+    fn push(&mut self, severity: &'static str, kind: &'static str, entry: Option<&str>, detail: String) {
+        self.issues.push(IntegrityIssue {
+            severity,
+            kind,
+            entry: entry.map(str::to_string),
+            detail,
+        });
+    }
+}
+
+fn severity_icon(severity: &str) -> &'static str {
+    match severity {
+        "error" => "❌",
+        "warning" => "⚠",
+        _ => "ℹ",
+    }
+}
+
+impl RpaEditor {
+    /// Checks the index against the archive on disk: bounds, prefixes and magic bytes of
+    /// every entry, overlapping data and unreferenced gaps.
+    pub(crate) fn verify_archive(&self) -> anyhow::Result<IntegrityReport> {
+        let archive_path = self
+            .archive_path
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("No archive loaded"))?;
+        let file_length = std::fs::metadata(archive_path)?.len();
+        let mut report = IntegrityReport {
+            archive_path: archive_path.clone(),
+            file_length,
+            index_offset: self.index_offset,
+            ..Default::default()
+        };
+        if self.index_offset > file_length {
+            report.push(
+                "error",
+                "header",
+                None,
+                format!("Index offset 0x{:x} is past the end of the file (0x{:x})", self.index_offset, file_length),
+            );
+        }
+
+        // (start, end, name) of every stored range, for the overlap check.
+        let mut ranges = Vec::new();
+        let mut names: Vec<&String> = self
+            .indexes
+            .iter()
+            .filter(|(_, entry)| entry.in_archive())
+            .map(|(name, _)| name)
+            .collect();
+        names.sort();
+        for name in &names {
+            for segment in &self.indexes[*name].segments {
+                let stored = segment.length.saturating_sub(segment.prefix.len() as u64);
+                let end = segment.offset.saturating_add(stored);
+                if end > self.index_offset && end <= file_length {
+                    report.push(
+                        "error",
+                        "bounds",
+                        Some(name),
+                        format!("Data 0x{:x}..0x{:x} runs into the index at 0x{:x}", segment.offset, end, self.index_offset),
+                    );
+                }
+                if end > segment.offset {
+                    ranges.push((segment.offset, end, name.as_str()));
+                }
+            }
+        }
+        report.entries_checked = names.len();
+
+        // Out-of-bounds ranges and prefixes longer than their entry. Reading the contents
+        // is left to the verify job `run_archive_verification` queues.
+        let mut warnings: Vec<(String, String)> = self.check_entry_bounds()?.into_iter().collect();
+        warnings.sort();
+        for (name, warning) in warnings {
+            report.push("error", "bounds", Some(&name), warning);
+        }
+        report.push(
+            "info",
+            "content",
+            None,
+            "Entry contents are checked by a Verify job; problems show up as entry warnings".to_string(),
+        );
+
+        ranges.sort();
+        let mut previous: Option<(u64, u64, &str)> = None;
+        for &(start, end, name) in &ranges {
+            if let Some((previous_start, previous_end, previous_name)) = previous
+                && start < previous_end
+            {
+                if start == previous_start && end == previous_end {
+                    report.push("info", "shared", Some(name), format!("Shares its data with {}", previous_name));
+                } else {
+                    report.push(
+                        "error",
+                        "overlap",
+                        Some(name),
+                        format!("0x{:x}..0x{:x} overlaps {} (ends at 0x{:x})", start, end, previous_name, previous_end),
+                    );
+                }
+            }
+            if previous.is_none_or(|(_, previous_end, _)| end > previous_end) {
+                previous = Some((start, end, name));
+            }
+        }
+
+        for (offset, length) in self.find_orphaned_ranges() {
+            report.push(
+                "warning",
+                "gap",
+                None,
+                format!("{} at 0x{:x} aren't used by any entry", Self::format_bytes(length), offset),
+            );
+        }
+
+        info!(
+            "🩺 Verified {}: {} errors, {} warnings",
+            archive_path,
+            report.count("error"),
+            report.count("warning")
+        );
+        Ok(report)
+    }
+
+    pub(crate) fn run_archive_verification(&mut self) {
+        match self.verify_archive() {
+            Ok(report) => {
+                for issue in &report.issues {
+                    if issue.severity != "info"
+                        && let Some(entry) = &issue.entry
+                    {
+                        self.entry_warnings.insert(entry.clone(), issue.detail.clone());
+                    }
+                }
+                self.status_message = format!(
+                    "Archive check: {} errors, {} warnings",
+                    report.count("error"),
+                    report.count("warning")
+                );
+                self.integrity_report = Some(report);
+                self.deep_verify();
+            }
+            Err(e) => self.add_error(format!("Verify error: {}", e)),
+        }
+    }
+
+    fn export_integrity_report(&self, path: &Path) -> anyhow::Result<()> {
+        let report = self
+            .integrity_report
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("No report"))?;
+        let mut out = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(&mut out, report)?;
+        out.flush()?;
+        Ok(())
+    }
+
+    pub(crate) fn show_integrity_report(&mut self, ui: &mut egui::Ui) {
+        let Some(report) = &self.integrity_report else {
+            return;
+        };
+        ui.label(format!(
+            "{} entries in {} ({}), index at 0x{:x}",
+            report.entries_checked,
+            report.archive_path,
+            Self::format_bytes(report.file_length),
+            report.index_offset
+        ));
+        let errors = report.count("error");
+        let warnings = report.count("warning");
+        if errors == 0 && warnings == 0 {
+            ui.colored_label(egui::Color32::GREEN, "✅ No problems found");
+        } else {
+            ui.label(format!("❌ {} errors, ⚠ {} warnings", errors, warnings));
+        }
+        ui.separator();
+
+        let mut select = None;
+        egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+            egui::Grid::new("integrity_issues").num_columns(3).striped(true).show(ui, |ui| {
+                for issue in &report.issues {
+                    ui.label(format!("{} {}", severity_icon(issue.severity), issue.kind));
+                    match &issue.entry {
+                        Some(entry) => {
+                            if ui.link(entry).clicked() {
+                                select = Some(entry.clone());
+                            }
+                        }
+                        None => {
+                            ui.label("—");
+                        }
+                    }
+                    ui.label(&issue.detail);
+                    ui.end_row();
+                }
+            });
+        });
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            if ui.button("💾 Export Report...").clicked()
+                && let Some(path) = rfd::FileDialog::new()
+                    .add_filter("JSON", &["json"])
+                    .set_file_name("integrity_report.json")
+                    .save_file()
+            {
+                match self.export_integrity_report(&path) {
+                    Ok(()) => self.add_toast(format!("Saved report to {}", path.display())),
+                    Err(e) => self.add_error(format!("Export error: {}", e)),
+                }
+            }
+            if ui.button("❌ Close").clicked() {
+                self.integrity_report = None;
+            }
+        });
+
+        if let Some(entry) = select {
+            self.selected_file = Some(entry.clone());
+            self.file_to_preview = Some(entry);
+        }
+    }
+}
@@ -0,0 +1,807 @@
+use std::fs::{create_dir_all, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Sender;
+use memmap2::Mmap;
+use tracing::{debug, error, info, warn};
+use crate::archive_index::ArchiveIndex;
+use crate::archive_reader::{copy_mapped_segments, mapped_entry};
+use crate::duplicates::{duplicate_groups, DuplicateGroup};
+use crate::entry_stream::EntryStream;
+use crate::extract_conflicts::{resolve_conflict, ExtractOutcome, ExtractReport};
+use crate::rpa::{MergedSource, RpaEditor, RpaSegment};
+use crate::rpyc;
+use crate::safe_path::entry_output_path;
+use crate::sha256::Sha256;
+use crate::sniff::SNIFF_LENGTH;
+use crate::thumbnails::cache_thumbnail;
+use crate::toast::Toast;
+use crate::validation::check_magic;
+use crate::video_frames::{export_frames, frames_folder, FrameRange};
+use crate::video_probe::{extract_thumbnail, probe_video, subfile_url, VideoInfo};
+
+/// Job kinds with their labels in the queue window. Internal kinds aren't listed:
+/// `probe_video_entry` starts "probe" for the selected video, with the preview temp folder
+/// as output, `start_archive_load` starts "load" when an archive is opened, and
+/// `start_key_recovery` starts "key" from Tools → Recover Key.
+pub const JOB_KINDS: &[(&str, &str)] = &[
+    ("extract", "📤 Extract"),
+    ("decompile", "📜 Decompile .rpyc"),
+    ("transcode", "🔁 Transcode"),
+    ("verify", "✅ Verify"),
+    ("duplicates", "♊ Find Duplicates"),
+    ("checksum", "🔐 SHA-256 Checksums"),
+    ("thumbnails", "🖼 Thumbnails"),
+    ("sniff", "🔎 Detect Types"),
+    ("frames", "🖼 Export Frames"),
+];
+
+/// Job kinds that only read entries and need no output folder.
+pub const READ_ONLY_JOB_KINDS: &[&str] = &["verify", "duplicates", "checksum", "thumbnails", "sniff"];
+
+/// Targets handled by the `image` crate; anything else goes through `ffmpeg`.
+const IMAGE_FORMATS: &[&str] = &["png", "jpg", "webp"];
+
+/// Problems kept in a job's summary; the rest only go to the log.
+const MAX_REPORTED_PROBLEMS: usize = 5;
+
+/// A queued or finished batch task over a list of entries.
+pub struct Job {
+    pub id: usize,
+    pub kind: String,
+    pub entries: Vec<String>,
+    /// Archive the entries belong to; results are applied to its tab.
+    pub archive: Option<String>,
+    pub output: Option<PathBuf>,
+    pub format: String,
+    /// What "extract" does with existing files, an `EXTRACT_POLICIES` key.
+    pub policy: String,
+    /// "extract" layout: "archive" keeps entry paths, "type" adds a folder per file type.
+    pub layout: String,
+    /// Frames a "frames" job exports from each video.
+    pub frames: FrameRange,
+    /// `edit_generation` of the archive's tab when the job read its entries.
+    pub generation: u64,
+    pub status: String,
+    pub processed: usize,
+    pub failed: usize,
+    pub message: String,
+    /// Set to stop a running job after the entry it's on.
+    pub cancel: Arc<AtomicBool>,
+}
+
+/// Sent from worker threads and applied by `process_jobs` every frame.
+pub enum JobEvent {
+    Progress { id: usize, processed: usize, failed: usize },
+    Finished { id: usize, message: String },
+    Duplicates { id: usize, groups: Vec<DuplicateGroup> },
+    Checksums { id: usize, hashes: Vec<(String, String)> },
+    /// `(entry, problem)` for every entry a verify job rejected.
+    Warnings { id: usize, warnings: Vec<(String, String)> },
+    /// `(entry, first bytes)` read by sniff and verify jobs, for content detection.
+    Heads { id: usize, heads: Vec<(String, Vec<u8>)> },
+    Extracted { id: usize, report: ExtractReport },
+    /// Header and index read by a "load" job.
+    Loaded { id: usize, index: anyhow::Result<ArchiveIndex> },
+    /// Key voted for by a "key" job.
+    KeyRecovered { id: usize, key: anyhow::Result<u32> },
+    VideoProbed { id: usize, name: String, info: VideoInfo, thumbnail: Option<egui::ColorImage> },
+}
+
+/// Entry bytes captured when the job starts, so workers never touch the editor.
+enum JobSource {
+    Memory(Vec<u8>),
+    Archive(Vec<RpaSegment>),
+    /// Merged from another archive and not saved yet.
+    Merged(MergedSource),
+}
+
+/// What a worker needs besides its entries.
+struct JobContext {
+    /// Archive that `JobSource::Archive` entries are read from.
+    path: Option<String>,
+    /// Shared mapping when "Memory-map archives" is on.
+    map: Option<Arc<Mmap>>,
+    cancel: Arc<AtomicBool>,
+    policy: String,
+    frames: FrameRange,
+}
+
+struct JobInput {
+    name: String,
+    source: JobSource,
+    /// Gallery cache file, for "thumbnails" jobs.
+    thumbnail_cache: Option<PathBuf>,
+    /// Folder below the output for "extract" jobs sorting by type.
+    folder: Option<&'static str>,
+}
+
+fn read_input(archive_path: Option<&str>, map: Option<&[u8]>, input: &JobInput) -> anyhow::Result<Vec<u8>> {
+    match (&input.source, map) {
+        (JobSource::Memory(data), _) => Ok(data.clone()),
+        (JobSource::Archive(segments), Some(map)) => {
+            let mut data = Vec::new();
+            copy_mapped_segments(map, segments, &mut data)?;
+            Ok(data)
+        }
+        (JobSource::Archive(segments), None) => {
+            let archive_path = archive_path.ok_or_else(|| anyhow::anyhow!("No archive loaded"))?;
+            read_stream(EntryStream::from_archive(File::open(archive_path)?, segments.clone()))
+        }
+        (JobSource::Merged(source), _) => read_stream(source.open()?),
+    }
+}
+
+/// Reads all of `stream`, failing when the archive ends before the entry does.
+fn read_stream(mut stream: EntryStream) -> anyhow::Result<Vec<u8>> {
+    let length = stream.total_len();
+    let mut data = Vec::with_capacity(length as usize);
+    stream.read_to_end(&mut data)?;
+    if (data.len() as u64) < length {
+        return Err(anyhow::anyhow!("Entry is truncated ({} of {} bytes)", data.len(), length));
+    }
+    Ok(data)
+}
+
+/// The first `SNIFF_LENGTH` bytes of an entry, without reading the rest when it can be helped.
+fn read_head(archive_path: Option<&str>, map: Option<&[u8]>, input: &JobInput) -> anyhow::Result<Vec<u8>> {
+    let head_of = |data: &[u8]| data[..data.len().min(SNIFF_LENGTH as usize)].to_vec();
+    match (&input.source, map) {
+        (JobSource::Archive(segments), Some(map)) => match mapped_entry(map, segments) {
+            Some(data) => Ok(head_of(data)),
+            None => read_input(archive_path, Some(map), input).map(|data| head_of(&data)),
+        },
+        (JobSource::Archive(segments), None) => {
+            let archive_path = archive_path.ok_or_else(|| anyhow::anyhow!("No archive loaded"))?;
+            let mut head = Vec::new();
+            EntryStream::from_archive(File::open(archive_path)?, segments.clone())
+                .take(SNIFF_LENGTH)
+                .read_to_end(&mut head)?;
+            Ok(head)
+        }
+        (JobSource::Merged(source), _) => {
+            let mut head = Vec::new();
+            source.open()?.take(SNIFF_LENGTH).read_to_end(&mut head)?;
+            Ok(head)
+        }
+        (JobSource::Memory(data), _) => Ok(head_of(data)),
+    }
+}
+
+/// Streams an entry to its place below `output`, settling an existing file by `policy`.
+/// Returns the outcome and the path written, or why nothing was.
+fn extract_input(
+    context: &JobContext,
+    map: Option<&[u8]>,
+    input: &JobInput,
+    output: Option<&Path>,
+) -> anyhow::Result<(&'static str, String)> {
+    let output = output.ok_or_else(|| anyhow::anyhow!("No output folder"))?;
+    let dir = match input.folder {
+        Some(folder) => output.join(folder),
+        None => output.to_path_buf(),
+    };
+    let path = entry_output_path(&dir, &input.name)?;
+    let Some((path, result)) = resolve_conflict(path.clone(), &context.policy) else {
+        return Ok(("skipped", format!("{} exists", path.display())));
+    };
+    if let Some(parent) = path.parent() {
+        create_dir_all(parent)?;
+    }
+
+    write_input(context, map, input, &mut File::create(&path)?)?;
+    Ok((result, path.display().to_string()))
+}
+
+/// Streams an entry to `out` without holding archived entries in memory.
+fn write_input(context: &JobContext, map: Option<&[u8]>, input: &JobInput, out: &mut impl Write) -> anyhow::Result<()> {
+    match (&input.source, map) {
+        (JobSource::Memory(data), _) => out.write_all(data)?,
+        (JobSource::Archive(segments), Some(map)) => match mapped_entry(map, segments) {
+            Some(data) => out.write_all(data)?,
+            None => {
+                let mut data = Vec::new();
+                copy_mapped_segments(map, segments, &mut data)?;
+                out.write_all(&data)?;
+            }
+        },
+        (JobSource::Archive(segments), None) => {
+            let archive_path = context.path.as_deref().ok_or_else(|| anyhow::anyhow!("No archive loaded"))?;
+            std::io::copy(&mut EntryStream::from_archive(File::open(archive_path)?, segments.clone()), out)?;
+        }
+        (JobSource::Merged(source), _) => {
+            std::io::copy(&mut source.open()?, out)?;
+        }
+    }
+    Ok(())
+}
+
+/// Length and SHA-256 of an entry, hashed in chunks as it streams past.
+fn hash_input(context: &JobContext, map: Option<&[u8]>, input: &JobInput) -> anyhow::Result<(u64, String)> {
+    let mut hasher = Sha256::default();
+    write_input(context, map, input, &mut hasher)?;
+    if let JobSource::Archive(segments) = &input.source {
+        let length: u64 = segments.iter().map(|segment| segment.length).sum();
+        if hasher.bytes_hashed() < length {
+            return Err(anyhow::anyhow!("Entry is truncated ({} of {} bytes)", hasher.bytes_hashed(), length));
+        }
+    }
+    Ok((hasher.bytes_hashed(), hasher.finish()))
+}
+
+/// Copies a video entry below `output` for `ffprobe` and `ffmpeg`, then removes the copy.
+fn probe_input(
+    context: &JobContext,
+    map: Option<&[u8]>,
+    input: &JobInput,
+    output: Option<&Path>,
+) -> anyhow::Result<(VideoInfo, Option<egui::ColorImage>)> {
+    let path = output_path(output, &input.name, None)?;
+    write_input(context, map, input, &mut File::create(&path)?)?;
+    let info = probe_video(&path);
+    let thumbnail = extract_thumbnail(&path);
+    let _ = std::fs::remove_file(&path);
+
+    let thumbnail = match thumbnail {
+        Ok(image) => Some(image),
+        Err(e) => {
+            warn!("⚠️ No thumbnail for {}: {}", input.name, e);
+            None
+        }
+    };
+    Ok((info?, thumbnail))
+}
+
+/// Exports the frames of a video entry below `output`. Entries stored in one piece are
+/// decoded in place; others are copied into the frames folder for ffmpeg first.
+fn frames_input(context: &JobContext, map: Option<&[u8]>, input: &JobInput, output: Option<&Path>) -> anyhow::Result<usize> {
+    let output = output.ok_or_else(|| anyhow::anyhow!("No output folder"))?;
+    let folder = frames_folder(output, &input.name);
+    if let (JobSource::Archive(segments), Some(archive_path)) = (&input.source, context.path.as_deref())
+        && let [segment] = segments.as_slice()
+        && segment.prefix.is_empty()
+        && segment.length > 0
+    {
+        let url = subfile_url(archive_path, segment.offset, segment.length);
+        return export_frames(&url, &folder, context.frames, &context.cancel);
+    }
+
+    create_dir_all(&folder)?;
+    let name = Path::new(&input.name).file_name().ok_or_else(|| anyhow::anyhow!("Invalid entry name"))?;
+    let copy = folder.join(name);
+    write_input(context, map, input, &mut File::create(&copy)?)?;
+    let result = export_frames(&copy.to_string_lossy(), &folder, context.frames, &context.cancel);
+    let _ = std::fs::remove_file(&copy);
+    result
+}
+
+fn output_path(output: Option<&Path>, name: &str, extension: Option<&str>) -> anyhow::Result<PathBuf> {
+    let output = output.ok_or_else(|| anyhow::anyhow!("No output folder"))?;
+    let mut path = entry_output_path(output, name)?;
+    if let Some(extension) = extension {
+        path.set_extension(extension);
+    }
+    if let Some(parent) = path.parent() {
+        create_dir_all(parent)?;
+    }
+    Ok(path)
+}
+
+fn transcode(data: &[u8], target: &Path, format: &str) -> anyhow::Result<()> {
+    if IMAGE_FORMATS.contains(&format) {
+        let image = image::load_from_memory(data)?;
+        // JPEG has no alpha channel.
+        if format == "jpg" {
+            image.to_rgb8().save(target)?;
+        } else {
+            image.save(target)?;
+        }
+        return Ok(());
+    }
+
+    let source = target.with_extension(format!("source.{}", std::process::id()));
+    std::fs::write(&source, data)?;
+    let output = Command::new("ffmpeg")
+        .arg("-y")
+        .arg("-loglevel")
+        .arg("error")
+        .arg("-i")
+        .arg(&source)
+        .arg(target)
+        .output();
+    let _ = std::fs::remove_file(&source);
+
+    let output = output.map_err(|e| anyhow::anyhow!("ffmpeg is not available: {}", e))?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "ffmpeg failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+fn run_task(kind: &str, format: &str, output: Option<&Path>, name: &str, data: Vec<u8>) -> anyhow::Result<()> {
+    match kind {
+        "decompile" => {
+            let source = rpyc::decompile(&data)?;
+            std::fs::write(output_path(output, name, Some("rpy"))?, source)?;
+        }
+        "transcode" => transcode(&data, &output_path(output, name, Some(format))?, format)?,
+        "verify" => {
+            if let Some(warning) = check_magic(name, &data) {
+                return Err(anyhow::anyhow!(warning));
+            }
+            let lower = name.to_lowercase();
+            if lower.ends_with(".rpyc") {
+                rpyc::load_statements(&data)?;
+            } else if [".png", ".jpg", ".jpeg", ".webp"].iter().any(|ext| lower.ends_with(ext)) {
+                image::load_from_memory(&data)?;
+            }
+        }
+        other => return Err(anyhow::anyhow!("Unknown job kind: {}", other)),
+    }
+    Ok(())
+}
+
+fn run_job(id: usize, kind: String, format: String, output: Option<PathBuf>, context: JobContext, inputs: Vec<JobInput>, sender: Sender<JobEvent>) {
+    let mut processed = 0;
+    let mut failed = 0;
+    let mut problems = Vec::new();
+    let mut hashes = Vec::new();
+    let map = context.map.as_deref().map(|map| &map[..]);
+
+    let mut warnings = Vec::new();
+    let mut heads = Vec::new();
+    let mut outcomes = Vec::new();
+    let mut frames = 0;
+
+    for input in &inputs {
+        if context.cancel.load(Ordering::Relaxed) {
+            break;
+        }
+        // Hashing reads mapped entries in place and streams the rest, never holding a whole entry.
+        if kind == "duplicates" || kind == "checksum" {
+            match hash_input(&context, map, input) {
+                Ok((length, hash)) => hashes.push((input.name.clone(), length, hash)),
+                Err(e) => {
+                    error!("❌ {} {}: {}", kind, input.name, e);
+                    failed += 1;
+                    if problems.len() < MAX_REPORTED_PROBLEMS {
+                        problems.push(format!("{}: {}", input.name, e));
+                    }
+                }
+            }
+            processed += 1;
+            let _ = sender.send(JobEvent::Progress { id, processed, failed });
+            continue;
+        }
+        // Thumbnails rendered by an earlier job or the gallery don't need the entry read.
+        if kind == "thumbnails" && input.thumbnail_cache.as_ref().is_some_and(|path| path.is_file()) {
+            processed += 1;
+            let _ = sender.send(JobEvent::Progress { id, processed, failed });
+            continue;
+        }
+        // Extraction streams each entry to disk instead of reading it whole.
+        if kind == "extract" {
+            let (result, detail) = extract_input(&context, map, input, output.as_deref())
+                .unwrap_or_else(|e| ("failed", e.to_string()));
+            if result == "failed" {
+                error!("❌ Failed to extract {}: {}", input.name, detail);
+                failed += 1;
+                if problems.len() < MAX_REPORTED_PROBLEMS {
+                    problems.push(format!("{}: {}", input.name, detail));
+                }
+            }
+            outcomes.push(ExtractOutcome {
+                name: input.name.clone(),
+                result,
+                detail,
+            });
+            processed += 1;
+            let _ = sender.send(JobEvent::Progress { id, processed, failed });
+            continue;
+        }
+        if kind == "probe" {
+            match probe_input(&context, map, input, output.as_deref()) {
+                Ok((info, thumbnail)) => {
+                    let name = input.name.clone();
+                    let _ = sender.send(JobEvent::VideoProbed { id, name, info, thumbnail });
+                }
+                Err(e) => {
+                    error!("❌ Probe failed for {}: {}", input.name, e);
+                    failed += 1;
+                    problems.push(format!("{}: {}", input.name, e));
+                }
+            }
+            processed += 1;
+            let _ = sender.send(JobEvent::Progress { id, processed, failed });
+            continue;
+        }
+        if kind == "frames" {
+            match frames_input(&context, map, input, output.as_deref()) {
+                Ok(count) => {
+                    info!("🖼️ Exported {} frames of {}", count, input.name);
+                    frames += count;
+                }
+                Err(e) => {
+                    error!("❌ Frame export failed for {}: {}", input.name, e);
+                    failed += 1;
+                    if problems.len() < MAX_REPORTED_PROBLEMS {
+                        problems.push(format!("{}: {}", input.name, e));
+                    }
+                }
+            }
+            processed += 1;
+            let _ = sender.send(JobEvent::Progress { id, processed, failed });
+            continue;
+        }
+        if kind == "sniff" {
+            match read_head(context.path.as_deref(), map, input) {
+                Ok(head) => heads.push((input.name.clone(), head)),
+                Err(e) => {
+                    debug!("🔎 Could not read {}: {}", input.name, e);
+                    failed += 1;
+                }
+            }
+            processed += 1;
+            let _ = sender.send(JobEvent::Progress { id, processed, failed });
+            continue;
+        }
+        let result = read_input(context.path.as_deref(), map, input).and_then(|data| {
+            if kind == "verify" {
+                heads.push((input.name.clone(), data[..data.len().min(SNIFF_LENGTH as usize)].to_vec()));
+            }
+            if kind == "thumbnails" {
+                let cache = input
+                    .thumbnail_cache
+                    .as_deref()
+                    .ok_or_else(|| anyhow::anyhow!("Unsaved entries have no thumbnail cache"))?;
+                return cache_thumbnail(&data, cache);
+            }
+            run_task(&kind, &format, output.as_deref(), &input.name, data)
+        });
+        processed += 1;
+        if let Err(e) = result {
+            error!("❌ {} {}: {}", kind, input.name, e);
+            failed += 1;
+            if kind == "verify" {
+                warnings.push((input.name.clone(), e.to_string()));
+            }
+            if problems.len() < MAX_REPORTED_PROBLEMS {
+                problems.push(format!("{}: {}", input.name, e));
+            }
+        }
+        let _ = sender.send(JobEvent::Progress { id, processed, failed });
+    }
+
+    let mut message = format!("{} of {} entries OK", processed - failed, processed);
+    if processed < inputs.len() {
+        message = format!("Cancelled after {} of {} entries", processed, inputs.len());
+    }
+    if !heads.is_empty() {
+        let _ = sender.send(JobEvent::Heads { id, heads });
+    }
+    if kind == "verify" && (processed == inputs.len() || !warnings.is_empty()) {
+        let _ = sender.send(JobEvent::Warnings { id, warnings });
+    }
+    if !hashes.is_empty() {
+        let checksums = hashes.iter().map(|(name, _, hash)| (name.clone(), hash.clone())).collect();
+        let _ = sender.send(JobEvent::Checksums { id, hashes: checksums });
+    }
+    if kind == "extract" {
+        let report = ExtractReport {
+            target: output.clone().unwrap_or_default(),
+            outcomes,
+        };
+        if processed == inputs.len() {
+            message = report.summary();
+        }
+        let _ = sender.send(JobEvent::Extracted { id, report });
+    }
+    if kind == "frames" && processed == inputs.len() {
+        let target = output.as_deref().map_or(String::new(), |output| output.display().to_string());
+        message = format!("Exported {} frames to {}", frames, target);
+    }
+    if kind == "duplicates" {
+        let groups = duplicate_groups(hashes);
+        message = format!("{} groups of identical entries", groups.len());
+        let _ = sender.send(JobEvent::Duplicates { id, groups });
+    }
+    for problem in problems {
+        message.push('\n');
+        message.push_str(&problem);
+    }
+    let _ = sender.send(JobEvent::Finished { id, message });
+}
+
+impl RpaEditor {
+    /// Entries a job of `kind` applies to, taken from the scope chosen in the queue window.
+    fn job_entries(&self, kind: &str) -> Vec<String> {
+        let mut entries: Vec<String> = match self.new_job_scope.as_str() {
+            "selected" => self.selected_file.iter().cloned().collect(),
+            "visible" => self
+                .get_filtered_sorted_files()
+                .into_iter()
+                .map(|(name, _)| name.clone())
+                .collect(),
+            _ => self.indexes.keys().cloned().collect(),
+        };
+        entries.retain(|name| {
+            let Some(entry) = self.indexes.get(name) else {
+                return false;
+            };
+            !entry.to_delete
+                && match kind {
+                    "decompile" => name.to_lowercase().ends_with(".rpyc"),
+                    "transcode" => matches!(self.get_file_type(name), "images" | "audio" | "videos"),
+                    "thumbnails" => self.get_file_type(name) == "images",
+                    "frames" => self.get_file_type(name) == "videos",
+                    _ => true,
+                }
+        });
+        entries.sort();
+        entries
+    }
+
+    pub(crate) fn queue_job(&mut self, kind: &str, output: Option<PathBuf>) -> usize {
+        let entries = self.job_entries(kind);
+        self.push_job(kind, entries, output)
+    }
+
+    pub(crate) fn push_job(&mut self, kind: &str, entries: Vec<String>, output: Option<PathBuf>) -> usize {
+        let count = entries.len();
+        self.next_job_id += 1;
+        self.jobs.push(Job {
+            id: self.next_job_id,
+            kind: kind.to_string(),
+            entries,
+            archive: self.archive_path.clone(),
+            output,
+            format: self.new_job_format.clone(),
+            policy: "overwrite".to_string(),
+            layout: "archive".to_string(),
+            frames: FrameRange::default(),
+            generation: self.edit_generation,
+            status: "queued".to_string(),
+            processed: 0,
+            failed: 0,
+            message: String::new(),
+            cancel: Arc::new(AtomicBool::new(false)),
+        });
+        count
+    }
+
+    /// Swaps a queued job with its neighbour; running and finished jobs stay put.
+    pub(crate) fn move_job(&mut self, index: usize, up: bool) {
+        let other = if up { index.checked_sub(1) } else { Some(index + 1) };
+        if let Some(other) = other
+            && other < self.jobs.len()
+            && self.jobs[index].status == "queued"
+            && self.jobs[other].status == "queued"
+        {
+            self.jobs.swap(index, other);
+        }
+    }
+
+    /// Starts job `index` on the archive it was queued for, which may have moved to a
+    /// background tab since.
+    pub(crate) fn start_job(&mut self, index: usize) {
+        let archive = self.jobs[index].archive.clone();
+        let prepared = if archive == self.archive_path {
+            Some((self.job_inputs(index), self.edit_generation))
+        } else {
+            archive
+                .as_deref()
+                .and_then(|path| self.background_tab_for(path))
+                .and_then(|tab| {
+                    self.with_background_tab(tab, |editor| (editor.job_inputs(index), editor.edit_generation))
+                })
+        };
+
+        let job = &mut self.jobs[index];
+        let Some(((inputs, context), generation)) = prepared else {
+            job.status = "failed".to_string();
+            job.message = "The archive was closed".to_string();
+            return;
+        };
+        job.generation = generation;
+        let id = job.id;
+        let kind = job.kind.clone();
+        let format = job.format.clone();
+        let output = job.output.clone();
+        let sender = self.job_sender.clone();
+        info!("⚙️ Starting job #{} ({}, {} entries)", id, kind, inputs.len());
+        std::thread::spawn(move || run_job(id, kind, format, output, context, inputs, sender));
+
+        job.status = "running".to_string();
+    }
+
+    /// The entry bytes and archive handle job `index` needs, from the active archive.
+    fn job_inputs(&self, index: usize) -> (Vec<JobInput>, JobContext) {
+        let job = &self.jobs[index];
+        let inputs: Vec<JobInput> = job
+            .entries
+            .iter()
+            .filter_map(|name| {
+                let entry = self.indexes.get(name)?;
+                let source = match (&entry.data, &entry.merged) {
+                    (Some(data), _) => JobSource::Memory(data.clone()),
+                    (None, Some(merged)) => JobSource::Merged(merged.clone()),
+                    (None, None) => JobSource::Archive(entry.segments.clone()),
+                };
+                let thumbnail_cache = if job.kind == "thumbnails" {
+                    self.thumbnail_cache_path(name)
+                } else {
+                    None
+                };
+                let folder = (job.kind == "extract" && job.layout == "type").then(|| self.get_file_type(name));
+                Some(JobInput { name: name.clone(), source, thumbnail_cache, folder })
+            })
+            .collect();
+        let context = JobContext {
+            path: self.archive_path.clone(),
+            map: self.archive_map(),
+            cancel: job.cancel.clone(),
+            policy: job.policy.clone(),
+            frames: job.frames,
+        };
+        (inputs, context)
+    }
+
+    /// Whether job `id` was queued for the archive shown right now.
+    fn is_active_job_archive(&self, id: usize) -> bool {
+        self.jobs.iter().any(|job| job.id == id && job.archive == self.archive_path)
+    }
+
+    /// Runs `apply` with the archive job `id` was queued for, in its background tab if
+    /// it isn't the active one. Results for an archive that was closed are dropped.
+    fn with_job_archive(&mut self, id: usize, apply: impl FnOnce(&mut Self)) {
+        if self.is_active_job_archive(id) {
+            apply(self);
+            return;
+        }
+        let tab = self
+            .jobs
+            .iter()
+            .find(|job| job.id == id)
+            .and_then(|job| job.archive.as_deref())
+            .and_then(|path| self.background_tab_for(path));
+        match tab {
+            Some(tab) => {
+                self.with_background_tab(tab, apply);
+            }
+            None => debug!("⏭️ Dropping results of job #{}: its archive is closed", id),
+        }
+    }
+
+    /// Applies worker progress and starts queued jobs while the queue is running.
+    pub(crate) fn process_jobs(&mut self, ctx: &egui::Context) {
+        while let Ok(event) = self.job_receiver.try_recv() {
+            match event {
+                JobEvent::Progress { id, processed, failed } => {
+                    if let Some(job) = self.jobs.iter_mut().find(|job| job.id == id) {
+                        job.processed = processed;
+                        job.failed = failed;
+                    }
+                }
+                JobEvent::Finished { id, message } => {
+                    if let Some(job) = self.jobs.iter_mut().find(|job| job.id == id) {
+                        job.status = if job.cancel.load(Ordering::Relaxed) {
+                            "cancelled"
+                        } else if job.failed == 0 {
+                            "done"
+                        } else {
+                            "failed"
+                        }
+                        .to_string();
+                        job.message = message;
+                    }
+                }
+                JobEvent::Checksums { id, hashes } => {
+                    let generation = self.jobs.iter().find(|job| job.id == id).map_or(0, |job| job.generation);
+                    self.with_job_archive(id, |editor| editor.apply_checksums(hashes, generation))
+                }
+                JobEvent::Warnings { id, warnings } => self.with_job_archive(id, |editor| {
+                    if warnings.is_empty() {
+                        editor.add_toast("Verify: every entry passed");
+                    } else {
+                        editor.add_warning(format!("Verify: {} entries have problems", warnings.len()));
+                    }
+                    editor.entry_warnings.extend(warnings);
+                }),
+                JobEvent::Heads { id, heads } => self.with_job_archive(id, |editor| {
+                    for (name, head) in &heads {
+                        editor.record_sniffed_type(name, head);
+                    }
+                    if !editor.sniffed_types.is_empty() {
+                        info!("🔎 {} entries were classified by content", editor.sniffed_types.len());
+                    }
+                }),
+                JobEvent::VideoProbed { id, name, info, thumbnail } => {
+                    // The thumbnail texture is made once here; only the selected video shows one.
+                    if self.is_active_job_archive(id)
+                        && self.selected_file.as_deref() == Some(name.as_str())
+                        && let Some(image) = thumbnail
+                    {
+                        let texture = ctx.load_texture("video_thumbnail", image, Default::default());
+                        self.video_thumbnail = Some((name.clone(), texture));
+                    }
+                    self.with_job_archive(id, |editor| {
+                        editor.video_info.insert(name, info);
+                    });
+                }
+                JobEvent::Extracted { id, report } => {
+                    let summary = report.summary();
+                    info!("📤 {}", summary);
+                    self.status_message = summary.clone();
+                    self.add_toast(summary);
+                    if report.has_conflicts() {
+                        self.with_job_archive(id, |editor| editor.extract_report = Some(report));
+                    }
+                }
+                JobEvent::Loaded { id, index } => self.finish_archive_load(id, index),
+                JobEvent::KeyRecovered { id, key } => {
+                    let generation = self.jobs.iter().find(|job| job.id == id).map_or(0, |job| job.generation);
+                    self.with_job_archive(id, |editor| editor.finish_key_recovery(key, generation))
+                }
+                JobEvent::Duplicates { id, groups } => self.with_job_archive(id, |editor| {
+                    if groups.is_empty() {
+                        editor.add_toast("No duplicate entries found");
+                    } else {
+                        editor.duplicate_groups = Some(groups);
+                    }
+                }),
+            }
+        }
+
+        if !self.jobs_running {
+            return;
+        }
+        let running = self.jobs.iter().filter(|job| job.status == "running").count();
+        let queued: Vec<usize> = (0..self.jobs.len())
+            .filter(|&i| self.jobs[i].status == "queued")
+            .collect();
+
+        if queued.is_empty() && running == 0 {
+            self.jobs_running = false;
+            self.push_toast(Toast::new("Job queue finished").with_action("jobs"));
+        } else if self.job_run_mode == "parallel" {
+            for index in queued {
+                self.start_job(index);
+            }
+        } else if running == 0
+            && let Some(&index) = queued.first()
+        {
+            self.start_job(index);
+        }
+    }
+
+    /// Jobs that are queued or running.
+    pub(crate) fn active_job_count(&self) -> usize {
+        self.jobs
+            .iter()
+            .filter(|job| job.status == "queued" || job.status == "running")
+            .count()
+    }
+
+    /// Stops every running job and drops the queued ones.
+    pub(crate) fn cancel_all_jobs(&mut self) {
+        for job in self.jobs.iter().filter(|job| job.status == "running") {
+            job.cancel.store(true, Ordering::Relaxed);
+        }
+        self.jobs.retain(|job| job.status != "queued");
+        self.status_message = "Cancelled all jobs".to_string();
+        info!("⏹ {}", self.status_message);
+    }
+
+    /// `(processed, total)` entries over every job in the queue.
+    pub(crate) fn job_queue_progress(&self) -> (usize, usize) {
+        self.jobs.iter().fold((0, 0), |(processed, total), job| {
+            (processed + job.processed, total + job.entries.len())
+        })
+    }
+}
@@ -0,0 +1,282 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Read;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Sender;
+use tracing::info;
+use crate::jobs::JobEvent;
+use crate::rpa::RpaEditor;
+
+/// Signatures searched for in the data area, keyed by the extensions they identify.
+const SIGNATURES: &[(&[u8], &[&str])] = &[
+    (b"\x89PNG\r\n\x1a\n", &[".png"]),
+    (b"OggS", &[".ogg", ".opus"]),
+    (b"\xFF\xD8\xFF", &[".jpg", ".jpeg"]),
+    (b"fLaC", &[".flac"]),
+];
+
+/// Entries used as known plaintext; more only slows the vote down.
+const MAX_PROBES: usize = 64;
+const CHUNK_SIZE: usize = 1 << 20;
+
+/// Positions of each signature in `reader`, as one sorted list per signature.
+/// `progress` gets the bytes scanned so far after every chunk; the scan stops with an
+/// error once it returns false.
+pub fn find_signatures(
+    mut reader: impl Read,
+    signatures: &[&[u8]],
+    mut progress: impl FnMut(u64) -> bool,
+) -> anyhow::Result<Vec<Vec<u64>>> {
+    let mut positions = vec![Vec::new(); signatures.len()];
+    let overlap = signatures.iter().map(|sig| sig.len()).max().unwrap_or(1) - 1;
+
+    let mut buffer = vec![0u8; CHUNK_SIZE + overlap];
+    let mut carried = 0;
+    let mut base = 0u64;
+
+    loop {
+        let read = reader.read(&mut buffer[carried..])?;
+        if read == 0 {
+            break;
+        }
+        let filled = carried + read;
+        for (i, signature) in signatures.iter().enumerate() {
+            for (pos, window) in buffer[..filled].windows(signature.len()).enumerate() {
+                if window == *signature {
+                    positions[i].push(base + pos as u64);
+                }
+            }
+        }
+        let keep = overlap.min(filled);
+        buffer.copy_within(filled - keep..filled, 0);
+        base += (filled - keep) as u64;
+        carried = keep;
+        if !progress(base + carried as u64) {
+            return Err(anyhow::anyhow!("Scan cancelled"));
+        }
+    }
+
+    // Matches inside the carried-over bytes are seen twice.
+    for list in &mut positions {
+        list.sort_unstable();
+        list.dedup();
+    }
+    Ok(positions)
+}
+
+impl RpaEditor {
+    /// True when most archived entries point past the index, which means the key is wrong.
+    pub(crate) fn index_looks_invalid(&self) -> bool {
+        let mut archived = 0;
+        let mut invalid = 0;
+        for entry in self.indexes.values().filter(|e| e.in_archive()) {
+            archived += 1;
+            if entry.segments.iter().any(|segment| {
+                let stored = segment.length.saturating_sub(segment.prefix.len() as u64);
+                segment.offset.saturating_add(stored) > self.index_offset
+            }) {
+                invalid += 1;
+            }
+        }
+        archived > 0 && invalid * 2 > archived
+    }
+
+    /// Starts a job voting for the XOR key that makes the most typed entries start on
+    /// their file signature. The data area is scanned off the UI thread; the job's entries
+    /// are its `CHUNK_SIZE` pieces, so the queue shows how far the scan got.
+    pub(crate) fn start_key_recovery(&mut self) -> anyhow::Result<()> {
+        let archive_path = self
+            .archive_path
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("No archive loaded"))?;
+        if self.key_recovery_running() {
+            return Err(anyhow::anyhow!("Key recovery is already running for this archive"));
+        }
+
+        // Raw index values are recovered by undoing the key that was applied on load.
+        let mut probes = Vec::new();
+        for (filename, entry) in &self.indexes {
+            let lower = filename.to_lowercase();
+            let Some(kind) = SIGNATURES
+                .iter()
+                .position(|(_, exts)| exts.iter().any(|ext| lower.ends_with(ext)))
+            else {
+                continue;
+            };
+            if let Some(segment) = entry.segments.first()
+                && entry.in_archive()
+                && segment.prefix.is_empty()
+            {
+                probes.push((kind, segment.offset ^ self.key as u64));
+            }
+            if probes.len() >= MAX_PROBES {
+                break;
+            }
+        }
+        if probes.is_empty() {
+            return Err(anyhow::anyhow!(
+                "No PNG/OGG/JPEG/FLAC entries without prefix to use as known plaintext"
+            ));
+        }
+
+        let chunks = (0..self.index_offset.div_ceil(CHUNK_SIZE as u64))
+            .map(|chunk| format!("bytes {:#x}+", chunk * CHUNK_SIZE as u64))
+            .collect();
+        self.push_job("key", chunks, None);
+        let Some(job) = self.jobs.last_mut() else {
+            return Ok(());
+        };
+        job.status = "running".to_string();
+        let (id, cancel, sender) = (job.id, job.cancel.clone(), self.job_sender.clone());
+        let data_length = self.index_offset;
+        self.status_message = format!("Recovering the key of {}...", archive_path);
+        info!("🔑 Scanning {} for the key (job #{})", archive_path, id);
+        std::thread::spawn(move || run_key_recovery(id, archive_path, data_length, probes, cancel, sender));
+        Ok(())
+    }
+
+    pub(crate) fn key_recovery_running(&self) -> bool {
+        self.jobs
+            .iter()
+            .any(|job| job.kind == "key" && job.status == "running" && job.archive == self.archive_path)
+    }
+
+    /// Applies the key found by a "key" job that read the entries at `generation`. The
+    /// vote used their offsets then, so it is dropped if the archive was edited since.
+    pub(crate) fn finish_key_recovery(&mut self, result: anyhow::Result<u32>, generation: u64) {
+        let applied = result.and_then(|key| {
+            if generation != self.edit_generation {
+                return Err(anyhow::anyhow!("the archive changed while it was scanned"));
+            }
+            self.apply_recovered_key(key)?;
+            Ok(key)
+        });
+        match applied {
+            Ok(key) => self.add_toast(format!("Recovered key {:08x}", key)),
+            Err(e) => self.add_error(format!("Key recovery failed: {}", e)),
+        }
+    }
+
+    fn apply_recovered_key(&mut self, key: u32) -> anyhow::Result<()> {
+        let delta = (self.key ^ key) as u64;
+        for entry in self.indexes.values_mut().filter(|e| e.in_archive()) {
+            for segment in &mut entry.segments {
+                segment.offset ^= delta;
+                segment.length ^= delta;
+            }
+            entry.length = entry.segments.iter().map(|s| s.length).sum();
+        }
+        self.key = key;
+        self.loaded_size = self.indexes.values().map(|e| e.length).sum();
+        // Saving rewrites the index under the new key; read-only mode only browses with it.
+        self.set_modified(self.modified || !self.read_only);
+
+        // Contents are checked by a verify job; its findings join these.
+        self.entry_warnings = self.check_entry_bounds()?;
+        self.deep_verify();
+        self.status_message = format!(
+            "Recovered key {:08x} ({} entries out of bounds, contents are being verified)",
+            key,
+            self.entry_warnings.len()
+        );
+        Ok(())
+    }
+}
+
+/// Scans the data area of `path` for the probes' signatures and votes for a key.
+fn run_key_recovery(
+    id: usize,
+    path: String,
+    data_length: u64,
+    probes: Vec<(usize, u64)>,
+    cancel: Arc<AtomicBool>,
+    sender: Sender<JobEvent>,
+) {
+    let signatures: Vec<&[u8]> = SIGNATURES.iter().map(|(signature, _)| *signature).collect();
+    let scanned = File::open(&path).map_err(anyhow::Error::from).and_then(|file| {
+        find_signatures(file.take(data_length), &signatures, |scanned| {
+            let processed = scanned.div_ceil(CHUNK_SIZE as u64) as usize;
+            let _ = sender.send(JobEvent::Progress { id, processed, failed: 0 });
+            !cancel.load(Ordering::Relaxed)
+        })
+    });
+    if cancel.load(Ordering::Relaxed) {
+        let _ = sender.send(JobEvent::Finished { id, message: String::new() });
+        return;
+    }
+
+    let result = scanned.and_then(|positions| vote_for_key(&probes, &positions));
+    let message = match &result {
+        Ok((key, count)) => format!("Key {:08x} ({}/{} entries matched)", key, count, probes.len()),
+        Err(e) => e.to_string(),
+    };
+    info!("🔑 {}", message);
+    let failed = usize::from(result.is_err());
+    let processed = data_length.div_ceil(CHUNK_SIZE as u64) as usize;
+    let _ = sender.send(JobEvent::Progress { id, processed, failed });
+    let _ = sender.send(JobEvent::KeyRecovered { id, key: result.map(|(key, _)| key) });
+    let _ = sender.send(JobEvent::Finished { id, message });
+}
+
+/// The key candidate most `(signature kind, raw offset)` probes agree on, with its votes.
+fn vote_for_key(probes: &[(usize, u64)], positions: &[Vec<u64>]) -> anyhow::Result<(u32, usize)> {
+    let mut votes: HashMap<u32, usize> = HashMap::new();
+    for (kind, raw_offset) in probes {
+        for position in &positions[*kind] {
+            let candidate = raw_offset ^ position;
+            if candidate <= u32::MAX as u64 {
+                *votes.entry(candidate as u32).or_default() += 1;
+            }
+        }
+    }
+
+    let (key, count) = votes
+        .into_iter()
+        .max_by_key(|&(key, count)| (count, std::cmp::Reverse(key)))
+        .ok_or_else(|| anyhow::anyhow!("No key candidate matched any signature"))?;
+    if probes.len() > 1 && count < 2 {
+        return Err(anyhow::anyhow!("No key candidate matched more than one entry"));
+    }
+    Ok((key, count))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PNG_HEAD: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+
+    #[test]
+    fn key_job_finds_the_key_of_a_misread_index() {
+        let dir = crate::test_dir::TestDir::new("key");
+        let path = dir.file("keyed.rpa");
+        let mut editor = RpaEditor {
+            key: 0x5EC2_E7A1,
+            ..RpaEditor::default()
+        };
+        for name in ["a.png", "b.png", "c.png"] {
+            editor.add_data(name, [PNG_HEAD, name.as_bytes()].concat()).unwrap();
+        }
+        editor.save_rpa(&path).unwrap();
+
+        let mut editor = RpaEditor::default();
+        editor.load_rpa(&path).unwrap();
+        // As if the header named the wrong key.
+        editor.apply_recovered_key(0x1234_5678).unwrap();
+        assert!(editor.index_looks_invalid());
+
+        editor.start_key_recovery().unwrap();
+        let ctx = egui::Context::default();
+        for _ in 0..500 {
+            editor.process_jobs(&ctx);
+            if !editor.key_recovery_running() {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert_eq!(editor.key, 0x5EC2_E7A1);
+        assert!(!editor.index_looks_invalid());
+        assert_eq!(editor.load_file_data("b.png").unwrap(), [PNG_HEAD, b"b.png"].concat());
+    }
+}
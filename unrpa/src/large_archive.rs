@@ -0,0 +1,50 @@
+use crate::rpa::{RpaEditor, RpaFileEntry};
+
+/// Entry count at which the "auto" setting turns large archive mode on.
+pub const LARGE_ARCHIVE_THRESHOLD: usize = 10_000;
+/// Entries bigger than this aren't loaded for preview in large archive mode.
+pub const LARGE_PREVIEW_LIMIT: u64 = 8 * 1024 * 1024;
+/// Longest side of preview images in large archive mode.
+pub const LARGE_PREVIEW_DIMENSION: u32 = 1024;
+
+impl RpaEditor {
+    /// Whether thumbnails, big previews and load-time checks should be skipped.
+    pub(crate) fn is_large_archive_mode(&self) -> bool {
+        match self.large_archive_mode.as_str() {
+            "on" => true,
+            "auto" => self.indexes.len() >= LARGE_ARCHIVE_THRESHOLD,
+            _ => false,
+        }
+    }
+
+    /// Virtualized list: only the visible rows are laid out. Returns the clicked entry.
+    pub(crate) fn show_compact_file_list(&self, ui: &mut egui::Ui, files: &[(&String, &RpaFileEntry)]) -> Option<String> {
+        let row_height = ui.spacing().interact_size.y;
+        let mut clicked = None;
+
+        egui::ScrollArea::vertical()
+            .auto_shrink([false, false])
+            .show_rows(ui, row_height, files.len(), |ui, rows| {
+                for (filename, entry) in &files[rows] {
+                    if self.show_compact_file_row(ui, filename, entry) {
+                        clicked = Some((*filename).clone());
+                    }
+                }
+            });
+
+        clicked
+    }
+
+    /// Runs the entry checks that large archive mode skipped on load.
+    pub(crate) fn run_deferred_checks(&mut self) -> anyhow::Result<()> {
+        self.entry_warnings = self.check_entry_bounds()?;
+        self.orphaned_ranges = self.find_orphaned_ranges();
+        self.deep_verify();
+        self.status_message = format!(
+            "{} entries out of bounds, {} orphaned ranges; contents are being verified",
+            self.entry_warnings.len(),
+            self.orphaned_ranges.len()
+        );
+        Ok(())
+    }
+}
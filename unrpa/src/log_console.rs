@@ -0,0 +1,308 @@
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tracing::field::{Field, Visit};
+use tracing::{info, Event, Level, Metadata, Subscriber};
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use tracing_subscriber::util::SubscriberInitExt;
+use crate::external_edit::open_with_default_app;
+use crate::health::config_dir;
+use crate::rpa::RpaEditor;
+
+/// Severities in the console filter, most severe first. Each shows itself and above.
+pub const LOG_LEVELS: &[(&str, &str)] = &[
+    ("error", "❌ Errors"),
+    ("warn", "⚠️ Warnings"),
+    ("info", "ℹ️ Info"),
+    ("debug", "🐞 Debug"),
+];
+
+/// Lines kept for the console; older ones are only in the log file.
+const CONSOLE_LENGTH: usize = 5000;
+
+/// Other crates' events are only logged from this level up.
+const DEPENDENCY_LEVEL: Level = Level::WARN;
+
+struct LogLine {
+    /// Counts up from the first line logged, so it stays put when old lines are dropped.
+    number: usize,
+    time: String,
+    level: Level,
+    message: String,
+}
+
+/// Console lines passing the level and search filter, updated with new lines as they
+/// come in instead of refiltering the whole buffer every frame.
+#[derive(Default)]
+pub struct LogView {
+    level: String,
+    search: String,
+    /// First line number not looked at yet.
+    scanned: usize,
+    /// Numbers of the matching lines.
+    matches: Vec<usize>,
+}
+
+impl LogView {
+    fn update(&mut self, console: &VecDeque<LogLine>, level: &str, search: &str) {
+        let search = search.to_lowercase();
+        if self.level != level || self.search != search {
+            *self = Self { level: level.to_string(), search, ..Self::default() };
+        }
+        let first = console.front().map_or(usize::MAX, |line| line.number);
+        self.matches.retain(|&number| number >= first);
+
+        let threshold = parse_level(level);
+        let start = self.scanned.saturating_sub(first).min(console.len());
+        for line in console.range(start..) {
+            if line.level <= threshold
+                && (self.search.is_empty() || line.message.to_lowercase().contains(&self.search))
+            {
+                self.matches.push(line.number);
+            }
+        }
+        self.scanned = console.back().map_or(self.scanned, |line| line.number + 1);
+    }
+}
+
+/// The console lines numbered `numbers`, with their time, level and message.
+fn console_lines(numbers: &[usize]) -> Vec<(String, Level, String)> {
+    let Ok(console) = CONSOLE.lock() else {
+        return Vec::new();
+    };
+    let first = console.front().map_or(0, |line| line.number);
+    numbers
+        .iter()
+        .filter_map(|number| console.get(number.checked_sub(first)?))
+        .map(|line| (line.time.clone(), line.level, line.message.clone()))
+        .collect()
+}
+
+static CONSOLE: Mutex<VecDeque<LogLine>> = Mutex::new(VecDeque::new());
+static LOG_FILE: Mutex<Option<File>> = Mutex::new(None);
+/// Errors logged since startup, for the status bar.
+static ERROR_COUNT: AtomicUsize = AtomicUsize::new(0);
+/// Lines logged since startup, numbering the console lines.
+static LINE_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+pub fn log_file_path() -> PathBuf {
+    config_dir().join("rpa_editor.log")
+}
+
+fn level_name(level: &Level) -> &'static str {
+    match *level {
+        Level::ERROR => "error",
+        Level::WARN => "warn",
+        Level::INFO => "info",
+        Level::DEBUG => "debug",
+        Level::TRACE => "trace",
+    }
+}
+
+fn parse_level(name: &str) -> Level {
+    match name {
+        "error" => Level::ERROR,
+        "warn" => Level::WARN,
+        "debug" => Level::DEBUG,
+        _ => Level::INFO,
+    }
+}
+
+/// The event's message followed by any other fields as `name=value`.
+#[derive(Default)]
+struct MessageVisitor(String);
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.0, "{:?}", value);
+        } else {
+            let _ = write!(self.0, " {}={:?}", field.name(), value);
+        }
+    }
+}
+
+/// Sends every event to the console buffer and the log file when one is open; debug
+/// builds also echo it to stderr.
+/// Debug events are only kept from the editor itself, not its dependencies.
+struct ConsoleLayer;
+
+impl<S: Subscriber> Layer<S> for ConsoleLayer {
+    fn enabled(&self, metadata: &Metadata<'_>, _ctx: Context<'_, S>) -> bool {
+        if metadata.target().starts_with(env!("CARGO_CRATE_NAME")) {
+            *metadata.level() <= Level::DEBUG
+        } else {
+            *metadata.level() <= DEPENDENCY_LEVEL
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+        let level = *event.metadata().level();
+        let now = chrono::Local::now();
+        let message = visitor.0;
+
+        if cfg!(debug_assertions) {
+            eprintln!("{}", message);
+        }
+        if let Ok(mut file) = LOG_FILE.lock()
+            && let Some(file) = file.as_mut()
+        {
+            let _ = writeln!(
+                file,
+                "{} {:5} {}",
+                now.format("%Y-%m-%d %H:%M:%S%.3f"),
+                level_name(&level).to_uppercase(),
+                message
+            );
+        }
+        if level == Level::ERROR {
+            ERROR_COUNT.fetch_add(1, Ordering::Relaxed);
+        }
+        if let Ok(mut console) = CONSOLE.lock() {
+            console.push_back(LogLine {
+                number: LINE_COUNT.fetch_add(1, Ordering::Relaxed),
+                time: now.format("%H:%M:%S").to_string(),
+                level,
+                message,
+            });
+            if console.len() > CONSOLE_LENGTH {
+                console.pop_front();
+            }
+        }
+    }
+}
+
+/// Installs the logger; called once at startup, before anything is logged.
+pub fn init_logging() {
+    if let Err(e) = tracing_subscriber::registry().with(ConsoleLayer).try_init() {
+        eprintln!("❌ Could not start logging: {}", e);
+    }
+}
+
+/// Starts or stops appending to `log_file_path()`.
+pub fn set_log_file(enabled: bool) -> anyhow::Result<()> {
+    let file = if enabled {
+        let path = log_file_path();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        Some(OpenOptions::new().create(true).append(true).open(&path)?)
+    } else {
+        None
+    };
+    if let Ok(mut current) = LOG_FILE.lock() {
+        *current = file;
+    }
+    Ok(())
+}
+
+pub fn logged_errors() -> usize {
+    ERROR_COUNT.load(Ordering::Relaxed)
+}
+
+impl RpaEditor {
+    pub(crate) fn set_log_to_file(&mut self, enabled: bool) {
+        self.log_to_file = enabled;
+        match set_log_file(enabled) {
+            Ok(()) if enabled => info!("📝 Logging to {}", log_file_path().display()),
+            Ok(()) => {}
+            Err(e) => {
+                self.log_to_file = false;
+                self.add_error(format!("Can't write the log file: {}", e));
+            }
+        }
+    }
+
+    /// Errors logged since the console was last opened.
+    pub(crate) fn unseen_log_errors(&self) -> usize {
+        logged_errors().saturating_sub(self.log_errors_seen)
+    }
+
+    pub(crate) fn open_log_console(&mut self) {
+        self.log_errors_seen = logged_errors();
+        self.show_log_console = true;
+    }
+
+    pub(crate) fn show_log_console(&mut self, ui: &mut egui::Ui) {
+        self.log_errors_seen = logged_errors();
+        ui.horizontal(|ui| {
+            ui.label("Show:");
+            egui::ComboBox::from_id_salt("log_level_filter")
+                .selected_text(
+                    LOG_LEVELS
+                        .iter()
+                        .find(|(level, _)| *level == self.log_level_filter)
+                        .map_or("", |(_, label)| *label),
+                )
+                .show_ui(ui, |ui| {
+                    for (level, label) in LOG_LEVELS {
+                        ui.selectable_value(&mut self.log_level_filter, level.to_string(), *label);
+                    }
+                });
+            ui.label("🔍");
+            ui.add(egui::TextEdit::singleline(&mut self.log_search).desired_width(160.0).hint_text("Search"));
+        });
+
+        if let Ok(console) = CONSOLE.lock() {
+            self.log_view.update(&console, &self.log_level_filter, &self.log_search);
+        }
+
+        ui.separator();
+        let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+        let matches = &self.log_view.matches;
+        egui::ScrollArea::vertical()
+            .max_height(360.0)
+            .auto_shrink([false, false])
+            .stick_to_bottom(true)
+            .show_rows(ui, row_height, matches.len(), |ui, range| {
+                for (time, level, message) in console_lines(&matches[range]) {
+                    let text = egui::RichText::new(format!("{} {}", time, message)).monospace();
+                    let text = match level {
+                        Level::ERROR => text.color(egui::Color32::LIGHT_RED),
+                        Level::WARN => text.color(egui::Color32::YELLOW),
+                        Level::INFO => text,
+                        _ => text.weak(),
+                    };
+                    ui.label(text);
+                }
+            });
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            let mut log_to_file = self.log_to_file;
+            if ui
+                .checkbox(&mut log_to_file, "Write to log file")
+                .on_hover_text(log_file_path().display().to_string())
+                .changed()
+            {
+                self.set_log_to_file(log_to_file);
+            }
+            if ui.add_enabled(log_file_path().is_file(), egui::Button::new("📂 Open Log File")).clicked()
+                && let Err(e) = open_with_default_app(&log_file_path())
+            {
+                self.add_error(format!("Can't open the log file: {}", e));
+            }
+            if ui.button("📋 Copy").on_hover_text("Copy the shown lines").clicked() {
+                let text: Vec<String> = console_lines(&self.log_view.matches)
+                    .iter()
+                    .map(|(time, level, message)| format!("{} {:5} {}", time, level_name(level).to_uppercase(), message))
+                    .collect();
+                ui.ctx().copy_text(text.join("\n"));
+            }
+            if ui.button("🧹 Clear").clicked()
+                && let Ok(mut console) = CONSOLE.lock()
+            {
+                console.clear();
+            }
+            if ui.button("Close").clicked() {
+                self.show_log_console = false;
+            }
+        });
+    }
+}
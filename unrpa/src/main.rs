@@ -1,18 +1,106 @@
+mod animation;
+mod annotations;
+mod archive_index;
+mod archive_load;
+mod archive_reader;
+mod archive_watch;
+mod autosave;
+mod backup_store;
+mod batch_replace;
+mod batch_rename;
+mod checksums;
+mod compact;
+mod crash_recovery;
+mod dialogue;
+mod diff;
+mod duplicates;
+mod entry_stream;
+mod external_edit;
+mod extract_conflicts;
+mod file_styles;
+mod file_table;
+mod file_tree;
+mod filter_query;
+mod folder_add;
+mod font_preview;
+mod font_subset;
+mod gallery;
+mod game_folder;
+mod glob;
+mod header;
+mod health;
+mod hex_editor;
+mod hex_view;
+mod highlight;
+mod image_compare;
+mod image_view;
+mod integrity;
+mod jobs;
+mod key_recovery;
+mod large_archive;
+mod log_console;
+mod manifest;
+mod merge;
+mod orphans;
+mod patch;
+mod playlist;
+mod preview_cache;
+mod project_export;
+mod provenance;
+mod read_only;
+mod recent;
+mod recovery;
+mod recycle_bin;
+mod revert;
 mod rpa;
+mod rpyc;
+mod safe_path;
+mod save_review;
+mod selection_export;
+mod settings;
+mod sha256;
+mod sniff;
+mod split;
+mod tabs;
+mod temp_files;
+#[cfg(test)]
+mod test_dir;
+mod text_editor;
+mod thumbnails;
 mod toast;
-
-use crate::rpa::{RpaEditor, RpaFileEntry};
+mod translation;
+mod unused_assets;
+mod validation;
+mod video_frames;
+mod video_probe;
+mod watch_folder;
+mod workspace;
+mod zip_import;
+
+use crate::external_edit::open_with_default_app;
+use crate::extract_conflicts::EXTRACT_LAYOUTS;
+use crate::file_tree::{FileTreeNode, TreeActions};
+use crate::filter_query::{FilterQuery, FILTER_QUERY_HELP};
+use crate::jobs::{JOB_KINDS, READ_ONLY_JOB_KINDS};
+use crate::playlist::AUDIO_LOOP_MODES;
+use crate::rpa::{RpaEditor, BACKUP_TRIGGERS, CHANGE_FILTERS};
 use eframe::egui;
 use egui_video::Player;
 use rodio::{Decoder, OutputStream, Sink, Source};
-use std::fs::create_dir_all;
-use std::io::Cursor;
 use std::ops::Div;
-use std::time::{Duration, Instant};
-
-impl eframe::App for RpaEditor {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use tracing::{debug, error, warn};
+
+impl RpaEditor {
+    /// One frame of the UI; `update` runs it under the crash guard.
+    fn show_frame(&mut self, ctx: &egui::Context) {
         ctx.request_repaint();
+        if let Some(rect) = ctx.input(|input| input.viewport().inner_rect) {
+            self.window_size = [rect.width(), rect.height()];
+        }
+        self.advance_animation(Duration::from_secs_f32(ctx.input(|input| input.stable_dt)));
         if let Some(filename) = self.file_to_preview.take() {
             self.preview_file(&filename);
             self.selected_file = Some(filename);
@@ -22,24 +110,22 @@ impl eframe::App for RpaEditor {
             self.remove_file(&filename);
         }
 
-        if let Some((filename, new_path)) = self.file_to_replace.take() {
-            println!("{filename}, {new_path}");
-            match self.replace_file(&filename, &new_path) {
-                Ok(()) => {
-                    if self.selected_file.as_ref() == Some(&filename) {
-                        self.preview_file(&filename);
-                    }
-                }
-                Err(e) => {
-                    self.status_message = format!("Replace error: {}", e);
-                }
-            }
+        if let Some((archive_name, source_path)) = self.file_to_replace.take() {
+            self.apply_replacement(&archive_name, &source_path, false);
         }
 
+        self.process_pending_adds();
+        self.process_jobs(ctx);
+        self.poll_external_edits();
+        self.poll_watch_folder();
+        self.poll_archive_on_disk();
+        self.poll_autosave();
+
         if let Some(folder_path) = self.batch_replace_to_execute.take() {
             match self.batch_replace_from_folder(&folder_path) {
-                Ok(count) => {
-                    self.status_message = format!("Batch replaced {} files", count);
+                Ok(report) => {
+                    self.add_toast(format!("Batch replaced {} files", report.replaced()));
+                    self.batch_replace_report = Some(report);
                 }
                 Err(e) => {
                     self.status_message = format!("Batch replace error: {}", e);
@@ -47,72 +133,61 @@ impl eframe::App for RpaEditor {
             }
         }
 
-        egui::TopBottomPanel::top("toasts_panel").show(ctx, |ui| {
-            ui.horizontal_wrapped(|ui| {
-                for toast in &self.toasts {
-                    ui.label(
-                        egui::RichText::new(&toast.message)
-                            .background_color(egui::Color32::DARK_GREEN)
-                            .color(egui::Color32::WHITE)
-                            .strong(),
-                    );
-                }
-            });
-        });
+        self.show_toasts(ctx);
 
         ctx.input(|i| {
             // Ctrl+O => Open RPA
-            if i.key_pressed(egui::Key::O) && i.modifiers.ctrl {
-                if let Some(path) = rfd::FileDialog::new()
+            if i.key_pressed(egui::Key::O)
+                && i.modifiers.ctrl
+                && let Some(path) = rfd::FileDialog::new()
                     .add_filter("RPA files", &["rpa"])
                     .pick_file()
-                {
-                    if let Err(e) = self.load_rpa(&path.to_string_lossy()) {
-                        self.add_toast(format!("Error loading: {}", e));
-                    } else {
-                        self.add_toast("RPA loaded successfully");
-                    }
-                }
+            {
+                self.open_archive(&path.to_string_lossy());
             }
             // Ctrl+S => Save
             if i.key_pressed(egui::Key::S) && i.modifiers.ctrl && !i.modifiers.shift {
                 if let Some(path) = self.archive_path.clone() {
-                    match self.save_rpa(&path) {
-                        Ok(()) => self.add_toast("Saved successfully"),
-                        Err(e) => self.add_toast(format!("Save error: {}", e)),
-                    }
+                    self.request_save(&path);
                 } else {
-                    self.add_toast("No file to save");
+                    self.add_warning("No file to save");
                 }
             }
 
             // Ctrl+Shift+S => Save As
             if i.key_pressed(egui::Key::S) && i.modifiers.ctrl && i.modifiers.shift {
-                if let Some(path) = rfd::FileDialog::new()
-                    .add_filter("RPA files", &["rpa"])
-                    .save_file()
-                {
-                    match self.save_rpa(&path.to_string_lossy()) {
-                        Ok(()) => self.add_toast("Saved As successfully"),
-                        Err(e) => self.add_toast(format!("Save error: {}", e)),
-                    }
-                }
+                self.open_save_as_dialog();
             }
 
             // Ctrl+W => Close rpa
             if i.key_pressed(egui::Key::W) && i.modifiers.ctrl {
                 if !self.modified {
-                    if let Err(e) = self.unload_rpa() {
-                        self.add_toast(format!("Error unloading: {}", e));
-                    }
+                    self.close_active_tab();
                 } else {
                     self.show_close_confirm = true;
                 }
             }
         });
 
+        let dropped: Vec<std::path::PathBuf> = ctx.input(|i| {
+            i.raw
+                .dropped_files
+                .iter()
+                .filter_map(|f| f.path.clone())
+                .collect()
+        });
+        if !dropped.is_empty() {
+            self.queue_dropped_files(dropped);
+        }
+
         self.toasts.retain(|toast| !toast.is_expired());
 
+        let title = self.get_window_title();
+        if title != self.window_title {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Title(title.clone()));
+            self.window_title = title;
+        }
+
         self.show_top_panel(ctx);
 
         egui::TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
@@ -133,7 +208,64 @@ impl eframe::App for RpaEditor {
                         ));
                         ui.separator();
 
-                        let visible_count = self.get_filtered_sorted_files().len();
+                        if self.read_only {
+                            ui.colored_label(egui::Color32::LIGHT_BLUE, "🔒 Read-only");
+                            ui.separator();
+                        }
+
+                        let errors = self.unseen_log_errors();
+                        if errors > 0 {
+                            let text = egui::RichText::new(format!("❌ {} errors", errors)).color(egui::Color32::LIGHT_RED);
+                            if ui.link(text).on_hover_text("Open the log console").clicked() {
+                                self.open_log_console();
+                            }
+                            ui.separator();
+                        }
+
+                        let active_jobs = self.active_job_count();
+                        if active_jobs > 0 {
+                            if ui.link(format!("⚙ {} jobs", active_jobs)).on_hover_text("Open the job queue").clicked() {
+                                self.show_jobs_dialog = true;
+                            }
+                            let (processed, total) = self.job_queue_progress();
+                            if self.jobs_running && total > 0 {
+                                ui.add(
+                                    egui::ProgressBar::new(processed as f32 / total as f32)
+                                        .desired_width(80.0)
+                                        .desired_height(8.0),
+                                );
+                            }
+                            ui.separator();
+                        }
+
+                        if let Some(watch) = &self.watch_folder {
+                            let label = format!("👁 Watching {}", watch.folder.display());
+                            if ui.link(label).clicked() {
+                                self.show_watch_folder_dialog = true;
+                            }
+                            ui.separator();
+                        }
+
+                        if !self.external_edits.is_empty() {
+                            let changed = self.external_edits.iter().filter(|edit| edit.changed).count();
+                            let text = if changed > 0 {
+                                egui::RichText::new(format!("✏️ {} changed externally", changed)).color(egui::Color32::YELLOW)
+                            } else {
+                                egui::RichText::new(format!("✏️ {} open externally", self.external_edits.len()))
+                            };
+                            if ui.link(text).clicked() {
+                                self.show_external_edits = true;
+                            }
+                            ui.separator();
+                        }
+
+                        let deleted = self.indexes.values().filter(|entry| entry.to_delete).count();
+                        if deleted > 0 {
+                            ui.colored_label(egui::Color32::RED, format!("🗑️ {} pending deletion", deleted));
+                            ui.separator();
+                        }
+
+                        let (visible_count, _) = self.filter_counts();
                         let total = self.indexes.len();
                         ui.label(if visible_count != total {
                             format!("{}/{} files", visible_count, total)
@@ -143,7 +275,7 @@ impl eframe::App for RpaEditor {
                     }
                     if self.archive_path.is_some() {
                         ui.separator();
-                        ui.label(format!("RPA {:.1}", self.version));
+                        ui.label(self.header_format.magic);
                     }
                 });
             });
@@ -158,7 +290,7 @@ impl eframe::App for RpaEditor {
                     ui.horizontal(|ui| {
                         ui.heading("📂 Files");
                         ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                            let visible_count = self.get_filtered_sorted_files().len();
+                            let (visible_count, _) = self.filter_counts();
                             let total = self.indexes.len();
                             ui.label(if visible_count != total {
                                 format!("{}/{}", visible_count, total)
@@ -189,74 +321,119 @@ impl eframe::App for RpaEditor {
                         }
                     });
 
+                    ui.horizontal(|ui| {
+                        let (_, counts) = self.filter_counts();
+                        for ((filter, label), count) in CHANGE_FILTERS.iter().zip(counts) {
+                            if ui
+                                .selectable_label(self.change_filter == *filter, format!("{} ({})", label, count))
+                                .clicked()
+                            {
+                                self.change_filter = filter.to_string();
+                            }
+                        }
+                    });
+
                     ui.separator();
 
                     ui.horizontal(|ui| {
                         ui.label("🔍");
-                        ui.text_edit_singleline(&mut self.search_filter);
+                        ui.text_edit_singleline(&mut self.search_filter)
+                            .on_hover_text(FILTER_QUERY_HELP);
                         if ui.button("❌").clicked() {
                             self.search_filter.clear();
                         }
                     });
+                    if let Err(e) = FilterQuery::parse(&self.search_filter) {
+                        ui.colored_label(egui::Color32::ORANGE, format!("⚠ {} (matching as plain text)", e));
+                    }
 
-                    ui.separator();
-
-                    egui::ScrollArea::vertical()
-                        .auto_shrink([false, false])
-                        .show(ui, |ui| {
-                            let files = self.get_filtered_sorted_files();
-
-                            let mut file_to_select: Option<String> = None;
-                            let mut file_to_preview: Option<String> = None;
-
-                            for (filename, entry) in files {
-                                let is_selected = Some(filename) == self.selected_file.as_ref();
-                                let filename_clone = filename.clone();
+                    ui.horizontal(|ui| {
+                        for (view, label) in [("list", "☰ List"), ("tree", "🌲 Tree"), ("gallery", "🖼 Gallery")] {
+                            if ui
+                                .selectable_label(self.file_list_view == view, label)
+                                .clicked()
+                            {
+                                self.file_list_view = view.to_string();
+                            }
+                        }
+                        if ui
+                            .button("📋")
+                            .on_hover_text("Extract visible files and copy their paths")
+                            .clicked()
+                        {
+                            let filenames: Vec<String> = self
+                                .get_filtered_sorted_files()
+                                .into_iter()
+                                .filter(|(_, entry)| !entry.to_delete)
+                                .map(|(name, _)| name.clone())
+                                .collect();
+                            self.extract_and_copy_paths(ctx, &filenames);
+                        }
+                    });
 
-                                ui.horizontal(|ui| {
-                                    ui.set_min_height(25.0);
+                    ui.separator();
 
-                                    ui.label(Self::get_file_icon(filename));
+                    self.show_deleted_section(ui);
 
-                                    let mut text = egui::RichText::new(filename);
+                    if self.file_list_view == "gallery" {
+                        self.show_gallery(ui);
+                        return;
+                    }
 
-                                    if entry.to_delete {
-                                        text = text.strikethrough().color(egui::Color32::RED);
-                                    } else if entry.modified {
-                                        text = text.color(egui::Color32::YELLOW);
-                                    } else {
-                                        text = text.color(Self::get_file_type_color(filename));
-                                    }
+                    if self.is_large_archive_mode() && self.file_list_view == "list" {
+                        let files = self.get_filtered_sorted_files();
+                        if let Some(preview) = self.show_compact_file_list(ui, &files) {
+                            self.selected_file = Some(preview.clone());
+                            self.file_to_preview = Some(preview);
+                        }
+                        return;
+                    }
 
-                                    let label = ui.selectable_label(is_selected, text);
+                    if self.file_list_view == "list" {
+                        self.show_file_table(ui);
+                        return;
+                    }
 
-                                    if label.clicked() {
-                                        file_to_select = Some(filename_clone.clone());
-                                        file_to_preview = Some(filename_clone);
-                                    }
+                    egui::ScrollArea::vertical()
+                        .auto_shrink([false, false])
+                        .show(ui, |ui| {
+                            let files = self.get_filtered_sorted_files();
+                            let mut actions = TreeActions::default();
 
-                                    ui.with_layout(
-                                        egui::Layout::right_to_left(egui::Align::Center),
-                                        |ui| {
-                                            ui.label(
-                                                egui::RichText::new(Self::format_bytes(
-                                                    entry.length,
-                                                ))
-                                                .small()
-                                                .weak(),
-                                            );
-                                        },
-                                    );
-                                });
+                            let tree = FileTreeNode::build(&files);
+                            self.show_tree_node(ui, &tree, "", &mut actions);
 
-                                ui.separator();
+                            if let Some(preview) = actions.file_to_preview {
+                                self.selected_file = Some(preview.clone());
+                                self.file_to_preview = Some(preview);
                             }
-
-                            if let Some(selected) = file_to_select {
-                                self.selected_file = Some(selected);
+                            if let Some(folder) = actions.folder_to_extract
+                                && let Some(base) = rfd::FileDialog::new().pick_folder()
+                            {
+                                self.request_extract("folder", &folder, base);
                             }
-                            if let Some(preview) = file_to_preview {
-                                self.file_to_preview = Some(preview);
+                            if let Some(folder) = actions.folder_to_copy_paths {
+                                let filenames: Vec<String> = self
+                                    .indexes
+                                    .iter()
+                                    .filter(|(name, entry)| name.starts_with(&folder) && !entry.to_delete)
+                                    .map(|(name, _)| name.clone())
+                                    .collect();
+                                self.extract_and_copy_paths(ctx, &filenames);
+                            }
+                            if let Some(folder) = actions.folder_to_replace
+                                && let Some(base) = rfd::FileDialog::new().pick_folder()
+                            {
+                                match self.replace_folder(&folder, &base) {
+                                    Ok((count, skipped)) => self.add_toast(format!(
+                                        "Replaced {} files in {} ({} skipped)",
+                                        count, folder, skipped
+                                    )),
+                                    Err(e) => self.add_error(format!("Replace Error: {}", e)),
+                                }
+                            }
+                            if let Some(folder) = actions.folder_to_remove {
+                                self.remove_folder(&folder);
                             }
                         });
                 });
@@ -265,121 +442,183 @@ impl eframe::App for RpaEditor {
         egui::CentralPanel::default().show(ctx, |ui| {
             if let Some(ref selected) = self.selected_file.clone() {
                 ui.horizontal(|ui| {
-                    ui.heading(format!("{} {}", Self::get_file_icon(selected), selected));
+                    ui.heading(format!("{} {}", self.file_icon(selected), selected));
 
                     ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                        if let Some(ref _img) = self.preview_image {
-                            ui.horizontal(|ui| {
-                                ui.label("🔍");
-                                if ui
-                                    .add(
-                                        egui::Slider::new(&mut self.image_zoom, 0.1..=3.0)
-                                            .text("Zoom"),
-                                    )
-                                    .changed()
-                                {}
-                            });
+                        self.show_animation_controls(ui);
+                        if self.preview_image.is_some() {
+                            self.show_image_zoom_controls(ui);
                         }
                     });
                 });
 
+                self.show_video_header(ui, selected);
+
                 ui.separator();
 
                 ui.horizontal(|ui| {
                     let selected_clone = selected.clone();
 
-                    if ui.button("📤 Extract").clicked() {
-                        if let Some(path) = rfd::FileDialog::new()
+                    if ui.button("📤 Extract").clicked()
+                        && let Some(path) = rfd::FileDialog::new()
                             .set_file_name(&selected_clone)
                             .save_file()
+                    {
+                        // The save dialog has already confirmed replacing an existing file.
+                        let outcome = self.write_entry(&selected_clone, path, "overwrite");
+                        self.status_message = if outcome.result == "failed" {
+                            format!("Extract Error: {}", outcome.detail)
+                        } else {
+                            format!("Extracted {}", selected_clone)
+                        };
+                    }
+
+                    if ui.button("📋 Extract & Copy Path").clicked() {
+                        self.extract_and_copy_paths(ctx, std::slice::from_ref(&selected_clone));
+                    }
+
+                    if self.editing_file.as_deref() == Some(selected_clone.as_str()) {
+                        if ui
+                            .add_enabled(self.text_edit_dirty(), egui::Button::new("✅ Apply to archive"))
+                            .clicked()
                         {
-                            if let Ok(data) = self.load_file_data(&selected_clone) {
-                                if std::fs::write(&path, data).is_ok() {
-                                    self.status_message = format!("Extracted {}", selected_clone);
-                                }
-                            }
+                            self.apply_text_edit();
+                        }
+                        if ui.button("↩ Close Editor").clicked() {
+                            self.discard_text_edit();
+                        }
+                        if self.text_edit_dirty() {
+                            ui.colored_label(egui::Color32::YELLOW, "● Unapplied edits");
+                        }
+                    } else if self.hex_editing_file.as_deref() == Some(selected_clone.as_str()) {
+                        if ui
+                            .add_enabled(self.hex_edit_dirty(), egui::Button::new("✅ Apply to archive"))
+                            .clicked()
+                        {
+                            self.apply_hex_edit();
+                        }
+                        if ui.button("↩ Close Hex Editor").clicked() {
+                            self.close_hex_edit();
+                        }
+                        if self.hex_edit_dirty() {
+                            ui.colored_label(egui::Color32::YELLOW, "● Unapplied edits");
+                        }
+                    } else {
+                        if self.can_edit_text(&selected_clone) && ui.add_enabled(!self.read_only, egui::Button::new("✏️ Edit")).clicked() {
+                            self.start_text_edit(&selected_clone);
+                        }
+                        if ui.add_enabled(!self.read_only, egui::Button::new("🔧 Hex Edit")).clicked() {
+                            self.start_hex_edit(&selected_clone);
                         }
+                        if ui
+                            .add_enabled(!self.read_only, egui::Button::new("↗ Edit Externally"))
+                            .on_hover_text("Open in the associated app and reimport saves made there")
+                            .clicked()
+                            && let Err(e) = self.open_in_external_editor(&selected_clone)
+                        {
+                            self.add_error(format!("Can't open {}: {}", selected_clone, e));
+                        }
+                    }
+
+                    if self.get_file_type(&selected_clone) == "videos"
+                        && ui.button("🖼 Export Frames").clicked()
+                    {
+                        self.frame_export_file = Some(selected_clone.clone());
+                        self.show_frame_export_dialog = true;
+                    }
+
+                    if self.indexes.get(&selected_clone).is_some_and(|entry| entry.to_delete) {
+                        if ui.button("♻ Restore").clicked() {
+                            self.restore_file(&selected_clone);
+                        }
+                    } else if ui.add_enabled(!self.read_only, egui::Button::new("🗑️ Remove")).clicked() {
+                        self.request_remove(&selected_clone);
                     }
 
-                    if ui.button("🗑️ Remove").clicked() {
-                        self.file_to_remove = Some(selected_clone.clone());
+                    if self.can_revert(&selected_clone)
+                        && ui.button("↩ Revert").on_hover_text("Drop changes to this entry").clicked()
+                    {
+                        self.revert_entry(&selected_clone);
                     }
 
-                    if ui.button("🔄 Replace").clicked() {
-                        if let Some(path) = rfd::FileDialog::new().pick_file() {
-                            self.file_to_replace =
-                                Some((path.to_string_lossy().to_string(), selected_clone.clone()));
+                    if ui.add_enabled(!self.read_only, egui::Button::new("🔄 Replace")).clicked()
+                        && let Some(path) = rfd::FileDialog::new().pick_file()
+                    {
+                        let path = path.to_string_lossy().to_string();
+                        // Images are compared first and replaced once confirmed.
+                        if self.get_file_type(&selected_clone) == "images" {
+                            if let Err(e) = self.stage_image_replacement(&selected_clone, &path) {
+                                self.add_warning(format!("Replacing without comparison: {}", e));
+                                self.file_to_replace = Some((selected_clone.clone(), path));
+                            }
+                        } else {
+                            self.file_to_replace = Some((selected_clone.clone(), path));
                         }
                     }
 
+                    let modified_image = self.get_file_type(&selected_clone) == "images"
+                        && self.indexes.get(&selected_clone).is_some_and(|entry| entry.modified && !entry.segments.is_empty());
+                    if modified_image
+                        && ui.button("🔍 Compare with original").clicked()
+                        && let Err(e) = self.compare_with_original(&selected_clone)
+                    {
+                        self.add_error(format!("Can't compare images: {}", e));
+                    }
+
                     if ui
                         .button(if self.is_playing { "Stop" } else { "Play" })
                         .clicked()
                     {
                         if self.is_playing {
-                            self.audio_player.stop();
-                            self.is_playing = false;
+                            self.stop_audio();
                             self.player = None;
-                        } else {
-                            if let Ok(data) = self.load_file_data(&selected_clone) {
-                                if selected_clone.ends_with(".ogg")
-                                    || selected_clone.ends_with(".mp3")
-                                    || selected_clone.ends_with(".wav")
-                                    || selected_clone.ends_with(".flac")
-                                {
-                                    println!("Playing audio {}", selected_clone);
-                                    self.audio_player.play_bytes(data);
-                                    self.is_playing = true;
-
-                                } else if selected_clone.ends_with(".mp4")
-                                    || selected_clone.ends_with(".avi")
-                                    || selected_clone.ends_with(".mov")
-                                    || selected_clone.ends_with(".mkv")
-                                    || selected_clone.ends_with(".webm")
-                                {
-                                    println!("Playing video {}", selected_clone);
-                                    let byte_video = Player::from_bytes(ctx, &data).unwrap();
-                                    if let None = byte_video.audio_streamer {
-                                        self.player = Some(
-                                            byte_video.with_audio(&mut self.audio_device).unwrap(),
-                                        );
-                                    } else {
-                                        self.player = Some(byte_video);
+                        } else if selected_clone.ends_with(".ogg")
+                            || selected_clone.ends_with(".mp3")
+                            || selected_clone.ends_with(".wav")
+                            || selected_clone.ends_with(".flac")
+                        {
+                            self.play_audio_entry(&selected_clone);
+                        } else if selected_clone.ends_with(".mp4")
+                            || selected_clone.ends_with(".avi")
+                            || selected_clone.ends_with(".mov")
+                            || selected_clone.ends_with(".mkv")
+                            || selected_clone.ends_with(".webm")
+                        {
+                            match self.video_source(&selected_clone) {
+                                Ok(path) => {
+                                    debug!("Playing video {} from {}", selected_clone, path);
+                                    match Player::new(ctx, &path) {
+                                        Ok(video) => {
+                                            if video.audio_streamer.is_none()
+                                                && let Some(device) = self.audio_device.as_mut()
+                                            {
+                                                self.player =
+                                                    Some(video.with_audio(device).unwrap());
+                                            } else {
+                                                self.player = Some(video);
+                                            }
+                                        }
+                                        Err(e) => {
+                                            self.status_message = format!("Playback error: {}", e);
+                                        }
                                     }
                                 }
+                                Err(e) => {
+                                    self.status_message = format!("Playback error: {}", e);
+                                }
                             }
                         }
                     }
 
                     if ui.button("📁 Open Folder").clicked() {
-                        if let Some(temp_dir) = std::env::temp_dir().parent() {
-                            let extract_dir = temp_dir.join("rpa_editor_temp");
-                            if create_dir_all(&extract_dir).is_ok() {
-                                let file_path = extract_dir.join(&selected_clone);
-                                if let Ok(data) = self.load_file_data(&selected_clone) {
-                                    if let Some(parent) = file_path.parent() {
-                                        let _ = create_dir_all(parent);
-                                    }
-                                    if std::fs::write(&file_path, data).is_ok() {
-                                        #[cfg(target_os = "windows")]
-                                        let _ = std::process::Command::new("explorer")
-                                            .arg(&extract_dir)
-                                            .spawn();
-
-                                        #[cfg(target_os = "macos")]
-                                        let _ = std::process::Command::new("open")
-                                            .arg(&extract_dir)
-                                            .spawn();
-
-                                        #[cfg(target_os = "linux")]
-                                        let _ = std::process::Command::new("xdg-open")
-                                            .arg(&extract_dir)
-                                            .spawn();
-
-                                        self.status_message =
-                                            format!("Opened folder for {}", selected_clone);
-                                    }
-                                }
+                        let extract_dir = self.archive_temp_dir("open");
+                        match self.stream_entry_to_dir(&selected_clone, &extract_dir) {
+                            Ok(_) => {
+                                let _ = open_with_default_app(&extract_dir);
+                                self.status_message = format!("Opened folder for {}", selected_clone);
+                            }
+                            Err(e) => {
+                                self.status_message = format!("Open folder error: {}", e);
                             }
                         }
                     }
@@ -398,9 +637,22 @@ impl eframe::App for RpaEditor {
                         }
 
                         if ui.button("⏹ Stop").clicked() {
-                            self.audio_player.stop();
+                            self.stop_audio();
                         }
 
+                        egui::ComboBox::from_id_salt("audio_loop_mode")
+                            .selected_text(
+                                AUDIO_LOOP_MODES
+                                    .iter()
+                                    .find(|(mode, _)| *mode == self.audio_loop_mode)
+                                    .map_or("", |(_, label)| *label),
+                            )
+                            .show_ui(ui, |ui| {
+                                for (mode, label) in AUDIO_LOOP_MODES {
+                                    ui.selectable_value(&mut self.audio_loop_mode, mode.to_string(), *label);
+                                }
+                            });
+
                         let mut volume = self.audio_player.get_volume();
                         if ui
                             .add(egui::Slider::new(&mut volume, 0.0..=1.0).text("🔊 Volume"))
@@ -409,28 +661,50 @@ impl eframe::App for RpaEditor {
                             self.audio_player.set_volume(volume);
                         }
 
+                        if let Some(playing) = &self.playing_audio {
+                            ui.label(format!("🎵 {}", playing));
+                        }
+
                         if self.audio_player.is_finished() {
-                            self.is_playing = false;
+                            self.on_audio_finished();
+                        } else if self.audio_player.is_paused() {
+                            ui.label("⏸ En pause");
                         } else {
                             ui.label("🎵 En cours de lecture...");
                         }
 
                         if let Some(dur) = self.audio_player.total_duration() {
                             let pos = self.audio_player.playback_position();
-
-                            let mut percent = pos.as_secs_f32() / dur.as_secs_f32();
-                            percent = percent.clamp(0.0, 1.0);
-
-                            ui.add_enabled_ui(false, |ui|{
-                                ui.add(
-                                    egui::Slider::new(&mut percent, 0.0..=1.0)
-                                        .text(format!(
-                                            "{:.0}/{:.0} sec",
-                                            pos.as_secs_f32(),
-                                            dur.as_secs_f32()
-                                        )),
-                                ); 
-                            });
+                            let total = dur.as_secs_f32();
+                            let mut seconds = self
+                                .audio_player
+                                .seek_preview
+                                .unwrap_or_else(|| pos.as_secs_f32().min(total));
+
+                            let label = format!(
+                                "{}/{}",
+                                format_playback_time(seconds),
+                                format_playback_time(total)
+                            );
+                            let response = ui.add(
+                                egui::Slider::new(&mut seconds, 0.0..=total)
+                                    .show_value(false)
+                                    .text(label),
+                            );
+                            if response.dragged() {
+                                self.audio_player.seek_preview = Some(seconds);
+                            }
+                            if response.drag_stopped() || (response.changed() && !response.dragged()) {
+                                self.audio_player.seek_preview = None;
+                                if let Err(e) = self.audio_player.seek(Duration::from_secs_f32(seconds)) {
+                                    self.status_message = format!("Audio seek error: {}", e);
+                                }
+                            }
+                        } else {
+                            ui.label(format!(
+                                "⏱ {}",
+                                format_playback_time(self.audio_player.playback_position().as_secs_f32())
+                            ));
                         }
                     });
                 }
@@ -439,124 +713,55 @@ impl eframe::App for RpaEditor {
                     player.ui(ui, player.size.div(2.5));
                 }
 
-                ui.separator();
-
-                egui::ScrollArea::both()
-                    .auto_shrink([false, false])
-                    .show(ui, |ui| {
-                        if let Some(ref img) = self.preview_image {
-                            let texture =
-                                ctx.load_texture("preview", img.clone(), Default::default());
-                            let max_size = ui.available_size();
-                            let img_size = egui::Vec2::new(img.width() as f32, img.height() as f32);
-
-                            let base_scale = (max_size.x / img_size.x)
-                                .min(max_size.y / img_size.y)
-                                .min(1.0);
-                            let display_size = img_size * base_scale * self.image_zoom;
-
-                            ui.add(
-                                egui::Image::new(&texture)
-                                    .max_size(display_size)
-                                    .maintain_aspect_ratio(true),
-                            );
-
-                            ui.separator();
-                            ui.label(format!(
-                                "Original: {}×{} | Display: {:.0}×{:.0} | Zoom: {:.1}%",
-                                img.width(),
-                                img.height(),
-                                display_size.x,
-                                display_size.y,
-                                base_scale * self.image_zoom * 100.0
-                            ));
-                        } else if let Some(ref text) = self.preview_text {
-                            let lines: Vec<&str> = text.lines().collect();
-                            for line in lines {
-                                if line.starts_with('#') {
-                                    ui.colored_label(egui::Color32::LIGHT_GREEN, line);
-                                } else if line.contains("label")
-                                    || line.contains("menu")
-                                    || line.contains("scene")
-                                {
-                                    ui.colored_label(egui::Color32::LIGHT_BLUE, line);
-                                } else if line.contains("Format:")
-                                    || line.contains("Size:")
-                                    || line.starts_with("🎬")
-                                    || line.starts_with("🎵")
-                                {
-                                    ui.colored_label(egui::Color32::LIGHT_YELLOW, line);
-                                } else if line.starts_with("✅") || line.starts_with("📊") {
-                                    ui.colored_label(egui::Color32::from_rgb(0, 255, 255), line);
-                                } else {
-                                    ui.label(line);
-                                }
-                            }
-                        } else if let Some(ref data) = self.preview_data {
-                            ui.horizontal(|ui| {
-                                ui.label("📊 File size:");
-                                ui.strong(Self::format_bytes(data.len() as u64));
-
-                                ui.separator();
-
-                                if ui.button("⬆️ Top").clicked() {
-                                    self.hex_view_offset = 0;
-                                }
-                                if ui.button("⬇️ Next").clicked() {
-                                    self.hex_view_offset = (self.hex_view_offset + 512)
-                                        .min(data.len().saturating_sub(512));
-                                }
-                                if ui.button("⬆️ Prev").clicked() {
-                                    self.hex_view_offset = self.hex_view_offset.saturating_sub(512);
-                                }
-                            });
+                egui::CollapsingHeader::new("ℹ Properties")
+                    .id_salt("entry_properties_header")
+                    .show(ui, |ui| self.show_entry_properties(ui, selected));
+                egui::CollapsingHeader::new("🏷 Tags & Notes")
+                    .id_salt("entry_annotations_header")
+                    .show(ui, |ui| self.show_entry_annotations(ui, selected));
 
-                            ui.separator();
+                ui.separator();
 
-                            ui.heading("🔍 Hex Preview");
-
-                            let start_offset = self.hex_view_offset;
-                            let preview_bytes = std::cmp::min(512, data.len() - start_offset);
-
-                            if preview_bytes > 0 {
-                                let hex_dump = data[start_offset..start_offset + preview_bytes]
-                                    .chunks(16)
-                                    .enumerate()
-                                    .map(|(i, chunk)| {
-                                        let addr = start_offset + i * 16;
-                                        let hex: String = chunk
-                                            .iter()
-                                            .map(|b| format!("{:02X}", b))
-                                            .collect::<Vec<_>>()
-                                            .join(" ");
-
-                                        let ascii: String = chunk
-                                            .iter()
-                                            .map(|&b| {
-                                                if b.is_ascii_graphic() || b == b' ' {
-                                                    b as char
-                                                } else {
-                                                    '.'
-                                                }
-                                            })
-                                            .collect();
-
-                                        format!("{:08X}: {:<48} {}", addr, hex, ascii)
-                                    })
-                                    .collect::<Vec<_>>()
-                                    .join("\n");
-
-                                ui.code(&hex_dump);
-
-                                if start_offset + preview_bytes < data.len() {
-                                    ui.label(format!(
-                                        "... and {} more bytes",
-                                        data.len() - start_offset - preview_bytes
-                                    ));
+                // The image view pans and zooms itself, so it stays outside the scroll area.
+                if self.preview_image.is_some() && self.hex_editing_file.as_deref() != Some(selected.as_str()) {
+                    self.show_image_preview(ui);
+                } else {
+                    egui::ScrollArea::both()
+                        .auto_shrink([false, false])
+                        .show(ui, |ui| {
+                            if self.hex_editing_file.as_deref() == Some(selected.as_str()) {
+                                self.show_hex_editor(ui);
+                            } else if self.editing_file.as_deref() == Some(selected.as_str()) {
+                                self.show_text_editor(ui);
+                            } else if let Some(ref job) = self.preview_highlight {
+                                ui.label(job.clone());
+                            } else if let Some(ref text) = self.preview_text {
+                                let lines: Vec<&str> = text.lines().collect();
+                                for line in lines {
+                                    if line.starts_with('#') {
+                                        ui.colored_label(egui::Color32::LIGHT_GREEN, line);
+                                    } else if line.contains("label")
+                                        || line.contains("menu")
+                                        || line.contains("scene")
+                                    {
+                                        ui.colored_label(egui::Color32::LIGHT_BLUE, line);
+                                    } else if line.contains("Format:")
+                                        || line.contains("Size:")
+                                        || line.starts_with("🎬")
+                                        || line.starts_with("🎵")
+                                    {
+                                        ui.colored_label(egui::Color32::LIGHT_YELLOW, line);
+                                    } else if line.starts_with("✅") || line.starts_with("📊") {
+                                        ui.colored_label(egui::Color32::from_rgb(0, 255, 255), line);
+                                    } else {
+                                        ui.label(line);
+                                    }
                                 }
+                            } else if self.preview_data.is_some() || self.get_preview_mode(selected) == "hex" {
+                                self.show_hex_view(ui, selected);
                             }
-                        }
-                    });
+                        });
+                }
             } else {
                 ui.centered_and_justified(|ui| {
                     ui.vertical_centered(|ui| {
@@ -591,16 +796,16 @@ impl eframe::App for RpaEditor {
                     ui.horizontal(|ui| {
                         ui.label("📁 File:");
                         ui.text_edit_singleline(&mut self.add_file_path);
-                        if ui.button("Browse...").clicked() {
-                            if let Some(path) = rfd::FileDialog::new().pick_file() {
-                                self.add_file_path = path.to_string_lossy().to_string();
-                                if self.add_file_name.is_empty() {
-                                    self.add_file_name = path
-                                        .file_name()
-                                        .unwrap_or_default()
-                                        .to_string_lossy()
-                                        .to_string();
-                                }
+                        if ui.button("Browse...").clicked()
+                            && let Some(path) = rfd::FileDialog::new().pick_file()
+                        {
+                            self.add_file_path = path.to_string_lossy().to_string();
+                            if self.add_file_name.is_empty() {
+                                self.add_file_name = path
+                                    .file_name()
+                                    .unwrap_or_default()
+                                    .to_string_lossy()
+                                    .to_string();
                             }
                         }
                     });
@@ -610,23 +815,34 @@ impl eframe::App for RpaEditor {
                         ui.text_edit_singleline(&mut self.add_file_name);
                     });
 
+                    ui.checkbox(
+                        &mut self.subset_fonts_on_add,
+                        "Subset fonts to characters used by scripts",
+                    );
+
                     ui.separator();
 
                     ui.horizontal(|ui| {
-                        if ui.button("✅ Add").clicked() {
-                            if !self.add_file_path.is_empty() && !self.add_file_name.is_empty() {
-                                let file_path = self.add_file_path.clone();
-                                let file_name = self.add_file_name.clone();
-
-                                if let Err(e) = self.add_file(&file_path, &file_name) {
-                                    self.status_message = format!("Add Error: {}", e);
-                                } else {
-                                    self.show_add_dialog = false;
-                                    self.add_file_path.clear();
-                                    self.add_file_name.clear();
-                                }
+                        if ui.button("📁 Add Folder...").clicked()
+                            && let Some(folder) = rfd::FileDialog::new().pick_folder()
+                        {
+                            match self.open_folder_add(&folder) {
+                                Ok(_) => self.show_add_dialog = false,
+                                Err(e) => self.add_error(format!("Add Error: {}", e)),
                             }
                         }
+                        if ui.button("✅ Add").clicked()
+                            && !self.add_file_path.is_empty()
+                            && !self.add_file_name.is_empty()
+                        {
+                            let file_path = self.add_file_path.clone();
+                            let file_name = self.add_file_name.clone();
+
+                            self.queue_add(&file_path, &file_name);
+                            self.show_add_dialog = false;
+                            self.add_file_path.clear();
+                            self.add_file_name.clear();
+                        }
 
                         if ui.button("❌ Cancel").clicked() {
                             self.show_add_dialog = false;
@@ -637,74 +853,840 @@ impl eframe::App for RpaEditor {
                 });
         }
 
-        if self.show_batch_replace_dialog {
-            egui::Window::new("📁 Batch Replace")
+        if let Some((archive_name, source_path, message)) = self.replace_type_mismatch.clone() {
+            egui::Window::new("⚠️ Different File Type")
                 .collapsible(false)
                 .resizable(false)
                 .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
                 .show(ctx, |ui| {
-                    ui.set_width(500.0);
-
-                    ui.label("Replace multiple files from a folder");
-                    ui.label("Files with matching names will be replaced automatically");
-
-                    ui.separator();
-
-                    ui.horizontal(|ui| {
-                        ui.label("📂 Folder:");
-                        ui.text_edit_singleline(&mut self.batch_replace_folder);
-                        if ui.button("Browse...").clicked() {
-                            if let Some(folder) = rfd::FileDialog::new().pick_folder() {
-                                self.batch_replace_folder = folder.to_string_lossy().to_string();
-                            }
-                        }
-                    });
-
+                    ui.label(&message);
+                    ui.label(egui::RichText::new(&source_path).weak());
+                    ui.label("The game may fail to load it if its code expects the original format.");
                     ui.separator();
-
                     ui.horizontal(|ui| {
-                        if ui.button("🔄 Replace All").clicked() {
-                            if !self.batch_replace_folder.is_empty() {
-                                self.batch_replace_to_execute =
-                                    Some(self.batch_replace_folder.clone());
-                            }
+                        if ui.button("🔄 Replace Anyway").clicked() {
+                            self.replace_type_mismatch = None;
+                            self.apply_replacement(&archive_name, &source_path, true);
                         }
-
                         if ui.button("❌ Cancel").clicked() {
-                            self.show_batch_replace_dialog = false;
-                            self.batch_replace_folder.clear();
+                            self.replace_type_mismatch = None;
                         }
                     });
                 });
         }
 
-        if self.show_statistics_dialog {
-            egui::Window::new("📊 Archive Statistics")
+        if self.folder_add.is_some() {
+            egui::Window::new("📁 Add Folder")
                 .collapsible(false)
                 .resizable(true)
-                .default_size([400.0, 500.0])
-                .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+                .default_width(550.0)
                 .show(ctx, |ui| {
-                    egui::ScrollArea::vertical().show(ui, |ui| {
-                        let stats = self.get_archive_statistics();
-                        for line in stats.lines() {
-                            if line.starts_with("📊") || line.starts_with("═") {
-                                ui.heading(line);
-                            } else if line.contains(':') {
-                                ui.label(line);
-                            } else {
-                                ui.label(line);
-                            }
-                        }
-                    });
+                    self.show_folder_add_dialog(ui);
+                });
+        }
 
-                    ui.separator();
-                    if ui.button("❌ Close").clicked() {
+        if let Some(conflict) = self.add_conflict.clone() {
+            egui::Window::new("⚠️ File Already Exists")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+                .show(ctx, |ui| {
+                    ui.label(format!("{} is already in the archive.", conflict.archive_name));
+                    if let Some(entry) = self.indexes.get(&conflict.archive_name) {
+                        ui.label(format!(
+                            "Existing: {}  →  New: {}",
+                            Self::format_bytes(entry.length),
+                            Self::format_bytes(conflict.size())
+                        ));
+                    }
+
+                    if !self.pending_adds.is_empty() {
+                        ui.checkbox(
+                            &mut self.conflict_apply_to_all,
+                            format!("Apply to all ({} more files queued)", self.pending_adds.len()),
+                        );
+                    }
+
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        if ui.button("🔄 Overwrite").clicked() {
+                            self.resolve_add_conflict("overwrite");
+                        }
+                        if ui.button("📑 Keep Both").clicked() {
+                            self.resolve_add_conflict("rename");
+                        }
+                        if ui.button("⏭️ Skip").clicked() {
+                            self.resolve_add_conflict("skip");
+                        }
+                        if ui.button("❌ Cancel All").clicked() {
+                            self.add_conflict = None;
+                            self.pending_adds.clear();
+                            self.save_after_merge = false;
+                        }
+                    });
+                });
+        }
+
+        if self.show_batch_replace_dialog {
+            egui::Window::new("📁 Batch Replace")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+                .show(ctx, |ui| {
+                    self.show_batch_replace_dialog(ui);
+                });
+        }
+
+        if self.show_statistics_dialog {
+            egui::Window::new("📊 Archive Statistics")
+                .collapsible(false)
+                .resizable(true)
+                .default_size([400.0, 500.0])
+                .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+                .show(ctx, |ui| {
+                    egui::ScrollArea::vertical().show(ui, |ui| {
+                        let stats = self.get_archive_statistics();
+                        for line in stats.lines() {
+                            if line.starts_with("📊") || line.starts_with("═") {
+                                ui.heading(line);
+                            } else {
+                                ui.label(line);
+                            }
+                        }
+                    });
+
+                    ui.separator();
+                    if ui.button("❌ Close").clicked() {
                         self.show_statistics_dialog = false;
                     }
                 });
         }
 
+        if self.show_header_dialog {
+            egui::Window::new("🔑 Header & Key")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+                .show(ctx, |ui| {
+                    if self.archive_path.is_none() {
+                        ui.label("No archive loaded");
+                    } else {
+                        egui::Grid::new("header_grid").num_columns(2).show(ui, |ui| {
+                            ui.label("Header:");
+                            ui.monospace(&self.header_line);
+                            ui.end_row();
+                            ui.label("Version:");
+                            ui.label(format!(
+                                "{} (RPA-{:.1} layout)",
+                                self.header_format.magic, self.version
+                            ));
+                            ui.end_row();
+                            ui.label("Index offset:");
+                            ui.monospace(format!("0x{:x} ({})", self.index_offset, self.index_offset));
+                            ui.end_row();
+                            ui.label("Subkeys:");
+                            if self.header_subkeys.is_empty() {
+                                ui.label("none");
+                            } else {
+                                ui.monospace(
+                                    self.header_subkeys
+                                        .iter()
+                                        .map(|k| format!("{:08x}", k))
+                                        .collect::<Vec<_>>()
+                                        .join(" ^ "),
+                                );
+                            }
+                            ui.end_row();
+                            ui.label("Effective key:");
+                            ui.monospace(format!("{:08x}", self.key));
+                            ui.end_row();
+                        });
+
+                        ui.separator();
+                        if !self.header_format.is_keyed() {
+                            ui.label("RPA-2.0 archives are not obfuscated; use Save As to pick a keyed format.");
+                        } else {
+                            ui.horizontal(|ui| {
+                                ui.label("Key for next save:");
+                                ui.add(
+                                    egui::TextEdit::singleline(&mut self.custom_key)
+                                        .desired_width(100.0)
+                                        .font(egui::TextStyle::Monospace),
+                                );
+                                if ui.button("✅ Apply").clicked() {
+                                    match u32::from_str_radix(self.custom_key.trim(), 16) {
+                                        Ok(key) if key != self.key => {
+                                            if !self.blocked_by_read_only() {
+                                                self.key = key;
+                                                self.set_modified(true);
+                                                self.add_toast(format!("Key set to {:08x}", key));
+                                            }
+                                        }
+                                        Ok(_) => {}
+                                        Err(_) => {
+                                            self.add_warning("Invalid key: expected 8 hex digits")
+                                        }
+                                    }
+                                }
+                            });
+                        }
+                    }
+
+                    ui.separator();
+                    if ui.button("❌ Close").clicked() {
+                        self.show_header_dialog = false;
+                    }
+                });
+        }
+
+        if self.show_orphans_dialog {
+            egui::Window::new("🧩 Orphaned Data")
+                .collapsible(false)
+                .resizable(true)
+                .default_size([420.0, 360.0])
+                .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+                .show(ctx, |ui| {
+                    if self.orphaned_ranges.is_empty() {
+                        ui.label("Every byte of the data area belongs to an entry.");
+                    } else {
+                        let total: u64 = self.orphaned_ranges.iter().map(|(_, length)| length).sum();
+                        ui.label(format!(
+                            "{} unreferenced ranges, {} in total",
+                            self.orphaned_ranges.len(),
+                            Self::format_bytes(total)
+                        ));
+                        let mut carve = None;
+                        egui::ScrollArea::vertical().max_height(220.0).show(ui, |ui| {
+                            for &(offset, length) in &self.orphaned_ranges {
+                                ui.horizontal(|ui| {
+                                    ui.monospace(format!(
+                                        "0x{:010x}  {}",
+                                        offset,
+                                        Self::format_bytes(length)
+                                    ));
+                                    if ui.small_button("📤").on_hover_text("Save this range as a file").clicked() {
+                                        carve = Some((offset, length));
+                                    }
+                                });
+                            }
+                        });
+                        if let Some((offset, length)) = carve {
+                            match self.carve_orphan_to_file(offset, length) {
+                                Ok(Some(path)) => self.add_toast(format!("Saved {}", path)),
+                                Ok(None) => {}
+                                Err(e) => self.add_error(format!("Carve error: {}", e)),
+                            }
+                        }
+                        ui.label("Orphaned bytes are discarded the next time the archive is saved.");
+
+                        ui.separator();
+                        ui.horizontal(|ui| {
+                            if ui.button("📤 Carve to folder...").clicked()
+                                && let Some(folder) = rfd::FileDialog::new().pick_folder()
+                            {
+                                match self.carve_orphans_to_folder(&folder) {
+                                    Ok(count) => self.add_toast(format!("Carved {} ranges", count)),
+                                    Err(e) => self.add_error(format!("Carve error: {}", e)),
+                                }
+                            }
+                            if ui.button("📥 Keep as entries").clicked() {
+                                match self.carve_orphans_into_archive() {
+                                    Ok(count) => self.add_toast(format!(
+                                        "Added {} orphaned ranges under orphaned/",
+                                        count
+                                    )),
+                                    Err(e) => self.add_error(format!("Carve error: {}", e)),
+                                }
+                            }
+                            if ui
+                                .add_enabled(!self.modified, egui::Button::new("🧹 Drop (Compact)"))
+                                .on_hover_text("Rewrite the archive now without these ranges")
+                                .on_disabled_hover_text("Save or discard the pending changes first")
+                                .clicked()
+                            {
+                                match self.compact_archive() {
+                                    Ok(reclaimed) => self.add_toast(format!(
+                                        "Compacted, {} reclaimed",
+                                        Self::format_bytes(reclaimed)
+                                    )),
+                                    Err(e) => self.add_error(format!("Compact error: {}", e)),
+                                }
+                            }
+                        });
+                    }
+
+                    ui.separator();
+                    if ui.button("❌ Close").clicked() {
+                        self.show_orphans_dialog = false;
+                    }
+                });
+        }
+
+        if self.show_jobs_dialog {
+            egui::Window::new("⚙ Job Queue")
+                .collapsible(false)
+                .resizable(true)
+                .default_size([520.0, 440.0])
+                .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+                .show(ctx, |ui| {
+                    ui.horizontal(|ui| {
+                        egui::ComboBox::from_id_salt("new_job_kind")
+                            .selected_text(
+                                JOB_KINDS
+                                    .iter()
+                                    .find(|(kind, _)| *kind == self.new_job_kind)
+                                    .map_or("", |(_, label)| *label),
+                            )
+                            .show_ui(ui, |ui| {
+                                for (kind, label) in JOB_KINDS {
+                                    ui.selectable_value(&mut self.new_job_kind, kind.to_string(), *label);
+                                }
+                            });
+                        egui::ComboBox::from_id_salt("new_job_scope")
+                            .selected_text(self.new_job_scope.as_str())
+                            .show_ui(ui, |ui| {
+                                for scope in ["visible", "selected", "all"] {
+                                    ui.selectable_value(&mut self.new_job_scope, scope.to_string(), scope);
+                                }
+                            });
+                        if self.new_job_kind == "transcode" {
+                            ui.label("to");
+                            ui.add(egui::TextEdit::singleline(&mut self.new_job_format).desired_width(50.0))
+                                .on_hover_text("png, jpg and webp are converted directly, other formats need ffmpeg");
+                        }
+
+                        if ui.button("➕ Queue").clicked() {
+                            let kind = self.new_job_kind.clone();
+                            let read_only = READ_ONLY_JOB_KINDS.contains(&kind.as_str());
+                            let output = if read_only {
+                                None
+                            } else {
+                                rfd::FileDialog::new().pick_folder()
+                            };
+                            if read_only || output.is_some() {
+                                let count = self.queue_job(&kind, output);
+                                self.status_message = format!("Queued {} job for {} entries", kind, count);
+                            }
+                        }
+                    });
+
+                    ui.horizontal(|ui| {
+                        ui.radio_value(&mut self.job_run_mode, "sequential".to_string(), "One at a time");
+                        ui.radio_value(&mut self.job_run_mode, "parallel".to_string(), "In parallel");
+                    });
+
+                    let (processed, total) = self.job_queue_progress();
+                    ui.add(
+                        egui::ProgressBar::new(if total == 0 { 0.0 } else { processed as f32 / total as f32 })
+                            .text(format!("{}/{} entries", processed, total)),
+                    );
+
+                    ui.separator();
+                    let mut move_job = None;
+                    let mut remove_job = None;
+                    egui::ScrollArea::vertical().max_height(260.0).show(ui, |ui| {
+                        for (index, job) in self.jobs.iter().enumerate() {
+                            ui.horizontal(|ui| {
+                                let icon = match job.status.as_str() {
+                                    "running" => "⏳",
+                                    "done" => "✅",
+                                    "failed" => "⚠",
+                                    "cancelled" => "⏹",
+                                    _ => "🕒",
+                                };
+                                ui.label(format!(
+                                    "{} #{} {} · {}/{}",
+                                    icon,
+                                    job.id,
+                                    job.kind,
+                                    job.processed,
+                                    job.entries.len()
+                                ));
+                                if job.status == "queued" {
+                                    if ui.small_button("⬆").clicked() {
+                                        move_job = Some((index, true));
+                                    }
+                                    if ui.small_button("⬇").clicked() {
+                                        move_job = Some((index, false));
+                                    }
+                                }
+                                if job.status == "running" {
+                                    if ui.small_button("⏹").on_hover_text("Cancel").clicked() {
+                                        job.cancel.store(true, Ordering::Relaxed);
+                                    }
+                                } else if ui.small_button("✖").clicked() {
+                                    remove_job = Some(index);
+                                }
+                            });
+                            if job.status == "running" && !job.entries.is_empty() {
+                                ui.add(
+                                    egui::ProgressBar::new(job.processed as f32 / job.entries.len() as f32)
+                                        .desired_height(6.0),
+                                );
+                            }
+                            if !job.message.is_empty() {
+                                ui.label(egui::RichText::new(&job.message).small().weak());
+                            }
+                        }
+                    });
+                    if let Some((index, up)) = move_job {
+                        self.move_job(index, up);
+                    }
+                    if let Some(index) = remove_job {
+                        self.jobs.remove(index);
+                    }
+
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        if self.jobs_running {
+                            if ui.button("⏸ Pause").on_hover_text("Running jobs finish, queued ones wait").clicked() {
+                                self.jobs_running = false;
+                            }
+                        } else if ui.button("▶ Run Queue").clicked() {
+                            self.jobs_running = true;
+                        }
+                        if ui
+                            .add_enabled(self.active_job_count() > 0, egui::Button::new("⏹ Cancel All"))
+                            .clicked()
+                        {
+                            self.cancel_all_jobs();
+                        }
+                        if ui.button("🧹 Clear Finished").clicked() {
+                            self.jobs.retain(|job| job.status == "queued" || job.status == "running");
+                        }
+                        if ui.button("❌ Close").clicked() {
+                            self.show_jobs_dialog = false;
+                        }
+                    });
+                });
+        }
+
+        if self.pending_extract.is_some() {
+            egui::Window::new("📤 Folder Not Empty")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+                .show(ctx, |ui| {
+                    self.show_extract_conflict_dialog(ui);
+                });
+        }
+
+        if self.batch_replace_report.is_some() {
+            egui::Window::new("📁 Batch Replace Report")
+                .collapsible(false)
+                .resizable(true)
+                .default_width(500.0)
+                .show(ctx, |ui| {
+                    self.show_batch_replace_report(ui);
+                });
+        }
+
+        if self.extract_report.is_some() {
+            egui::Window::new("📤 Extraction Report")
+                .collapsible(true)
+                .resizable(true)
+                .default_width(600.0)
+                .show(ctx, |ui| {
+                    self.show_extract_report(ui);
+                });
+        }
+
+        if self.show_game_folder_dialog {
+            egui::Window::new("🎮 Game Folder")
+                .collapsible(false)
+                .resizable(true)
+                .show(ctx, |ui| {
+                    self.show_game_folder(ui);
+                });
+        }
+
+        if self.archive_diff.is_some() {
+            egui::Window::new("🔀 Archive Diff")
+                .collapsible(true)
+                .resizable(true)
+                .show(ctx, |ui| {
+                    self.show_archive_diff(ui);
+                });
+        }
+
+        if self.entry_diff.is_some() {
+            egui::Window::new("📜 Script Diff")
+                .collapsible(false)
+                .resizable(true)
+                .default_width(700.0)
+                .show(ctx, |ui| {
+                    self.show_entry_text_diff(ui);
+                });
+        }
+
+        if self.integrity_report.is_some() {
+            egui::Window::new("🩺 Archive Integrity")
+                .collapsible(true)
+                .resizable(true)
+                .default_width(650.0)
+                .show(ctx, |ui| {
+                    self.show_integrity_report(ui);
+                });
+        }
+
+        if self.recovery_offer.is_some() {
+            egui::Window::new("🩹 Archive Damaged")
+                .collapsible(false)
+                .resizable(false)
+                .default_width(450.0)
+                .show(ctx, |ui| {
+                    self.show_recovery_dialog(ui);
+                });
+        }
+
+        if self.archive_changed_on_disk {
+            egui::Window::new("⚠️ Archive Changed on Disk")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+                .show(ctx, |ui| {
+                    self.show_archive_changed_dialog(ui);
+                });
+        }
+
+        if self.archive_loading.is_some() {
+            egui::Window::new("📂 Opening Archive")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+                .show(ctx, |ui| {
+                    self.show_archive_loading(ui);
+                });
+        }
+
+        if self.autosave_offer.is_some() {
+            egui::Window::new("💾 Autosaved Changes Found")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+                .show(ctx, |ui| {
+                    self.show_autosave_dialog(ui);
+                });
+        }
+
+        if !self.crash_recoveries.is_empty() {
+            egui::Window::new("🛟 Recover Unsaved Changes")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+                .show(ctx, |ui| {
+                    self.show_crash_recovery_dialog(ui);
+                });
+        }
+
+        if self.show_notification_history {
+            egui::Window::new("🔔 Notifications")
+                .collapsible(false)
+                .resizable(true)
+                .show(ctx, |ui| {
+                    self.show_notification_history(ui);
+                });
+        }
+
+        if self.show_log_console {
+            egui::Window::new("📋 Log Console")
+                .collapsible(false)
+                .resizable(true)
+                .default_size([700.0, 460.0])
+                .show(ctx, |ui| {
+                    self.show_log_console(ui);
+                });
+        }
+
+        if self.show_watch_folder_dialog {
+            egui::Window::new("👁 Watch Folder")
+                .collapsible(false)
+                .resizable(false)
+                .show(ctx, |ui| {
+                    self.show_watch_folder_dialog(ui);
+                });
+        }
+
+        if self.show_external_edits && !self.external_edits.is_empty() {
+            egui::Window::new("✏️ External Edits")
+                .collapsible(false)
+                .resizable(true)
+                .default_width(500.0)
+                .show(ctx, |ui| {
+                    self.show_external_edits(ui);
+                });
+        }
+
+        if self.save_review.is_some() {
+            egui::Window::new("📝 Review Changes")
+                .collapsible(false)
+                .resizable(true)
+                .default_width(500.0)
+                .show(ctx, |ui| {
+                    self.show_save_review(ui);
+                });
+        }
+
+        if self.checksum_report.is_some() {
+            egui::Window::new("🔐 Checksum Verification")
+                .collapsible(true)
+                .resizable(true)
+                .show(ctx, |ui| {
+                    self.show_checksum_report(ui);
+                });
+        }
+
+        if self.duplicate_groups.is_some() {
+            egui::Window::new("♊ Duplicate Entries")
+                .collapsible(true)
+                .resizable(true)
+                .default_width(550.0)
+                .show(ctx, |ui| {
+                    self.show_duplicates(ui);
+                });
+        }
+
+        if self.show_save_selection_dialog {
+            egui::Window::new("💾 Save Selection as RPA")
+                .collapsible(false)
+                .resizable(false)
+                .show(ctx, |ui| {
+                    self.show_save_selection_dialog(ui);
+                });
+        }
+
+        if self.show_split_dialog {
+            egui::Window::new("✂️ Split Archive")
+                .collapsible(false)
+                .resizable(false)
+                .show(ctx, |ui| {
+                    self.show_split_dialog(ui);
+                });
+        }
+
+        if self.show_batch_rename_dialog {
+            egui::Window::new("✏️ Batch Rename")
+                .collapsible(false)
+                .resizable(true)
+                .default_width(550.0)
+                .show(ctx, |ui| {
+                    self.show_batch_rename_dialog(ui);
+                });
+        }
+
+        if self.remove_to_confirm.is_some() {
+            egui::Window::new("🗑️ Remove Entry")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+                .show(ctx, |ui| {
+                    self.show_remove_confirm(ui);
+                });
+        }
+
+        if self.show_discard_dialog {
+            egui::Window::new("↩ Discard All Changes")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+                .show(ctx, |ui| {
+                    let changed = self.changed_entries();
+                    ui.label(format!(
+                        "Discard changes to {} entries and go back to the archive on disk?",
+                        changed.len()
+                    ));
+                    egui::ScrollArea::vertical().max_height(200.0).show(ui, |ui| {
+                        for filename in &changed {
+                            ui.label(format!("{} {} ({})", self.file_icon(filename), filename, self.entry_state(filename)));
+                        }
+                    });
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        if ui.button("↩ Discard").clicked() {
+                            let count = self.revert_all();
+                            self.add_toast(format!("Discarded changes to {} entries", count));
+                            self.show_discard_dialog = false;
+                        }
+                        if ui.button("❌ Cancel").clicked() {
+                            self.show_discard_dialog = false;
+                        }
+                    });
+                });
+        }
+
+        if self.image_comparison.is_some() {
+            egui::Window::new("🔍 Compare Images")
+                .collapsible(false)
+                .resizable(true)
+                .default_size([900.0, 600.0])
+                .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+                .show(ctx, |ui| {
+                    self.show_image_comparison(ui);
+                });
+        }
+
+        if self.show_frame_export_dialog {
+            egui::Window::new("🖼 Export Frames")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+                .show(ctx, |ui| {
+                    let filename = self.frame_export_file.clone().unwrap_or_default();
+                    ui.label(format!("Video: {}", filename));
+                    if let Some(video) = self.video_info.get(&filename) {
+                        ui.label(egui::RichText::new(video.summary()).small().weak());
+                    }
+                    ui.separator();
+
+                    ui.horizontal(|ui| {
+                        ui.label("Every");
+                        ui.add(egui::DragValue::new(&mut self.frame_export_every).range(1..=1000));
+                        ui.label("frame(s)");
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("From:");
+                        ui.add(egui::TextEdit::singleline(&mut self.frame_export_start).desired_width(70.0).hint_text("start"));
+                        ui.label("To:");
+                        ui.add(egui::TextEdit::singleline(&mut self.frame_export_end).desired_width(70.0).hint_text("end"));
+                    });
+                    ui.label(
+                        egui::RichText::new("Times in seconds or m:ss; leave empty for the whole video")
+                            .small()
+                            .weak(),
+                    );
+
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        if ui.button("📁 Export to...").clicked()
+                            && let Some(parent) = rfd::FileDialog::new().pick_folder()
+                        {
+                            match self.export_video_frames(&filename, &parent) {
+                                Ok(()) => self.show_frame_export_dialog = false,
+                                Err(e) => self.add_error(format!("Frame export error: {}", e)),
+                            }
+                        }
+                        if ui.button("❌ Cancel").clicked() {
+                            self.show_frame_export_dialog = false;
+                        }
+                    });
+                });
+        }
+
+        if self.show_unused_assets_dialog {
+            egui::Window::new("🧹 Unused Assets")
+                .collapsible(false)
+                .resizable(true)
+                .default_size([460.0, 400.0])
+                .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+                .show(ctx, |ui| {
+                    if self.unused_assets.is_empty() {
+                        ui.label("Every image, audio and video entry is referenced by a script.");
+                    } else {
+                        let total: u64 = self
+                            .unused_assets
+                            .iter()
+                            .filter_map(|name| self.indexes.get(name))
+                            .map(|entry| entry.length)
+                            .sum();
+                        ui.label(format!(
+                            "{} entries not referenced by any script ({})",
+                            self.unused_assets.len(),
+                            Self::format_bytes(total)
+                        ));
+                        ui.colored_label(
+                            egui::Color32::LIGHT_YELLOW,
+                            "Names built at runtime (e.g. \"[mood].png\") can't be detected, check before saving.",
+                        );
+                        egui::ScrollArea::vertical().max_height(260.0).show(ui, |ui| {
+                            let mut keep = None;
+                            for (i, filename) in self.unused_assets.iter().enumerate() {
+                                ui.horizontal(|ui| {
+                                    if ui.small_button("✖").on_hover_text("Keep this entry").clicked() {
+                                        keep = Some(i);
+                                    }
+                                    ui.label(format!("{} {}", self.file_icon(filename), filename));
+                                });
+                            }
+                            if let Some(i) = keep {
+                                self.unused_assets.remove(i);
+                            }
+                        });
+
+                        ui.separator();
+                        if ui.button("🗑️ Remove all unused").clicked() {
+                            let count = self.remove_unused_assets();
+                            self.add_toast(format!("Marked {} unused assets for deletion", count));
+                            self.show_unused_assets_dialog = false;
+                        }
+                    }
+
+                    ui.separator();
+                    if ui.button("❌ Close").clicked() {
+                        self.show_unused_assets_dialog = false;
+                    }
+                });
+        }
+
+        if self.show_dialogue_import_dialog {
+            egui::Window::new("🌐 Import Dialogue Translation")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+                .show(ctx, |ui| {
+                    if let Some(path) = &self.dialogue_import_path {
+                        ui.label(format!("From: {}", path.display()));
+                    }
+                    ui.separator();
+                    ui.radio_value(
+                        &mut self.dialogue_import_mode,
+                        "patch".to_string(),
+                        "Replace strings in .rpy scripts",
+                    );
+                    ui.radio_value(
+                        &mut self.dialogue_import_mode,
+                        "tl".to_string(),
+                        "Generate tl/ language file",
+                    );
+                    if self.dialogue_import_mode == "tl" {
+                        ui.horizontal(|ui| {
+                            ui.label("Language:");
+                            ui.text_edit_singleline(&mut self.dialogue_import_language);
+                        });
+                    } else {
+                        ui.label("Compiled .rpyc entries are left as they are.");
+                    }
+
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        if ui.button("✅ Import").clicked()
+                            && let Some(path) = self.dialogue_import_path.clone()
+                        {
+                            let result = Self::read_dialogue_translations(&path).and_then(|lines| {
+                                if self.dialogue_import_mode == "tl" {
+                                    let language = self.dialogue_import_language.trim().to_string();
+                                    self.generate_tl_translations(&lines, &language)
+                                } else {
+                                    self.patch_dialogue_translations(&lines)
+                                }
+                            });
+                            match result {
+                                Ok((applied, missed)) => {
+                                    self.add_toast(format!(
+                                        "Imported {} translations ({} skipped)",
+                                        applied, missed
+                                    ));
+                                    self.show_dialogue_import_dialog = false;
+                                }
+                                Err(e) => self.add_error(format!("Import error: {}", e)),
+                            }
+                        }
+                        if ui.button("❌ Cancel").clicked() {
+                            self.show_dialogue_import_dialog = false;
+                        }
+                    });
+                });
+        }
+
         if self.show_backup_dialog {
             egui::Window::new("🔄 Backup History")
                 .collapsible(false)
@@ -712,16 +1694,30 @@ impl eframe::App for RpaEditor {
                 .default_size([500.0, 400.0])
                 .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
                 .show(ctx, |ui| {
-                    if self.backup_history.is_empty() {
+                    ui.horizontal(|ui| {
+                        ui.selectable_value(&mut self.backup_tab, "session".to_string(), "This session");
+                        ui.selectable_value(
+                            &mut self.backup_tab,
+                            "disk".to_string(),
+                            format!("On disk ({})", self.disk_backups.len()),
+                        );
+                    });
+                    ui.separator();
+
+                    if self.backup_tab == "disk" {
+                        self.show_disk_backups(ui);
+                    } else if self.backup_history.is_empty() {
                         ui.label("No backups available");
                     } else {
+                        let mut restore = None;
                         egui::ScrollArea::vertical().show(ui, |ui| {
-                            for backup in &self.backup_history {
+                            for (i, backup) in self.backup_history.iter().enumerate() {
                                 ui.horizontal(|ui| {
                                     ui.label(format!("📄 {}", backup.filename));
                                     ui.label(format!(
-                                        "({:.1} KB)",
-                                        backup.data.len() as f32 / 1024.0
+                                        "({} → {})",
+                                        Self::format_bytes(backup.original_size),
+                                        Self::format_bytes(backup.data.len() as u64)
                                     ));
                                     ui.label(format!(
                                         "📅 {}",
@@ -729,25 +1725,65 @@ impl eframe::App for RpaEditor {
                                     ));
 
                                     if ui.button("📤 Restore").clicked() {
-                                        let entry = RpaFileEntry {
-                                            offset: 0,
-                                            length: backup.data.len() as u64,
-                                            prefix: Vec::new(),
-                                            data: Some(backup.data.clone()),
-                                            modified: true,
-                                            to_delete: false,
-                                        };
-                                        self.indexes.insert(backup.filename.clone(), entry);
-                                        self.modified = true;
-                                        self.status_message =
-                                            format!("Restored backup of {}", backup.filename);
+                                        restore = Some(i);
                                     }
                                 });
                                 ui.separator();
                             }
                         });
+                        if let Some(i) = restore {
+                            let backup = self.backup_history[i].clone();
+                            if let Err(e) = backup
+                                .restore_data()
+                                .and_then(|data| self.restore_entry_from_backup(&backup.filename, data, backup.timestamp))
+                            {
+                                self.status_message = format!("Restore error: {}", e);
+                            }
+                        }
                     }
 
+                    ui.separator();
+                    egui::CollapsingHeader::new("⚙ Backup settings").show(ui, |ui| {
+                        ui.checkbox(&mut self.auto_backup, "Back up entries before overwriting them");
+                        ui.horizontal(|ui| {
+                            ui.checkbox(&mut self.persist_backups, "Also keep backups in");
+                            ui.monospace(self.backup_dir.display().to_string());
+                            if ui.button("📁 Change...").clicked()
+                                && let Some(folder) = rfd::FileDialog::new().pick_folder()
+                            {
+                                self.backup_dir = folder;
+                                self.scan_disk_backups();
+                            }
+                        });
+                        ui.add_enabled_ui(self.auto_backup, |ui| {
+                            ui.label("Create backups when:");
+                            for (trigger, label) in BACKUP_TRIGGERS {
+                                let mut enabled = self.backup_triggers.contains(*trigger);
+                                if ui.checkbox(&mut enabled, *label).changed() {
+                                    if enabled {
+                                        self.backup_triggers.insert(trigger.to_string());
+                                    } else {
+                                        self.backup_triggers.remove(*trigger);
+                                    }
+                                }
+                            }
+
+                            ui.label("Never back up:");
+                            ui.horizontal_wrapped(|ui| {
+                                for file_type in ["images", "videos", "audio", "scripts", "fonts", "files", "other"] {
+                                    let mut excluded = self.backup_excluded_types.contains(file_type);
+                                    if ui.checkbox(&mut excluded, file_type).changed() {
+                                        if excluded {
+                                            self.backup_excluded_types.insert(file_type.to_string());
+                                        } else {
+                                            self.backup_excluded_types.remove(file_type);
+                                        }
+                                    }
+                                }
+                            });
+                        });
+                    });
+
                     ui.separator();
                     ui.horizontal(|ui| {
                         if ui.button("🗑️ Clear All").clicked() {
@@ -761,6 +1797,201 @@ impl eframe::App for RpaEditor {
                 });
         }
 
+        if self.show_icon_legend {
+            egui::Window::new("🎨 Icon Legend")
+                .collapsible(false)
+                .resizable(true)
+                .default_size([420.0, 360.0])
+                .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+                .show(ctx, |ui| {
+                    self.show_icon_legend(ui);
+                    ui.separator();
+                    if ui.button("❌ Close").clicked() {
+                        self.show_icon_legend = false;
+                    }
+                });
+        }
+
+        if self.show_health_dialog {
+            egui::Window::new("🩺 Environment Report")
+                .collapsible(false)
+                .resizable(true)
+                .default_size([520.0, 320.0])
+                .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+                .show(ctx, |ui| {
+                    self.show_health_report(ui);
+                    if ui.button("❌ Close").clicked() {
+                        self.show_health_dialog = false;
+                    }
+                });
+        }
+
+        if self.show_preview_settings_dialog {
+            egui::Window::new("👁 Preview Settings")
+                .collapsible(false)
+                .resizable(true)
+                .default_size([400.0, 300.0])
+                .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+                .show(ctx, |ui| {
+                    ui.label("Override how files are previewed by extension");
+                    ui.separator();
+
+                    let mut association_to_remove: Option<String> = None;
+                    let mut associations: Vec<_> = self.preview_associations.iter().collect();
+                    associations.sort();
+
+                    if associations.is_empty() {
+                        ui.label("No custom associations");
+                    } else {
+                        egui::ScrollArea::vertical().show(ui, |ui| {
+                            for (ext, mode) in associations {
+                                ui.horizontal(|ui| {
+                                    ui.label(format!("{} → {}", ext, mode));
+                                    if ui.button("🗑️").clicked() {
+                                        association_to_remove = Some(ext.clone());
+                                    }
+                                });
+                            }
+                        });
+                    }
+
+                    if let Some(ext) = association_to_remove {
+                        self.preview_associations.remove(&ext);
+                    }
+
+                    ui.separator();
+
+                    ui.horizontal(|ui| {
+                        ui.label("Extension:");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut self.new_association_ext)
+                                .desired_width(80.0),
+                        );
+                        egui::ComboBox::from_id_salt("preview_mode")
+                            .selected_text(self.new_association_mode.clone())
+                            .show_ui(ui, |ui| {
+                                for mode in ["text", "hex", "image", "font", "media", "none"] {
+                                    ui.selectable_value(
+                                        &mut self.new_association_mode,
+                                        mode.to_string(),
+                                        mode,
+                                    );
+                                }
+                            });
+                        if ui.button("➕ Add").clicked() {
+                            let ext = self.new_association_ext.clone();
+                            let mode = self.new_association_mode.clone();
+                            self.set_preview_association(&ext, &mode);
+                            self.new_association_ext.clear();
+                        }
+                    });
+
+                    ui.separator();
+                    if ui.button("❌ Close").clicked() {
+                        self.show_preview_settings_dialog = false;
+                    }
+                });
+        }
+
+        if self.show_encoding_dialog {
+            egui::Window::new("🔤 Encoding Conversion")
+                .collapsible(false)
+                .resizable(true)
+                .default_size([600.0, 500.0])
+                .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+                .show(ctx, |ui| {
+                    ui.label("Convert scripts from a legacy encoding to UTF-8");
+                    ui.separator();
+
+                    if self.encoding_conversions.is_empty() {
+                        ui.label("All text and script files are already UTF-8");
+                    } else {
+                        egui::ScrollArea::vertical().max_height(380.0).show(ui, |ui| {
+                            for conversion in &mut self.encoding_conversions {
+                                ui.horizontal(|ui| {
+                                    ui.checkbox(&mut conversion.selected, "");
+                                    ui.label(format!("📜 {}", conversion.filename));
+                                    ui.label(
+                                        egui::RichText::new(conversion.encoding.name())
+                                            .small()
+                                            .weak(),
+                                    );
+                                });
+                                egui::CollapsingHeader::new("Preview")
+                                    .id_salt(&conversion.filename)
+                                    .show(ui, |ui| {
+                                        ui.code(&conversion.preview);
+                                    });
+                                ui.separator();
+                            }
+                        });
+                    }
+
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        if ui.button("🔍 Rescan").clicked() {
+                            self.scan_script_encodings();
+                        }
+                        if ui.button("✅ Convert Selected").clicked() {
+                            match self.apply_encoding_conversions() {
+                                Ok(count) => {
+                                    self.add_toast(format!("Converted {} scripts to UTF-8", count))
+                                }
+                                Err(e) => self.add_error(format!("Conversion error: {}", e)),
+                            }
+                            self.show_encoding_dialog = false;
+                        }
+                        if ui.button("❌ Close").clicked() {
+                            self.show_encoding_dialog = false;
+                        }
+                    });
+                });
+        }
+
+        if !self.dropped_files.is_empty() {
+            egui::Window::new("📥 Dropped Files")
+                .collapsible(false)
+                .resizable(true)
+                .default_size([600.0, 300.0])
+                .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+                .show(ctx, |ui| {
+                    ui.label("Choose where each dropped file goes in the archive");
+                    ui.separator();
+
+                    egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                        for file in &mut self.dropped_files {
+                            ui.horizontal(|ui| {
+                                ui.checkbox(&mut file.selected, "");
+                                let action = if self.indexes.contains_key(&file.archive_name) {
+                                    egui::RichText::new("Replace").color(egui::Color32::YELLOW)
+                                } else {
+                                    egui::RichText::new("Add").color(egui::Color32::LIGHT_GREEN)
+                                };
+                                ui.label(action);
+                                ui.text_edit_singleline(&mut file.archive_name);
+                            })
+                            .response
+                            .on_hover_text(&file.path);
+                        }
+                    });
+
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        if ui.button("✅ Apply").clicked() {
+                            let (done, failed) = self.apply_dropped_files();
+                            if failed > 0 {
+                                self.add_warning(format!("Imported {} files, {} failed", done, failed));
+                            } else {
+                                self.add_toast(format!("Imported {} dropped files", done));
+                            }
+                        }
+                        if ui.button("❌ Cancel").clicked() {
+                            self.dropped_files.clear();
+                        }
+                    });
+                });
+        }
+
         if self.show_dump_dialog {
             egui::Window::new("📤 Bulk Extract")
                 .collapsible(false)
@@ -772,22 +2003,43 @@ impl eframe::App for RpaEditor {
                     ui.heading("Extract files by category");
                     ui.separator();
 
+                    ui.horizontal(|ui| {
+                        ui.label("Layout:");
+                        for (layout, label) in EXTRACT_LAYOUTS {
+                            ui.radio_value(&mut self.extract_layout, layout.to_string(), *label);
+                        }
+                    });
+                    if self.extract_layout == "archive" {
+                        ui.weak("Entries keep their archive paths, ready to drop into a game/ folder.");
+                    }
+                    ui.horizontal(|ui| {
+                        ui.label("Pattern:");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut self.extract_pattern)
+                                .hint_text("e.g. images/ch3/*.png or *.ogg"),
+                        );
+                    });
+                    ui.checkbox(&mut self.extract_respect_filter, "Respect current search and filter");
+                    let narrowed = !self.extract_pattern.trim().is_empty() || self.extract_respect_filter;
+                    if narrowed {
+                        let include = self.extract_selector(self.extract_pattern.trim(), self.extract_respect_filter);
+                        let matching = self
+                            .indexes
+                            .iter()
+                            .filter(|(name, entry)| !entry.to_delete && include(name))
+                            .count();
+                        ui.label(format!("{} entries match; the buttons below only extract those", matching));
+                    }
+                    ui.separator();
+
                     let counts = self.count_files_by_type();
 
                     ui.horizontal(|ui| {
-                        if ui.button("🎯 Extract All Files").clicked() {
-                            if let Some(folder) = rfd::FileDialog::new().pick_folder() {
-                                match self.dump_all_files(&folder) {
-                                    Ok(count) => {
-                                        self.status_message = format!(
-                                            "Extracted {} files to organized folders",
-                                            count
-                                        )
-                                    }
-                                    Err(e) => self.status_message = format!("Extract Error: {}", e),
-                                }
-                                self.show_dump_dialog = false;
-                            }
+                        if ui.button("🎯 Extract All Files").clicked()
+                            && let Some(folder) = rfd::FileDialog::new().pick_folder()
+                        {
+                            self.request_bulk_extract("all", folder);
+                            self.show_dump_dialog = false;
                         }
                         ui.label(format!("({} total files)", self.indexes.len()));
                     });
@@ -811,22 +2063,10 @@ impl eframe::App for RpaEditor {
                                         file_type.to_uppercase()
                                     ))
                                     .clicked()
+                                    && let Some(folder) = rfd::FileDialog::new().pick_folder()
                                 {
-                                    if let Some(folder) = rfd::FileDialog::new().pick_folder() {
-                                        match self.dump_files_by_type(file_type, &folder) {
-                                            Ok(extracted) => {
-                                                self.status_message = format!(
-                                                    "Extracted {} {} files",
-                                                    extracted, file_type
-                                                )
-                                            }
-                                            Err(e) => {
-                                                self.status_message =
-                                                    format!("Extract Error: {}", e)
-                                            }
-                                        }
-                                        self.show_dump_dialog = false;
-                                    }
+                                    self.request_bulk_extract(file_type, folder);
+                                    self.show_dump_dialog = false;
                                 }
                                 ui.label(format!("({} files)", count));
                             });
@@ -841,12 +2081,51 @@ impl eframe::App for RpaEditor {
                 });
         }
     }
+
+}
+
+impl eframe::App for RpaEditor {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        // A panic would lose every unsaved edit; keep them for the next launch first.
+        if let Err(panic) = std::panic::catch_unwind(AssertUnwindSafe(|| self.show_frame(ctx))) {
+            self.write_crash_recovery();
+            std::panic::resume_unwind(panic);
+        }
+    }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        self.clean_temp_on_exit();
+        self.remove_session_autosaves();
+        if let Err(e) = self.save_settings() {
+            error!("❌ Could not save settings: {}", e);
+        }
+    }
 }
 
 fn main() -> Result<(), eframe::Error> {
+    log_console::init_logging();
+    crash_recovery::install_panic_hook();
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some("gallery") {
+        if args.len() != 4 {
+            eprintln!("Usage: {} gallery <archive.rpa> <output_dir>", args[0]);
+            std::process::exit(2);
+        }
+        match gallery::run_gallery(&args[2], std::path::Path::new(&args[3])) {
+            Ok(count) => {
+                println!("✅ Rendered {} thumbnails into {}", count, args[3]);
+                return Ok(());
+            }
+            Err(e) => {
+                eprintln!("❌ Gallery error: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
-            .with_inner_size([1500.0, 1000.0])
+            .with_inner_size(settings::saved_window_size())
             .with_title("🎮 RPA Archive Editor - Enhanced v2.0")
             .with_min_inner_size([900.0, 700.0]),
         centered: true,
@@ -861,40 +2140,63 @@ fn main() -> Result<(), eframe::Error> {
 }
 
 
+/// `m:ss` for the audio position slider.
+fn format_playback_time(seconds: f32) -> String {
+    let seconds = seconds.max(0.0) as u64;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
 pub struct AudioPlayer {
     sink: Sink,
-    _stream: OutputStream,
+    _stream: Option<OutputStream>,
     volume: f32,
-    started_at: Option<Instant>,
     duration: Option<Duration>,
+    /// Slider value while the user drags it; the seek happens on release.
+    seek_preview: Option<f32>,
+}
+
+impl Default for AudioPlayer {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl AudioPlayer {
     pub fn new() -> Self {
-        let (_stream, handle) =
-            OutputStream::try_default().expect("Erreur lors de la création du périphérique audio");
-        let sink = Sink::try_new(&handle).expect("Erreur lors de la création du Sink audio");
+        // Without an output device (e.g. headless runs) fall back to a sink that never plays.
+        let (sink, _stream) = match OutputStream::try_default() {
+            Ok((stream, handle)) => {
+                let sink = Sink::try_new(&handle).expect("Erreur lors de la création du Sink audio");
+                (sink, Some(stream))
+            }
+            Err(e) => {
+                warn!("Périphérique audio indisponible: {}", e);
+                (Sink::new_idle().0, None)
+            }
+        };
         sink.set_volume(1.0);
         Self {
             sink,
             _stream,
             volume: 1.0,
-            started_at: None,
             duration: None,
+            seek_preview: None,
         }
     }
 
-    pub fn play_bytes(&mut self, data: Vec<u8>) {
-        let cursor = Cursor::new(data.clone());
-        match Decoder::new(cursor) {
+    pub fn play_stream<R>(&mut self, reader: R)
+    where
+        R: std::io::Read + std::io::Seek + Send + Sync + 'static,
+    {
+        match Decoder::new(reader) {
             Ok(source) => {
                 self.duration = source.total_duration();
-                self.started_at = Some(Instant::now());
+                self.seek_preview = None;
                 self.sink.append(source);
                 self.sink.play();
             }
             Err(e) => {
-                eprintln!("Erreur de lecture audio: {}", e);
+                error!("Erreur de lecture audio: {}", e);
             }
         }
     }
@@ -920,20 +2222,37 @@ impl AudioPlayer {
         self.volume
     }
 
+    /// False when no output device was found and the idle sink is in use.
+    pub fn has_output(&self) -> bool {
+        self._stream.is_some()
+    }
+
     pub fn is_finished(&self) -> bool {
         self.sink.empty()
     }
 
+    /// Position inside the current track; the sink stops counting while paused.
     pub fn playback_position(&self) -> Duration {
-        if let Some(started) = self.started_at {
-            if self.sink.is_paused() {
-                Duration::ZERO
-            } else {
-                started.elapsed()
-            }
-        } else {
-            Duration::ZERO
+        self.sink.get_pos()
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.sink.is_paused()
+    }
+
+    /// Jumps to `position`; not every decoder supports seeking (e.g. mp3).
+    pub fn seek(&mut self, position: Duration) -> anyhow::Result<()> {
+        // The idle fallback sink never pulls samples, so a seek would wait forever.
+        if !self.has_output() {
+            return Err(anyhow::anyhow!("No audio output device"));
         }
+        let position = match self.duration {
+            Some(duration) => position.min(duration),
+            None => position,
+        };
+        self.sink
+            .try_seek(position)
+            .map_err(|e| anyhow::anyhow!("Seek failed: {}", e))
     }
 
     pub fn total_duration(&self) -> Option<Duration> {
@@ -0,0 +1,202 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+use crate::dialogue::{csv_field, parse_csv};
+use crate::rpa::{RpaEditor, RpaFileEntry};
+use crate::sha256::sha256_hex;
+
+const CSV_HEADER: &[&str] = &["name", "offset", "length", "type", "sha256", "modified"];
+
+/// One index entry as written to a manifest; `offset` is `None` for in-memory content.
+#[derive(Debug, Serialize)]
+pub struct ManifestEntry {
+    pub name: String,
+    pub offset: Option<u64>,
+    pub length: u64,
+    #[serde(rename = "type")]
+    pub file_type: String,
+    pub sha256: String,
+    pub modified: bool,
+}
+
+/// Batch operations read from an imported manifest.
+///
+/// JSON: `{"rename": {"old": "new"}, "delete": ["name"]}`.
+/// CSV: `action,name,target` rows with `rename` or `delete` actions.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ManifestOperations {
+    pub rename: BTreeMap<String, String>,
+    pub delete: Vec<String>,
+}
+
+impl ManifestOperations {
+    fn from_csv(text: &str) -> anyhow::Result<Self> {
+        let mut records = parse_csv(text).into_iter();
+        let header = records.next().ok_or_else(|| anyhow::anyhow!("Empty CSV"))?;
+        let column = |name: &str| header.iter().position(|h| h.trim() == name);
+        let action = column("action").ok_or_else(|| anyhow::anyhow!("CSV has no action column"))?;
+        let name = column("name").ok_or_else(|| anyhow::anyhow!("CSV has no name column"))?;
+        let target = column("target");
+
+        let mut operations = Self::default();
+        for (row, record) in records.enumerate() {
+            let get = |index: usize| record.get(index).map(|value| value.trim().to_string()).unwrap_or_default();
+            match get(action).as_str() {
+                "rename" => {
+                    let new_name = target.map(get).unwrap_or_default();
+                    if new_name.is_empty() {
+                        return Err(anyhow::anyhow!("Row {} renames {} without a target", row + 2, get(name)));
+                    }
+                    operations.rename.insert(get(name), new_name);
+                }
+                "delete" => operations.delete.push(get(name)),
+                "" => {}
+                other => return Err(anyhow::anyhow!("Row {}: unknown action {}", row + 2, other)),
+            }
+        }
+        Ok(operations)
+    }
+}
+
+/// What importing a manifest did.
+#[derive(Debug, Default)]
+pub struct ManifestReport {
+    pub renamed: usize,
+    pub deleted: usize,
+    pub skipped: Vec<String>,
+}
+
+impl RpaEditor {
+    pub(crate) fn manifest_entries(&self) -> anyhow::Result<Vec<ManifestEntry>> {
+        let mut names: Vec<&String> = self
+            .indexes
+            .iter()
+            .filter(|(_, entry)| !entry.to_delete)
+            .map(|(name, _)| name)
+            .collect();
+        names.sort();
+
+        let mut data = Vec::new();
+        names
+            .into_iter()
+            .map(|name| {
+                let entry = &self.indexes[name];
+                self.read_entry_into(name, &mut data)?;
+                Ok(ManifestEntry {
+                    name: name.clone(),
+                    offset: entry.archive_offset(),
+                    length: entry.length,
+                    file_type: self.get_file_type(name).to_string(),
+                    sha256: sha256_hex(&data),
+                    modified: self.entry_state(name) != "original",
+                })
+            })
+            .collect()
+    }
+
+    /// Writes the index as JSON, or as CSV when `path` ends in `.csv`.
+    pub(crate) fn export_manifest(&self, path: &Path) -> anyhow::Result<usize> {
+        let entries = self.manifest_entries()?;
+        let is_csv = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+        let mut out = BufWriter::new(File::create(path)?);
+        if is_csv {
+            writeln!(out, "{}", CSV_HEADER.join(","))?;
+            for entry in &entries {
+                writeln!(
+                    out,
+                    "{},{},{},{},{},{}",
+                    csv_field(&entry.name),
+                    entry.offset.map(|offset| offset.to_string()).unwrap_or_default(),
+                    entry.length,
+                    entry.file_type,
+                    entry.sha256,
+                    entry.modified
+                )?;
+            }
+        } else {
+            serde_json::to_writer_pretty(&mut out, &entries)?;
+        }
+        out.flush()?;
+        info!("📋 Wrote manifest of {} entries to {}", entries.len(), path.display());
+        Ok(entries.len())
+    }
+
+    /// Gives an entry a new name; a loaded entry leaves a deletion behind so it can be reverted.
+    pub(crate) fn rename_entry(&mut self, old_name: &str, new_name: &str) -> anyhow::Result<()> {
+        self.ensure_writable()?;
+        if self.indexes.get(new_name).is_some_and(|entry| !entry.to_delete) {
+            return Err(anyhow::anyhow!("{} already exists", new_name));
+        }
+        let loaded = self.loaded_names.contains(old_name);
+        let old = self
+            .indexes
+            .get_mut(old_name)
+            .filter(|entry| !entry.to_delete)
+            .ok_or_else(|| anyhow::anyhow!("{} isn't in the archive", old_name))?;
+
+        let renamed = RpaFileEntry {
+            segments: old.segments.clone(),
+            length: old.length,
+            data: old.data.take(),
+            merged: old.merged.take(),
+            modified: true,
+            to_delete: false,
+            provenance: format!("renamed from {}", old_name),
+        };
+        if loaded {
+            old.to_delete = true;
+        } else {
+            self.indexes.remove(old_name);
+        }
+        self.indexes.insert(new_name.to_string(), renamed);
+        if let Some(hash) = self.checksums.remove(old_name) {
+            self.checksums.insert(new_name.to_string(), hash);
+        }
+        if self.selected_file.as_deref() == Some(old_name) {
+            self.selected_file = Some(new_name.to_string());
+        }
+        self.set_modified(true);
+        Ok(())
+    }
+
+    /// Applies the renames and deletions listed in a JSON or CSV manifest.
+    pub(crate) fn import_manifest(&mut self, path: &Path) -> anyhow::Result<ManifestReport> {
+        self.ensure_writable()?;
+        let text = std::fs::read_to_string(path)?;
+        let operations = if path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("csv")) {
+            ManifestOperations::from_csv(&text)?
+        } else {
+            serde_json::from_str(&text)?
+        };
+
+        let mut report = ManifestReport::default();
+        for (old_name, new_name) in &operations.rename {
+            match self.rename_entry(old_name, new_name) {
+                Ok(()) => report.renamed += 1,
+                Err(e) => report.skipped.push(format!("rename {}: {}", old_name, e)),
+            }
+        }
+        for name in &operations.delete {
+            if self.indexes.get(name).is_some_and(|entry| !entry.to_delete) {
+                self.remove_file(name);
+                report.deleted += 1;
+            } else {
+                report.skipped.push(format!("delete {}: not in the archive", name));
+            }
+        }
+        for skipped in &report.skipped {
+            warn!("⚠️ Manifest: skipped {}", skipped);
+        }
+        self.status_message = format!(
+            "Manifest: {} renamed, {} deleted, {} skipped",
+            report.renamed,
+            report.deleted,
+            report.skipped.len()
+        );
+        Ok(report)
+    }
+}
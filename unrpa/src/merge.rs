@@ -0,0 +1,37 @@
+use std::path::Path;
+use tracing::info;
+use crate::archive_index::read_archive_index;
+use crate::rpa::{MergedSource, PendingAdd, RpaEditor};
+
+impl RpaEditor {
+    /// Queues every entry of another archive for import; name clashes go through the
+    /// usual conflict prompt, and Save As opens once the queue is done. Entries stay in
+    /// the source archive until the save copies them over.
+    pub(crate) fn merge_archive(&mut self, source_path: &str) -> anyhow::Result<usize> {
+        self.ensure_writable()?;
+        if self.archive_path.as_deref() == Some(source_path) {
+            return Err(anyhow::anyhow!("Can't merge an archive into itself"));
+        }
+        let source = read_archive_index(source_path, false)?;
+
+        let source_name = Path::new(source_path)
+            .file_name()
+            .map_or(source_path.to_string(), |name| name.to_string_lossy().to_string());
+        let mut entries: Vec<_> = source.entries.into_iter().collect();
+        entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+        let count = entries.len();
+        for (name, entry) in entries {
+            self.pending_adds.push_back(PendingAdd {
+                path: format!("{}:{}", source_name, name),
+                archive_name: name,
+                merged: Some(MergedSource {
+                    archive: source_path.to_string(),
+                    segments: entry.segments,
+                }),
+            });
+        }
+        self.save_after_merge = true;
+        info!("🔀 Merging {} entries from {}", count, source_path);
+        Ok(count)
+    }
+}
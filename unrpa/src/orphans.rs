@@ -0,0 +1,153 @@
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use crate::rpa::{RpaEditor, RpaFileEntry, RpaSegment};
+use crate::sniff::SNIFF_LENGTH;
+
+/// Largest gap after the header treated as padding rather than orphaned data.
+pub(crate) const HEADER_PADDING_MAX: u64 = 64;
+
+/// Guesses an extension for carved data from its leading bytes.
+fn sniff_extension(head: &[u8]) -> &'static str {
+    if head.starts_with(b"\x89PNG") {
+        "png"
+    } else if head.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        "jpg"
+    } else if head.starts_with(b"RIFF") && head.get(8..12) == Some(b"WEBP") {
+        "webp"
+    } else if head.starts_with(b"RIFF") && head.get(8..12) == Some(b"WAVE") {
+        "wav"
+    } else if head.starts_with(b"OggS") {
+        "ogg"
+    } else if head.starts_with(b"ID3") {
+        "mp3"
+    } else if head.starts_with(&[0x1A, 0x45, 0xDF, 0xA3]) {
+        "webm"
+    } else if head.starts_with(b"RENPY RPC2") {
+        "rpyc"
+    } else {
+        "bin"
+    }
+}
+
+impl RpaEditor {
+    /// Byte ranges `(offset, length)` between the header and the index that no entry uses.
+    pub(crate) fn find_orphaned_ranges(&self) -> Vec<(u64, u64)> {
+        let data_start = self.header_line.len() as u64 + 1;
+        let mut used: Vec<(u64, u64)> = self
+            .indexes
+            .values()
+            .filter(|entry| entry.in_archive())
+            .flat_map(|entry| entry.segments.iter())
+            .map(|segment| {
+                let stored = segment.length.saturating_sub(segment.prefix.len() as u64);
+                (segment.offset, segment.offset.saturating_add(stored))
+            })
+            .filter(|(start, end)| end > start)
+            .collect();
+        used.sort();
+
+        let mut orphans = Vec::new();
+        let mut cursor = data_start;
+        for (start, end) in used {
+            if start > cursor {
+                orphans.push((cursor, start.min(self.index_offset) - cursor));
+            }
+            cursor = cursor.max(end);
+            if cursor >= self.index_offset {
+                break;
+            }
+        }
+        if cursor < self.index_offset {
+            orphans.push((cursor, self.index_offset - cursor));
+        }
+        // Writers pad the header area (Ren'Py adds "Made with Ren'Py."), which isn't lost data.
+        orphans.retain(|&(offset, length)| {
+            length > 0 && !(offset == data_start && length <= HEADER_PADDING_MAX)
+        });
+        orphans
+    }
+
+    pub(crate) fn read_range(file: &mut File, offset: u64, length: u64) -> anyhow::Result<Vec<u8>> {
+        file.seek(SeekFrom::Start(offset))?;
+        let mut data = Vec::new();
+        file.take(length).read_to_end(&mut data)?;
+        Ok(data)
+    }
+
+    /// Streams a range to `out`; orphaned ranges can be most of the archive.
+    fn copy_range(file: &mut File, offset: u64, length: u64, out: &mut impl Write) -> anyhow::Result<u64> {
+        file.seek(SeekFrom::Start(offset))?;
+        Ok(std::io::copy(&mut file.take(length), out)?)
+    }
+
+    /// Enough of a range to guess its type.
+    fn read_range_head(file: &mut File, offset: u64, length: u64) -> anyhow::Result<Vec<u8>> {
+        Self::read_range(file, offset, length.min(SNIFF_LENGTH))
+    }
+
+    /// Writes each orphaned range to `orphan_<offset>.<ext>` in `folder`.
+    pub(crate) fn carve_orphans_to_folder(&self, folder: &Path) -> anyhow::Result<usize> {
+        let archive_path = self
+            .archive_path
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("No archive loaded"))?;
+        let mut file = File::open(archive_path)?;
+
+        let ranges = self.find_orphaned_ranges();
+        for &(offset, length) in &ranges {
+            let head = Self::read_range_head(&mut file, offset, length)?;
+            let name = format!("orphan_{:08x}.{}", offset, sniff_extension(&head));
+            Self::copy_range(&mut file, offset, length, &mut File::create(folder.join(name))?)?;
+        }
+        Ok(ranges.len())
+    }
+
+    /// Asks where to save one orphaned range, suggesting an extension from its content.
+    pub(crate) fn carve_orphan_to_file(&self, offset: u64, length: u64) -> anyhow::Result<Option<String>> {
+        let archive_path = self
+            .archive_path
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("No archive loaded"))?;
+        let mut file = File::open(archive_path)?;
+        let head = Self::read_range_head(&mut file, offset, length)?;
+        let Some(path) = rfd::FileDialog::new()
+            .set_file_name(format!("orphan_{:08x}.{}", offset, sniff_extension(&head)))
+            .save_file()
+        else {
+            return Ok(None);
+        };
+        Self::copy_range(&mut file, offset, length, &mut File::create(&path)?)?;
+        Ok(Some(path.display().to_string()))
+    }
+
+    /// Turns orphaned ranges into `orphaned/` entries so they survive the next save. The
+    /// entries point at the ranges in the archive, like original entries, and are copied
+    /// over when saving.
+    pub(crate) fn carve_orphans_into_archive(&mut self) -> anyhow::Result<usize> {
+        self.ensure_writable()?;
+        let archive_path = self
+            .archive_path
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("No archive loaded"))?;
+        let mut file = File::open(archive_path)?;
+
+        let ranges = self.find_orphaned_ranges();
+        for &(offset, length) in &ranges {
+            let head = Self::read_range_head(&mut file, offset, length)?;
+            let name = format!("orphaned/orphan_{:08x}.{}", offset, sniff_extension(&head));
+            self.record_sniffed_type(&name, &head);
+            let mut entry = RpaFileEntry::from_segments(vec![RpaSegment {
+                offset,
+                length,
+                prefix: Vec::new(),
+            }]);
+            entry.modified = true;
+            entry.provenance = format!("carved from offset 0x{:x}", offset);
+            self.indexes.insert(name, entry);
+            self.set_modified(true);
+        }
+        self.orphaned_ranges.clear();
+        Ok(ranges.len())
+    }
+}
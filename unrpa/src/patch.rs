@@ -0,0 +1,38 @@
+use std::collections::HashSet;
+use std::path::Path;
+use tracing::info;
+use crate::rpa::RpaEditor;
+
+impl RpaEditor {
+    /// Writes the added and changed entries of the current diff as a standalone archive.
+    ///
+    /// RPA has no way to express a deletion, so removed entries are only counted.
+    pub(crate) fn export_patch(&mut self, patch_path: &Path) -> anyhow::Result<(usize, usize)> {
+        let diff = self
+            .archive_diff
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Compare with a base archive first"))?;
+        let entries: HashSet<&str> = diff
+            .added
+            .iter()
+            .chain(&diff.changed)
+            .map(String::as_str)
+            .collect();
+        if entries.is_empty() {
+            return Err(anyhow::anyhow!("Nothing was added or changed"));
+        }
+        let removed = diff.removed.len();
+        self.save_entries_as(&patch_path.to_string_lossy(), self.header_format, self.key, |name| {
+            entries.contains(name)
+        })?;
+        info!("🩹 Wrote patch {} with {} entries", patch_path.display(), entries.len());
+        Ok((entries.len(), removed))
+    }
+
+    /// Merges a patch archive into the open one, replacing entries without asking.
+    pub(crate) fn apply_patch_archive(&mut self, patch_path: &str) -> anyhow::Result<usize> {
+        let count = self.merge_archive(patch_path)?;
+        self.conflict_resolution = Some("overwrite".to_string());
+        Ok(count)
+    }
+}
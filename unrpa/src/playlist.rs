@@ -0,0 +1,73 @@
+use tracing::debug;
+use crate::rpa::RpaEditor;
+
+/// What happens when an audio entry finishes, with its label in the controller.
+pub const AUDIO_LOOP_MODES: &[(&str, &str)] = &[
+    ("off", "➡ Play once"),
+    ("single", "🔂 Loop track"),
+    ("next", "⏭ Play next in list"),
+];
+
+impl RpaEditor {
+    pub(crate) fn play_audio_entry(&mut self, filename: &str) {
+        match self.open_entry_stream(filename) {
+            Ok(stream) => {
+                debug!("Playing audio {}", filename);
+                self.audio_player.play_stream(stream);
+                self.is_playing = true;
+                self.playing_audio = Some(filename.to_string());
+            }
+            Err(e) => {
+                self.status_message = format!("Playback error: {}", e);
+                self.is_playing = false;
+            }
+        }
+    }
+
+    pub(crate) fn stop_audio(&mut self) {
+        self.audio_player.stop();
+        self.is_playing = false;
+        self.playing_audio = None;
+    }
+
+    /// Next audio entry after `current` in the filtered, sorted file list.
+    fn next_audio_entry(&self, current: &str) -> Option<String> {
+        let files = self.get_filtered_sorted_files();
+        let position = files.iter().position(|(name, _)| name.as_str() == current);
+        files
+            .iter()
+            .skip(position.map_or(0, |i| i + 1))
+            .map(|(name, _)| name.as_str())
+            .find(|name| self.get_file_type(name) == "audio")
+            .map(str::to_string)
+    }
+
+    /// Called once the sink runs dry; replays or advances according to `audio_loop_mode`.
+    pub(crate) fn on_audio_finished(&mut self) {
+        let Some(current) = self.playing_audio.clone() else {
+            self.is_playing = false;
+            return;
+        };
+        // The idle fallback sink is always empty, which would skip through the whole list.
+        if !self.audio_player.has_output() {
+            self.stop_audio();
+            return;
+        }
+
+        match self.audio_loop_mode.as_str() {
+            "single" => self.play_audio_entry(&current),
+            "next" => match self.next_audio_entry(&current) {
+                Some(next) => {
+                    self.selected_file = Some(next.clone());
+                    self.file_to_preview = Some(next.clone());
+                    self.play_audio_entry(&next);
+                }
+                None => {
+                    self.stop_audio();
+                    self.add_toast("Reached the end of the audio list");
+                }
+            },
+            _ => self.stop_audio(),
+        }
+    }
+}
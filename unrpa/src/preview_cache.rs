@@ -0,0 +1,93 @@
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+use crate::health::config_dir;
+use crate::rpa::RpaEditor;
+use crate::rpyc;
+use crate::sha256::sha256_hex;
+
+/// Root of the on-disk cache; one subfolder per kind ("thumbnails", "rpyc").
+pub fn preview_cache_dir() -> PathBuf {
+    config_dir().join("cache")
+}
+
+/// Cache file for `entry_hash` in `archive_path`, e.g. `cache/rpyc/<key>.rpy`.
+pub fn cache_file(kind: &str, archive_path: &str, entry_hash: &str, extension: &str) -> PathBuf {
+    let archive = std::fs::canonicalize(archive_path)
+        .map(|path| path.to_string_lossy().to_string())
+        .unwrap_or_else(|_| archive_path.to_string());
+    let key = sha256_hex(format!("{}|{}", archive, entry_hash).as_bytes());
+    preview_cache_dir().join(kind).join(format!("{}.{}", &key[..32], extension))
+}
+
+/// Writes `data` to a cache file; the cache is best-effort, so failures are only logged.
+pub fn store_cached(path: &Path, data: &[u8]) {
+    let result = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|()| std::fs::write(path, data));
+    if let Err(e) = result {
+        warn!("⚠️ Could not write cache file {}: {}", path.display(), e);
+    }
+}
+
+pub fn dir_size(dir: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.file_type() {
+            Ok(kind) if kind.is_dir() => dir_size(&entry.path()),
+            _ => entry.metadata().map_or(0, |metadata| metadata.len()),
+        })
+        .sum()
+}
+
+pub fn preview_cache_size() -> u64 {
+    dir_size(&preview_cache_dir())
+}
+
+impl RpaEditor {
+    /// Identifies an entry's content without reading it when possible: its known SHA-256,
+    /// or for unmodified entries, where it sits in this exact archive file.
+    pub(crate) fn entry_cache_hash(&self, name: &str) -> Option<String> {
+        if let Some(hash) = self.checksums.get(name) {
+            return Some(hash.clone());
+        }
+        let path = self.archive_path.as_ref()?;
+        let entry = self.indexes.get(name).filter(|entry| entry.in_archive())?;
+        let metadata = std::fs::metadata(path).ok()?;
+        let modified = metadata.modified().ok()?.duration_since(std::time::UNIX_EPOCH).ok()?;
+        let location: Vec<(u64, u64)> = entry.segments.iter().map(|segment| (segment.offset, segment.length)).collect();
+        Some(format!("{}|{}|{}|{:?}", metadata.len(), modified.as_nanos(), name, location))
+    }
+
+    /// Decompiles a `.rpyc` entry, reusing the text from an earlier session when the
+    /// same bytes were decompiled before.
+    pub(crate) fn decompile_cached(&self, data: &[u8]) -> anyhow::Result<String> {
+        let cache_path = self
+            .archive_path
+            .as_deref()
+            .map(|archive| cache_file("rpyc", archive, &sha256_hex(data), "rpy"));
+        if let Some(path) = &cache_path
+            && let Ok(source) = std::fs::read_to_string(path)
+        {
+            return Ok(source);
+        }
+        let source = rpyc::decompile(data)?;
+        if let Some(path) = &cache_path {
+            store_cached(path, source.as_bytes());
+        }
+        Ok(source)
+    }
+
+    pub(crate) fn clear_preview_cache(&mut self) -> anyhow::Result<()> {
+        let dir = preview_cache_dir();
+        if dir.exists() {
+            std::fs::remove_dir_all(&dir)?;
+        }
+        self.thumbnails.clear();
+        info!("🗑️ Cleared {}", dir.display());
+        Ok(())
+    }
+}
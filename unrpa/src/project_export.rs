@@ -0,0 +1,91 @@
+use std::fs::create_dir_all;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+use crate::rpa::RpaEditor;
+use crate::rpyc;
+use crate::safe_path::entry_output_path;
+
+/// What ended up in an exported project.
+pub struct ProjectExport {
+    pub root: PathBuf,
+    pub files: usize,
+    pub decompiled: usize,
+    pub kept_compiled: usize,
+}
+
+impl RpaEditor {
+    /// Writes `<parent>/<archive name>/game/` with every entry, replacing `.rpyc` scripts by
+    /// decompiled `.rpy` files so the Ren'Py SDK can open and rebuild the project.
+    pub(crate) fn export_renpy_project(&self, parent: &Path) -> anyhow::Result<ProjectExport> {
+        let name = self
+            .archive_path
+            .as_deref()
+            .and_then(|path| Path::new(path).file_stem())
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_else(|| "project".to_string());
+        let root = parent.join(name);
+        let game = root.join("game");
+        create_dir_all(&game)?;
+
+        let mut export = ProjectExport {
+            root,
+            files: 0,
+            decompiled: 0,
+            kept_compiled: 0,
+        };
+
+        let mut names: Vec<&String> = self
+            .indexes
+            .iter()
+            .filter(|(_, entry)| !entry.to_delete)
+            .map(|(name, _)| name)
+            .collect();
+        names.sort();
+
+        for filename in names {
+            let relative = filename.strip_prefix("game/").unwrap_or(filename);
+            if !filename.to_lowercase().ends_with(".rpyc") {
+                let target = entry_output_path(&game, relative)?;
+                if let Some(parent) = target.parent() {
+                    create_dir_all(parent)?;
+                }
+                let mut stream = self.open_entry_stream(filename)?;
+                std::io::copy(&mut stream, &mut std::fs::File::create(&target)?)?;
+                export.files += 1;
+                continue;
+            }
+
+            // Ren'Py recompiles from the source when it exists, so the .rpyc isn't needed.
+            let source_name = &filename[..filename.len() - 1];
+            if self.indexes.contains_key(source_name) {
+                continue;
+            }
+
+            let data = self.load_file_data(filename)?;
+            let source_path = entry_output_path(&game, &relative[..relative.len() - 1])?;
+            if let Some(parent) = source_path.parent() {
+                create_dir_all(parent)?;
+            }
+            match rpyc::decompile(&data) {
+                Ok(source) => {
+                    std::fs::write(&source_path, source)?;
+                    export.decompiled += 1;
+                }
+                Err(e) => {
+                    warn!("⚠️ Keeping {} compiled, decompilation failed: {}", filename, e);
+                    std::fs::write(entry_output_path(&game, relative)?, data)?;
+                    export.kept_compiled += 1;
+                }
+            }
+            export.files += 1;
+        }
+
+        info!(
+            "📦 Exported Ren'Py project to {} ({} files, {} decompiled)",
+            export.root.display(),
+            export.files,
+            export.decompiled
+        );
+        Ok(export)
+    }
+}
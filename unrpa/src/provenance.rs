@@ -0,0 +1,110 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use crate::rpa::RpaEditor;
+
+impl RpaEditor {
+    /// Records where the current bytes of `filename` came from.
+    pub(crate) fn set_provenance(&mut self, filename: &str, source: impl Into<String>) {
+        if let Some(entry) = self.indexes.get_mut(filename) {
+            entry.provenance = source.into();
+        }
+    }
+
+    /// "added", "modified", "deleted" or "original".
+    pub(crate) fn entry_state(&self, filename: &str) -> &'static str {
+        let Some(entry) = self.indexes.get(filename) else {
+            return "original";
+        };
+        let loaded = self.loaded_names.contains(filename);
+        if entry.to_delete {
+            if loaded { "deleted" } else { "original" }
+        } else if !loaded {
+            "added"
+        } else if entry.modified || entry.data.is_some() {
+            "modified"
+        } else {
+            "original"
+        }
+    }
+
+    pub(crate) fn show_entry_properties(&self, ui: &mut egui::Ui, filename: &str) {
+        let Some(entry) = self.indexes.get(filename) else {
+            return;
+        };
+
+        egui::Grid::new("entry_properties").num_columns(2).show(ui, |ui| {
+            ui.label("Type:");
+            ui.label(self.get_file_type(filename));
+            ui.end_row();
+
+            ui.label("Size:");
+            ui.label(Self::format_bytes(entry.length));
+            ui.end_row();
+
+            ui.label("State:");
+            ui.label(self.entry_state(filename));
+            ui.end_row();
+
+            if let Some(segment) = entry.segments.first()
+                && entry.in_archive()
+            {
+                ui.label("Offset:");
+                ui.monospace(format!("0x{:x} ({} segments)", segment.offset, entry.segments.len()));
+                ui.end_row();
+            }
+
+            ui.label("Source:");
+            ui.label(&entry.provenance);
+            ui.end_row();
+
+            if let Some(hash) = self.checksums.get(filename) {
+                ui.label("SHA-256:");
+                ui.monospace(hash);
+                ui.end_row();
+            }
+        });
+    }
+
+    /// Writes a Markdown list of added, modified and deleted entries with their sources.
+    pub(crate) fn export_changelog(&self, path: &Path) -> anyhow::Result<usize> {
+        let mut names: Vec<&String> = self.indexes.keys().collect();
+        names.sort();
+
+        let mut out = BufWriter::new(File::create(path)?);
+        writeln!(
+            out,
+            "# Changes to {}\n",
+            self.archive_path.as_deref().unwrap_or("new archive")
+        )?;
+        writeln!(out, "Generated {}\n", chrono::Utc::now().format("%Y-%m-%d %H:%M UTC"))?;
+
+        let mut total = 0;
+        for (state, heading) in [("added", "Added"), ("modified", "Modified"), ("deleted", "Deleted")] {
+            let changed: Vec<&&String> = names.iter().filter(|name| self.entry_state(name) == state).collect();
+            if changed.is_empty() {
+                continue;
+            }
+            writeln!(out, "## {} ({})\n", heading, changed.len())?;
+            for name in changed {
+                let entry = &self.indexes[*name];
+                if state == "deleted" {
+                    writeln!(out, "- `{}`", name)?;
+                } else {
+                    writeln!(
+                        out,
+                        "- `{}` ({}), source: {}",
+                        name,
+                        Self::format_bytes(entry.length),
+                        entry.provenance
+                    )?;
+                }
+                total += 1;
+            }
+            writeln!(out)?;
+        }
+        out.flush()?;
+
+        Ok(total)
+    }
+}
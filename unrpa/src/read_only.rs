@@ -0,0 +1,41 @@
+use tracing::info;
+use crate::rpa::RpaEditor;
+
+/// Command line switch that opens the editor in read-only mode.
+pub const READ_ONLY_FLAG: &str = "--read-only";
+
+impl RpaEditor {
+    /// Fails while read-only mode is on. Every operation that would change the archive
+    /// or write it to disk checks this first.
+    pub(crate) fn ensure_writable(&self) -> anyhow::Result<()> {
+        if self.read_only {
+            return Err(anyhow::anyhow!("Read-only mode is on"));
+        }
+        Ok(())
+    }
+
+    /// `ensure_writable` for actions without an error to return: tells the user and
+    /// returns true when the action has to be skipped.
+    pub(crate) fn blocked_by_read_only(&mut self) -> bool {
+        if self.read_only {
+            self.add_warning("Read-only mode: the archive can't be changed");
+        }
+        self.read_only
+    }
+
+    pub(crate) fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
+        if read_only {
+            // Nothing queued before the switch may reach the archive afterwards.
+            self.pending_adds.clear();
+            self.dropped_files.clear();
+            self.save_review = None;
+            self.watch_folder = None;
+            self.folder_add = None;
+            self.status_message = "Read-only mode: browsing only, nothing will be written".to_string();
+        } else {
+            self.status_message = "Read-only mode off".to_string();
+        }
+        info!("🔒 {}", self.status_message);
+    }
+}
@@ -0,0 +1,117 @@
+use std::path::Path;
+use crate::archive_index::ArchiveIndex;
+use crate::read_only::READ_ONLY_FLAG;
+use crate::rpa::RpaEditor;
+use crate::workspace::WORKSPACE_FLAG;
+
+/// Archives listed under File > Open Recent.
+pub const MAX_RECENT_FILES: usize = 10;
+
+impl RpaEditor {
+    /// Moves `path` to the top of the recent list.
+    fn remember_recent(&mut self, path: &str) {
+        let path = std::fs::canonicalize(path)
+            .map(|full| full.to_string_lossy().to_string())
+            .unwrap_or_else(|_| path.to_string());
+        self.recent_files.retain(|recent| *recent != path);
+        self.recent_files.insert(0, path);
+        self.recent_files.truncate(MAX_RECENT_FILES);
+    }
+
+    /// Loads an archive picked by the user and records it as recently opened. The index
+    /// is read by a job; `finish_open_archive` takes over once it's in.
+    pub(crate) fn open_archive(&mut self, path: &str) {
+        self.clean_archive_temp();
+        self.remove_autosave();
+        self.autosave_offer = None;
+        self.start_archive_load(path);
+    }
+
+    pub(crate) fn finish_open_archive(&mut self, path: &str, index: anyhow::Result<ArchiveIndex>) {
+        match index.and_then(|index| self.apply_archive_index(path, index)) {
+            Ok(()) => {
+                self.external_edits = Vec::new();
+                self.remember_recent(path);
+                self.add_toast("RPA loaded successfully");
+                self.check_autosave(path);
+            }
+            Err(e) => {
+                self.add_error(format!("Error loading: {}", e));
+                if Path::new(path).is_file() {
+                    self.recovery_offer = Some((path.to_string(), e.to_string()));
+                }
+            }
+        }
+    }
+
+    /// Opens the archive passed on the command line, e.g. by a file association.
+    pub(crate) fn open_from_args(&mut self) {
+        let mut archive = None;
+        let mut workspace = None;
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            if arg == WORKSPACE_FLAG {
+                workspace = args.next();
+            } else if arg != READ_ONLY_FLAG && archive.is_none() {
+                archive = Some(arg);
+            }
+        }
+
+        // Handed over by an editor that relaunched itself with more rights.
+        if let Some(workspace) = workspace {
+            let archive = archive.unwrap_or_default();
+            match self.import_workspace(Path::new(&workspace), &archive) {
+                Ok(count) => {
+                    let _ = std::fs::remove_file(&workspace);
+                    if !archive.is_empty() {
+                        self.remember_recent(&archive);
+                    }
+                    self.add_toast(format!("Restored {} unsaved changes", count));
+                }
+                Err(e) => self.add_error(format!("Could not restore unsaved changes from {}: {}", workspace, e)),
+            }
+            return;
+        }
+
+        let Some(path) = archive else {
+            return;
+        };
+        if Path::new(&path).is_file() {
+            self.open_archive(&path);
+        } else {
+            self.add_warning(format!("File not found: {}", path));
+        }
+    }
+
+    pub(crate) fn show_recent_menu(&mut self, ui: &mut egui::Ui) {
+        ui.menu_button("Open Recent", |ui| {
+            if self.recent_files.is_empty() {
+                ui.label("No recent archives");
+                return;
+            }
+            let mut open = None;
+            for path in &self.recent_files {
+                let exists = Path::new(path).is_file();
+                let name = Path::new(path)
+                    .file_name()
+                    .map_or(path.clone(), |name| name.to_string_lossy().to_string());
+                let response = ui
+                    .add_enabled(exists, egui::Button::new(name))
+                    .on_hover_text(path)
+                    .on_disabled_hover_text(format!("{} (missing)", path));
+                if response.clicked() {
+                    open = Some(path.clone());
+                }
+            }
+            ui.separator();
+            if ui.button("Clear Recent").clicked() {
+                self.recent_files.clear();
+                ui.close_menu();
+            }
+            if let Some(path) = open {
+                self.open_archive(&path);
+                ui.close_menu();
+            }
+        });
+    }
+}
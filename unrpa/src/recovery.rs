@@ -0,0 +1,261 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufReader;
+use tracing::info;
+use crate::header::HeaderFormat;
+use crate::key_recovery::find_signatures;
+use crate::rpa::{RpaEditor, RpaFileEntry, RpaSegment};
+
+/// Signatures that start a file, with the kind used to measure it. `ftyp` sits 4 bytes in.
+const RECOVERY_SIGNATURES: &[(&[u8], &str)] = &[
+    (b"\x89PNG\r\n\x1a\n", "png"),
+    (b"\xFF\xD8\xFF", "jpg"),
+    (b"OggS\0", "ogg"),
+    (b"RIFF", "riff"),
+    (b"ftyp", "mp4"),
+    (b"\x1A\x45\xDF\xA3", "webm"),
+    (b"RENPY RPC2", "rpyc"),
+];
+
+/// Upper bound on chunks, pages or boxes walked for one file.
+const MAX_PARTS: usize = 1 << 20;
+
+/// How far a file found by the scan reaches.
+enum Extent {
+    Known(u64),
+    /// Runs up to the next file found.
+    Unknown,
+    /// The signature matched, but not at the start of a file (e.g. a later Ogg page).
+    NotAStart,
+}
+
+fn read_at(file: &mut File, offset: u64, length: u64) -> Option<Vec<u8>> {
+    RpaEditor::read_range(file, offset, length).ok()
+}
+
+fn be32(bytes: &[u8]) -> u64 {
+    u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as u64
+}
+
+fn le32(bytes: &[u8]) -> u64 {
+    u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as u64
+}
+
+/// Walks PNG chunks up to IEND.
+fn png_extent(file: &mut File, offset: u64, file_length: u64) -> Extent {
+    let mut pos = offset + 8;
+    for _ in 0..MAX_PARTS {
+        let Some(chunk) = read_at(file, pos, 8) else {
+            return Extent::Unknown;
+        };
+        pos += 12 + be32(&chunk);
+        if pos > file_length {
+            return Extent::Unknown;
+        }
+        if &chunk[4..8] == b"IEND" {
+            return Extent::Known(pos - offset);
+        }
+    }
+    Extent::Unknown
+}
+
+/// Walks Ogg pages from the first page of a stream to its last.
+fn ogg_extent(file: &mut File, offset: u64, file_length: u64) -> Extent {
+    let mut pos = offset;
+    for page in 0..MAX_PARTS {
+        let Some(header) = read_at(file, pos, 27).filter(|header| header.starts_with(b"OggS")) else {
+            return if page == 0 { Extent::Unknown } else { Extent::Known(pos - offset) };
+        };
+        let flags = header[5];
+        if page == 0 && flags & 0x02 == 0 {
+            return Extent::NotAStart;
+        }
+        let Some(lacing) = read_at(file, pos + 27, header[26] as u64) else {
+            return Extent::Unknown;
+        };
+        pos += 27 + lacing.len() as u64 + lacing.iter().map(|&l| l as u64).sum::<u64>();
+        if pos > file_length {
+            return Extent::Unknown;
+        }
+        if flags & 0x04 != 0 {
+            return Extent::Known(pos - offset);
+        }
+    }
+    Extent::Unknown
+}
+
+/// Walks the top-level boxes of an MP4 file, starting with `ftyp`.
+fn mp4_extent(file: &mut File, offset: u64, file_length: u64) -> Extent {
+    let mut pos = offset;
+    for _ in 0..MAX_PARTS {
+        let Some(header) = read_at(file, pos, 16) else {
+            break;
+        };
+        let box_type = &header[4..8];
+        if !box_type.iter().all(|c| c.is_ascii_alphanumeric() || *c == b' ') {
+            break;
+        }
+        let size = match be32(&header) {
+            0 => file_length - pos,
+            1 => u64::from_be_bytes(header[8..16].try_into().unwrap_or_default()),
+            size => size,
+        };
+        // A size past the end, even one that overflows, means the file ends here.
+        match pos.checked_add(size) {
+            Some(end) if size >= 8 && end <= file_length => pos = end,
+            _ => break,
+        }
+    }
+    if pos == offset { Extent::NotAStart } else { Extent::Known(pos - offset) }
+}
+
+/// Compiled scripts list their chunks right after the header.
+fn rpyc_extent(file: &mut File, offset: u64) -> Extent {
+    let mut end = 0;
+    for slot in 0..16 {
+        let Some(entry) = read_at(file, offset + 10 + slot * 12, 12) else {
+            return Extent::Unknown;
+        };
+        if le32(&entry) == 0 {
+            break;
+        }
+        end = end.max(le32(&entry[4..]) + le32(&entry[8..]));
+    }
+    if end == 0 { Extent::Unknown } else { Extent::Known(end) }
+}
+
+fn measure(file: &mut File, offset: u64, kind: &str, file_length: u64) -> (Extent, &'static str) {
+    match kind {
+        "png" => (png_extent(file, offset, file_length), "png"),
+        "ogg" => (ogg_extent(file, offset, file_length), "ogg"),
+        "rpyc" => (rpyc_extent(file, offset), "rpyc"),
+        "mp4" => {
+            let audio = read_at(file, offset + 8, 4).is_some_and(|brand| brand == b"M4A ");
+            (mp4_extent(file, offset, file_length), if audio { "m4a" } else { "mp4" })
+        }
+        "riff" => {
+            let Some(header) = read_at(file, offset, 12) else {
+                return (Extent::Unknown, "bin");
+            };
+            let extension = match &header[8..12] {
+                b"WEBP" => "webp",
+                b"WAVE" => "wav",
+                b"AVI " => "avi",
+                _ => return (Extent::NotAStart, "bin"),
+            };
+            let length = 8 + le32(&header[4..]);
+            (Extent::Known(length + length % 2), extension)
+        }
+        "jpg" => (Extent::Unknown, "jpg"),
+        _ => (Extent::Unknown, "webm"),
+    }
+}
+
+impl RpaEditor {
+    /// Rebuilds an index for an archive whose header or index can't be read, by looking for
+    /// files in its body. Found files get names like `recovered/0001a2b0.png`.
+    pub(crate) fn recover_archive(&mut self, path: &str) -> anyhow::Result<usize> {
+        let file_length = std::fs::metadata(path)?.len();
+        let signatures: Vec<&[u8]> = RECOVERY_SIGNATURES.iter().map(|(signature, _)| *signature).collect();
+        let positions = find_signatures(BufReader::new(File::open(path)?), &signatures, |_| true)?;
+
+        let mut candidates: Vec<(u64, &str)> = Vec::new();
+        for (list, (_, kind)) in positions.iter().zip(RECOVERY_SIGNATURES) {
+            for &position in list {
+                if *kind == "mp4" {
+                    if position >= 4 {
+                        candidates.push((position - 4, kind));
+                    }
+                } else {
+                    candidates.push((position, kind));
+                }
+            }
+        }
+        candidates.sort();
+
+        let mut file = File::open(path)?;
+        let mut indexes = HashMap::new();
+        let mut cursor = 0;
+        for (i, &(offset, kind)) in candidates.iter().enumerate() {
+            // Signatures inside a file already recovered, e.g. an embedded thumbnail.
+            if offset < cursor {
+                continue;
+            }
+            let next = candidates[i + 1..]
+                .iter()
+                .map(|&(start, _)| start)
+                .find(|&start| start > offset)
+                .unwrap_or(file_length);
+            let (extent, extension) = measure(&mut file, offset, kind, file_length);
+            let length = match extent {
+                Extent::Known(length) if offset + length <= file_length => length,
+                Extent::NotAStart => continue,
+                _ => next - offset,
+            };
+            cursor = offset + length;
+            indexes.insert(
+                format!("recovered/{:08x}.{}", offset, extension),
+                RpaFileEntry {
+                    segments: vec![RpaSegment {
+                        offset,
+                        length,
+                        prefix: Vec::new(),
+                    }],
+                    length,
+                    data: None,
+                    merged: None,
+                    modified: false,
+                    to_delete: false,
+                    provenance: format!("recovered from offset 0x{:x}", offset),
+                },
+            );
+        }
+        if indexes.is_empty() {
+            return Err(anyhow::anyhow!("No known file signatures found"));
+        }
+
+        self.unload_rpa()?;
+        let count = indexes.len();
+        self.indexes = indexes;
+        self.archive_path = Some(path.to_string());
+        self.loaded_names = self.indexes.keys().cloned().collect();
+        self.loaded_size = self.indexes.values().map(|entry| entry.length).sum();
+        self.index_offset = file_length;
+        self.header_format = HeaderFormat::standard(3.0);
+        self.version = self.header_format.version;
+        self.key = Self::generate_key();
+        // Saving writes a fresh header and index, which is what repairs the file.
+        self.set_modified(!self.read_only);
+        self.detect_entry_types();
+
+        self.status_message = format!("Recovered {} files from {}; save to rebuild the index", count, path);
+        info!("🛟 {}", self.status_message);
+        Ok(count)
+    }
+
+    /// Offered when an archive fails to load.
+    pub(crate) fn show_recovery_dialog(&mut self, ui: &mut egui::Ui) {
+        let Some((path, error)) = &self.recovery_offer else {
+            return;
+        };
+        ui.label(format!("{} could not be opened:", path));
+        ui.colored_label(egui::Color32::LIGHT_RED, error);
+        ui.label("A recovery scan looks for PNG, JPEG, Ogg, RIFF, MP4, WebM and .rpyc files in the archive body and builds a new index from what it finds. Names are lost.");
+        ui.separator();
+        ui.horizontal(|ui| {
+            if ui.button("🔎 Recovery Scan").clicked()
+                && let Some((path, _)) = self.recovery_offer.take()
+            {
+                self.open_in_new_tab_with(&path, |editor, path| {
+                    match editor.recover_archive(path) {
+                        Ok(count) => editor.add_toast(format!("Recovered {} files", count)),
+                        Err(e) => editor.add_error(format!("Recovery failed: {}", e)),
+                    }
+                });
+            }
+            if ui.button("Cancel").clicked() {
+                self.recovery_offer = None;
+            }
+        });
+    }
+}
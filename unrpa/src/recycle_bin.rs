@@ -0,0 +1,107 @@
+use tracing::info;
+use crate::rpa::RpaEditor;
+
+impl RpaEditor {
+    /// Entries marked for deletion, sorted.
+    pub(crate) fn deleted_entries(&self) -> Vec<String> {
+        let mut deleted: Vec<String> = self
+            .indexes
+            .iter()
+            .filter(|(_, entry)| entry.to_delete)
+            .map(|(name, _)| name.clone())
+            .collect();
+        deleted.sort();
+        deleted
+    }
+
+    /// The Remove button: asks first when "Confirm before removing" is on.
+    pub(crate) fn request_remove(&mut self, filename: &str) {
+        if self.blocked_by_read_only() {
+            return;
+        }
+        if self.confirm_remove {
+            self.remove_to_confirm = Some(filename.to_string());
+        } else {
+            self.file_to_remove = Some(filename.to_string());
+        }
+    }
+
+    /// Takes an entry back out of the Deleted section; any edits made before removing it are kept.
+    pub(crate) fn restore_file(&mut self, filename: &str) {
+        if let Some(entry) = self.indexes.get_mut(filename) {
+            entry.to_delete = false;
+            self.set_modified(!self.changed_entries().is_empty());
+            self.status_message = format!("Restored {}", filename);
+            info!("♻️ Restored {}", filename);
+        }
+    }
+
+    pub(crate) fn restore_all_deleted(&mut self) -> usize {
+        let deleted = self.deleted_entries();
+        for filename in &deleted {
+            if let Some(entry) = self.indexes.get_mut(filename) {
+                entry.to_delete = false;
+            }
+        }
+        self.set_modified(!self.changed_entries().is_empty());
+        self.status_message = format!("Restored {} entries", deleted.len());
+        info!("♻️ {}", self.status_message);
+        deleted.len()
+    }
+
+    /// Collapsible list of pending deletions above the file list.
+    pub(crate) fn show_deleted_section(&mut self, ui: &mut egui::Ui) {
+        let deleted = self.deleted_entries();
+        if deleted.is_empty() {
+            return;
+        }
+
+        let mut to_restore = None;
+        let mut restore_all = false;
+        egui::CollapsingHeader::new(format!("🗑️ Deleted ({})", deleted.len()))
+            .id_salt("deleted_section")
+            .show(ui, |ui| {
+                ui.label(egui::RichText::new("Removed from the archive on the next save.").small().weak());
+                egui::ScrollArea::vertical()
+                    .id_salt("deleted_entries")
+                    .max_height(150.0)
+                    .show(ui, |ui| {
+                        for filename in &deleted {
+                            ui.horizontal(|ui| {
+                                if ui.small_button("♻").on_hover_text("Restore").clicked() {
+                                    to_restore = Some(filename.clone());
+                                }
+                                ui.label(egui::RichText::new(filename).strikethrough().color(egui::Color32::RED));
+                            });
+                        }
+                    });
+                restore_all = ui.button("♻ Restore All").clicked();
+            });
+
+        if restore_all {
+            let count = self.restore_all_deleted();
+            self.add_toast(format!("Restored {} entries", count));
+        } else if let Some(filename) = to_restore {
+            self.restore_file(&filename);
+        }
+    }
+
+    pub(crate) fn show_remove_confirm(&mut self, ui: &mut egui::Ui) {
+        let Some(filename) = self.remove_to_confirm.clone() else {
+            return;
+        };
+        ui.label(format!("Remove {}?", filename));
+        ui.label("It moves to the Deleted section and leaves the archive on the next save. Until then it can be restored.");
+        ui.checkbox(&mut self.confirm_remove, "Ask before removing");
+        ui.separator();
+        ui.horizontal(|ui| {
+            if ui.button("🗑️ Remove").clicked() {
+                self.file_to_remove = Some(filename);
+                self.remove_to_confirm = None;
+            }
+            if ui.button("❌ Cancel").clicked() {
+                self.remove_to_confirm = None;
+            }
+        });
+    }
+}
@@ -0,0 +1,66 @@
+use tracing::info;
+use crate::rpa::RpaEditor;
+
+impl RpaEditor {
+    pub(crate) fn can_revert(&self, filename: &str) -> bool {
+        self.indexes.contains_key(filename) && self.entry_state(filename) != "original"
+    }
+
+    /// Entries that differ from the archive on disk.
+    pub(crate) fn changed_entries(&self) -> Vec<String> {
+        let mut changed: Vec<String> = self
+            .indexes
+            .keys()
+            .filter(|name| self.can_revert(name))
+            .cloned()
+            .collect();
+        changed.sort();
+        changed
+    }
+
+    /// Drops in-memory changes to one entry; entries added in the editor are removed.
+    fn revert_entry_state(&mut self, filename: &str) {
+        self.checksums.remove(filename);
+        self.thumbnails.forget(filename);
+        if !self.loaded_names.contains(filename) {
+            self.indexes.remove(filename);
+            if self.selected_file.as_deref() == Some(filename) {
+                self.selected_file = None;
+            }
+            return;
+        }
+        if let Some(entry) = self.indexes.get_mut(filename) {
+            entry.data = None;
+            entry.merged = None;
+            entry.modified = false;
+            entry.to_delete = false;
+            entry.length = entry.segments.iter().map(|segment| segment.length).sum();
+            entry.provenance = "original archive".to_string();
+        }
+        if let Ok(data) = self.load_file_data(filename) {
+            self.record_sniffed_type(filename, &data);
+        }
+        if self.selected_file.as_deref() == Some(filename) {
+            self.file_to_preview = Some(filename.to_string());
+        }
+    }
+
+    pub(crate) fn revert_entry(&mut self, filename: &str) {
+        self.revert_entry_state(filename);
+        self.set_modified(!self.changed_entries().is_empty());
+        self.status_message = format!("Reverted {}", filename);
+        info!("↩️ Reverted {}", filename);
+    }
+
+    /// Discards every pending change, as if the archive had just been opened.
+    pub(crate) fn revert_all(&mut self) -> usize {
+        let changed = self.changed_entries();
+        for filename in &changed {
+            self.revert_entry_state(filename);
+        }
+        self.set_modified(false);
+        self.status_message = format!("Discarded changes to {} entries", changed.len());
+        info!("↩️ Discarded changes to {} entries", changed.len());
+        changed.len()
+    }
+}
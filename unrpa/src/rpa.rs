@@ -1,141 +1,828 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
 use std::fs::{create_dir_all, File};
-use std::io::{Read, Seek, SeekFrom, Write};
-use std::path::Path;
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::mpsc::{Receiver, Sender};
+use std::time::{Duration, Instant};
 use egui_video::{AudioDevice, Player};
 use flate2::Compression;
 use flate2::read::ZlibDecoder;
 use flate2::write::ZlibEncoder;
-use serde_pickle::{DeOptions, Value};
+use serde_pickle::{HashableValue, Value};
+use tracing::{debug, error, info, warn};
 use crate::AudioPlayer;
+use crate::animation::{decode_animation, AnimationFrame};
+use crate::archive_index::{read_archive_index, ArchiveIndex};
+use crate::archive_load::ArchiveLoad;
+use crate::archive_reader::ArchiveReader;
+use crate::archive_watch::ArchiveStamp;
+use crate::autosave::AutosaveOffer;
+use crate::batch_replace::{BatchReplacePlan, BatchReplaceReport};
+use crate::batch_rename::BatchRename;
+use crate::checksums::ChecksumReport;
+use crate::crash_recovery::CrashRecovery;
+use crate::diff::{ArchiveDiff, DiffLine};
+use crate::duplicates::DuplicateGroup;
+use crate::backup_store::DiskBackup;
+use crate::entry_stream::EntryStream;
+use crate::external_edit::ExternalEdit;
+use crate::extract_conflicts::{ExtractReport, PendingExtract};
+use crate::file_styles::FileStyle;
+use crate::folder_add::FolderAdd;
+use crate::font_subset::subset_font;
+use crate::game_folder::GameFolder;
+use crate::health::HealthCheck;
+use crate::image_compare::ImageComparison;
+use crate::integrity::IntegrityReport;
+use crate::header::{HeaderFormat, HEADER_FORMATS};
+use crate::hex_view::HexSearch;
+use crate::jobs::{Job, JobEvent};
+use crate::large_archive::{LARGE_ARCHIVE_THRESHOLD, LARGE_PREVIEW_DIMENSION, LARGE_PREVIEW_LIMIT};
+use crate::log_console::LogView;
+use crate::preview_cache::{preview_cache_dir, preview_cache_size};
+use crate::read_only::READ_ONLY_FLAG;
+use crate::safe_path::entry_output_path;
+use crate::save_review::SaveReview;
+use crate::settings::{settings_path, theme_preference, Settings};
+use crate::sniff::{sniff_type, sniffed_preview_mode, TypeMismatch, SNIFF_LENGTH};
+use crate::tabs::ArchiveTab;
+use crate::thumbnails::Thumbnails;
 use crate::toast::Toast;
+use crate::video_probe::VideoInfo;
+use crate::watch_folder::WatchFolder;
+use crate::workspace::WORKSPACE_FLAG;
+
+/// Operations that can snapshot an entry before overwriting it, with their settings label.
+pub const BACKUP_TRIGGERS: &[(&str, &str)] = &[
+    ("add", "Adding over an existing entry"),
+    ("replace", "Replacing from disk"),
+    ("edit", "In-app edits and conversions"),
+];
+
+/// Pending-change filters for the file list, with their chip label.
+pub const CHANGE_FILTERS: &[(&str, &str)] = &[
+    ("all", "All"),
+    ("modified", "✏️ Modified"),
+    ("delete", "🗑️ To delete"),
+    ("new", "➕ New"),
+];
+
+/// Default position of the first entry, leaving room for the longest header line.
+pub const DEFAULT_DATA_START: u64 = 0x34;
+
+/// Whether both paths name the same existing file.
+pub(crate) fn same_file(a: &str, b: &str) -> bool {
+    match (std::fs::canonicalize(a), std::fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// Removes a half-written file when dropped, unless it was kept.
+struct TempFile {
+    path: String,
+    keep: bool,
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        if !self.keep {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
 
+pub(crate) const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+
+pub(crate) fn fnv1a(mut hash: u64, bytes: &[u8]) -> u64 {
+    for &byte in bytes {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+/// Number of leading bytes moved into the index when prefixes are regenerated on save.
+pub(crate) const PREFIX_LENGTH: u64 = 16;
+
+/// One `(offset, length, prefix)` tuple of an index entry.
+///
+/// `length` includes the prefix, so only `length - prefix.len()` bytes live in the archive.
 #[derive(Debug, Clone)]
-pub struct RpaFileEntry {
+pub struct RpaSegment {
     pub offset: u64,
     pub length: u64,
     pub prefix: Vec<u8>,
+}
+
+/// Where a merged entry's bytes stay until a save copies them into the archive.
+#[derive(Debug, Clone)]
+pub struct MergedSource {
+    pub archive: String,
+    pub segments: Vec<RpaSegment>,
+}
+
+impl MergedSource {
+    pub fn open(&self) -> anyhow::Result<EntryStream> {
+        Ok(EntryStream::from_archive(File::open(&self.archive)?, self.segments.clone()))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RpaFileEntry {
+    pub segments: Vec<RpaSegment>,
+    pub length: u64,
     pub data: Option<Vec<u8>>,
+    /// Bytes merged from another archive; like `data`, `segments` then only remember
+    /// the original location.
+    pub merged: Option<MergedSource>,
     pub modified: bool,
     pub to_delete: bool,
+    /// Where the current bytes came from: the original archive, a disk path, an import...
+    pub provenance: String,
+}
+
+impl RpaFileEntry {
+    pub fn from_segments(segments: Vec<RpaSegment>) -> Self {
+        Self {
+            length: segments.iter().map(|s| s.length).sum(),
+            segments,
+            data: None,
+            merged: None,
+            modified: false,
+            to_delete: false,
+            provenance: "original archive".to_string(),
+        }
+    }
+
+    pub fn from_data(data: Vec<u8>) -> Self {
+        Self {
+            segments: Vec::new(),
+            length: data.len() as u64,
+            data: Some(data),
+            merged: None,
+            modified: true,
+            to_delete: false,
+            provenance: "created in editor".to_string(),
+        }
+    }
+
+    pub fn from_merged(source: MergedSource) -> Self {
+        Self {
+            segments: Vec::new(),
+            length: source.segments.iter().map(|segment| segment.length).sum(),
+            provenance: format!("merged from {}", source.archive),
+            data: None,
+            merged: Some(source),
+            modified: true,
+            to_delete: false,
+        }
+    }
+
+    /// The pending change saving would make, as a `CHANGE_FILTERS` key.
+    pub fn change_kind(&self) -> Option<&'static str> {
+        if self.to_delete {
+            Some("delete")
+        } else if self.modified && self.segments.is_empty() {
+            Some("new")
+        } else if self.modified {
+            Some("modified")
+        } else {
+            None
+        }
+    }
+
+    /// Whether the entry's bytes are at `segments` in the open archive.
+    pub fn in_archive(&self) -> bool {
+        self.data.is_none() && self.merged.is_none()
+    }
+
+    /// Where the entry's bytes start in the archive file; `None` once they live elsewhere.
+    pub fn archive_offset(&self) -> Option<u64> {
+        if self.in_archive() {
+            self.segments.first().map(|segment| segment.offset)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EncodingConversion {
+    pub filename: String,
+    pub encoding: &'static encoding_rs::Encoding,
+    pub preview: String,
+    pub selected: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct DroppedFile {
+    pub path: String,
+    pub archive_name: String,
+    pub selected: bool,
+}
+
+/// Pending change totals shown in the window title, counted at `generation`.
+#[derive(Debug, Clone, Copy)]
+pub struct TitleCounts {
+    pub generation: u64,
+    pub added: usize,
+    pub removed: usize,
+    pub size: u64,
+}
+
+/// File list counts shown in the side panel, counted at `generation` under `filters`.
+#[derive(Debug, Clone)]
+pub struct FilterCounts {
+    pub generation: u64,
+    /// Type, change and search filters the visible count was taken with.
+    pub filters: [String; 3],
+    pub visible: usize,
+    /// Entries matching each of `CHANGE_FILTERS`, in order.
+    pub changes: [usize; CHANGE_FILTERS.len()],
+}
+
+/// File waiting to be added, held back while a name conflict is resolved.
+#[derive(Debug, Clone)]
+pub struct PendingAdd {
+    pub path: String,
+    pub archive_name: String,
+    /// Entry of another archive; `path` then only names the source.
+    pub merged: Option<MergedSource>,
+}
+
+impl PendingAdd {
+    pub fn size(&self) -> u64 {
+        match &self.merged {
+            Some(source) => source.segments.iter().map(|segment| segment.length).sum(),
+            None => std::fs::metadata(&self.path).map(|m| m.len()).unwrap_or(0),
+        }
+    }
 }
 
+/// Previous version of an entry, kept zlib-compressed to limit RAM usage.
 #[derive(Debug, Clone)]
 pub struct BackupEntry {
     pub filename: String,
     pub data: Vec<u8>,
+    pub original_size: u64,
     pub timestamp: chrono::DateTime<chrono::Utc>,
 }
 
+impl BackupEntry {
+    pub fn restore_data(&self) -> anyhow::Result<Vec<u8>> {
+        let mut decoder = ZlibDecoder::new(&self.data[..]);
+        let mut data = Vec::with_capacity(self.original_size as usize);
+        decoder.read_to_end(&mut data)?;
+        Ok(data)
+    }
+}
+
 pub struct RpaEditor {
     pub version: f32,
     pub key: u32,
     pub indexes: HashMap<String, RpaFileEntry>,
     pub archive_path: Option<String>,
+    /// Archive being read into this tab by a "load" job; `archive_path` is still the old one.
+    pub archive_loading: Option<ArchiveLoad>,
     pub modified: bool,
     pub selected_file: Option<String>,
     pub preview_data: Option<Vec<u8>>,
+    pub thumbnails: Thumbnails,
+    /// Shared handle on `archive_path` for entry reads.
+    pub archive_reader: Mutex<Option<ArchiveReader>>,
     pub preview_image: Option<egui::ColorImage>,
     pub preview_text: Option<String>,
     pub search_filter: String,
+    /// One of `CHANGE_FILTERS`.
+    pub change_filter: String,
     pub show_add_dialog: bool,
     pub add_file_path: String,
     pub add_file_name: String,
     pub status_message: String,
     pub file_to_preview: Option<String>,
     pub file_to_remove: Option<String>,
+    pub remove_to_confirm: Option<String>,
+    pub confirm_remove: bool,
+    /// Root of the files written for playback, "Open Folder" and external editing.
+    pub temp_dir: PathBuf,
+    pub clean_temp_on_close: bool,
+    /// (archive name, replacement path)
     pub file_to_replace: Option<(String, String)>,
+    /// (archive name, replacement path, message) waiting for "Replace Anyway".
+    pub replace_type_mismatch: Option<(String, String, String)>,
     pub batch_replace_to_execute: Option<String>,
     pub show_dump_dialog: bool,
     pub show_backup_dialog: bool,
     pub backup_history: Vec<BackupEntry>,
+    pub persist_backups: bool,
+    pub backup_dir: PathBuf,
+    pub disk_backups: Vec<DiskBackup>,
+    pub backup_tab: String,
+    pub backup_only_this_archive: bool,
+    pub backup_prune_days: u32,
     pub show_batch_replace_dialog: bool,
     pub batch_replace_folder: String,
+    pub batch_replace_plan: Option<BatchReplacePlan>,
     pub show_statistics_dialog: bool,
     pub auto_backup: bool,
+    pub backup_triggers: HashSet<String>,
+    pub backup_excluded_types: HashSet<String>,
     pub compression_level: u32,
     pub filter_type: String,
     pub sort_by: String,
     pub sort_ascending: bool,
+    pub file_list_view: String,
+    pub loaded_names: HashSet<String>,
+    pub loaded_size: u64,
+    pub window_title: String,
+    pub save_fallback_path: Option<String>,
+    pub validate_on_open: bool,
+    /// Read entries and the index through a memory mapping instead of file reads.
+    pub use_mmap: bool,
+    pub review_before_save: bool,
+    pub save_review: Option<SaveReview>,
+    pub large_archive_mode: String,
+    pub entry_warnings: HashMap<String, String>,
+    pub dropped_files: Vec<DroppedFile>,
+    pub show_save_as_dialog: bool,
+    pub header_format: &'static HeaderFormat,
+    pub save_as_format: &'static HeaderFormat,
+    pub save_as_key: String,
+    pub header_line: String,
+    pub index_offset: u64,
+    pub header_subkeys: Vec<u32>,
+    pub show_header_dialog: bool,
+    pub custom_key: String,
+    pub data_start_offset: u64,
+    pub header_padding: String,
+    pub reproducible_build: bool,
+    pub archive_bak_count: usize,
+    pub show_discard_dialog: bool,
+    pub window_size: [f32; 2],
+    pub theme: String,
+    pub recent_files: Vec<String>,
+    /// One slot per open archive; the active one is `None` because its state is in the fields above.
+    pub archive_tabs: Vec<Option<ArchiveTab>>,
+    pub active_tab: usize,
+    pub game_folder: Option<GameFolder>,
+    pub show_game_folder_dialog: bool,
+    pub save_after_merge: bool,
+    pub show_split_dialog: bool,
+    pub show_save_selection_dialog: bool,
+    pub selection_scope: String,
+    pub batch_rename: BatchRename,
+    pub show_batch_rename_dialog: bool,
+    pub external_edits: Vec<ExternalEdit>,
+    pub external_edits_polled: Instant,
+    pub show_external_edits: bool,
+    pub watch_folder: Option<WatchFolder>,
+    pub watch_folder_path: String,
+    pub watch_auto_save: bool,
+    pub show_watch_folder_dialog: bool,
+    pub show_log_console: bool,
+    pub autosave_enabled: bool,
+    pub autosave_minutes: u32,
+    pub autosaved_at: Instant,
+    /// Bumped by every edit, so autosave can skip archives that haven't changed.
+    pub edit_generation: u64,
+    /// `edit_generation` the last autosave snapshot was taken at.
+    pub autosaved_generation: u64,
+    /// Recounted when `edit_generation` moves on rather than every frame.
+    pub title_counts: Option<TitleCounts>,
+    /// Recounted when `edit_generation` or a file list filter changes.
+    pub filter_counts: Option<FilterCounts>,
+    /// Writes the latest autosave round off the UI thread.
+    pub autosave_thread: Option<std::thread::JoinHandle<()>>,
+    /// Sidecars written this session, removed on exit.
+    pub autosave_files: HashSet<PathBuf>,
+    pub autosave_offer: Option<AutosaveOffer>,
+    /// Changes left by a crash, offered back until restored or discarded.
+    pub crash_recoveries: Vec<CrashRecovery>,
+    pub crash_panic_message: String,
+    /// Least severe level shown in the log console, one of `LOG_LEVELS`.
+    pub log_level_filter: String,
+    pub log_search: String,
+    pub log_view: LogView,
+    pub log_to_file: bool,
+    /// Error count when the console was last looked at.
+    pub log_errors_seen: usize,
+    pub archive_stamp: Option<ArchiveStamp>,
+    pub archive_checked_at: Instant,
+    pub archive_changed_on_disk: bool,
+    /// Set from the Tools menu or `--read-only`; nothing may change or write the archive.
+    pub read_only: bool,
+    pub split_mode: String,
+    pub split_max_mb: u64,
+    pub archive_diff: Option<ArchiveDiff>,
+    pub entry_diff: Option<(String, Vec<DiffLine>)>,
+    /// Entries whose content doesn't match their extension, with the detected type.
+    pub sniffed_types: HashMap<String, &'static str>,
+    /// How bulk extraction treats files that already exist, see `EXTRACT_POLICIES`.
+    pub extract_policy: String,
+    pub extract_layout: String,
+    pub extract_pattern: String,
+    pub extract_respect_filter: bool,
+    pub pending_extract: Option<PendingExtract>,
+    pub extract_report: Option<ExtractReport>,
+    pub batch_replace_report: Option<BatchReplaceReport>,
+    pub duplicate_groups: Option<Vec<DuplicateGroup>>,
+    /// SHA-256 of entries hashed so far; dropped when an entry's content changes.
+    pub checksums: HashMap<String, String>,
+    pub show_checksum_column: bool,
+    /// Checksum file waiting for the hashing job before it is compared.
+    pub checksum_verify: Option<(PathBuf, BTreeMap<String, String>)>,
+    pub checksum_report: Option<ChecksumReport>,
+    pub integrity_report: Option<IntegrityReport>,
+    /// Archive that failed to load and the error, offered for a recovery scan.
+    pub recovery_offer: Option<(String, String)>,
+    pub pending_adds: VecDeque<PendingAdd>,
+    pub add_conflict: Option<PendingAdd>,
+    pub folder_add: Option<FolderAdd>,
+    pub conflict_apply_to_all: bool,
+    pub conflict_resolution: Option<String>,
+    pub orphaned_ranges: Vec<(u64, u64)>,
+    pub show_orphans_dialog: bool,
+    pub unused_assets: Vec<String>,
+    pub show_unused_assets_dialog: bool,
+    pub editing_file: Option<String>,
+    pub edit_buffer: String,
+    pub preview_highlight: Option<egui::text::LayoutJob>,
+    pub hex_editing_file: Option<String>,
+    pub hex_buffer: Vec<u8>,
+    pub hex_cursor: usize,
+    pub hex_anchor: Option<usize>,
+    pub hex_nibble: Option<u8>,
+    pub hex_input: String,
+    pub hex_page: Vec<u8>,
+    pub hex_page_key: Option<(String, usize)>,
+    pub hex_goto_input: String,
+    pub hex_search: String,
+    pub hex_search_mode: String,
+    pub hex_match: Option<usize>,
+    pub hex_search_task: Option<HexSearch>,
+    pub file_styles: Vec<FileStyle>,
+    pub show_icon_legend: bool,
+    pub health_checks: Vec<HealthCheck>,
+    pub graphics_backend: Option<String>,
+    pub show_health_dialog: bool,
+    pub new_style_pattern: String,
+    pub new_style_icon: String,
+    pub new_style_color: [u8; 3],
+    pub jobs: Vec<Job>,
+    pub next_job_id: usize,
+    pub job_sender: Sender<JobEvent>,
+    pub job_receiver: Receiver<JobEvent>,
+    pub jobs_running: bool,
+    pub job_run_mode: String,
+    pub show_jobs_dialog: bool,
+    pub new_job_kind: String,
+    pub new_job_scope: String,
+    pub new_job_format: String,
+    pub show_dialogue_import_dialog: bool,
+    pub dialogue_import_path: Option<PathBuf>,
+    pub dialogue_import_mode: String,
+    pub dialogue_import_language: String,
     pub image_zoom: f32,
+    pub image_pan: egui::Vec2,
+    pub image_fit_mode: String,
+    pub image_background: String,
+    pub image_background_color: [u8; 3],
+    pub image_alpha_only: bool,
+    /// Texture of `preview_image`, with whether it shows the alpha only.
+    pub preview_texture: Option<(bool, egui::TextureHandle)>,
+    pub image_comparison: Option<ImageComparison>,
+    pub image_compare_mode: String,
+    pub image_compare_split: f32,
     pub hex_view_offset: usize,
     pub audio_player: AudioPlayer,
     pub is_playing: bool,
+    pub playing_audio: Option<String>,
+    pub video_info: HashMap<String, VideoInfo>,
+    pub video_thumbnail: Option<(String, egui::TextureHandle)>,
+    pub preview_animation: Vec<AnimationFrame>,
+    pub animation_frame: usize,
+    pub animation_playing: bool,
+    pub animation_elapsed: Duration,
+    pub show_frame_export_dialog: bool,
+    pub frame_export_file: Option<String>,
+    pub frame_export_every: u32,
+    pub frame_export_start: String,
+    pub frame_export_end: String,
+    pub audio_loop_mode: String,
     pub show_close_confirm: bool,
     pub toasts: Vec<Toast>,
-
-
-    pub audio_device: AudioDevice,
+    pub toast_history: VecDeque<Toast>,
+    pub show_notification_history: bool,
+    pub preview_associations: HashMap<String, String>,
+    pub show_preview_settings_dialog: bool,
+    pub new_association_ext: String,
+    pub new_association_mode: String,
+    pub show_encoding_dialog: bool,
+    pub encoding_conversions: Vec<EncodingConversion>,
+    /// Per-entry labels, notes and bookmarks; session state, never written to the archive.
+    pub entry_tags: HashMap<String, Vec<String>>,
+    pub entry_notes: HashMap<String, String>,
+    pub bookmarks: BTreeSet<String>,
+    pub new_tag: String,
+    pub regenerate_prefixes: bool,
+    pub subset_fonts_on_add: bool,
+
+
+    pub audio_device: Option<AudioDevice>,
     pub player: Option<Player>,
 }
 
 impl Default for RpaEditor {
     fn default() -> Self {
+        let (job_sender, job_receiver) = std::sync::mpsc::channel();
+        let settings = Settings::default();
         Self {
             version: 3.2,
             key: 0xDEADBEEF,
             indexes: HashMap::new(),
             archive_path: None,
+            archive_loading: None,
             modified: false,
             selected_file: None,
             preview_data: None,
+            thumbnails: Thumbnails::default(),
+            archive_reader: Mutex::new(None),
             preview_image: None,
             preview_text: None,
             search_filter: String::new(),
+            change_filter: "all".to_string(),
             show_add_dialog: false,
             add_file_path: String::new(),
             add_file_name: String::new(),
             status_message: "Ready".to_string(),
             file_to_preview: None,
             file_to_remove: None,
+            remove_to_confirm: None,
+            confirm_remove: settings.confirm_remove,
+            temp_dir: settings.temp_dir,
+            clean_temp_on_close: settings.clean_temp_on_close,
             file_to_replace: None,
+            replace_type_mismatch: None,
             batch_replace_to_execute: None,
             show_dump_dialog: false,
             show_backup_dialog: false,
             backup_history: Vec::new(),
+            persist_backups: settings.persist_backups,
+            backup_dir: settings.backup_dir,
+            disk_backups: Vec::new(),
+            backup_tab: "session".to_string(),
+            backup_only_this_archive: true,
+            backup_prune_days: settings.backup_prune_days,
             show_batch_replace_dialog: false,
             batch_replace_folder: String::new(),
+            batch_replace_plan: None,
             show_statistics_dialog: false,
-            auto_backup: true,
-            compression_level: 6,
-            filter_type: "all".to_string(),
-            sort_by: "name".to_string(),
-            sort_ascending: true,
+            auto_backup: settings.auto_backup,
+            backup_triggers: settings.backup_triggers,
+            backup_excluded_types: settings.backup_excluded_types,
+            compression_level: settings.compression_level,
+            filter_type: settings.filter_type,
+            sort_by: settings.sort_by,
+            sort_ascending: settings.sort_ascending,
+            file_list_view: settings.file_list_view,
+            loaded_names: HashSet::new(),
+            loaded_size: 0,
+            window_title: String::new(),
+            save_fallback_path: None,
+            validate_on_open: settings.validate_on_open,
+            use_mmap: settings.use_mmap,
+            review_before_save: settings.review_before_save,
+            save_review: None,
+            large_archive_mode: settings.large_archive_mode,
+            entry_warnings: HashMap::new(),
+            dropped_files: Vec::new(),
+            show_save_as_dialog: false,
+            header_format: HeaderFormat::standard(3.2),
+            save_as_format: HeaderFormat::standard(3.0),
+            save_as_key: String::new(),
+            header_line: String::new(),
+            index_offset: 0,
+            header_subkeys: Vec::new(),
+            show_header_dialog: false,
+            custom_key: String::new(),
+            data_start_offset: DEFAULT_DATA_START,
+            header_padding: String::new(),
+            reproducible_build: settings.reproducible_build,
+            archive_bak_count: settings.archive_bak_count,
+            show_discard_dialog: false,
+            window_size: settings.window_size,
+            theme: settings.theme,
+            recent_files: settings.recent_files,
+            archive_tabs: vec![None],
+            active_tab: 0,
+            game_folder: None,
+            show_game_folder_dialog: false,
+            save_after_merge: false,
+            show_split_dialog: false,
+            show_save_selection_dialog: false,
+            selection_scope: "visible".to_string(),
+            batch_rename: BatchRename::default(),
+            show_batch_rename_dialog: false,
+            external_edits: Vec::new(),
+            external_edits_polled: Instant::now(),
+            show_external_edits: false,
+            watch_folder: None,
+            watch_folder_path: String::new(),
+            watch_auto_save: false,
+            show_watch_folder_dialog: false,
+            show_log_console: false,
+            autosave_enabled: settings.autosave_enabled,
+            autosave_minutes: settings.autosave_minutes,
+            autosaved_at: Instant::now(),
+            edit_generation: 0,
+            autosaved_generation: 0,
+            title_counts: None,
+            filter_counts: None,
+            autosave_thread: None,
+            autosave_files: HashSet::new(),
+            autosave_offer: None,
+            crash_recoveries: Vec::new(),
+            crash_panic_message: String::new(),
+            log_level_filter: settings.log_level_filter,
+            log_search: String::new(),
+            log_view: LogView::default(),
+            log_to_file: settings.log_to_file,
+            log_errors_seen: 0,
+            archive_stamp: None,
+            archive_checked_at: Instant::now(),
+            archive_changed_on_disk: false,
+            read_only: false,
+            split_mode: "type".to_string(),
+            split_max_mb: 2000,
+            archive_diff: None,
+            entry_diff: None,
+            sniffed_types: HashMap::new(),
+            extract_policy: settings.extract_policy,
+            extract_layout: settings.extract_layout,
+            extract_pattern: String::new(),
+            extract_respect_filter: false,
+            pending_extract: None,
+            extract_report: None,
+            batch_replace_report: None,
+            duplicate_groups: None,
+            checksums: HashMap::new(),
+            show_checksum_column: false,
+            checksum_verify: None,
+            checksum_report: None,
+            integrity_report: None,
+            recovery_offer: None,
+            pending_adds: VecDeque::new(),
+            add_conflict: None,
+            folder_add: None,
+            conflict_apply_to_all: false,
+            conflict_resolution: None,
+            orphaned_ranges: Vec::new(),
+            show_orphans_dialog: false,
+            unused_assets: Vec::new(),
+            show_unused_assets_dialog: false,
+            editing_file: None,
+            edit_buffer: String::new(),
+            preview_highlight: None,
+            hex_editing_file: None,
+            hex_buffer: Vec::new(),
+            hex_cursor: 0,
+            hex_anchor: None,
+            hex_nibble: None,
+            hex_input: String::new(),
+            hex_page: Vec::new(),
+            hex_page_key: None,
+            hex_goto_input: String::new(),
+            hex_search: String::new(),
+            hex_search_mode: "hex".to_string(),
+            hex_match: None,
+            hex_search_task: None,
+            file_styles: settings.file_styles,
+            show_icon_legend: false,
+            health_checks: Vec::new(),
+            graphics_backend: None,
+            show_health_dialog: false,
+            new_style_pattern: String::new(),
+            new_style_icon: "⭐".to_string(),
+            new_style_color: [255, 200, 80],
+            jobs: Vec::new(),
+            next_job_id: 0,
+            job_sender,
+            job_receiver,
+            jobs_running: false,
+            job_run_mode: settings.job_run_mode,
+            show_jobs_dialog: false,
+            new_job_kind: "extract".to_string(),
+            new_job_scope: "visible".to_string(),
+            new_job_format: "png".to_string(),
+            show_dialogue_import_dialog: false,
+            dialogue_import_path: None,
+            dialogue_import_mode: "patch".to_string(),
+            dialogue_import_language: String::new(),
             image_zoom: 1.0,
+            image_pan: egui::Vec2::ZERO,
+            image_fit_mode: settings.image_fit_mode,
+            image_background: settings.image_background,
+            image_background_color: settings.image_background_color,
+            image_alpha_only: false,
+            preview_texture: None,
+            image_comparison: None,
+            image_compare_mode: settings.image_compare_mode,
+            image_compare_split: 0.5,
             hex_view_offset: 0,
             audio_player: AudioPlayer::new(),
             is_playing: false,
+            playing_audio: None,
+            video_info: HashMap::new(),
+            video_thumbnail: None,
+            preview_animation: Vec::new(),
+            animation_frame: 0,
+            animation_playing: true,
+            animation_elapsed: Duration::ZERO,
+            show_frame_export_dialog: false,
+            frame_export_file: None,
+            frame_export_every: 1,
+            frame_export_start: String::new(),
+            frame_export_end: String::new(),
+            audio_loop_mode: settings.audio_loop_mode,
             show_close_confirm: false,
             toasts: Vec::new(),
-            audio_device: AudioDevice::new().unwrap(),
+            toast_history: VecDeque::new(),
+            show_notification_history: false,
+            preview_associations: settings.preview_associations,
+            show_preview_settings_dialog: false,
+            new_association_ext: String::new(),
+            new_association_mode: "text".to_string(),
+            show_encoding_dialog: false,
+            encoding_conversions: Vec::new(),
+            entry_tags: HashMap::new(),
+            entry_notes: HashMap::new(),
+            bookmarks: BTreeSet::new(),
+            new_tag: String::new(),
+            regenerate_prefixes: settings.regenerate_prefixes,
+            subset_fonts_on_add: settings.subset_fonts_on_add,
+            audio_device: AudioDevice::new().ok(),
             player: None,
         }
     }
 }
 
 impl RpaEditor {
-    pub(crate) fn new(_cc: &eframe::CreationContext<'_>) -> Self {
-        Self::default()
+    pub(crate) fn new(cc: &eframe::CreationContext<'_>) -> Self {
+        let mut editor = Self {
+            graphics_backend: Self::detect_graphics(cc),
+            ..Self::default()
+        };
+        editor.run_health_check();
+        if settings_path().is_file()
+            && let Err(e) = editor.load_settings()
+        {
+            editor.add_error(format!("Could not read settings: {}", e));
+        }
+        cc.egui_ctx.set_theme(theme_preference(&editor.theme));
+        editor.find_crash_recovery();
+        if std::env::args().any(|arg| arg == READ_ONLY_FLAG) {
+            editor.set_read_only(true);
+        }
+        editor.open_from_args();
+        editor
     }
 
     pub(crate) fn unload_rpa(&mut self) -> anyhow::Result<()> {
+        self.clean_archive_temp();
+        self.remove_autosave();
+        self.autosave_offer = None;
+        self.close_archive_reader();
         self.version = 3.2;
         self.key = 0xDEADBEEF;
+        self.header_format = HeaderFormat::standard(3.2);
         self.indexes = HashMap::new();
         self.archive_path = None;
+        self.archive_loading = None;
         self.modified = false;
         self.selected_file = None;
         self.preview_data = None;
-        self.preview_image = None;
+        self.set_preview_image(None);
         self.preview_text = None;
         self.search_filter = String::new();
+        self.change_filter = "all".to_string();
+        self.save_review = None;
         self.show_add_dialog = false;
         self.add_file_path = String::new();
         self.add_file_name = String::new();
         self.status_message = "Ready".to_string();
         self.file_to_preview = None;
         self.file_to_remove = None;
+        self.remove_to_confirm = None;
+        self.show_batch_rename_dialog = false;
+        self.external_edits = Vec::new();
+        self.show_external_edits = false;
+        self.watch_folder = None;
+        self.archive_stamp = None;
+        self.archive_changed_on_disk = false;
         self.file_to_replace= None;
+        self.replace_type_mismatch = None;
         self.batch_replace_to_execute= None;
         self.show_dump_dialog= false;
 
@@ -143,314 +830,184 @@ impl RpaEditor {
         self.backup_history= Vec::new();
         self.show_batch_replace_dialog= false;
         self.batch_replace_folder= String::new();
+        self.batch_replace_plan = None;
         self.show_statistics_dialog= false;
-        self.auto_backup = true;
-        self.compression_level= 6;
 
-        self.filter_type= "all".to_string();
-        self.sort_by= "name".to_string();
-        self.sort_ascending= true;
 
         self.image_zoom= 1.0;
+        self.image_pan = egui::Vec2::ZERO;
         self.hex_view_offset= 0;
         self.audio_player= AudioPlayer::new();
         self.is_playing= false;
+        self.playing_audio = None;
+        self.video_info = HashMap::new();
+        self.video_thumbnail = None;
+        self.preview_animation = Vec::new();
+        self.loaded_names = HashSet::new();
+        self.loaded_size = 0;
+        self.title_counts = None;
+        self.filter_counts = None;
+        self.entry_warnings = HashMap::new();
+        self.sniffed_types = HashMap::new();
+        self.pending_extract = None;
+        self.extract_report = None;
+        self.pending_adds = VecDeque::new();
+        self.add_conflict = None;
+        self.conflict_resolution = None;
+        self.conflict_apply_to_all = false;
+        self.save_after_merge = false;
+        self.folder_add = None;
+        self.batch_replace_report = None;
+        self.duplicate_groups = None;
+        self.checksums = HashMap::new();
+        self.thumbnails.clear();
+        self.checksum_verify = None;
+        self.checksum_report = None;
+        self.integrity_report = None;
+        self.unused_assets = Vec::new();
+        self.encoding_conversions = Vec::new();
+        self.entry_tags = HashMap::new();
+        self.entry_notes = HashMap::new();
+        self.bookmarks = BTreeSet::new();
+        self.header_line = String::new();
+        self.index_offset = 0;
+        self.header_subkeys = Vec::new();
+        self.orphaned_ranges = Vec::new();
+        self.editing_file = None;
+        self.edit_buffer = String::new();
+        self.hex_editing_file = None;
+        self.hex_buffer = Vec::new();
+        self.hex_search_task = None;
         Ok(())
     }
 
     pub(crate) fn load_rpa(&mut self, path: &str) -> anyhow::Result<()> {
-        let mut file = File::open(path)?;
-
-        self.version = self.get_version(&mut file)?;
+        let index = read_archive_index(path, self.use_mmap)?;
+        self.apply_archive_index(path, index)
+    }
 
-        self.indexes = self.extract_indexes(&mut file)?;
+    /// Shows the archive at `path` from its already read `index`.
+    pub(crate) fn apply_archive_index(&mut self, path: &str, index: ArchiveIndex) -> anyhow::Result<()> {
+        self.close_archive_reader();
+
+        self.header_format = index.header.format;
+        self.version = index.header.format.version;
+        self.key = index.header.key;
+        self.header_line = index.header_line;
+        self.index_offset = index.header.offset;
+        self.header_subkeys = index.header.subkeys;
+        self.indexes = index.entries;
         self.archive_path = Some(path.to_string());
         self.modified = false;
+        self.loaded_names = self.indexes.keys().cloned().collect();
+        self.loaded_size = self.indexes.values().map(|e| e.length).sum();
+        self.title_counts = None;
+        self.filter_counts = None;
 
         self.selected_file = None;
         self.preview_data = None;
-        self.preview_image = None;
+        self.set_preview_image(None);
         self.preview_text = None;
 
-        self.status_message = format!("Loaded {} files from {}", self.indexes.len(), path);
-        Ok(())
-    }
-
-    fn load_entries_data(&self, index: &mut HashMap<String, RpaFileEntry>, file: &mut File, ) -> anyhow::Result<()> {
-        for (filename, entry) in index.iter() {
-            if entry.offset + entry.length > file.metadata()?.len() {
-                println!("❌ ERREUR : dépassement du fichier !");
-            }
-
-            file.seek(SeekFrom::Start(entry.offset))?;
-            let mut buffer = vec![0u8; entry.length as usize];
-            match file.read_exact(&mut buffer) {
-                Ok(_) => {}
-                Err(e) => println!("❌ Lecture échouée: {filename} ({})", e),
-            }
+        self.entry_warnings = HashMap::new();
+        let large = self.is_large_archive_mode();
+        if self.index_looks_invalid() {
+            self.add_warning("Index offsets look invalid, try Tools > Recover Key");
+        } else if self.validate_on_open {
+            self.entry_warnings = self.check_entry_bounds()?;
         }
 
-        Ok(())
-    }
-
-    fn parse_index_pickle(&self, data: &[u8]) -> anyhow::Result<HashMap<String, RpaFileEntry>> {
-        let value: Value = serde_pickle::value_from_slice(data, DeOptions::new().decode_strings())?;
-
-        let mut indexes = HashMap::new();
-
-        if let Value::Dict(dict) = value {
-            for (key, val) in dict {
-                let filename = key.to_string().replace("\"", "");
-
-                if let Value::List(list) = val {
-                    if list.len() == 1 {
-                        if let Value::Tuple(tuple) = &list[0] {
-                            if tuple.len() == 3 {
-                                if let (
-                                    Value::I64(offset),
-                                    Value::I64(length),
-                                    Value::Bytes(prefix),
-                                ) = (&tuple[0], &tuple[1], &tuple[2])
-                                {
-                                    let offset = *offset as u64 ^ self.key as u64;
-                                    let length = *length as u64 ^ self.key as u64;
-                                    indexes.insert(
-                                        filename.clone(),
-                                        RpaFileEntry {
-                                            offset,
-                                            length,
-                                            prefix: prefix.clone(),
-                                            data: None,
-                                            modified: false,
-                                            to_delete: false,
-                                        },
-                                    );
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-
-            Ok(indexes)
+        self.orphaned_ranges = if large || self.index_looks_invalid() {
+            Vec::new()
         } else {
-            Err(anyhow::anyhow!("Pickle root is not a dict"))
-        }
-    }
-
-    fn get_version(&self, file: &mut File) -> anyhow::Result<f32> {
-        file.seek(SeekFrom::Start(0))?;
-        let mut buffer = vec![0u8; 32];
-        file.read_exact(&mut buffer)?;
-
-        let header = String::from_utf8_lossy(&buffer);
+            self.find_orphaned_ranges()
+        };
+        self.detect_entry_types();
 
-        if header.starts_with("RPA-3.2 ") {
-            Ok(3.2)
-        } else if header.starts_with("RPA-3.0 ") {
-            Ok(3.0)
-        } else if header.starts_with("RPA-2") {
-            Ok(2.0)
+        self.status_message = if self.entry_warnings.is_empty() {
+            format!("Loaded {} files from {}", self.indexes.len(), path)
         } else {
-            Err(anyhow::anyhow!("Unsupported RPA version"))
-        }
-    }
-
-    fn extract_indexes(&mut self, file: &mut File, ) -> anyhow::Result<HashMap<String, RpaFileEntry>> {
-        file.seek(SeekFrom::Start(0))?;
-        let mut line = Vec::new();
-        let mut byte = [0u8; 1];
-        while file.read_exact(&mut byte).is_ok() && byte[0] != b'\n' {
-            line.push(byte[0]);
-        }
-
-        let header_line = String::from_utf8_lossy(&line);
-        let parts: Vec<&str> = header_line.split_whitespace().collect();
-
-        let offset = u64::from_str_radix(parts[1], 16)?;
-
-        if self.version >= 3.0 {
-            self.key = 0;
-            let key_start = if self.version >= 3.2 { 3 } else { 2 };
-            for &key_part in &parts[key_start..] {
-                let subkey = u32::from_str_radix(key_part, 16)?;
-                self.key ^= subkey;
-            }
+            format!(
+                "Loaded {} files from {} ({} suspicious)",
+                self.indexes.len(),
+                path,
+                self.entry_warnings.len()
+            )
+        };
+        if !self.orphaned_ranges.is_empty() {
+            let orphaned: u64 = self.orphaned_ranges.iter().map(|(_, length)| length).sum();
+            self.status_message
+                .push_str(&format!(", {} orphaned", Self::format_bytes(orphaned)));
         }
-
-        file.seek(SeekFrom::Start(offset))?;
-        let mut compressed_data = Vec::new();
-        file.read_to_end(&mut compressed_data)?;
-
-        let mut decoder = ZlibDecoder::new(&compressed_data[..]);
-        let mut decompressed = Vec::new();
-        decoder.read_to_end(&mut decompressed)?;
-
-        match self.parse_index_pickle(&decompressed) {
-            Ok(mut indexes) => {
-                self.load_entries_data(&mut indexes, file)?;
-                Ok(indexes)
-            }
-            Err(e) => {
-                eprintln!("⚠️ Erreur pickle: {e}, on tente l'extraction heuristique...");
-                self.parse_binary_dict(&decompressed)
-            }
+        if large {
+            self.status_message
+                .push_str(" (large archive mode: checks deferred to Tools > Run Deferred Checks)");
         }
+        self.remember_archive_stamp();
+        Ok(())
     }
 
-    fn parse_binary_dict(&self, data: &[u8]) -> anyhow::Result<HashMap<String, RpaFileEntry>> {
-        let mut indexes = HashMap::new();
-        let mut pos = 0;
-
-        while pos < data.len() {
-            if let Some((filename, filename_end)) = self.extract_filename_at_pos(data, pos) {
-                if let Some(entry) = self.find_entry_data_after_filename(data, filename_end) {
-                    indexes.insert(filename, entry);
-                    pos = filename_end + 50;
-                } else {
-                    pos = filename_end;
-                }
-            } else {
-                pos += 1;
-            }
-        }
-
-        Ok(indexes)
+    pub(crate) fn load_file_data(&self, filename: &str) -> anyhow::Result<Vec<u8>> {
+        let mut content = Vec::new();
+        self.read_entry_into(filename, &mut content)?;
+        Ok(content)
     }
 
-    fn extract_filename_at_pos(&self, data: &[u8], start_pos: usize) -> Option<(String, usize)> {
-        let mut pos = start_pos;
-
-        while pos < data.len() {
-            if data[pos].is_ascii_graphic() || data[pos] == b'/' {
-                break;
+    pub(crate) fn open_entry_stream(&self, filename: &str) -> anyhow::Result<EntryStream> {
+        if let Some(entry) = self.indexes.get(filename) {
+            if let Some(ref data) = entry.data {
+                return Ok(EntryStream::from_memory(data.clone()));
             }
-            pos += 1;
-        }
-
-        if pos >= data.len() {
-            return None;
-        }
-
-        let filename_start = pos;
-
-        let is_valid_char = |c: u8| {
-            c.is_ascii() && (c as char).is_ascii_graphic() && !"\"\\:*?<>|".contains(c as char)
-        };
-
-        while pos < data.len() && is_valid_char(data[pos]) {
-            pos += 1;
-        }
-
-        let slice = &data[filename_start..pos];
-
-        if let Ok(filename) = std::str::from_utf8(slice) {
-            if self.is_valid_filename(filename) {
-                return Some((filename.to_string(), pos));
+            if let Some(ref source) = entry.merged {
+                return source.open();
             }
-        }
-
-        None
-    }
-
-    fn find_entry_data_after_filename(&self, data: &[u8], start_pos: usize, ) -> Option<RpaFileEntry> {
-        let search_end = std::cmp::min(start_pos + 100, data.len());
 
-        for pos in start_pos..search_end {
-            if pos + 10 < data.len() && data[pos] == b'J' {
-                if let Some((offset, length, prefix)) = self.extract_j_values_at(data, pos) {
-                    if self.is_reasonable_entry(offset, length) {
-                        return Some(RpaFileEntry {
-                            offset,
-                            length,
-                            prefix,
-                            data: None,
-                            modified: false,
-                            to_delete: false,
-                        });
-                    }
-                }
+            if let Some(ref archive_path) = self.archive_path {
+                let file = File::open(archive_path)?;
+                return Ok(EntryStream::from_archive(file, entry.segments.clone()));
             }
         }
 
-        None
+        Err(anyhow::anyhow!("File not found"))
     }
 
-    fn extract_j_values_at(&self, data: &[u8], pos: usize) -> Option<(u64, u64, Vec<u8>)> {
-        if pos + 9 < data.len() && data[pos] == b'J' {
-            let val1_bytes = [data[pos + 1], data[pos + 2], data[pos + 3], data[pos + 4]];
-            let val1 = u32::from_le_bytes(val1_bytes);
-
-            for next_pos in (pos + 5)..(pos + 15) {
-                if next_pos + 4 < data.len() && data[next_pos] == b'J' {
-                    let val2_bytes = [
-                        data[next_pos + 1],
-                        data[next_pos + 2],
-                        data[next_pos + 3],
-                        data[next_pos + 4],
-                    ];
-                    let val2 = u32::from_le_bytes(val2_bytes);
-
-                    let offset = (val1 ^ self.key) as u64;
-                    let length = (val2 ^ self.key) as u64;
-
-                    if self.is_reasonable_entry(offset, length) {
-                        return Some((offset, length, Vec::new()));
-                    }
-                }
-            }
-        }
+    pub(crate) fn stream_entry_to_temp(&self, filename: &str) -> anyhow::Result<std::path::PathBuf> {
+        self.stream_entry_to_dir(filename, &self.archive_temp_dir("preview"))
+    }
 
-        None
+    pub(crate) fn stream_entry_to_dir(&self, filename: &str, dir: &Path) -> anyhow::Result<std::path::PathBuf> {
+        let file_path = entry_output_path(dir, filename)?;
+        self.stream_entry_to_file(filename, &file_path)?;
+        Ok(file_path)
     }
 
-    fn is_valid_filename(&self, filename: &str) -> bool {
-        if filename.len() < 2 || filename.len() > 200 {
-            return false;
+    pub(crate) fn stream_entry_to_file(&self, filename: &str, path: &Path) -> anyhow::Result<()> {
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent)?;
         }
 
-        let extensions = [
-            ".png", ".jpg", ".jpeg", ".webp", ".webm", ".avi", ".mp4", ".mov", ".ogg", ".wav",
-            ".mp3", ".flac", ".rpy", ".rpyc",
-        ];
-
-        extensions.iter().any(|&ext| filename.ends_with(ext))
-    }
-
-    fn is_reasonable_entry(&self, offset: u64, length: u64) -> bool {
-        offset > 50
-            && offset < 2_000_000_000
-            && length > 0
-            && length < 500_000_000
-            && offset + length < 2_000_000_000
+        let mut stream = self.open_entry_stream(filename)?;
+        let mut out = File::create(path)?;
+        std::io::copy(&mut stream, &mut out)?;
+        Ok(())
     }
 
-    pub(crate) fn load_file_data(&self, filename: &str) -> anyhow::Result<Vec<u8>> {
-        if let Some(entry) = self.indexes.get(filename) {
-            if let Some(ref data) = entry.data {
-                return Ok(data.clone());
-            }
-
-            if let Some(ref archive_path) = self.archive_path {
-                let mut file = File::open(archive_path)?;
-                file.seek(SeekFrom::Start(entry.offset))?;
-
-                let mut content = Vec::new();
-                content.extend_from_slice(&entry.prefix);
-
-                let remaining_length = entry.length - entry.prefix.len() as u64;
-                let mut buffer = vec![0u8; remaining_length as usize];
-                file.read_exact(&mut buffer)?;
-                content.extend_from_slice(&buffer);
-
-                return Ok(content);
+    /// Extracts entries to a working folder and puts their paths on the clipboard, one per line.
+    pub(crate) fn extract_and_copy_paths(&mut self, ctx: &egui::Context, filenames: &[String]) {
+        let dir = self.archive_temp_dir("extract");
+        let mut paths = Vec::new();
+        for filename in filenames {
+            match self.stream_entry_to_dir(filename, &dir) {
+                Ok(path) => paths.push(path.to_string_lossy().to_string()),
+                Err(e) => error!("❌ Failed to extract {}: {}", filename, e),
             }
         }
 
-        Err(anyhow::anyhow!("File not found"))
+        ctx.copy_text(paths.join("\n"));
+        self.add_toast(format!("Extracted {} files, paths copied to clipboard", paths.len()));
     }
 
-    fn decompile_rpyc(&self, data: &[u8]) -> Option<String> {
+    pub(crate) fn decompile_rpyc(&self, data: &[u8]) -> Option<String> {
         if data.len() < 16 {
             return None;
         }
@@ -473,25 +1030,25 @@ impl RpaEditor {
                     u32::from_le_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]])
                         as usize;
 
-                if str_len > 0 && str_len < 1000 && pos + 4 + str_len < data.len() {
-                    if let Ok(s) = String::from_utf8(data[pos + 4..pos + 4 + str_len].to_vec()) {
-                        if s.len() > 2
-                            && s.chars().all(|c| c.is_ascii_graphic() || c.is_whitespace())
-                        {
-                            if s.starts_with("label_") || s.starts_with("scene_") {
-                                labels.push(s.clone());
-                            } else if s.len() < 30
-                                && s.chars().any(|c| c.is_alphabetic())
-                                && !s.contains(' ')
-                            {
-                                characters.push(s.clone());
-                            } else {
-                                found_strings.push(s);
-                            }
-                            pos += 4 + str_len;
-                            continue;
-                        }
+                if str_len > 0
+                    && str_len < 1000
+                    && pos + 4 + str_len < data.len()
+                    && let Ok(s) = String::from_utf8(data[pos + 4..pos + 4 + str_len].to_vec())
+                    && s.len() > 2
+                    && s.chars().all(|c| c.is_ascii_graphic() || c.is_whitespace())
+                {
+                    if s.starts_with("label_") || s.starts_with("scene_") {
+                        labels.push(s.clone());
+                    } else if s.len() < 30
+                        && s.chars().any(|c| c.is_alphabetic())
+                        && !s.contains(' ')
+                    {
+                        characters.push(s.clone());
+                    } else {
+                        found_strings.push(s);
                     }
+                    pos += 4 + str_len;
+                    continue;
                 }
             }
             pos += 1;
@@ -527,63 +1084,191 @@ impl RpaEditor {
     }
 
     pub(crate) fn preview_file(&mut self, filename: &str) {
+        if self.text_edit_dirty() || self.hex_edit_dirty() {
+            self.add_warning("Unapplied edits were discarded");
+        }
+        self.discard_text_edit();
+        self.close_hex_edit();
+        self.hex_match = None;
+        self.hex_search_task = None;
+        self.hex_page_key = None;
+        self.hex_view_offset = 0;
+        self.preview_animation = Vec::new();
+        self.animation_frame = 0;
+        self.animation_elapsed = Duration::ZERO;
+
+        // Hex pages are read from the archive on demand, so big entries never load whole.
+        if self.get_preview_mode(filename) == "hex" {
+            self.preview_data = None;
+            self.set_preview_image(None);
+            self.preview_text = None;
+            self.preview_highlight = None;
+            let size = self.indexes.get(filename).map_or(0, |entry| entry.length);
+            self.status_message = format!("Hex view of {} ({})", filename, Self::format_bytes(size));
+            return;
+        }
+
+        let large = self.is_large_archive_mode();
+        if large
+            && let Some(length) = self.indexes.get(filename).map(|entry| entry.length)
+            && length > LARGE_PREVIEW_LIMIT
+        {
+            self.preview_data = None;
+            self.set_preview_image(None);
+            self.preview_text = None;
+            self.preview_highlight = None;
+            self.status_message = format!(
+                "{} is {}, not previewed in large archive mode",
+                filename,
+                Self::format_bytes(length)
+            );
+            return;
+        }
+
         if let Ok(data) = self.load_file_data(filename) {
-            self.preview_data = Some(data.clone());
-            self.preview_image = None;
+            self.preview_data = None;
+            self.set_preview_image(None);
             self.preview_text = None;
             self.image_zoom = 1.0;
+            self.image_pan = egui::Vec2::ZERO;
             self.hex_view_offset = 0;
 
-            let lower = filename.to_lowercase();
-
-            if lower.ends_with(".png")
-                || lower.ends_with(".jpg")
-                || lower.ends_with(".jpeg")
-                || lower.ends_with(".webp")
-            {
-                if let Ok(img) = image::load_from_memory(&data) {
-                    let rgba = img.to_rgba8();
-                    let size = [rgba.width() as usize, rgba.height() as usize];
-                    let color_image = egui::ColorImage::from_rgba_unmultiplied(size, &rgba);
-                    self.preview_image = Some(color_image);
-                    self.status_message = format!(
-                        "Loaded image: {}×{} ({:.1} KB)",
-                        rgba.width(),
-                        rgba.height(),
-                        data.len() as f32 / 1024.0
-                    );
-                } else {
-                    self.status_message = "Failed to load image".to_string();
+            let mode = self.get_preview_mode(filename).to_string();
+            match mode.as_str() {
+                "image" => {
+                    let animation = if large { None } else { decode_animation(filename, &data) };
+                    if let Some(frames) = animation {
+                        let first = frames[0].image.clone();
+                        self.status_message = format!(
+                            "Loaded animation: {}×{}, {} frames",
+                            first.width(),
+                            first.height(),
+                            frames.len()
+                        );
+                        self.set_preview_image(Some(first));
+                        self.preview_animation = frames;
+                        self.animation_playing = true;
+                    } else if let Ok(img) = image::load_from_memory(&data) {
+                        let img = if large {
+                            img.thumbnail(LARGE_PREVIEW_DIMENSION, LARGE_PREVIEW_DIMENSION)
+                        } else {
+                            img
+                        };
+                        let rgba = img.to_rgba8();
+                        let size = [rgba.width() as usize, rgba.height() as usize];
+                        let color_image = egui::ColorImage::from_rgba_unmultiplied(size, &rgba);
+                        self.set_preview_image(Some(color_image));
+                        self.status_message = format!(
+                            "Loaded image: {}×{} ({:.1} KB)",
+                            rgba.width(),
+                            rgba.height(),
+                            data.len() as f32 / 1024.0
+                        );
+                    } else {
+                        self.status_message = "Failed to load image".to_string();
+                    }
                 }
-            } else if lower.ends_with(".rpyc") {
-                if let Some(decompiled) = self.decompile_rpyc(&data) {
-                    self.preview_text = Some(decompiled);
-                    self.status_message = "Decompiled .rpyc file (enhanced extraction)".to_string();
-                } else {
-                    self.status_message = "Could not decompile .rpyc file".to_string();
+                "font" => self.preview_font(filename, &data),
+                "rpyc" => match self.decompile_cached(&data) {
+                    Ok(decompiled) => {
+                        self.preview_text = Some(decompiled);
+                        self.status_message = "Decompiled .rpyc file".to_string();
+                    }
+                    Err(e) => {
+                        warn!("⚠️ AST decompilation failed ({}), falling back to string scan", e);
+                        if let Some(decompiled) = self.decompile_rpyc(&data) {
+                            self.preview_text = Some(decompiled);
+                            self.status_message =
+                                "Decompiled .rpyc file (string extraction)".to_string();
+                        } else {
+                            self.status_message = "Could not decompile .rpyc file".to_string();
+                        }
+                    }
+                },
+                "text" => {
+                    if let Ok(text) = std::str::from_utf8(&data).map(str::to_string) {
+                        self.preview_text = Some(text);
+                        self.status_message = "Loaded Ren'Py script".to_string();
+                    } else {
+                        self.status_message = "Could not decode a text file".to_string();
+                    }
                 }
-            } else if lower.ends_with(".rpy")
-                || lower.ends_with(".py")
-                || lower.ends_with(".json")
-                || lower.ends_with(".txt")
-                || lower.ends_with(".ini")
-                || lower.ends_with(".xml")
-                || lower.ends_with(".yaml")
-                || lower.ends_with(".yml")
-            {
-                if let Ok(text) = String::from_utf8(data.clone()) {
-                    self.preview_text = Some(text);
-                    self.status_message = "Loaded Ren'Py script".to_string();
-                } else {
-                    self.status_message = "Could not decode a text file".to_string();
+                "hex" => {
+                    self.status_message =
+                        format!("Hex view of {} ({:.1} KB)", filename, data.len() as f32 / 1024.0);
                 }
-            } else {
-                let info = self.generate_media_info(filename, &data);
-                self.preview_text = Some(info);
-                self.status_message =
-                    format!("Loaded {} ({:.1} KB)", filename, data.len() as f32 / 1024.0);
+                "none" => {
+                    self.status_message = format!("Preview disabled for {}", filename);
+                }
+                _ => {
+                    let info = self.generate_media_info(filename, &data);
+                    if self.get_file_type(filename) == "videos" {
+                        self.probe_video_entry(filename);
+                    }
+                    self.preview_text = Some(info);
+                    self.status_message =
+                        format!("Loaded {} ({:.1} KB)", filename, data.len() as f32 / 1024.0);
+                }
+            }
+            // Moved in after decoding rather than cloned up front.
+            if mode != "none" {
+                self.preview_data = Some(data);
+            }
+        }
+        self.refresh_highlight(filename);
+    }
+
+    pub(crate) fn get_preview_mode(&self, filename: &str) -> &str {
+        let lower = filename.to_lowercase();
+
+        if let Some(ext) = Path::new(&lower).extension() {
+            let ext = format!(".{}", ext.to_string_lossy());
+            if let Some(mode) = self.preview_associations.get(&ext) {
+                return mode;
             }
         }
+        if let Some(file_type) = self.sniffed_types.get(filename) {
+            return sniffed_preview_mode(file_type);
+        }
+
+        if lower.ends_with(".png")
+            || lower.ends_with(".jpg")
+            || lower.ends_with(".jpeg")
+            || lower.ends_with(".webp")
+            || lower.ends_with(".gif")
+        {
+            "image"
+        } else if lower.ends_with(".ttf")
+            || lower.ends_with(".otf")
+            || lower.ends_with(".woff")
+            || lower.ends_with(".woff2")
+        {
+            "font"
+        } else if lower.ends_with(".rpyc") {
+            "rpyc"
+        } else if lower.ends_with(".rpy")
+            || lower.ends_with(".py")
+            || lower.ends_with(".json")
+            || lower.ends_with(".txt")
+            || lower.ends_with(".ini")
+            || lower.ends_with(".xml")
+            || lower.ends_with(".yaml")
+            || lower.ends_with(".yml")
+        {
+            "text"
+        } else {
+            "media"
+        }
+    }
+
+    pub(crate) fn set_preview_association(&mut self, extension: &str, mode: &str) {
+        let ext = extension.trim().to_lowercase();
+        if ext.is_empty() {
+            return;
+        }
+        let ext = if ext.starts_with('.') { ext } else { format!(".{}", ext) };
+        self.status_message = format!("{} files will preview as {}", ext, mode);
+        self.preview_associations.insert(ext, mode.to_string());
     }
 
     fn generate_media_info(&self, filename: &str, data: &[u8]) -> String {
@@ -655,87 +1340,137 @@ impl RpaEditor {
         info
     }
 
-    pub(crate) fn replace_file(&mut self, filename: &str, new_file_path: &str) -> anyhow::Result<()> {
-        println!(
-            "🔄 Attempting to replace {} with {}",
-            filename, new_file_path
-        );
-
-        let new_path = Path::new(filename);
-        if !new_path.exists() {
-            return Err(anyhow::anyhow!(
-                "Replacement file isn't found: {}",
-                filename
-            ));
+    /// Replaces the content of `archive_name` with the file at `source_path`. Unless
+    /// `allow_type_change` is set, a replacement of a different detected type (a JPEG
+    /// over an `.ogg`, say) fails with a [`TypeMismatch`] error.
+    pub(crate) fn replace_entry(
+        &mut self,
+        archive_name: &str,
+        source_path: &str,
+        allow_type_change: bool,
+    ) -> anyhow::Result<()> {
+        self.ensure_writable()?;
+        info!("🔄 Replacing {} with {}", archive_name, source_path);
+
+        if self.indexes.get(archive_name).is_none_or(|entry| entry.to_delete) {
+            return Err(anyhow::anyhow!("File isn't found in the archive: {}", archive_name));
         }
-
-        if !new_path.is_file() {
-            return Err(anyhow::anyhow!("Path is not a file: {}", filename));
+        let source = Path::new(source_path);
+        if !source.is_file() {
+            return Err(anyhow::anyhow!("Replacement file isn't found: {}", source_path));
+        }
+        let new_data = std::fs::read(source)
+            .map_err(|e| anyhow::anyhow!("Failed to read replacement file '{}': {}", source_path, e))?;
+
+        if !allow_type_change {
+            let expected = self.get_file_type(archive_name);
+            let head = &new_data[..new_data.len().min(SNIFF_LENGTH as usize)];
+            let found = sniff_type(head).unwrap_or_else(|| self.get_file_type(source_path));
+            if expected != found && expected != "other" && found != "other" {
+                return Err(TypeMismatch {
+                    archive_name: archive_name.to_string(),
+                    expected,
+                    found,
+                }
+                .into());
+            }
         }
 
-        let new_data = std::fs::read(filename).map_err(|e| {
-            anyhow::anyhow!("Failed to read replacement file '{}': {}", filename, e)
-        })?;
-
-        println!(
-            "✅ Successfully read {} bytes from {}",
-            new_data.len(),
-            new_file_path
-        );
-
-        if let Some(entry) = self.indexes.get_mut(new_file_path) {
-            entry.data = Some(new_data.clone());
-            entry.modified = true;
-            entry.length = new_data.len() as u64;
-            self.modified = true;
+        self.backup_before(archive_name, "replace");
+        let length = new_data.len();
+        self.insert_data(archive_name, new_data)?;
+        self.set_provenance(archive_name, format!("disk: {}", source_path));
+        self.status_message = format!("Replaced: {} ({} bytes)", archive_name, length);
+        info!("✅ Replaced {} with {} ({} bytes)", archive_name, source_path, length);
+        Ok(())
+    }
 
-            self.status_message = format!("Replaced: {} ({} bytes)", filename, new_data.len());
+    /// Runs a replacement picked in the UI. A type mismatch is put to the user instead of
+    /// failing outright.
+    pub(crate) fn apply_replacement(&mut self, archive_name: &str, source_path: &str, allow_type_change: bool) {
+        match self.replace_entry(archive_name, source_path, allow_type_change) {
+            Ok(()) => {
+                if self.selected_file.as_deref() == Some(archive_name) {
+                    self.preview_file(archive_name);
+                }
+            }
+            Err(e) => match e.downcast_ref::<TypeMismatch>() {
+                Some(mismatch) => {
+                    self.replace_type_mismatch =
+                        Some((archive_name.to_string(), source_path.to_string(), mismatch.to_string()));
+                }
+                None => self.status_message = format!("Replace error: {}", e),
+            },
+        }
+    }
 
-            println!(
-                "✅ Replaced {} with {} ({} bytes)",
-                filename,
-                new_file_path,
-                new_data.len()
+    pub(crate) fn add_file(&mut self, file_path: &str, archive_name: &str) -> anyhow::Result<()> {
+        let mut data = std::fs::read(file_path)?;
+
+        let lower = archive_name.to_lowercase();
+        if self.subset_fonts_on_add && (lower.ends_with(".ttf") || lower.ends_with(".otf")) {
+            let chars = self.collect_script_characters();
+            // ASCII is kept anyway, so an ASCII-only set means the scripts' text wasn't
+            // found and the subset would drop every glyph the game needs.
+            if chars.iter().all(char::is_ascii) {
+                return Err(anyhow::anyhow!(
+                    "Not subsetting {}: no non-ASCII text was found in the archive's scripts; turn off font subsetting to add it whole",
+                    archive_name
+                ));
+            }
+            let original_size = data.len();
+            data = subset_font(&data, &chars)?;
+            info!(
+                "🔤 Subset {} to {} characters: {} → {}",
+                archive_name,
+                chars.len(),
+                Self::format_bytes(original_size as u64),
+                Self::format_bytes(data.len() as u64)
             );
-            Ok(())
-        } else {
-            Err(anyhow::anyhow!(
-                "File isn't found in the archive: {}",
-                filename
-            ))
         }
+
+        self.add_data(archive_name, data)?;
+        self.set_provenance(archive_name, format!("disk: {}", file_path));
+        Ok(())
     }
 
-    pub(crate) fn add_file(&mut self, file_path: &str, archive_name: &str) -> anyhow::Result<()> {
-        let data = std::fs::read(file_path)?;
+    pub(crate) fn add_data(&mut self, archive_name: &str, data: Vec<u8>) -> anyhow::Result<()> {
+        self.ensure_writable()?;
+        self.backup_before(archive_name, "add");
+        self.insert_data(archive_name, data)
+    }
 
-        if self.auto_backup && self.indexes.contains_key(archive_name) {
-            if let Ok(old_data) = self.load_file_data(archive_name) {
-                let backup = BackupEntry {
-                    filename: archive_name.to_string(),
-                    data: old_data,
-                    timestamp: chrono::Utc::now(),
-                };
-                self.backup_history.push(backup);
+    /// Stores `data` under `archive_name` without taking a backup. Every change to an
+    /// entry's bytes goes through here, so read-only mode is enforced here too.
+    pub(crate) fn insert_data(&mut self, archive_name: &str, data: Vec<u8>) -> anyhow::Result<()> {
+        self.ensure_writable()?;
+        let head = data[..data.len().min(SNIFF_LENGTH as usize)].to_vec();
+        self.insert_entry(archive_name, RpaFileEntry::from_data(data), &head);
+        Ok(())
+    }
 
-                if self.backup_history.len() > 10 {
-                    self.backup_history.remove(0);
-                }
-            }
-        }
+    /// Adds an entry of another archive by reference; its bytes are copied on save.
+    pub(crate) fn add_merged(&mut self, archive_name: &str, source: MergedSource) -> anyhow::Result<()> {
+        self.ensure_writable()?;
+        self.backup_before(archive_name, "add");
+        let mut head = Vec::new();
+        source.open()?.take(SNIFF_LENGTH).read_to_end(&mut head)?;
+        self.insert_entry(archive_name, RpaFileEntry::from_merged(source), &head);
+        Ok(())
+    }
 
-        let entry = RpaFileEntry {
-            offset: 0,
-            length: data.len() as u64,
-            prefix: Vec::new(),
-            data: Some(data),
-            modified: true,
-            to_delete: false,
-        };
+    fn insert_entry(&mut self, archive_name: &str, mut entry: RpaFileEntry, head: &[u8]) {
+        self.record_sniffed_type(archive_name, head);
+        self.checksums.remove(archive_name);
+        self.thumbnails.forget(archive_name);
+        // The original location stays known so the entry can be reverted.
+        if let Some(previous) = self.indexes.get(archive_name) {
+            entry.segments = previous.segments.clone();
+        }
 
         let is_new = !self.indexes.contains_key(archive_name);
         self.indexes.insert(archive_name.to_string(), entry);
-        self.modified = true;
+        self.set_modified(true);
 
         if is_new {
             self.status_message = format!(
@@ -750,75 +1485,689 @@ impl RpaEditor {
                 self.indexes.len()
             );
         }
+    }
 
-        Ok(())
+    /// Flags the archive as changed, or not; every edit goes through here.
+    pub(crate) fn set_modified(&mut self, modified: bool) {
+        self.modified = modified;
+        self.edit_generation += 1;
+    }
+
+    pub(crate) fn queue_add(&mut self, path: &str, archive_name: &str) {
+        if self.blocked_by_read_only() {
+            return;
+        }
+        self.pending_adds.push_back(PendingAdd {
+            path: path.to_string(),
+            archive_name: archive_name.to_string(),
+            merged: None,
+        });
+    }
+
+    /// Adds queued files until one collides with an existing entry and needs a decision.
+    pub(crate) fn process_pending_adds(&mut self) {
+        while self.add_conflict.is_none() {
+            let Some(item) = self.pending_adds.pop_front() else {
+                self.conflict_resolution = None;
+                self.conflict_apply_to_all = false;
+                if std::mem::take(&mut self.save_after_merge) {
+                    self.open_save_as_dialog();
+                }
+                return;
+            };
+
+            let exists = self
+                .indexes
+                .get(&item.archive_name)
+                .is_some_and(|entry| !entry.to_delete);
+            if !exists {
+                self.apply_pending_add(item, "overwrite");
+            } else if let Some(resolution) = self.conflict_resolution.clone() {
+                self.apply_pending_add(item, &resolution);
+            } else {
+                self.add_conflict = Some(item);
+            }
+        }
+    }
+
+    pub(crate) fn resolve_add_conflict(&mut self, resolution: &str) {
+        let Some(item) = self.add_conflict.take() else {
+            return;
+        };
+        if self.conflict_apply_to_all {
+            self.conflict_resolution = Some(resolution.to_string());
+        }
+        self.apply_pending_add(item, resolution);
+    }
+
+    pub(crate) fn apply_pending_add(&mut self, item: PendingAdd, resolution: &str) {
+        let archive_name = match resolution {
+            "skip" => {
+                info!("⏭️ Skipped existing entry: {}", item.archive_name);
+                return;
+            }
+            "rename" => self.unique_archive_name(&item.archive_name),
+            _ => item.archive_name,
+        };
+        if let Some(source) = item.merged {
+            match self.add_merged(&archive_name, source) {
+                Ok(()) => self.set_provenance(&archive_name, format!("merged from {}", item.path)),
+                Err(e) => self.add_error(format!("Add Error: {}", e)),
+            }
+        } else if let Err(e) = self.add_file(&item.path, &archive_name) {
+            self.add_error(format!("Add Error: {}", e));
+        }
+    }
+
+    /// `dir/name_2.ext`, `dir/name_3.ext`, ... whichever is free first.
+    fn unique_archive_name(&self, archive_name: &str) -> String {
+        let (dir, file) = match archive_name.rsplit_once('/') {
+            Some((dir, file)) => (format!("{}/", dir), file),
+            None => (String::new(), archive_name),
+        };
+        let (stem, ext) = match file.rsplit_once('.') {
+            Some((stem, ext)) if !stem.is_empty() => (stem, format!(".{}", ext)),
+            _ => (file, String::new()),
+        };
+        (2..)
+            .map(|n| format!("{}{}_{}{}", dir, stem, n, ext))
+            .find(|name| !self.indexes.contains_key(name))
+            .unwrap_or_default()
+    }
+
+    /// Queues files dropped on the window, matching same-named entries for replacement.
+    pub(crate) fn queue_dropped_files(&mut self, paths: Vec<std::path::PathBuf>) {
+        if self.blocked_by_read_only() {
+            return;
+        }
+        for path in paths {
+            if !path.is_file() {
+                continue;
+            }
+            let file_name = path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+
+            let mut matches: Vec<&String> = self
+                .indexes
+                .keys()
+                .filter(|name| name.rsplit('/').next() == Some(file_name.as_str()))
+                .collect();
+            matches.sort();
+
+            let archive_name = match (self.selected_file.as_ref(), matches.first()) {
+                (Some(selected), _) if matches.contains(&selected) => selected.clone(),
+                (_, Some(first)) => (*first).clone(),
+                _ => file_name,
+            };
+
+            self.dropped_files.push(DroppedFile {
+                path: path.to_string_lossy().to_string(),
+                archive_name,
+                selected: true,
+            });
+        }
+    }
+
+    pub(crate) fn apply_dropped_files(&mut self) -> (usize, usize) {
+        let dropped = std::mem::take(&mut self.dropped_files);
+        let mut done = 0;
+        let mut failed = 0;
+
+        for file in dropped.iter().filter(|f| f.selected && !f.archive_name.is_empty()) {
+            match self.add_file(&file.path, &file.archive_name) {
+                Ok(()) => done += 1,
+                Err(e) => {
+                    failed += 1;
+                    error!("❌ Failed to add {}: {}", file.path, e);
+                }
+            }
+        }
+
+        (done, failed)
+    }
+
+    /// Snapshots an existing entry if backups are on for `trigger` and its file type.
+    pub(crate) fn backup_before(&mut self, filename: &str, trigger: &str) {
+        if !self.auto_backup
+            || !self.backup_triggers.contains(trigger)
+            || !self.indexes.contains_key(filename)
+        {
+            return;
+        }
+        let file_type = self.get_file_type(filename);
+        if self.backup_excluded_types.contains(file_type) {
+            info!("⏭️ Not backing up {} ({} are excluded)", filename, file_type);
+            return;
+        }
+        self.push_backup(filename);
+    }
+
+    fn push_backup(&mut self, filename: &str) {
+        match self.compress_entry(filename) {
+            Ok((data, original_size)) => {
+                let backup = BackupEntry {
+                    filename: filename.to_string(),
+                    data,
+                    original_size,
+                    timestamp: chrono::Utc::now(),
+                };
+                if self.persist_backups {
+                    match self.write_disk_backup(&backup) {
+                        Ok(path) => info!("💾 Backup of {} saved to {}", filename, path.display()),
+                        Err(e) => warn!("⚠️ Could not write backup of {} to disk: {}", filename, e),
+                    }
+                }
+                self.backup_history.push(backup);
+
+                if self.backup_history.len() > 10 {
+                    self.backup_history.remove(0);
+                }
+            }
+            Err(e) => warn!("⚠️ Backup of {} failed: {}", filename, e),
+        }
+    }
+
+    /// Runs an entry through zlib without making another uncompressed copy: pending data
+    /// is compressed in place, archived entries are streamed from disk.
+    fn compress_entry(&self, filename: &str) -> anyhow::Result<(Vec<u8>, u64)> {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::new(self.compression_level));
+        if let Some(data) = self.indexes.get(filename).and_then(|entry| entry.data.as_ref()) {
+            encoder.write_all(data)?;
+            return Ok((encoder.finish()?, data.len() as u64));
+        }
+        let mut stream = self.open_entry_stream(filename)?;
+        let original_size = std::io::copy(&mut stream, &mut encoder)?;
+        Ok((encoder.finish()?, original_size))
     }
 
     pub(crate) fn remove_file(&mut self, filename: &str) {
+        if self.blocked_by_read_only() {
+            return;
+        }
         if let Some(entry) = self.indexes.get_mut(filename) {
             entry.to_delete = true;
-            self.modified = true;
+            self.set_modified(true);
             self.status_message = format!("Marked {} for deletion", filename);
         }
     }
 
     pub(crate) fn save_rpa(&self, archive_path: &str) -> anyhow::Result<()> {
-        let old_data = std::fs::read(&self.archive_path.clone().unwrap())?;
-        let mut offset = 0x34;
-        let mut out = File::create(archive_path)?;
+        self.save_rpa_as(archive_path, self.header_format, self.key)
+    }
+
+    /// Writes the archive in the given format; keyed formats obfuscate the index with `key`.
+    pub(crate) fn save_rpa_as(&self, archive_path: &str, format: &HeaderFormat, key: u32) -> anyhow::Result<()> {
+        self.save_entries_as(archive_path, format, key, |_| true)
+    }
+
+    /// Like [`Self::save_rpa_as`], but only writes the entries `include` accepts.
+    pub(crate) fn save_entries_as(
+        &self,
+        archive_path: &str,
+        format: &HeaderFormat,
+        key: u32,
+        include: impl Fn(&str) -> bool,
+    ) -> anyhow::Result<()> {
+        // Splits, patches and selection exports write new files, which read-only mode allows.
+        if self.archive_path.as_deref().is_some_and(|current| same_file(current, archive_path)) {
+            self.ensure_writable()?;
+        }
+        let key = if self.reproducible_build && format.is_keyed() {
+            self.reproducible_key()
+        } else {
+            key
+        };
+        let mut source = match self.archive_path {
+            Some(ref path) => Some(File::open(path)?),
+            None => None,
+        };
+        let header_len = format.header_len();
+        if self.data_start_offset < header_len {
+            return Err(anyhow::anyhow!(
+                "Data start offset 0x{:x} overlaps the {}-byte header",
+                self.data_start_offset,
+                header_len
+            ));
+        }
 
+        let mut temp = TempFile {
+            path: format!("{}.tmp", archive_path),
+            keep: false,
+        };
+        let mut offset = self.data_start_offset;
+        let mut out = BufWriter::new(File::create(&temp.path)?);
+
+        // The header is written last over the start of this area.
+        let padding = self.header_padding.as_bytes();
+        if !padding.is_empty() {
+            let gap = &padding[..std::cmp::min(padding.len(), (offset - header_len) as usize)];
+            out.seek(SeekFrom::Start(header_len))?;
+            out.write_all(gap)?;
+        }
         out.seek(SeekFrom::Start(offset))?;
 
-        let mut new_indexes = HashMap::new();
+        let mut new_indexes = Vec::new();
 
-        let mut files: Vec<_> = self.indexes.iter().collect();
+        // Entries marked for deletion are dropped here, whether or not the build is
+        // reproducible; saving is what deletes them.
+        let mut files: Vec<_> = self
+            .indexes
+            .iter()
+            .filter(|(name, e)| !e.to_delete && include(name))
+            .collect();
         files.sort_by_key(|(k, _)| *k);
 
         for (name, entry) in files {
-            let data = if let Some(d) = &entry.data {
-                d.clone()
+            let mut content: Box<dyn Read> = if let Some(d) = &entry.data {
+                Box::new(&d[..])
+            } else if let Some(merged) = &entry.merged {
+                Box::new(merged.open()?)
             } else {
-                let start = entry.offset as usize;
-                let end = start + entry.length as usize;
-                old_data
-                    .get(start..end)
-                    .ok_or_else(|| {
-                        anyhow::anyhow!("Data isn't found in the old archive for {name}")
-                    })?
-                    .to_vec()
+                let source = source.as_mut().ok_or_else(|| {
+                    anyhow::anyhow!("Data isn't found in the old archive for {name}")
+                })?;
+                Box::new(EntryStream::from_archive(
+                    source.try_clone()?,
+                    entry.segments.clone(),
+                ))
             };
 
-            out.write_all(&data)?;
+            let mut prefix = Vec::new();
+            if self.regenerate_prefixes {
+                (&mut content).take(PREFIX_LENGTH).read_to_end(&mut prefix)?;
+            }
+            let written = std::io::copy(&mut content, &mut out)?;
+            let length = prefix.len() as u64 + written;
 
-            if self.version == 3.0 {
-                new_indexes.insert(
-                    name.clone(),
-                    vec![(
-                        offset ^ self.key as u64,
-                        data.len() as u64 ^ self.key as u64,
-                    )],
-                );
-            } else {
-                new_indexes.insert(name.clone(), vec![(offset, data.len() as u64)]);
+            if entry.data.is_none() && length != entry.length {
+                return Err(anyhow::anyhow!(
+                    "The stored length of {name} doesn't match its data ({length} of {} bytes)",
+                    entry.length
+                ));
             }
 
-            offset += data.len() as u64;
+            let (index_offset, index_length) = if format.is_keyed() {
+                (offset ^ key as u64, length ^ key as u64)
+            } else {
+                (offset, length)
+            };
+            new_indexes.push((
+                HashableValue::String(name.clone()),
+                Value::List(vec![Value::Tuple(vec![
+                    Value::I64(index_offset as i64),
+                    Value::I64(index_length as i64),
+                    Value::Bytes(prefix),
+                ])]),
+            ));
+
+            offset += written;
         }
 
-        let raw_index = serde_pickle::to_vec(&new_indexes, Default::default())?;
-        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        let raw_index = serde_pickle::value_to_vec(
+            &Value::Dict(new_indexes.into_iter().collect()),
+            Default::default(),
+        )?;
+        // Fixed level so identical inputs always produce the same index bytes.
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::new(6));
         encoder.write_all(&raw_index)?;
         let compressed_index = encoder.finish()?;
 
         out.write_all(&compressed_index)?;
 
         out.seek(SeekFrom::Start(0))?;
-        if self.version == 3.0 {
-            write!(out, "RPA-3.0 {:016x} {:08x}\n", offset, self.key)?;
+        format.write(&mut out, offset, key)?;
+
+        out.flush()?;
+        drop(out);
+        drop(source);
+
+        if self.archive_path.as_deref().is_some_and(|current| same_file(current, archive_path)) {
+            self.close_archive_reader();
+        }
+        let backed_up = self.archive_bak_count > 0 && Path::new(archive_path).is_file();
+        if backed_up {
+            Self::rotate_archive_backups(archive_path, self.archive_bak_count)?;
+        }
+        if let Err(e) = std::fs::rename(&temp.path, archive_path) {
+            // Put the original back so a failed save never leaves the path empty.
+            if backed_up {
+                let _ = std::fs::rename(format!("{}.bak", archive_path), archive_path);
+            }
+            return Err(e.into());
+        }
+
+        temp.keep = true;
+        Ok(())
+    }
+
+    /// Shifts `archive.bak` → `archive.bak.1` → … keeping `count` copies, then moves
+    /// the current archive to `archive.bak`.
+    fn rotate_archive_backups(archive_path: &str, count: usize) -> anyhow::Result<()> {
+        let name = |n: usize| {
+            if n == 0 {
+                format!("{}.bak", archive_path)
+            } else {
+                format!("{}.bak.{}", archive_path, n)
+            }
+        };
+        let _ = std::fs::remove_file(name(count - 1));
+        for n in (0..count - 1).rev() {
+            if Path::new(&name(n)).exists() {
+                std::fs::rename(name(n), name(n + 1))?;
+            }
+        }
+        std::fs::rename(archive_path, name(0))?;
+        info!("💾 Kept the previous archive as {}", name(0));
+        Ok(())
+    }
+
+    /// Added and removed entries and the total size, recounted only after an edit.
+    fn title_counts(&mut self) -> TitleCounts {
+        if let Some(counts) = self.title_counts
+            && counts.generation == self.edit_generation
+        {
+            return counts;
+        }
+        let counts = TitleCounts {
+            generation: self.edit_generation,
+            added: self
+                .indexes
+                .iter()
+                .filter(|(name, entry)| !entry.to_delete && !self.loaded_names.contains(*name))
+                .count(),
+            removed: self
+                .loaded_names
+                .iter()
+                .filter(|name| self.indexes.get(*name).is_none_or(|entry| entry.to_delete))
+                .count(),
+            size: self
+                .indexes
+                .values()
+                .filter(|entry| !entry.to_delete)
+                .map(|entry| entry.length)
+                .sum(),
+        };
+        self.title_counts = Some(counts);
+        counts
+    }
+
+    /// Visible entries and entries per change filter, recounted only after an edit or
+    /// when a filter changes rather than every frame.
+    pub(crate) fn filter_counts(&mut self) -> (usize, [usize; CHANGE_FILTERS.len()]) {
+        let filters = [self.filter_type.clone(), self.change_filter.clone(), self.search_filter.clone()];
+        if let Some(counts) = &self.filter_counts
+            && counts.generation == self.edit_generation
+            && counts.filters == filters
+        {
+            return (counts.visible, counts.changes);
+        }
+        let changes = match &self.filter_counts {
+            Some(counts) if counts.generation == self.edit_generation => counts.changes,
+            _ => std::array::from_fn(|i| {
+                let filter = CHANGE_FILTERS[i].0;
+                self.indexes
+                    .values()
+                    .filter(|entry| filter == "all" || entry.change_kind() == Some(filter))
+                    .count()
+            }),
+        };
+        let visible = self.get_filtered_sorted_files().len();
+        self.filter_counts = Some(FilterCounts {
+            generation: self.edit_generation,
+            filters,
+            visible,
+            changes,
+        });
+        (visible, changes)
+    }
+
+    pub(crate) fn get_window_title(&mut self) -> String {
+        let Some(ref archive_path) = self.archive_path else {
+            return "🎮 RPA Archive Editor - Enhanced v2.0".to_string();
+        };
+
+        let name = Path::new(archive_path)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| archive_path.clone());
+
+        let mut title = format!("🎮 RPA Archive Editor - {}", name);
+        if self.modified {
+            let TitleCounts { added, removed, size, .. } = self.title_counts();
+            let size_delta = if size >= self.loaded_size {
+                format!("+{}", Self::format_bytes(size - self.loaded_size))
+            } else {
+                format!("−{}", Self::format_bytes(self.loaded_size - size))
+            };
+            title.push_str(&format!(
+                " ● +{} / −{} files, {}",
+                added, removed, size_delta
+            ));
+        }
+        title
+    }
+
+    pub(crate) fn open_save_as_dialog(&mut self) {
+        self.save_as_format = match &self.game_folder {
+            // A new archive follows the format of the game it is made for.
+            Some(folder) if self.archive_path.is_none() => folder.preferred_format(),
+            _ => self.header_format,
+        };
+        let key = if self.header_format.is_keyed() && self.key != 0 {
+            self.key
         } else {
-            write!(out, "RPA-2.0 {:016x}\n", offset)?;
+            Self::generate_key()
+        };
+        self.save_as_key = format!("{:08x}", key);
+        self.show_save_as_dialog = true;
+    }
+
+    /// Key derived from the entry list, so reproducible builds don't depend on a random key.
+    pub(crate) fn reproducible_key(&self) -> u32 {
+        let mut names: Vec<(&String, &RpaFileEntry)> = self
+            .indexes
+            .iter()
+            .filter(|(_, entry)| !entry.to_delete)
+            .collect();
+        names.sort_by_key(|(name, _)| *name);
+
+        let mut hash = FNV_OFFSET;
+        for (name, entry) in names {
+            hash = fnv1a(hash, name.as_bytes());
+            hash = fnv1a(hash, &entry.length.to_le_bytes());
+        }
+        (hash ^ (hash >> 32)) as u32
+    }
+
+    /// FNV-1a digest of a saved archive, shown so two builds can be compared at a glance.
+    pub(crate) fn build_digest(path: &str) -> anyhow::Result<u64> {
+        let mut file = File::open(path)?;
+        let mut buffer = vec![0u8; 1 << 16];
+        let mut hash = FNV_OFFSET;
+        loop {
+            let read = file.read(&mut buffer)?;
+            if read == 0 {
+                return Ok(hash);
+            }
+            hash = fnv1a(hash, &buffer[..read]);
+        }
+    }
+
+    fn saved_message(&self, path: &str) -> String {
+        if !self.reproducible_build {
+            return format!("Saved {}", path);
+        }
+        match Self::build_digest(path) {
+            Ok(digest) => format!("Saved {} (build digest {:016x})", path, digest),
+            Err(_) => format!("Saved {}", path),
+        }
+    }
+
+    pub(crate) fn generate_key() -> u32 {
+        use std::hash::{BuildHasher, Hasher};
+        let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+        hasher.write_u128(
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_nanos())
+                .unwrap_or_default(),
+        );
+        hasher.finish() as u32
+    }
+
+    /// Saves, switching to the permission fallback dialog instead of a bare error toast.
+    /// Returns whether the file was written.
+    pub(crate) fn save_with_fallback(&mut self, path: &str, format: &HeaderFormat, key: u32) -> bool {
+        if !Self::is_writable(path) {
+            self.save_fallback_path = Some(path.to_string());
+            return false;
+        }
+
+        match self.save_rpa_as(path, format, key) {
+            Ok(()) => {
+                if self.archive_path.as_deref() == Some(path) {
+                    if let Err(e) = self.reload_after_save() {
+                        self.add_error(format!("Saved, but the archive couldn't be reopened: {}", e));
+                    }
+                    self.remove_autosave();
+                }
+                let message = self.saved_message(path);
+                self.add_toast(message);
+                true
+            }
+            Err(e) if Self::is_permission_error(&e) => {
+                self.save_fallback_path = Some(path.to_string());
+                false
+            }
+            Err(e) => {
+                self.add_error(format!("Save error: {}", e));
+                false
+            }
+        }
+    }
+
+    /// Rereads the index just written over the open archive, so every entry points at its
+    /// new offsets and the saved changes stop being pending. The selection is kept.
+    pub(crate) fn reload_after_save(&mut self) -> anyhow::Result<()> {
+        let path = self
+            .archive_path
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("No archive loaded"))?;
+        let selected = self.selected_file.clone();
+        let status = std::mem::take(&mut self.status_message);
+        self.load_rpa(&path)?;
+        self.status_message = status;
+        self.selected_file = selected.filter(|name| self.indexes.contains_key(name));
+        self.file_to_preview = self.selected_file.clone();
+        self.remember_archive_stamp();
+        Ok(())
+    }
+
+    fn is_permission_error(error: &anyhow::Error) -> bool {
+        error
+            .downcast_ref::<std::io::Error>()
+            .is_some_and(|e| e.kind() == std::io::ErrorKind::PermissionDenied)
+    }
+
+    fn is_writable(path: &str) -> bool {
+        if let Ok(metadata) = std::fs::metadata(path)
+            && metadata.permissions().readonly()
+        {
+            return false;
+        }
+
+        // A fresh name, so the probe can never clobber a file that happens to be there.
+        let probe = format!(
+            "{}.{}-{}.probe",
+            path,
+            std::process::id(),
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        );
+        match std::fs::OpenOptions::new().write(true).create_new(true).open(&probe) {
+            Ok(_) => {
+                let _ = std::fs::remove_file(&probe);
+                true
+            }
+            Err(e) => e.kind() != std::io::ErrorKind::PermissionDenied,
+        }
+    }
+
+    /// Starts an elevated copy of the editor on the same archive. The caller exits right
+    /// after, so unsaved changes are handed over in a workspace file. Only the active
+    /// tab's changes can be, so this refuses while another tab has unsaved changes.
+    fn relaunch_elevated(&self) -> anyhow::Result<()> {
+        let modified_tabs = self.modified_background_tabs();
+        if !modified_tabs.is_empty() {
+            return Err(anyhow::anyhow!(
+                "{} other tab(s) have unsaved changes; save or close them first",
+                modified_tabs.len()
+            ));
+        }
+        let exe = std::env::current_exe()?;
+        let mut args: Vec<String> = self.archive_path.iter().cloned().collect();
+        if self.modified {
+            let workspace = std::env::temp_dir().join(format!("rpa_editor_relaunch_{}.rpaws", std::process::id()));
+            let count = self.write_workspace(&workspace)?;
+            info!("💾 Handing {} unsaved changes to the elevated editor via {}", count, workspace.display());
+            args.push(WORKSPACE_FLAG.to_string());
+            args.push(workspace.to_string_lossy().to_string());
+        }
+
+        // Paths only ever reach PowerShell as single-quoted literals; Start-Process joins
+        // the list with spaces, so each argument is double-quoted inside its literal too.
+        #[cfg(target_os = "windows")]
+        {
+            let literal = |text: &str| {
+                let mut quoted = String::from("'");
+                for c in text.chars() {
+                    // Typographic single quotes end a literal too, and are escaped the same way.
+                    if matches!(c, '\'' | '\u{2018}' | '\u{2019}' | '\u{201a}' | '\u{201b}') {
+                        quoted.push(c);
+                    }
+                    quoted.push(c);
+                }
+                quoted.push('\'');
+                quoted
+            };
+            let mut command = format!("Start-Process -FilePath {} -Verb RunAs", literal(&exe.to_string_lossy()));
+            if !args.is_empty() {
+                let list: Vec<String> = args.iter().map(|arg| literal(&format!("\"{}\"", arg))).collect();
+                command.push_str(&format!(" -ArgumentList @({})", list.join(", ")));
+            }
+            std::process::Command::new("powershell")
+                .arg("-NoProfile")
+                .arg("-Command")
+                .arg(command)
+                .spawn()?;
         }
 
+        // The paths are passed as script arguments and shell-quoted by AppleScript itself.
+        #[cfg(target_os = "macos")]
+        std::process::Command::new("osascript")
+            .args([
+                "-e", "on run argv",
+                "-e", "set command to \"\"",
+                "-e", "repeat with arg in argv",
+                "-e", "set command to command & quoted form of (arg as text) & \" \"",
+                "-e", "end repeat",
+                "-e", "do shell script command & \"&> /dev/null &\" with administrator privileges",
+                "-e", "end run",
+            ])
+            .arg(&exe)
+            .args(&args)
+            .spawn()?;
+
+        #[cfg(target_os = "linux")]
+        std::process::Command::new("pkexec")
+            .arg("env")
+            .arg(format!("DISPLAY={}", std::env::var("DISPLAY").unwrap_or_default()))
+            .arg(format!("XAUTHORITY={}", std::env::var("XAUTHORITY").unwrap_or_default()))
+            .arg(exe)
+            .args(&args)
+            .spawn()?;
+
         Ok(())
     }
 
@@ -839,45 +2188,15 @@ impl RpaEditor {
         }
     }
 
-    pub(crate) fn get_file_icon(filename: &str) -> &'static str {
-        let lower = filename.to_lowercase();
-        if lower.ends_with(".png")
-            || lower.ends_with(".jpg")
-            || lower.ends_with(".jpeg")
-            || lower.ends_with(".webp")
-        {
-            "🖼️"
-        } else if lower.ends_with(".webm") || lower.ends_with(".mp4") || lower.ends_with(".avi") {
-            "🎬"
-        } else if lower.ends_with(".ogg") || lower.ends_with(".wav") || lower.ends_with(".mp3") {
-            "🎵"
-        } else if lower.ends_with(".rpy") || lower.ends_with(".rpyc") {
-            "📜"
-        } else {
-            "📄"
-        }
-    }
-
-    pub(crate) fn get_file_type_color(filename: &str) -> egui::Color32 {
-        let lower = filename.to_lowercase();
-        if lower.ends_with(".png")
-            || lower.ends_with(".jpg")
-            || lower.ends_with(".jpeg")
-            || lower.ends_with(".webp")
-        {
-            egui::Color32::from_rgb(100, 200, 100)
-        } else if lower.ends_with(".webm") || lower.ends_with(".mp4") || lower.ends_with(".avi") {
-            egui::Color32::from_rgb(200, 100, 100)
-        } else if lower.ends_with(".ogg") || lower.ends_with(".wav") || lower.ends_with(".mp3") {
-            egui::Color32::from_rgb(100, 100, 200)
-        } else if lower.ends_with(".rpy") || lower.ends_with(".rpyc") {
-            egui::Color32::from_rgb(200, 200, 100)
-        } else {
-            egui::Color32::GRAY
+    pub(crate) fn get_file_type(&self, filename: &str) -> &'static str {
+        match self.sniffed_types.get(filename) {
+            Some(file_type) => file_type,
+            None => Self::file_type_by_extension(filename),
         }
     }
 
-    fn get_file_type(&self, filename: &str) -> &'static str {
+    /// The type an entry's extension suggests, before any sniffing.
+    pub(crate) fn file_type_by_extension(filename: &str) -> &'static str {
         let lower = filename.to_lowercase();
         if lower.ends_with(".png")
             || lower.ends_with(".jpg")
@@ -925,66 +2244,46 @@ impl RpaEditor {
         counts
     }
 
-    pub(crate) fn dump_files_by_type(&self, file_type: &str, base_path: &Path) -> anyhow::Result<usize> {
-        let mut count = 0;
+    /// Entries of `file_type` ("all" for any) that `include` accepts, sorted, for bulk extraction.
+    pub(crate) fn entries_of_type(&self, file_type: &str, include: impl Fn(&str) -> bool) -> Vec<String> {
+        let mut filenames: Vec<String> = self
+            .indexes
+            .iter()
+            .filter(|(filename, entry)| {
+                !entry.to_delete
+                    && (file_type == "all" || self.get_file_type(filename) == file_type)
+                    && include(filename)
+            })
+            .map(|(filename, _)| filename.clone())
+            .collect();
+        filenames.sort();
+        filenames
+    }
 
-        let type_dir = base_path.join(file_type);
-        create_dir_all(&type_dir)?;
+    pub(crate) fn get_filtered_sorted_files(&self) -> Vec<(&String, &RpaFileEntry)> {
+        let mut files: Vec<_> = self.indexes.iter().collect();
 
-        for (filename, entry) in &self.indexes {
-            if entry.to_delete {
-                continue;
-            }
+        if self.filter_type != "all" {
+            files.retain(|(filename, _)| self.get_file_type(filename) == self.filter_type);
+        }
 
-            let current_type = self.get_file_type(filename);
-            if current_type == file_type || file_type == "all" {
-                if let Ok(data) = self.load_file_data(filename) {
-                    let file_path = if file_type == "all" {
-                        let subdir = base_path.join(current_type);
-                        create_dir_all(&subdir)?;
-                        subdir.join(filename)
-                    } else {
-                        type_dir.join(filename)
-                    };
-
-                    if let Some(parent) = file_path.parent() {
-                        create_dir_all(parent)?;
-                    }
-
-                    std::fs::write(&file_path, data)?;
-                    count += 1;
-                }
-            }
-        }
-
-        Ok(count)
-    }
-
-    pub(crate) fn dump_all_files(&self, base_path: &Path) -> anyhow::Result<usize> {
-        self.dump_files_by_type("all", base_path)
-    }
-
-    pub(crate) fn get_filtered_sorted_files(&self) -> Vec<(&String, &RpaFileEntry)> {
-        let mut files: Vec<_> = self.indexes.iter().collect();
-
-        if self.filter_type != "all" {
-            files.retain(|(filename, _)| self.get_file_type(filename) == self.filter_type);
-        }
+        if self.change_filter != "all" {
+            files.retain(|(_, entry)| entry.change_kind() == Some(self.change_filter.as_str()));
+        }
 
         if !self.search_filter.is_empty() {
-            files.retain(|(filename, _)| {
-                filename
-                    .to_lowercase()
-                    .contains(&self.search_filter.to_lowercase())
-            });
+            let query = self.search_query();
+            files.retain(|(filename, entry)| query.matches(filename, entry, self.get_file_type(filename)));
         }
 
         match self.sort_by.as_str() {
-            "name" => files.sort_by(|(a, _), (b, _)| a.cmp(b)),
-            "size" => files.sort_by(|(_, a), (_, b)| a.length.cmp(&b.length)),
+            "name" => files.sort_by_key(|(name, _)| *name),
+            "size" => files.sort_by_key(|(_, entry)| entry.length),
             "type" => {
                 files.sort_by(|(a, _), (b, _)| self.get_file_type(a).cmp(self.get_file_type(b)))
             }
+            "offset" => files.sort_by_key(|(name, entry)| (entry.archive_offset(), *name)),
+            "modified" => files.sort_by_key(|(name, entry)| (entry.to_delete, entry.modified, *name)),
             _ => {}
         }
 
@@ -1007,7 +2306,8 @@ impl RpaEditor {
             📁 Total Files: {}\n\
             📦 Total Size: {}\n\
             ✏️ Modified: {}\n\
-            🗑️ To Delete: {}\n\n\
+            🗑️ To Delete: {}\n\
+            🧹 Slack: {}\n\n\
             📊 By Type:\n\
             🖼️ Images: {}\n\
             🎬 Videos: {}\n\
@@ -1022,6 +2322,7 @@ impl RpaEditor {
             Self::format_bytes(total_size),
             modified_count,
             deleted_count,
+            Self::format_bytes(self.slack_bytes()),
             counts.get("images").unwrap_or(&0),
             counts.get("videos").unwrap_or(&0),
             counts.get("audio").unwrap_or(&0),
@@ -1033,35 +2334,81 @@ impl RpaEditor {
         )
     }
 
-    pub(crate) fn batch_replace_from_folder(&mut self, folder_path: &str) -> anyhow::Result<usize> {
-        let folder = Path::new(folder_path);
-        let mut replaced_count = 0;
+    pub(crate) fn scan_script_encodings(&mut self) -> usize {
+        let mut conversions = Vec::new();
+
+        let mut files: Vec<_> = self
+            .indexes
+            .iter()
+            .filter(|(filename, entry)| {
+                !entry.to_delete
+                    && !filename.to_lowercase().ends_with(".rpyc")
+                    && matches!(self.get_file_type(filename), "scripts" | "files")
+            })
+            .map(|(filename, _)| filename.clone())
+            .collect();
+        files.sort();
+
+        for filename in files {
+            let Ok(data) = self.load_file_data(&filename) else {
+                continue;
+            };
+            if std::str::from_utf8(&data).is_ok() {
+                continue;
+            }
+
+            let mut detector = chardetng::EncodingDetector::new();
+            detector.feed(&data, true);
+            let encoding = detector.guess(None, true);
+            let (decoded, _, _) = encoding.decode(&data);
+            let preview: String = decoded.chars().take(500).collect();
 
-        for entry in std::fs::read_dir(folder)? {
-            let entry = entry?;
-            let file_path = entry.path();
+            conversions.push(EncodingConversion {
+                filename,
+                encoding,
+                preview,
+                selected: true,
+            });
+        }
 
-            if file_path.is_file() {
-                if let Some(filename) = file_path.file_name() {
-                    let filename_str = filename.to_string_lossy().to_string();
+        let count = conversions.len();
+        self.encoding_conversions = conversions;
+        self.status_message = format!("Found {} scripts with a legacy encoding", count);
+        count
+    }
 
-                    if self.indexes.contains_key(&filename_str) {
-                        match self.replace_file(&filename_str, &file_path.to_string_lossy()) {
-                            Ok(()) => {
-                                replaced_count += 1;
-                                println!("🔄 Replaced: {}", filename_str);
-                            }
-                            Err(e) => {
-                                println!("❌ Failed to replace {}: {}", filename_str, e);
-                            }
-                        }
-                    }
-                }
+    pub(crate) fn apply_encoding_conversions(&mut self) -> anyhow::Result<usize> {
+        self.ensure_writable()?;
+        let conversions = std::mem::take(&mut self.encoding_conversions);
+        let mut converted = 0;
+
+        for conversion in conversions.iter().filter(|c| c.selected) {
+            let data = self.load_file_data(&conversion.filename)?;
+            let (decoded, _, had_errors) = conversion.encoding.decode(&data);
+            if had_errors {
+                warn!(
+                    "⚠️ {} contains bytes invalid for {}",
+                    conversion.filename,
+                    conversion.encoding.name()
+                );
+            }
+            let utf8 = decoded.into_owned().into_bytes();
+
+            self.backup_before(&conversion.filename, "edit");
+
+            if let Some(entry) = self.indexes.get_mut(&conversion.filename) {
+                entry.length = utf8.len() as u64;
+                entry.data = Some(utf8);
+                entry.merged = None;
+                entry.modified = true;
+                entry.provenance = format!("converted from {}", conversion.encoding.name());
+                self.set_modified(true);
+                converted += 1;
             }
         }
 
-        self.status_message = format!("Batch replaced {} files", replaced_count);
-        Ok(replaced_count)
+        self.status_message = format!("Converted {} scripts to UTF-8", converted);
+        Ok(converted)
     }
 
     pub(crate) fn show_file_menu(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
@@ -1071,43 +2418,135 @@ impl RpaEditor {
                     .add_filter("RPA files", &["rpa"])
                     .pick_file()
                 {
-                    if let Err(e) = self.load_rpa(&path.to_string_lossy()) {
-                        self.add_toast(format!("Error loading: {}", e));
-                    } else {
-                        self.add_toast("RPA loaded successfully");
-                    }
+                    self.open_archive(&path.to_string_lossy());
+                }
+                ui.close_menu();
+            }
+            if ui.button("Open in New Tab...").clicked() {
+                if let Some(path) = rfd::FileDialog::new()
+                    .add_filter("RPA files", &["rpa"])
+                    .pick_file()
+                {
+                    self.open_in_new_tab(&path.to_string_lossy());
+                }
+                ui.close_menu();
+            }
+            self.show_recent_menu(ui);
+            if ui.button("Open Game Folder...").clicked() {
+                if let Some(folder) = rfd::FileDialog::new().pick_folder() {
+                    self.open_game_folder(&folder);
                 }
                 ui.close_menu();
             }
 
-            if ui.button("Save").clicked() && self.archive_path.is_some() {
+            if ui.add_enabled(!self.read_only, egui::Button::new("Save")).clicked() && self.archive_path.is_some() {
                 let path = self.archive_path.clone().unwrap();
-                match self.save_rpa(&path) {
-                    Ok(_) => self.add_toast("Save Succes"),
-                    Err(e) => self.add_toast(format!("Save error: {}", e)),
-                    
+                self.request_save(&path);
+                ui.close_menu();
+            }
+
+            if ui.add_enabled(!self.read_only, egui::Button::new("Save As...")).clicked() {
+                self.open_save_as_dialog();
+                ui.close_menu();
+            }
+
+            if ui.add_enabled(!self.read_only, egui::Button::new("Save Selection as RPA...")).clicked() {
+                self.show_save_selection_dialog = true;
+                ui.close_menu();
+            }
+
+            if ui
+                .add_enabled(self.modified, egui::Button::new("Discard All Changes..."))
+                .clicked()
+            {
+                self.show_discard_dialog = true;
+                ui.close_menu();
+            }
+
+            ui.separator();
+
+            if ui.button("Export as Ren'Py Project...").clicked() {
+                if let Some(folder) = rfd::FileDialog::new()
+                    .set_title("Folder to create the project in")
+                    .pick_folder()
+                {
+                    match self.export_renpy_project(&folder) {
+                        Ok(export) => self.add_toast(format!(
+                            "Exported {} files to {} ({} scripts decompiled, {} kept as .rpyc)",
+                            export.files,
+                            export.root.display(),
+                            export.decompiled,
+                            export.kept_compiled
+                        )),
+                        Err(e) => self.add_error(format!("Export error: {}", e)),
+                    }
                 }
                 ui.close_menu();
             }
 
-            if ui.button("Save As...").clicked() {
+            if ui.button("Export Changelog...").clicked() {
                 if let Some(path) = rfd::FileDialog::new()
-                    .add_filter("RPA files", &["rpa"])
+                    .add_filter("Markdown", &["md"])
+                    .set_file_name("CHANGES.md")
                     .save_file()
                 {
-                    match self.save_rpa(&path.to_string_lossy()) {
-                        Ok(()) => self.add_toast(format!("Save Succes at {}", path.to_string_lossy())),
-                        Err(e) => self.add_toast(format!("Save error: {}", e)),
+                    match self.export_changelog(&path) {
+                        Ok(count) => self.add_toast(format!("Wrote changelog with {} entries", count)),
+                        Err(e) => self.add_error(format!("Export error: {}", e)),
                     }
                 }
                 ui.close_menu();
             }
 
+            if ui.button("Export Workspace...").clicked() {
+                if let Some(path) = rfd::FileDialog::new()
+                    .add_filter("RPA workspace", &["rpaws"])
+                    .set_file_name("session.rpaws")
+                    .save_file()
+                {
+                    match self.export_workspace(&path) {
+                        Ok(count) => self.add_toast(format!("Exported workspace with {} pending changes", count)),
+                        Err(e) => self.add_error(format!("Export error: {}", e)),
+                    }
+                }
+                ui.close_menu();
+            }
+
+            if ui.add_enabled(!self.read_only, egui::Button::new("Import Workspace...")).clicked() {
+                if let Some(path) = rfd::FileDialog::new()
+                    .add_filter("RPA workspace", &["rpaws"])
+                    .pick_file()
+                {
+                    match Self::read_workspace_manifest(&path) {
+                        Ok(manifest) => {
+                            // The archive usually lives elsewhere on the importing machine.
+                            let archive = if Path::new(&manifest.archive).is_file() {
+                                Some(manifest.archive.clone())
+                            } else {
+                                rfd::FileDialog::new()
+                                    .set_title(format!("Locate {}", manifest.archive))
+                                    .add_filter("RPA files", &["rpa"])
+                                    .pick_file()
+                                    .map(|p| p.to_string_lossy().to_string())
+                            };
+                            if let Some(archive) = archive {
+                                match self.import_workspace(&path, &archive) {
+                                    Ok(count) => self.add_toast(format!("Imported workspace with {} pending changes", count)),
+                                    Err(e) => self.add_error(format!("Import error: {}", e)),
+                                }
+                            }
+                        }
+                        Err(e) => self.add_error(format!("Import error: {}", e)),
+                    }
+                }
+                ui.close_menu();
+            }
+
+            ui.separator();
+
             if ui.button("Close rpa").clicked() {
                 if !self.modified {
-                    if let Err(e) = self.unload_rpa() {
-                        self.add_toast(format!("Error unloading: {}", e));
-                    }
+                    self.close_active_tab();
                 } else {
                     self.show_close_confirm = true;
                 }
@@ -1124,9 +2563,7 @@ impl RpaEditor {
                     ui.label("Are you sure you want to close without saving?");
                     ui.horizontal(|ui| {
                         if ui.button("Yes").clicked() {
-                            if let Err(e) = self.unload_rpa() {
-                                self.add_toast(format!("Error unloading: {}", e));
-                            }
+                            self.close_active_tab();
                             self.show_close_confirm = false;
                         }
                         if ui.button("Cancel").clicked() {
@@ -1135,49 +2572,530 @@ impl RpaEditor {
                     });
                 });
         }
+
+        if self.show_save_as_dialog {
+            egui::Window::new("💾 Save As")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+                .show(ctx, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.label("Format:");
+                        egui::ComboBox::from_id_salt("save_as_format")
+                            .selected_text(self.save_as_format.magic)
+                            .show_ui(ui, |ui| {
+                                for format in HEADER_FORMATS {
+                                    if ui
+                                        .selectable_label(
+                                            std::ptr::eq(self.save_as_format, format),
+                                            format.magic,
+                                        )
+                                        .clicked()
+                                    {
+                                        self.save_as_format = format;
+                                    }
+                                }
+                            });
+                    });
+
+                    if self.save_as_format.is_keyed() && self.reproducible_build {
+                        ui.label(format!(
+                            "Key: {:08x} (derived from entries)",
+                            self.reproducible_key()
+                        ));
+                    } else if self.save_as_format.is_keyed() {
+                        ui.horizontal(|ui| {
+                            ui.label("Key:");
+                            ui.add(
+                                egui::TextEdit::singleline(&mut self.save_as_key)
+                                    .desired_width(100.0)
+                                    .font(egui::TextStyle::Monospace),
+                            );
+                            if ui.button("🎲 Generate").clicked() {
+                                self.save_as_key = format!("{:08x}", Self::generate_key());
+                            }
+                        });
+                    }
+
+                    if !std::ptr::eq(self.save_as_format, self.header_format) {
+                        ui.label(format!(
+                            "Converting from {} to {}",
+                            self.header_format.magic, self.save_as_format.magic
+                        ));
+                    }
+
+                    egui::CollapsingHeader::new("Advanced").show(ui, |ui| {
+                        ui.horizontal(|ui| {
+                            ui.label("Data start offset:");
+                            ui.add(
+                                egui::DragValue::new(&mut self.data_start_offset)
+                                    .range(0x19..=0xffff)
+                                    .hexadecimal(4, false, false)
+                                    .prefix("0x"),
+                            );
+                            if ui.button("Reset").clicked() {
+                                self.data_start_offset = DEFAULT_DATA_START;
+                            }
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("Padding after header:");
+                            ui.add(
+                                egui::TextEdit::singleline(&mut self.header_padding)
+                                    .hint_text("Made with Ren'Py.")
+                                    .desired_width(160.0),
+                            );
+                        });
+                    });
+
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        if ui.button("💾 Choose file...").clicked() {
+                            match u32::from_str_radix(self.save_as_key.trim(), 16) {
+                                Err(_) if self.save_as_format.is_keyed() => {
+                                    self.add_warning("Invalid key: expected 8 hex digits");
+                                }
+                                parsed => {
+                                    let key = parsed.unwrap_or(0);
+                                    if let Some(path) = rfd::FileDialog::new()
+                                        .add_filter("RPA files", &["rpa"])
+                                        .save_file()
+                                    {
+                                        self.request_save_as(&path.to_string_lossy(), self.save_as_format, key);
+                                        self.show_save_as_dialog = false;
+                                    }
+                                }
+                            }
+                        }
+                        if ui.button("Cancel").clicked() {
+                            self.show_save_as_dialog = false;
+                        }
+                    });
+                });
+        }
+
+        if let Some(path) = self.save_fallback_path.clone() {
+            egui::Window::new("🔒 Cannot Write Archive")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+                .show(ctx, |ui| {
+                    ui.label(format!("You don't have permission to write to:\n{}", path));
+                    ui.label("The folder is probably write-protected (Program Files, Steam library...).");
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        if ui.button("💾 Save As...").clicked() {
+                            self.save_fallback_path = None;
+                            if let Some(new_path) = rfd::FileDialog::new()
+                                .add_filter("RPA files", &["rpa"])
+                                .set_file_name(
+                                    Path::new(&path)
+                                        .file_name()
+                                        .map(|n| n.to_string_lossy().to_string())
+                                        .unwrap_or_default(),
+                                )
+                                .save_file()
+                            {
+                                self.request_save(&new_path.to_string_lossy());
+                            }
+                        }
+                        if ui.button("🛡️ Relaunch as administrator").clicked() {
+                            match self.relaunch_elevated() {
+                                Ok(()) => std::process::exit(0),
+                                Err(e) => self.add_error(format!("Relaunch error: {}", e)),
+                            }
+                        }
+                        if ui.button("Cancel").clicked() {
+                            self.save_fallback_path = None;
+                        }
+                    });
+                    if !self.modified_background_tabs().is_empty() {
+                        ui.label("Other tabs have unsaved changes; save or close them before relaunching.");
+                    } else if self.modified {
+                        ui.label("Unsaved changes are carried over to the relaunched editor.");
+                    }
+                });
+        }
     }
 
     pub(crate) fn show_tools_menu(&mut self, ui: &mut egui::Ui) {
         ui.menu_button("Tools", |ui| {
-            if ui.button("Add File...").clicked() {
+            if ui.add_enabled(!self.read_only, egui::Button::new("Add File...")).clicked() {
                 self.show_add_dialog = true;
                 ui.close_menu();
             }
 
+            if ui.add_enabled(!self.read_only, egui::Button::new("Add Folder...")).clicked() {
+                if let Some(folder) = rfd::FileDialog::new().pick_folder()
+                    && let Err(e) = self.open_folder_add(&folder)
+                {
+                    self.add_error(format!("Add Error: {}", e));
+                }
+                ui.close_menu();
+            }
+
+            if ui.add_enabled(!self.read_only, egui::Button::new("🔀 Merge Archive...")).clicked() {
+                if let Some(path) = rfd::FileDialog::new()
+                    .add_filter("RPA files", &["rpa"])
+                    .pick_file()
+                {
+                    match self.merge_archive(&path.to_string_lossy()) {
+                        Ok(count) => self.add_toast(format!("Merging {} entries", count)),
+                        Err(e) => self.add_error(format!("Merge error: {}", e)),
+                    }
+                }
+                ui.close_menu();
+            }
+
+            if ui.button("🔀 Compare with Archive...").clicked() {
+                if let Some(path) = rfd::FileDialog::new()
+                    .set_title("Select the base archive")
+                    .add_filter("RPA files", &["rpa"])
+                    .pick_file()
+                    && let Err(e) = self.diff_with_archive(&path.to_string_lossy())
+                {
+                    self.add_error(format!("Diff error: {}", e));
+                }
+                ui.close_menu();
+            }
+
+            if ui.button("🩹 Apply Patch Archive...").clicked() {
+                if let Some(path) = rfd::FileDialog::new()
+                    .set_title("Select the patch archive")
+                    .add_filter("RPA files", &["rpa"])
+                    .pick_file()
+                {
+                    match self.apply_patch_archive(&path.to_string_lossy()) {
+                        Ok(count) => self.add_toast(format!("Applying {} patched entries", count)),
+                        Err(e) => self.add_error(format!("Patch error: {}", e)),
+                    }
+                }
+                ui.close_menu();
+            }
+
+            if ui.button("✂️ Split Archive...").clicked() {
+                self.show_split_dialog = true;
+                ui.close_menu();
+            }
+
+            if ui.button("Export Translation Kit...").clicked() {
+                if let Some(path) = rfd::FileDialog::new()
+                    .add_filter("ZIP files", &["zip"])
+                    .set_file_name("translation_kit.zip")
+                    .save_file()
+                {
+                    match self.export_translation_kit(&path) {
+                        Ok(count) => self.add_toast(format!("Exported {} files to translation kit", count)),
+                        Err(e) => self.add_error(format!("Export error: {}", e)),
+                    }
+                }
+                ui.close_menu();
+            }
+
+            if ui.button("Export Manifest (JSON/CSV)...").clicked() {
+                if let Some(path) = rfd::FileDialog::new()
+                    .add_filter("JSON", &["json"])
+                    .add_filter("CSV", &["csv"])
+                    .set_file_name("manifest.json")
+                    .save_file()
+                {
+                    match self.export_manifest(&path) {
+                        Ok(count) => self.add_toast(format!("Manifest of {} entries written", count)),
+                        Err(e) => self.add_error(format!("Export error: {}", e)),
+                    }
+                }
+                ui.close_menu();
+            }
+
+            if ui.add_enabled(!self.read_only, egui::Button::new("Apply Manifest...")).on_hover_text("Rename and delete entries listed in a JSON or CSV file").clicked() {
+                if let Some(path) = rfd::FileDialog::new()
+                    .add_filter("Manifest", &["json", "csv"])
+                    .pick_file()
+                {
+                    match self.import_manifest(&path) {
+                        Ok(_) => self.add_toast(self.status_message.clone()),
+                        Err(e) => self.add_error(format!("Manifest error: {}", e)),
+                    }
+                }
+                ui.close_menu();
+            }
+
+            if ui.add_enabled(!self.read_only, egui::Button::new("✏️ Batch Rename...")).clicked() {
+                self.show_batch_rename_dialog = true;
+                ui.close_menu();
+            }
+
+            if ui.add_enabled(!self.read_only, egui::Button::new("Import from ZIP...")).clicked() {
+                if let Some(path) = rfd::FileDialog::new()
+                    .add_filter("ZIP files", &["zip"])
+                    .pick_file()
+                {
+                    match self.import_zip(&path) {
+                        Ok(count) => self.add_toast(format!("Imported {} files", count)),
+                        Err(e) => self.add_error(format!("Import error: {}", e)),
+                    }
+                }
+                ui.close_menu();
+            }
+
+            if ui.button("Import Translation Kit...").clicked() {
+                if let Some(path) = rfd::FileDialog::new()
+                    .add_filter("ZIP files", &["zip"])
+                    .pick_file()
+                {
+                    match self.import_translation_kit(&path) {
+                        Ok(count) => self.add_toast(format!("Imported {} translated files", count)),
+                        Err(e) => self.add_error(format!("Import error: {}", e)),
+                    }
+                }
+                ui.close_menu();
+            }
+
+            if ui.button("Export Dialogue (CSV/PO)...").clicked() {
+                if let Some(path) = rfd::FileDialog::new()
+                    .add_filter("CSV", &["csv"])
+                    .add_filter("gettext PO", &["po", "pot"])
+                    .set_file_name("dialogue.csv")
+                    .save_file()
+                {
+                    match self.export_dialogue(&path) {
+                        Ok(count) => self.add_toast(format!("Exported {} dialogue strings", count)),
+                        Err(e) => self.add_error(format!("Export error: {}", e)),
+                    }
+                }
+                ui.close_menu();
+            }
+
+            if ui.button("Import Dialogue Translation...").clicked() {
+                if let Some(path) = rfd::FileDialog::new()
+                    .add_filter("CSV or PO", &["csv", "po"])
+                    .pick_file()
+                {
+                    self.dialogue_import_path = Some(path);
+                    self.show_dialogue_import_dialog = true;
+                }
+                ui.close_menu();
+            }
+
+            if ui.button("Convert Script Encodings...").clicked() {
+                self.scan_script_encodings();
+                self.show_encoding_dialog = true;
+                ui.close_menu();
+            }
+
             ui.horizontal(|ui| {
-                if ui.button("🎯 Extract All Files").clicked() {
-                    if let Some(folder) = rfd::FileDialog::new().pick_folder() {
-                        match self.dump_all_files(&folder) {
-                            Ok(count) => {
-                                self.add_toast(format!(
-                                    "Extracted {} files to organized folders",
-                                    count
-                                ))
-                            }
-                            Err(e) => self.add_toast(format!("Extract Error: {}", e)),
+                if ui.button("🎯 Extract All Files").clicked()
+                    && let Some(folder) = rfd::FileDialog::new().pick_folder()
+                {
+                    self.request_extract("type", "all", folder);
+                    self.show_dump_dialog = false;
+                }
+                ui.label(format!("({} total files)", self.indexes.len()));
+            });
+
+            ui.checkbox(&mut self.regenerate_prefixes, "Regenerate prefixes on save");
+            ui.checkbox(&mut self.validate_on_open, "Check entry bounds on open");
+            let mut read_only = self.read_only;
+            if ui
+                .checkbox(&mut read_only, "🔒 Read-only mode")
+                .on_hover_text("Browse without any risk of changing the archive: adding, removing, replacing and saving are off")
+                .changed()
+            {
+                self.set_read_only(read_only);
+            }
+            ui.checkbox(&mut self.review_before_save, "Review changes before saving");
+            ui.checkbox(&mut self.confirm_remove, "Confirm before removing");
+            ui.checkbox(&mut self.use_mmap, "Memory-map archives").on_hover_text(
+                "Faster random access on multi-GB archives. Don't let other programs modify an archive while it's open.",
+            );
+
+            if self.is_large_archive_mode() && ui.button("Run Deferred Checks").clicked() {
+                match self.run_deferred_checks() {
+                    Ok(()) => self.add_toast(self.status_message.clone()),
+                    Err(e) => self.add_error(format!("Validation error: {}", e)),
+                }
+                ui.close_menu();
+            }
+            ui.checkbox(&mut self.reproducible_build, "Reproducible builds")
+                .on_hover_text("Derive the key from the entry list and report a digest after saving");
+            ui.horizontal(|ui| {
+                ui.label("Keep");
+                ui.add(egui::DragValue::new(&mut self.archive_bak_count).range(0..=10));
+                ui.label(".bak copies when overwriting an archive");
+            });
+            if ui.button("Reset Settings to Defaults").clicked() {
+                self.reset_settings();
+                self.set_theme(ui.ctx(), &self.theme.clone());
+                ui.close_menu();
+            }
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.autosave_enabled, "Autosave unsaved changes every")
+                    .on_hover_text("Snapshots pending edits next to the archive; reopening it offers them back");
+                ui.add_enabled(
+                    self.autosave_enabled,
+                    egui::DragValue::new(&mut self.autosave_minutes).range(1..=60).suffix(" min"),
+                );
+            });
+            ui.checkbox(&mut self.clean_temp_on_close, "Delete temp files when closing")
+                .on_hover_text("Files written for playback, Open Folder and external editing");
+            ui.horizontal(|ui| {
+                ui.label("Temp folder:");
+                ui.monospace(self.temp_dir.display().to_string());
+                if ui.button("📁 Change...").clicked()
+                    && let Some(folder) = rfd::FileDialog::new().pick_folder()
+                {
+                    // Cleaning empties the temp folder, so never point it at the picked one itself.
+                    self.temp_dir = folder.join("rpa_editor");
+                    ui.close_menu();
+                }
+            });
+            if ui
+                .button("🧹 Clean Temp Files")
+                .on_hover_text(format!(
+                    "Temp files in {} ({}); copies open in other apps are kept",
+                    self.temp_dir.display(),
+                    Self::format_bytes(self.temp_files_size())
+                ))
+                .clicked()
+            {
+                let freed = self.clean_temp_files();
+                self.add_toast(format!("Freed {}", Self::format_bytes(freed)));
+                ui.close_menu();
+            }
+            if ui
+                .button("Clear Preview Cache")
+                .on_hover_text(format!(
+                    "Cached thumbnails and decompiled scripts in {} ({})",
+                    preview_cache_dir().display(),
+                    Self::format_bytes(preview_cache_size())
+                ))
+                .clicked()
+            {
+                match self.clear_preview_cache() {
+                    Ok(()) => self.add_toast("Preview cache cleared"),
+                    Err(e) => self.add_error(format!("Could not clear the cache: {}", e)),
+                }
+                ui.close_menu();
+            }
+
+            if ui.button("🧩 Find Orphaned Data").clicked() {
+                self.orphaned_ranges = self.find_orphaned_ranges();
+                self.show_orphans_dialog = true;
+                ui.close_menu();
+            }
+
+            if ui.button("⚙ Job Queue").clicked() {
+                self.show_jobs_dialog = true;
+                ui.close_menu();
+            }
+
+            if ui
+                .add_enabled(!self.indexes.is_empty(), egui::Button::new("♊ Find Duplicate Entries"))
+                .clicked()
+            {
+                self.find_duplicates();
+                ui.close_menu();
+            }
+
+            if ui
+                .add_enabled(self.archive_path.is_some(), egui::Button::new("🩺 Verify Archive"))
+                .clicked()
+            {
+                self.run_archive_verification();
+                ui.close_menu();
+            }
+
+            if ui
+                .add_enabled(!self.indexes.is_empty(), egui::Button::new("🔬 Deep Verify"))
+                .on_hover_text("Read and decode every entry in the background")
+                .clicked()
+            {
+                self.deep_verify();
+                ui.close_menu();
+            }
+
+            if ui
+                .add_enabled(
+                    self.archive_path.is_some() && !self.read_only && !self.modified,
+                    egui::Button::new("🧹 Compact Archive"),
+                )
+                .on_hover_text(format!(
+                    "Rewrite the archive without unused data ({} now)",
+                    Self::format_bytes(self.slack_bytes())
+                ))
+                .on_disabled_hover_text("Save or discard the pending changes first")
+                .clicked()
+            {
+                match self.compact_archive() {
+                    Ok(reclaimed) => self.add_toast(format!("Compacted, {} reclaimed", Self::format_bytes(reclaimed))),
+                    Err(e) => self.add_error(format!("Compact error: {}", e)),
+                }
+                ui.close_menu();
+            }
+
+            ui.menu_button("🔐 Checksums", |ui| {
+                if ui.button("Compute SHA-256").clicked() {
+                    self.show_checksum_column = true;
+                    self.compute_checksums();
+                    ui.close_menu();
+                }
+                if ui.button("Export Checksum File...").clicked() {
+                    if let Some(path) = rfd::FileDialog::new()
+                        .add_filter("Checksums", &["sha256"])
+                        .set_file_name("checksums.sha256")
+                        .save_file()
+                    {
+                        match self.export_checksums(&path) {
+                            Ok(count) => self.add_toast(format!("Wrote {} checksums", count)),
+                            Err(e) => self.add_error(format!("Checksum export error: {}", e)),
                         }
-                        self.show_dump_dialog = false;
                     }
+                    ui.close_menu();
+                }
+                if ui.button("Verify Against Checksum File...").clicked() {
+                    if let Some(path) = rfd::FileDialog::new()
+                        .add_filter("Checksums", &["sha256", "txt"])
+                        .pick_file()
+                        && let Err(e) = self.verify_checksum_file(&path)
+                    {
+                        self.add_error(format!("Checksum file error: {}", e));
+                    }
+                    ui.close_menu();
                 }
-                ui.label(format!("({} total files)", self.indexes.len()));
             });
 
+            if ui.button("🧹 Find Unused Assets").clicked() {
+                self.unused_assets = self.find_unused_assets();
+                self.show_unused_assets_dialog = true;
+                ui.close_menu();
+            }
+
+            if ui
+                .add_enabled(!self.key_recovery_running(), egui::Button::new("🔑 Recover Key"))
+                .on_disabled_hover_text("Scanning the archive; see the job queue")
+                .clicked()
+            {
+                if let Err(e) = self.start_key_recovery() {
+                    self.add_error(format!("Key recovery failed: {}", e));
+                }
+                ui.close_menu();
+            }
+
             if ui.button("Replace...").clicked() {
                 if let Some(path) = rfd::FileDialog::new()
                     .set_title("Select replacement file")
                     .pick_file()
                 {
                     let file_path = path.to_string_lossy().to_string();
-                    println!("🔍 Selected replacement file: {}", file_path);
+                    debug!("🔍 Selected replacement file: {}", file_path);
 
                     if Path::new(&file_path).exists() {
                         if let Some(filename) = self.selected_file.clone() {
                             self.file_to_replace = Some((filename, file_path));
                         } else {
-                            self.add_toast("No file selected to replace".to_string());
+                            self.add_warning("No file selected to replace".to_string());
                         }
                     } else {
-                        self.add_toast(format!("Selected file does not exist: {}", file_path));
+                        self.add_warning(format!("Selected file does not exist: {}", file_path));
                     }
                 }
                 ui.close_menu();
@@ -1187,21 +3105,60 @@ impl RpaEditor {
 
     pub(crate) fn show_view_menu(&mut self, ui: &mut egui::Ui) {
         ui.menu_button("View", |ui| {
-            if ui.button("Add File").clicked() {
+            if ui.add_enabled(!self.read_only, egui::Button::new("Add File")).clicked() {
                 self.show_add_dialog = true;
             }
-            if ui.button("Batch Replace").clicked() {
+            if ui.add_enabled(!self.read_only, egui::Button::new("Batch Replace")).clicked() {
                 self.show_batch_replace_dialog = true;
             }
+            if ui.add_enabled(!self.read_only, egui::Button::new("Watch Folder")).clicked() {
+                self.show_watch_folder_dialog = true;
+            }
             if ui.button("Archive Statistics").clicked() {
                 self.show_statistics_dialog = true;
             }
             if ui.button("Backup File").clicked() {
+                self.scan_disk_backups();
                 self.show_backup_dialog = true;
             }
             if ui.button("Special Dump").clicked() {
                 self.show_dump_dialog = true;
             }
+            if ui.button("Preview Settings").clicked() {
+                self.show_preview_settings_dialog = true;
+            }
+            if ui.button("Icon Legend").clicked() {
+                self.show_icon_legend = true;
+            }
+            self.show_theme_menu(ui);
+            ui.checkbox(&mut self.show_checksum_column, "Show Checksums");
+            if ui.button("Notification History").clicked() {
+                self.show_notification_history = true;
+            }
+            if ui.button("Log Console").clicked() {
+                self.open_log_console();
+            }
+            if ui.button("Environment Report").clicked() {
+                self.show_health_dialog = true;
+            }
+            if ui.button("Header & Key").clicked() {
+                self.custom_key = format!("{:08x}", self.key);
+                self.show_header_dialog = true;
+            }
+
+            ui.separator();
+            ui.label("Large archive mode");
+            ui.horizontal(|ui| {
+                for (mode, label) in [("off", "Off"), ("auto", "Auto"), ("on", "On")] {
+                    ui.radio_value(&mut self.large_archive_mode, mode.to_string(), label);
+                }
+            })
+            .response
+            .on_hover_text(format!(
+                "Virtualized list, small image previews, no previews above {} and no load-time checks. Auto turns on at {} entries.",
+                Self::format_bytes(LARGE_PREVIEW_LIMIT),
+                LARGE_ARCHIVE_THRESHOLD
+            ));
         });
     }
 
@@ -1216,9 +3173,224 @@ impl RpaEditor {
                 }
             });
         });
+        if self.archive_tabs.len() > 1 || self.archive_path.is_some() {
+            egui::TopBottomPanel::top("archive_tabs").show(ctx, |ui| {
+                self.show_archive_tabs(ui);
+            });
+        }
     }
 
     pub(crate) fn add_toast(&mut self, message: impl Into<String>) {
-        self.toasts.push(Toast::new(message));
+        self.push_toast(Toast::new(message));
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TestDir;
+
+    const PNG_HEAD: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+    const OGG_HEAD: &[u8] = b"OggS\0\x02\0\0\0\0\0\0\0\0";
+
+    /// An editor holding `images/bg.png` as an unsaved entry, with nothing backed up.
+    fn editor_with_image() -> RpaEditor {
+        let mut editor = RpaEditor {
+            auto_backup: false,
+            ..RpaEditor::default()
+        };
+        editor
+            .indexes
+            .insert("images/bg.png".to_string(), RpaFileEntry::from_data(PNG_HEAD.to_vec()));
+        editor
+    }
+
+    fn source_file(dir: &TestDir, name: &str, data: &[u8]) -> PathBuf {
+        let path = dir.path().join(name);
+        std::fs::write(&path, data).unwrap();
+        path
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn replace_missing_entry() {
+        let mut editor = editor_with_image();
+        let dir = TestDir::new("replace");
+        let source = source_file(&dir, "missing_entry.png", PNG_HEAD);
+        let result = editor.replace_entry("images/other.png", &source.to_string_lossy(), false);
+        assert!(result.unwrap_err().to_string().contains("isn't found in the archive"));
+        assert!(!editor.indexes.contains_key("images/other.png"));
+    }
+
+    #[test]
+    fn replace_missing_source() {
+        let mut editor = editor_with_image();
+        let dir = TestDir::new("replace");
+        let source = dir.path().join("no_such_file.png");
+        let result = editor.replace_entry("images/bg.png", &source.to_string_lossy(), false);
+        assert!(result.unwrap_err().to_string().contains("Replacement file isn't found"));
+        assert_eq!(editor.indexes["images/bg.png"].data.as_deref(), Some(PNG_HEAD));
+    }
+
+    #[test]
+    fn replace_type_mismatch() {
+        let mut editor = editor_with_image();
+        let dir = TestDir::new("replace");
+        let source = source_file(&dir, "mismatch.ogg", OGG_HEAD);
+        let error = editor
+            .replace_entry("images/bg.png", &source.to_string_lossy(), false)
+            .unwrap_err();
+        let mismatch = error.downcast_ref::<TypeMismatch>().expect("a TypeMismatch");
+        assert_eq!(mismatch.archive_name, "images/bg.png");
+        assert_eq!(mismatch.expected, "images");
+        assert_eq!(mismatch.found, "audio");
+        assert_eq!(editor.indexes["images/bg.png"].data.as_deref(), Some(PNG_HEAD));
+    }
+
+    #[test]
+    fn replace_allowing_type_change() {
+        let mut editor = editor_with_image();
+        let dir = TestDir::new("replace");
+        let source = source_file(&dir, "allowed.ogg", OGG_HEAD);
+        editor
+            .replace_entry("images/bg.png", &source.to_string_lossy(), true)
+            .unwrap();
+        assert_eq!(editor.indexes["images/bg.png"].data.as_deref(), Some(OGG_HEAD));
+        assert!(editor.modified);
+    }
+
+    /// `file_to_replace` holds (archive name, source path); passing them the other way
+    /// round used to look the disk path up in the archive.
+    #[test]
+    fn replace_argument_order() {
+        let mut editor = editor_with_image();
+        let replacement = [PNG_HEAD, b"new pixels"].concat();
+        let dir = TestDir::new("replace");
+        let source = source_file(&dir, "order.png", &replacement);
+        let source = source.to_string_lossy().to_string();
+        editor.file_to_replace = Some(("images/bg.png".to_string(), source.clone()));
+
+        let (archive_name, source_path) = editor.file_to_replace.take().unwrap();
+        editor.replace_entry(&archive_name, &source_path, false).unwrap();
+        assert_eq!(editor.indexes["images/bg.png"].data.as_deref(), Some(replacement.as_slice()));
+        assert!(!editor.indexes.contains_key(&source));
+
+        assert!(editor.replace_entry(&source, "images/bg.png", false).is_err());
+        assert_eq!(editor.indexes.len(), 1);
+    }
+
+    /// An archive holding `a.txt` followed by `b.txt`, saved and opened in a new editor.
+    fn saved_archive(dir: &TestDir, name: &str) -> (RpaEditor, String) {
+        let path = dir.file(name);
+        let mut editor = RpaEditor {
+            auto_backup: false,
+            archive_bak_count: 0,
+            ..RpaEditor::default()
+        };
+        editor.add_data("a.txt", b"short".to_vec()).unwrap();
+        editor.add_data("b.txt", b"unchanged".to_vec()).unwrap();
+        editor.save_rpa(&path).unwrap();
+        let mut editor = RpaEditor {
+            auto_backup: false,
+            archive_bak_count: 0,
+            ..RpaEditor::default()
+        };
+        editor.load_rpa(&path).unwrap();
+        (editor, path)
+    }
+
+    /// Saving over the open archive moves the entries after a resized one; the index has
+    /// to follow them or the next save copies the wrong bytes.
+    #[test]
+    fn save_twice_after_resize() {
+        let dir = TestDir::new("save_twice");
+        let (mut editor, path) = saved_archive(&dir, "save_twice.rpa");
+        let format = editor.header_format;
+        let key = editor.key;
+
+        editor.add_data("a.txt", b"a much longer replacement".to_vec()).unwrap();
+        assert!(editor.save_with_fallback(&path, format, key));
+        assert!(!editor.modified);
+        assert!(editor.indexes["a.txt"].data.is_none());
+        assert_eq!(editor.load_file_data("b.txt").unwrap(), b"unchanged");
+
+        editor.add_data("a.txt", b"tiny".to_vec()).unwrap();
+        editor.remove_file("a.txt");
+        assert!(editor.save_with_fallback(&path, format, key));
+        assert!(!editor.indexes.contains_key("a.txt"));
+
+        let mut reopened = RpaEditor::default();
+        reopened.load_rpa(&path).unwrap();
+        assert_eq!(reopened.load_file_data("b.txt").unwrap(), b"unchanged");
+        assert_eq!(reopened.indexes.len(), 1);
+    }
+
+    /// Two editors given the same entries in a different order, each with its own
+    /// random key, write the same bytes.
+    #[test]
+    fn reproducible_builds_are_byte_identical() {
+        let entries: [(&str, &[u8]); 3] = [
+            ("images/bg.png", PNG_HEAD),
+            ("audio/a.ogg", OGG_HEAD),
+            ("script.rpy", b"label start:\n"),
+        ];
+        let dir = TestDir::new("reproducible");
+        let mut outputs = Vec::new();
+        for (run, order) in [[0, 1, 2], [2, 0, 1]].iter().enumerate() {
+            let mut editor = RpaEditor {
+                auto_backup: false,
+                reproducible_build: true,
+                key: RpaEditor::generate_key(),
+                ..RpaEditor::default()
+            };
+            for &i in order {
+                editor.add_data(entries[i].0, entries[i].1.to_vec()).unwrap();
+            }
+            let path = dir.file(&format!("reproducible_{}.rpa", run));
+            editor.save_rpa(&path).unwrap();
+            outputs.push(std::fs::read(&path).unwrap());
+        }
+        assert_eq!(outputs[0], outputs[1]);
+    }
+
+    #[test]
+    fn entries_marked_for_deletion_are_not_written() {
+        let dir = TestDir::new("deleted_entry");
+        let (mut editor, path) = saved_archive(&dir, "deleted_entry.rpa");
+        editor.remove_file("a.txt");
+        let target = format!("{}.copy", path);
+        editor.save_rpa(&target).unwrap();
+
+        let mut reopened = RpaEditor::default();
+        reopened.load_rpa(&target).unwrap();
+        assert!(!reopened.indexes.contains_key("a.txt"));
+        assert_eq!(reopened.load_file_data("b.txt").unwrap(), b"unchanged");
+    }
+
+    #[test]
+    fn failed_save_removes_its_temp_file() {
+        let dir = TestDir::new("failed_save");
+        let (mut editor, path) = saved_archive(&dir, "failed_save.rpa");
+        editor.indexes.get_mut("b.txt").unwrap().length += 1;
+        let target = format!("{}.copy", path);
+
+        let error = editor.save_rpa(&target).unwrap_err();
+        assert!(error.to_string().contains("stored length of b.txt"), "{}", error);
+        assert!(!Path::new(&format!("{}.tmp", target)).exists());
+        assert!(!Path::new(&target).exists());
+    }
+
+    #[test]
+    fn filter_counts_follow_edits_and_filters() {
+        let dir = TestDir::new("filter_counts");
+        let (mut editor, _) = saved_archive(&dir, "filter_counts.rpa");
+        assert_eq!(editor.filter_counts(), (2, [2, 0, 0, 0]));
+
+        editor.change_filter = "delete".to_string();
+        assert_eq!(editor.filter_counts(), (0, [2, 0, 0, 0]));
+
+        editor.remove_file("a.txt");
+        assert_eq!(editor.filter_counts(), (1, [2, 0, 1, 0]));
+
+        editor.search_filter = "b.txt".to_string();
+        assert_eq!(editor.filter_counts().0, 0);
+    }
+}
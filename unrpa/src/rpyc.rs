@@ -0,0 +1,1172 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io::Read;
+use std::rc::Rc;
+use flate2::read::ZlibDecoder;
+
+const RPC2_MAGIC: &[u8] = b"RENPY RPC2";
+
+/// Value produced by the pickle machine.
+///
+/// Containers are shared because pickles memoize a list or object first and
+/// fill it afterwards, and Ren'Py's AST references the same nodes from several places.
+#[derive(Debug, Clone)]
+pub enum PyValue {
+    None,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Str(String),
+    Bytes(Vec<u8>),
+    Tuple(Rc<Vec<PyValue>>),
+    List(Rc<RefCell<Vec<PyValue>>>),
+    Dict(Rc<RefCell<Vec<(PyValue, PyValue)>>>),
+    Global(String),
+    Object(Rc<RefCell<PyObject>>),
+}
+
+/// Instance of a class the machine can't construct, kept as class name plus state.
+#[derive(Debug, Default)]
+pub struct PyObject {
+    pub class: String,
+    pub args: Vec<PyValue>,
+    pub attrs: HashMap<String, PyValue>,
+    pub state: Option<PyValue>,
+    pub items: Vec<PyValue>,
+}
+
+impl PyValue {
+    fn object(class: String, args: Vec<PyValue>) -> Self {
+        PyValue::Object(Rc::new(RefCell::new(PyObject {
+            class,
+            args,
+            ..Default::default()
+        })))
+    }
+
+    /// String content, looking through `PyExpr`-style `str` subclasses.
+    pub fn as_str(&self) -> Option<String> {
+        match self {
+            PyValue::Str(s) => Some(s.clone()),
+            PyValue::Bytes(b) => Some(String::from_utf8_lossy(b).to_string()),
+            PyValue::Object(obj) => {
+                let obj = obj.borrow();
+                obj.args.first().and_then(PyValue::as_str).or_else(|| {
+                    obj.attrs.get("source").and_then(PyValue::as_str)
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Elements of a list, tuple, or list subclass.
+    pub fn items(&self) -> Vec<PyValue> {
+        match self {
+            PyValue::Tuple(items) => items.to_vec(),
+            PyValue::List(items) => items.borrow().clone(),
+            PyValue::Object(obj) => {
+                let obj = obj.borrow();
+                if obj.items.is_empty() {
+                    obj.args.first().map(|a| a.items()).unwrap_or_default()
+                } else {
+                    obj.items.clone()
+                }
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn is_none(&self) -> bool {
+        matches!(self, PyValue::None)
+    }
+
+    pub fn class_name(&self) -> Option<String> {
+        match self {
+            PyValue::Object(obj) => Some(obj.borrow().class.clone()),
+            _ => None,
+        }
+    }
+
+    pub fn attr(&self, name: &str) -> PyValue {
+        match self {
+            PyValue::Object(obj) => obj.borrow().attrs.get(name).cloned().unwrap_or(PyValue::None),
+            PyValue::Dict(items) => items
+                .borrow()
+                .iter()
+                .find(|(k, _)| k.as_str().as_deref() == Some(name))
+                .map(|(_, v)| v.clone())
+                .unwrap_or(PyValue::None),
+            _ => PyValue::None,
+        }
+    }
+
+    fn truthy(&self) -> bool {
+        match self {
+            PyValue::None => false,
+            PyValue::Bool(b) => *b,
+            PyValue::Int(i) => *i != 0,
+            PyValue::Float(f) => *f != 0.0,
+            PyValue::Str(s) => !s.is_empty(),
+            PyValue::Tuple(t) => !t.is_empty(),
+            PyValue::List(l) => !l.borrow().is_empty(),
+            _ => true,
+        }
+    }
+}
+
+struct Unpickler<'a> {
+    data: &'a [u8],
+    pos: usize,
+    stack: Vec<PyValue>,
+    marks: Vec<usize>,
+    memo: HashMap<u64, PyValue>,
+}
+
+impl<'a> Unpickler<'a> {
+    fn take(&mut self, n: usize) -> anyhow::Result<&'a [u8]> {
+        let end = self
+            .pos
+            .checked_add(n)
+            .filter(|&end| end <= self.data.len())
+            .ok_or_else(|| anyhow::anyhow!("Truncated pickle at {}", self.pos))?;
+        let slice = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn u8(&mut self) -> anyhow::Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> anyhow::Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into()?))
+    }
+
+    fn u64(&mut self) -> anyhow::Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into()?))
+    }
+
+    fn line(&mut self) -> anyhow::Result<String> {
+        let start = self.pos;
+        while self.u8()? != b'\n' {}
+        Ok(String::from_utf8_lossy(&self.data[start..self.pos - 1]).to_string())
+    }
+
+    fn pop(&mut self) -> anyhow::Result<PyValue> {
+        self.stack.pop().ok_or_else(|| anyhow::anyhow!("Pickle stack underflow"))
+    }
+
+    fn pop_mark(&mut self) -> anyhow::Result<Vec<PyValue>> {
+        let mark = self.marks.pop().ok_or_else(|| anyhow::anyhow!("Pickle mark underflow"))?;
+        if mark > self.stack.len() {
+            return Err(anyhow::anyhow!("Pickle mark past stack end"));
+        }
+        Ok(self.stack.split_off(mark))
+    }
+
+    fn top(&mut self) -> anyhow::Result<&mut PyValue> {
+        self.stack.last_mut().ok_or_else(|| anyhow::anyhow!("Pickle stack underflow"))
+    }
+
+    fn string(&mut self, n: usize) -> anyhow::Result<PyValue> {
+        Ok(PyValue::Str(String::from_utf8_lossy(self.take(n)?).to_string()))
+    }
+
+    fn long(&mut self, n: usize) -> anyhow::Result<PyValue> {
+        let bytes = self.take(n)?;
+        if bytes.is_empty() {
+            return Ok(PyValue::Int(0));
+        }
+        let mut buf = if bytes[bytes.len() - 1] & 0x80 != 0 { [0xFF; 8] } else { [0; 8] };
+        let len = n.min(8);
+        buf[..len].copy_from_slice(&bytes[..len]);
+        Ok(PyValue::Int(i64::from_le_bytes(buf)))
+    }
+
+    fn append(&mut self, values: Vec<PyValue>) -> anyhow::Result<()> {
+        match self.top()? {
+            PyValue::List(list) => list.borrow_mut().extend(values),
+            PyValue::Object(obj) => obj.borrow_mut().items.extend(values),
+            _ => return Err(anyhow::anyhow!("APPEND on a non-list")),
+        }
+        Ok(())
+    }
+
+    fn set_items(&mut self, values: Vec<PyValue>) -> anyhow::Result<()> {
+        let mut pairs = Vec::new();
+        let mut iter = values.into_iter();
+        while let (Some(k), Some(v)) = (iter.next(), iter.next()) {
+            pairs.push((k, v));
+        }
+        match self.top()? {
+            PyValue::Dict(dict) => dict.borrow_mut().extend(pairs),
+            PyValue::Object(obj) => {
+                let mut obj = obj.borrow_mut();
+                for (k, v) in pairs {
+                    if let Some(k) = k.as_str() {
+                        obj.attrs.insert(k, v);
+                    }
+                }
+            }
+            _ => return Err(anyhow::anyhow!("SETITEM on a non-dict")),
+        }
+        Ok(())
+    }
+
+    fn reduce(callable: PyValue, args: PyValue) -> PyValue {
+        let name = match &callable {
+            PyValue::Global(name) => name.clone(),
+            _ => String::new(),
+        };
+        let args = args.items();
+        match name.as_str() {
+            "copy_reg._reconstructor" | "copyreg._reconstructor" => match args.first() {
+                Some(PyValue::Global(class)) => PyValue::object(class.clone(), Vec::new()),
+                _ => PyValue::object(name, args),
+            },
+            "__builtin__.set" | "builtins.set" | "__builtin__.frozenset" | "builtins.frozenset"
+            | "__builtin__.list" | "builtins.list" => PyValue::List(Rc::new(RefCell::new(
+                args.first().map(|a| a.items()).unwrap_or_default(),
+            ))),
+            "collections.OrderedDict" => PyValue::Dict(Rc::default()),
+            _ => PyValue::object(name, args),
+        }
+    }
+
+    fn build(target: &PyValue, state: PyValue) {
+        let PyValue::Object(obj) = target else {
+            return;
+        };
+        let mut obj = obj.borrow_mut();
+        // Classes with __slots__ pickle `(dict_or_none, slots_dict)`.
+        let parts = match &state {
+            PyValue::Tuple(parts) if parts.len() == 2 && matches!(parts[1], PyValue::Dict(_)) => {
+                parts.to_vec()
+            }
+            _ => vec![state.clone()],
+        };
+        let mut applied = false;
+        for part in parts {
+            if let PyValue::Dict(items) = part {
+                for (k, v) in items.borrow().iter() {
+                    if let Some(k) = k.as_str() {
+                        obj.attrs.insert(k, v.clone());
+                    }
+                }
+                applied = true;
+            }
+        }
+        if !applied {
+            obj.state = Some(state);
+        }
+    }
+
+    fn run(&mut self) -> anyhow::Result<PyValue> {
+        loop {
+            let op = self.u8()?;
+            match op {
+                0x80 => {
+                    self.u8()?;
+                }
+                0x95 => {
+                    self.u64()?;
+                }
+                b'.' => return self.pop(),
+                b'(' => self.marks.push(self.stack.len()),
+                b')' => self.stack.push(PyValue::Tuple(Rc::default())),
+                b']' => self.stack.push(PyValue::List(Rc::default())),
+                b'}' => self.stack.push(PyValue::Dict(Rc::default())),
+                0x8f => self.stack.push(PyValue::List(Rc::default())),
+                b'N' => self.stack.push(PyValue::None),
+                0x88 => self.stack.push(PyValue::Bool(true)),
+                0x89 => self.stack.push(PyValue::Bool(false)),
+                b'J' => {
+                    let v = self.u32()? as i32;
+                    self.stack.push(PyValue::Int(v as i64));
+                }
+                b'K' => {
+                    let v = self.u8()?;
+                    self.stack.push(PyValue::Int(v as i64));
+                }
+                b'M' => {
+                    let v = u16::from_le_bytes(self.take(2)?.try_into()?);
+                    self.stack.push(PyValue::Int(v as i64));
+                }
+                0x8a => {
+                    let n = self.u8()? as usize;
+                    let v = self.long(n)?;
+                    self.stack.push(v);
+                }
+                0x8b => {
+                    let n = self.u32()? as usize;
+                    let v = self.long(n)?;
+                    self.stack.push(v);
+                }
+                b'G' => {
+                    let v = f64::from_be_bytes(self.take(8)?.try_into()?);
+                    self.stack.push(PyValue::Float(v));
+                }
+                b'I' | b'L' => {
+                    let line = self.line()?;
+                    let line = line.trim_end_matches('L');
+                    self.stack.push(match line {
+                        "00" => PyValue::Bool(false),
+                        "01" => PyValue::Bool(true),
+                        _ => PyValue::Int(line.parse().unwrap_or_default()),
+                    });
+                }
+                b'F' => {
+                    let line = self.line()?;
+                    self.stack.push(PyValue::Float(line.parse().unwrap_or_default()));
+                }
+                b'V' | b'S' => {
+                    let line = self.line()?;
+                    self.stack.push(PyValue::Str(line.trim_matches('\'').to_string()));
+                }
+                b'X' | b'T' => {
+                    let n = self.u32()? as usize;
+                    let v = self.string(n)?;
+                    self.stack.push(v);
+                }
+                0x8c | b'U' => {
+                    let n = self.u8()? as usize;
+                    let v = self.string(n)?;
+                    self.stack.push(v);
+                }
+                0x8d => {
+                    let n = self.u64()? as usize;
+                    let v = self.string(n)?;
+                    self.stack.push(v);
+                }
+                b'C' => {
+                    let n = self.u8()? as usize;
+                    let v = self.take(n)?.to_vec();
+                    self.stack.push(PyValue::Bytes(v));
+                }
+                b'B' => {
+                    let n = self.u32()? as usize;
+                    let v = self.take(n)?.to_vec();
+                    self.stack.push(PyValue::Bytes(v));
+                }
+                0x8e | 0x96 => {
+                    let n = self.u64()? as usize;
+                    let v = self.take(n)?.to_vec();
+                    self.stack.push(PyValue::Bytes(v));
+                }
+                b'q' => {
+                    let i = self.u8()? as u64;
+                    let v = self.top()?.clone();
+                    self.memo.insert(i, v);
+                }
+                b'r' => {
+                    let i = self.u32()? as u64;
+                    let v = self.top()?.clone();
+                    self.memo.insert(i, v);
+                }
+                b'p' => {
+                    let i = self.line()?.parse()?;
+                    let v = self.top()?.clone();
+                    self.memo.insert(i, v);
+                }
+                0x94 => {
+                    let i = self.memo.len() as u64;
+                    let v = self.top()?.clone();
+                    self.memo.insert(i, v);
+                }
+                b'h' | b'j' | b'g' => {
+                    let i = match op {
+                        b'h' => self.u8()? as u64,
+                        b'j' => self.u32()? as u64,
+                        _ => self.line()?.parse()?,
+                    };
+                    let v = self
+                        .memo
+                        .get(&i)
+                        .cloned()
+                        .ok_or_else(|| anyhow::anyhow!("Missing memo entry {}", i))?;
+                    self.stack.push(v);
+                }
+                b't' => {
+                    let items = self.pop_mark()?;
+                    self.stack.push(PyValue::Tuple(Rc::new(items)));
+                }
+                0x85..=0x87 => {
+                    let n = (op - 0x84) as usize;
+                    if self.stack.len() < n {
+                        return Err(anyhow::anyhow!("Pickle stack underflow"));
+                    }
+                    let items = self.stack.split_off(self.stack.len() - n);
+                    self.stack.push(PyValue::Tuple(Rc::new(items)));
+                }
+                b'l' | 0x91 => {
+                    let items = self.pop_mark()?;
+                    self.stack.push(PyValue::List(Rc::new(RefCell::new(items))));
+                }
+                b'd' => {
+                    let items = self.pop_mark()?;
+                    self.stack.push(PyValue::Dict(Rc::default()));
+                    self.set_items(items)?;
+                }
+                b'a' => {
+                    let v = self.pop()?;
+                    self.append(vec![v])?;
+                }
+                b'e' | 0x90 => {
+                    let items = self.pop_mark()?;
+                    self.append(items)?;
+                }
+                b's' => {
+                    let v = self.pop()?;
+                    let k = self.pop()?;
+                    self.set_items(vec![k, v])?;
+                }
+                b'u' => {
+                    let items = self.pop_mark()?;
+                    self.set_items(items)?;
+                }
+                b'c' => {
+                    let module = self.line()?;
+                    let name = self.line()?;
+                    self.stack.push(PyValue::Global(format!("{}.{}", module, name)));
+                }
+                0x93 => {
+                    let name = self.pop()?.as_str().unwrap_or_default();
+                    let module = self.pop()?.as_str().unwrap_or_default();
+                    self.stack.push(PyValue::Global(format!("{}.{}", module, name)));
+                }
+                b'R' => {
+                    let args = self.pop()?;
+                    let callable = self.pop()?;
+                    self.stack.push(Self::reduce(callable, args));
+                }
+                0x81 => {
+                    let args = self.pop()?;
+                    let class = self.pop()?;
+                    let class = match class {
+                        PyValue::Global(name) => name,
+                        _ => String::new(),
+                    };
+                    self.stack.push(PyValue::object(class, args.items()));
+                }
+                0x92 => {
+                    let _kwargs = self.pop()?;
+                    let args = self.pop()?;
+                    let class = match self.pop()? {
+                        PyValue::Global(name) => name,
+                        _ => String::new(),
+                    };
+                    self.stack.push(PyValue::object(class, args.items()));
+                }
+                b'o' => {
+                    let mut items = self.pop_mark()?;
+                    if items.is_empty() {
+                        return Err(anyhow::anyhow!("OBJ without a class"));
+                    }
+                    let class = match items.remove(0) {
+                        PyValue::Global(name) => name,
+                        _ => String::new(),
+                    };
+                    self.stack.push(PyValue::object(class, items));
+                }
+                b'i' => {
+                    let module = self.line()?;
+                    let name = self.line()?;
+                    let items = self.pop_mark()?;
+                    self.stack.push(PyValue::object(format!("{}.{}", module, name), items));
+                }
+                b'b' => {
+                    let state = self.pop()?;
+                    let target = self.top()?.clone();
+                    Self::build(&target, state);
+                }
+                b'0' => {
+                    self.pop()?;
+                }
+                b'1' => {
+                    self.pop_mark()?;
+                }
+                b'2' => {
+                    let v = self.top()?.clone();
+                    self.stack.push(v);
+                }
+                _ => {
+                    return Err(anyhow::anyhow!(
+                        "Unsupported pickle opcode 0x{:02x} at {}",
+                        op,
+                        self.pos - 1
+                    ))
+                }
+            }
+        }
+    }
+}
+
+pub fn unpickle(data: &[u8]) -> anyhow::Result<PyValue> {
+    Unpickler {
+        data,
+        pos: 0,
+        stack: Vec::new(),
+        marks: Vec::new(),
+        memo: HashMap::new(),
+    }
+    .run()
+}
+
+/// Returns the zlib-compressed pickle of slot 1, or the whole file for pre-RPC2 scripts.
+fn script_slot(data: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let compressed = if data.starts_with(RPC2_MAGIC) {
+        let mut pos = RPC2_MAGIC.len();
+        let mut slot_data = None;
+        while pos + 12 <= data.len() {
+            let read = |at: usize| u32::from_le_bytes(data[at..at + 4].try_into().unwrap()) as usize;
+            let (slot, start, length) = (read(pos), read(pos + 4), read(pos + 8));
+            if slot == 0 {
+                break;
+            }
+            if slot == 1 {
+                slot_data = data.get(start..start + length);
+                break;
+            }
+            pos += 12;
+        }
+        slot_data.ok_or_else(|| anyhow::anyhow!("No script slot in .rpyc"))?
+    } else {
+        data
+    };
+
+    let mut decoder = ZlibDecoder::new(compressed);
+    let mut pickle = Vec::new();
+    decoder.read_to_end(&mut pickle)?;
+    Ok(pickle)
+}
+
+/// Top-level AST statements of a compiled script.
+pub fn load_statements(data: &[u8]) -> anyhow::Result<Vec<PyValue>> {
+    let root = unpickle(&script_slot(data)?)?;
+    let items = root.items();
+    match items.as_slice() {
+        [_, stmts] => Ok(stmts.items()),
+        _ => Err(anyhow::anyhow!("Unexpected .rpyc root object")),
+    }
+}
+
+pub fn decompile(data: &[u8]) -> anyhow::Result<String> {
+    let statements = load_statements(data)?;
+    let mut decompiler = Decompiler::default();
+    decompiler.block(&statements);
+    Ok(decompiler.out)
+}
+
+/// Escapes a Ren'Py string literal the way the lexer reads it back.
+pub fn quote(text: &str) -> String {
+    let escaped = text
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n");
+    format!("\"{}\"", escaped)
+}
+
+fn short_class(node: &PyValue) -> String {
+    node.class_name()
+        .map(|c| c.rsplit('.').next().unwrap_or_default().to_string())
+        .unwrap_or_default()
+}
+
+fn joined(value: &PyValue, separator: &str) -> String {
+    value
+        .items()
+        .iter()
+        .filter_map(PyValue::as_str)
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+#[derive(Default)]
+struct Decompiler {
+    out: String,
+    indent: usize,
+}
+
+impl Decompiler {
+    fn line(&mut self, text: &str) {
+        for _ in 0..self.indent {
+            self.out.push_str("    ");
+        }
+        self.out.push_str(text);
+        self.out.push('\n');
+    }
+
+    fn nested(&mut self, header: &str, block: &[PyValue]) {
+        self.line(header);
+        self.indent += 1;
+        if block.is_empty() {
+            self.line("pass");
+        } else {
+            self.block(block);
+        }
+        self.indent -= 1;
+    }
+
+    fn code_block(&mut self, header: &str, source: &str) {
+        self.line(header);
+        self.indent += 1;
+        for line in source.trim_end().lines() {
+            self.line(line);
+        }
+        self.indent -= 1;
+    }
+
+    fn block(&mut self, nodes: &[PyValue]) {
+        for node in nodes {
+            self.node(node);
+        }
+    }
+
+    fn source(code: &PyValue) -> String {
+        // PyCode pickles `(version, source, location, mode[, py])`.
+        if let PyValue::Object(obj) = code
+            && let Some(PyValue::Tuple(state)) = &obj.borrow().state
+            && let Some(source) = state.get(1)
+        {
+            return source.as_str().unwrap_or_default();
+        }
+        code.attr("source").as_str().or_else(|| code.as_str()).unwrap_or_default()
+    }
+
+    fn imspec(imspec: &PyValue) -> String {
+        let parts = imspec.items();
+        let get = |i: usize| parts.get(i).cloned().unwrap_or(PyValue::None);
+        let (name, expression, tag, at_list, layer, zorder, behind) = if parts.len() == 3 {
+            (get(0), PyValue::None, PyValue::None, get(1), get(2), PyValue::None, PyValue::None)
+        } else {
+            (get(0), get(1), get(2), get(3), get(4), get(5), get(6))
+        };
+
+        let mut words = Vec::new();
+        match expression.as_str() {
+            Some(expr) => words.push(format!("expression {}", expr)),
+            None => words.push(joined(&name, " ")),
+        }
+        if let Some(tag) = tag.as_str() {
+            words.push(format!("as {}", tag));
+        }
+        if !at_list.items().is_empty() {
+            words.push(format!("at {}", joined(&at_list, ", ")));
+        }
+        if let Some(layer) = layer.as_str() {
+            words.push(format!("onlayer {}", layer));
+        }
+        if let Some(zorder) = zorder.as_str() {
+            words.push(format!("zorder {}", zorder));
+        }
+        if !behind.items().is_empty() {
+            words.push(format!("behind {}", joined(&behind, ", ")));
+        }
+        words.join(" ")
+    }
+
+    fn parameters(node: &PyValue) -> String {
+        let parameters = node.attr("parameters");
+        if parameters.is_none() {
+            return String::new();
+        }
+        let list = match parameters.attr("parameters") {
+            PyValue::None => parameters.items(),
+            PyValue::Dict(items) => items.borrow().iter().map(|(_, v)| v.clone()).collect(),
+            other => other.items(),
+        };
+        let params: Vec<String> = list
+            .iter()
+            .map(|param| {
+                let (name, default) = match param {
+                    PyValue::Tuple(pair) => (
+                        pair.first().and_then(PyValue::as_str).unwrap_or_default(),
+                        pair.get(1).and_then(PyValue::as_str),
+                    ),
+                    _ => (
+                        param.attr("name").as_str().unwrap_or_default(),
+                        param.attr("default").as_str(),
+                    ),
+                };
+                match default {
+                    Some(default) => format!("{}={}", name, default),
+                    None => name,
+                }
+            })
+            .collect();
+        format!("({})", params.join(", "))
+    }
+
+    fn arguments(arguments: &PyValue) -> String {
+        if arguments.is_none() {
+            return String::new();
+        }
+        let args: Vec<String> = arguments
+            .attr("arguments")
+            .items()
+            .iter()
+            .map(|arg| {
+                let parts = arg.items();
+                let name = parts.first().and_then(PyValue::as_str);
+                let value = parts.get(1).and_then(PyValue::as_str).unwrap_or_default();
+                match name {
+                    Some(name) => format!("{}={}", name, value),
+                    None => value,
+                }
+            })
+            .collect();
+        format!("({})", args.join(", "))
+    }
+
+    fn say(node: &PyValue) -> String {
+        let mut words = Vec::new();
+        if let Some(who) = node.attr("who").as_str() {
+            words.push(who);
+        }
+        let attributes = joined(&node.attr("attributes"), " ");
+        if !attributes.is_empty() {
+            words.push(attributes);
+        }
+        let temporary = joined(&node.attr("temporary_attributes"), " ");
+        if !temporary.is_empty() {
+            words.push(format!("@ {}", temporary));
+        }
+        words.push(quote(&node.attr("what").as_str().unwrap_or_default()));
+        if matches!(node.attr("interact"), PyValue::Bool(false)) {
+            words.push("nointeract".to_string());
+        }
+        if let Some(identifier) = node.attr("identifier").as_str() {
+            words.push(format!("id {}", identifier));
+        }
+        if let Some(with) = node.attr("with_").as_str() {
+            words.push(format!("with {}", with));
+        }
+        words.join(" ")
+    }
+
+    fn stored_name(node: &PyValue) -> String {
+        let store = node.attr("store").as_str().unwrap_or_else(|| "store".to_string());
+        let varname = node.attr("varname").as_str().unwrap_or_default();
+        match store.strip_prefix("store.") {
+            Some(namespace) => format!("{}.{}", namespace, varname),
+            None => varname,
+        }
+    }
+
+    fn node(&mut self, node: &PyValue) {
+        let class = short_class(node);
+        match class.as_str() {
+            "Label" => {
+                let name = node.attr("name").as_str().unwrap_or_default();
+                let hide = if node.attr("hide").truthy() { " hide" } else { "" };
+                let header = format!("label {}{}{}:", name, Self::parameters(node), hide);
+                self.nested(&header, &node.attr("block").items());
+                self.out.push('\n');
+            }
+            "Say" | "TranslateSay" => {
+                let text = Self::say(node);
+                self.line(&text);
+            }
+            "Menu" => {
+                self.line("menu:");
+                self.indent += 1;
+                if let Some(set) = node.attr("set").as_str() {
+                    self.line(&format!("set {}", set));
+                }
+                for item in node.attr("items").items() {
+                    let parts = item.items();
+                    let caption = parts.first().and_then(PyValue::as_str).unwrap_or_default();
+                    let condition = parts.get(1).and_then(PyValue::as_str).unwrap_or_default();
+                    let block = parts.get(2).cloned().unwrap_or(PyValue::None);
+                    if block.is_none() {
+                        self.line(&quote(&caption));
+                        continue;
+                    }
+                    let header = if condition == "True" || condition.is_empty() {
+                        format!("{}:", quote(&caption))
+                    } else {
+                        format!("{} if {}:", quote(&caption), condition)
+                    };
+                    self.nested(&header, &block.items());
+                }
+                self.indent -= 1;
+            }
+            "Python" | "EarlyPython" => {
+                let source = Self::source(&node.attr("code"));
+                let store = node.attr("store").as_str().unwrap_or_else(|| "store".to_string());
+                let hide = node.attr("hide").truthy();
+                if class == "Python" && !hide && store == "store" && !source.trim().contains('\n') {
+                    self.line(&format!("$ {}", source.trim()));
+                } else {
+                    let mut header = "python".to_string();
+                    if class == "EarlyPython" {
+                        header.push_str(" early");
+                    }
+                    if hide {
+                        header.push_str(" hide");
+                    }
+                    if let Some(namespace) = store.strip_prefix("store.") {
+                        header.push_str(&format!(" in {}", namespace));
+                    }
+                    header.push(':');
+                    self.code_block(&header, &source);
+                }
+            }
+            "Define" | "Default" => {
+                let keyword = class.to_lowercase();
+                let name = Self::stored_name(node);
+                let index = node.attr("index");
+                let index = if index.is_none() {
+                    String::new()
+                } else {
+                    format!("[{}]", Self::source(&index))
+                };
+                let operator = node.attr("operator").as_str().unwrap_or_else(|| "=".to_string());
+                let code = Self::source(&node.attr("code"));
+                self.line(&format!("{} {}{} {} {}", keyword, name, index, operator, code));
+            }
+            "Image" => {
+                let name = joined(&node.attr("imgname"), " ");
+                let code = node.attr("code");
+                if code.is_none() {
+                    self.line(&format!("image {}:", name));
+                    self.indent += 1;
+                    self.line("# ATL block not decompiled");
+                    self.indent -= 1;
+                } else {
+                    self.line(&format!("image {} = {}", name, Self::source(&code)));
+                }
+            }
+            "Show" | "Scene" | "Hide" | "ShowLayer" => {
+                let keyword = match class.as_str() {
+                    "ShowLayer" => "show layer".to_string(),
+                    other => other.to_lowercase(),
+                };
+                let imspec = node.attr("imspec");
+                let mut text = if imspec.is_none() {
+                    match node.attr("layer").as_str() {
+                        Some(layer) if class == "Scene" => format!("scene onlayer {}", layer),
+                        Some(layer) => format!("{} {}", keyword, layer),
+                        None => keyword,
+                    }
+                } else {
+                    format!("{} {}", keyword, Self::imspec(&imspec))
+                };
+                if node.attr("atl").is_none() {
+                    self.line(&text);
+                } else {
+                    text.push(':');
+                    self.line(&text);
+                    self.indent += 1;
+                    self.line("# ATL block not decompiled");
+                    self.indent -= 1;
+                }
+            }
+            "With" => {
+                // `show x with y` compiles to a paired With around the Show.
+                if node.attr("paired").is_none() {
+                    let expr = node.attr("expr").as_str().unwrap_or_default();
+                    if expr != "None" {
+                        self.line(&format!("with {}", expr));
+                    }
+                }
+            }
+            "Jump" | "Call" => {
+                let keyword = class.to_lowercase();
+                let target = node
+                    .attr(if class == "Jump" { "target" } else { "label" })
+                    .as_str()
+                    .unwrap_or_default();
+                let expression = if node.attr("expression").truthy() { "expression " } else { "" };
+                let arguments = if class == "Call" { Self::arguments(&node.attr("arguments")) } else { String::new() };
+                self.line(&format!("{} {}{}{}", keyword, expression, target, arguments));
+            }
+            "Return" => match node.attr("expression").as_str() {
+                Some(expr) => self.line(&format!("return {}", expr)),
+                None => self.line("return"),
+            },
+            "If" => {
+                let entries = node.attr("entries").items();
+                for (i, entry) in entries.iter().enumerate() {
+                    let parts = entry.items();
+                    let condition = parts.first().and_then(PyValue::as_str).unwrap_or_default();
+                    let block = parts.get(1).map(PyValue::items).unwrap_or_default();
+                    let header = if i > 0 && i == entries.len() - 1 && condition == "True" {
+                        "else:".to_string()
+                    } else if i == 0 {
+                        format!("if {}:", condition)
+                    } else {
+                        format!("elif {}:", condition)
+                    };
+                    self.nested(&header, &block);
+                }
+            }
+            "While" => {
+                let condition = node.attr("condition").as_str().unwrap_or_default();
+                self.nested(&format!("while {}:", condition), &node.attr("block").items());
+            }
+            "Pass" => self.line("pass"),
+            "Init" => {
+                let block = node.attr("block").items();
+                let priority = match node.attr("priority") {
+                    PyValue::Int(p) => p,
+                    _ => 0,
+                };
+                let implicit = matches!(
+                    block.first().map(short_class).as_deref(),
+                    Some("Define" | "Default" | "Image" | "Transform" | "Screen" | "Style" | "Testcase")
+                );
+                if block.len() == 1 && implicit && priority == 0 {
+                    self.node(&block[0]);
+                } else if block.len() == 1 && short_class(&block[0]) == "Python" {
+                    let inner = &block[0];
+                    let mut header = "init".to_string();
+                    if priority != 0 {
+                        header.push_str(&format!(" {}", priority));
+                    }
+                    header.push_str(" python");
+                    if inner.attr("hide").truthy() {
+                        header.push_str(" hide");
+                    }
+                    if let Some(namespace) = inner
+                        .attr("store")
+                        .as_str()
+                        .and_then(|s| s.strip_prefix("store.").map(str::to_string))
+                    {
+                        header.push_str(&format!(" in {}", namespace));
+                    }
+                    header.push(':');
+                    self.code_block(&header, &Self::source(&inner.attr("code")));
+                } else {
+                    let header = if priority != 0 {
+                        format!("init {}:", priority)
+                    } else {
+                        "init:".to_string()
+                    };
+                    self.nested(&header, &block);
+                }
+            }
+            "Translate" => {
+                let block = node.attr("block").items();
+                match node.attr("language").as_str() {
+                    // Default-language dialogue is wrapped in an implicit translate block.
+                    None => self.block(&block),
+                    Some(language) => {
+                        let identifier = node.attr("identifier").as_str().unwrap_or_default();
+                        self.nested(&format!("translate {} {}:", language, identifier), &block);
+                        self.out.push('\n');
+                    }
+                }
+            }
+            "EndTranslate" => {}
+            "TranslateString" => {
+                let language = node.attr("language").as_str().unwrap_or_else(|| "None".to_string());
+                self.line(&format!("translate {} strings:", language));
+                self.indent += 1;
+                self.line(&format!("old {}", quote(&node.attr("old").as_str().unwrap_or_default())));
+                self.line(&format!("new {}", quote(&node.attr("new").as_str().unwrap_or_default())));
+                self.indent -= 1;
+                self.out.push('\n');
+            }
+            "TranslateBlock" | "TranslateEarlyBlock" => {
+                let language = node.attr("language").as_str().unwrap_or_else(|| "None".to_string());
+                for inner in node.attr("block").items() {
+                    let start = self.out.len() + self.indent * 4;
+                    self.node(&inner);
+                    // Nodes that print nothing, like a paired `with`, get no prefix.
+                    if start < self.out.len() {
+                        self.out.insert_str(start, &format!("translate {} ", language));
+                    }
+                }
+            }
+            "UserStatement" => {
+                let line = node.attr("line").as_str().unwrap_or_default();
+                self.line(&line);
+            }
+            "Screen" => {
+                let name = node.attr("screen").attr("name").as_str().unwrap_or_default();
+                self.line(&format!("screen {}:", name));
+                self.indent += 1;
+                self.line("# screen language body not decompiled");
+                self.indent -= 1;
+                self.out.push('\n');
+            }
+            "Transform" => {
+                let name = node.attr("varname").as_str().unwrap_or_default();
+                self.line(&format!("transform {}{}:", name, Self::parameters(node)));
+                self.indent += 1;
+                self.line("# ATL block not decompiled");
+                self.indent -= 1;
+            }
+            "Style" => {
+                let name = node.attr("style_name").as_str().unwrap_or_default();
+                let mut header = format!("style {}", name);
+                if let Some(parent) = node.attr("parent").as_str() {
+                    header.push_str(&format!(" is {}", parent));
+                }
+                let properties = match node.attr("properties") {
+                    PyValue::Dict(items) => items.borrow().clone(),
+                    _ => Vec::new(),
+                };
+                if properties.is_empty() {
+                    self.line(&header);
+                } else {
+                    self.line(&format!("{}:", header));
+                    self.indent += 1;
+                    for (key, value) in properties {
+                        let key = key.as_str().unwrap_or_default();
+                        self.line(&format!("{} {}", key, value.as_str().unwrap_or_default()));
+                    }
+                    self.indent -= 1;
+                }
+            }
+            "" => {}
+            other => self.line(&format!("# unsupported statement: {}", other)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use flate2::Compression;
+    use flate2::write::ZlibEncoder;
+
+    /// `pickle.dumps(value, protocol=2)` from CPython, for
+    /// `{"items": [1, 300, -2, 2**40], "pair": ("a", None), "flag": True, "ratio": 0.5,
+    /// "nodes": [say, say]}` where `say` is one `renpy.ast.Say` with `who` and `what`.
+    const PROTOCOL_2: &[u8] = b"\x80\x02}q\x00(X\x05\x00\x00\x00itemsq\x01]q\x02(K\x01M,\x01J\xfe\xff\xff\xff\x8a\x06\x00\x00\x00\x00\x00\x01eX\x04\x00\x00\x00pairq\x03X\x01\x00\x00\x00aq\x04N\x86q\x05X\x04\x00\x00\x00flagq\x06\x88X\x05\x00\x00\x00ratioq\x07G?\xe0\x00\x00\x00\x00\x00\x00X\x05\x00\x00\x00nodesq\x08]q\t(crenpy.ast\nSay\nq\n)\x81q\x0b}q\x0c(X\x03\x00\x00\x00whoq\rX\x01\x00\x00\x00eq\x0eX\x04\x00\x00\x00whatq\x0fX\x02\x00\x00\x00Hiq\x10ubh\x0beu.";
+
+    #[test]
+    fn unpickles_protocol_2() {
+        let root = unpickle(PROTOCOL_2).unwrap();
+        let ints: Vec<i64> = root
+            .attr("items")
+            .items()
+            .iter()
+            .map(|item| match item {
+                PyValue::Int(i) => *i,
+                other => panic!("not an int: {:?}", other),
+            })
+            .collect();
+        assert_eq!(ints, vec![1, 300, -2, 1 << 40]);
+
+        let pair = root.attr("pair").items();
+        assert_eq!(pair[0].as_str().as_deref(), Some("a"));
+        assert!(pair[1].is_none());
+        assert!(matches!(root.attr("flag"), PyValue::Bool(true)));
+        assert!(matches!(root.attr("ratio"), PyValue::Float(r) if r == 0.5));
+
+        // The memoized node comes back as the same object.
+        let nodes = root.attr("nodes").items();
+        assert_eq!(nodes[0].class_name().as_deref(), Some("renpy.ast.Say"));
+        assert_eq!(nodes[0].attr("who").as_str().as_deref(), Some("e"));
+        match (&nodes[0], &nodes[1]) {
+            (PyValue::Object(a), PyValue::Object(b)) => assert!(Rc::ptr_eq(a, b)),
+            other => panic!("not objects: {:?}", other),
+        }
+    }
+
+    fn text(value: &str) -> Vec<u8> {
+        let mut out = vec![b'X'];
+        out.extend_from_slice(&(value.len() as u32).to_le_bytes());
+        out.extend_from_slice(value.as_bytes());
+        out
+    }
+
+    fn list(items: Vec<Vec<u8>>) -> Vec<u8> {
+        [vec![b']', b'('], items.concat(), vec![b'e']].concat()
+    }
+
+    fn tuple(items: Vec<Vec<u8>>) -> Vec<u8> {
+        [vec![b'('], items.concat(), vec![b't']].concat()
+    }
+
+    /// A `renpy.ast` node pickled the way protocol 2 does: NEWOBJ, then BUILD with its dict.
+    fn node(class: &str, attrs: Vec<(&str, Vec<u8>)>) -> Vec<u8> {
+        let mut out = format!("crenpy.ast\n{}\n)", class).into_bytes();
+        out.extend_from_slice(b"\x81}(");
+        for (name, value) in attrs {
+            out.extend(text(name));
+            out.extend(value);
+        }
+        out.extend_from_slice(b"ub");
+        out
+    }
+
+    fn say(what: &str) -> Vec<u8> {
+        node("Say", vec![("who", b"N".to_vec()), ("what", text(what))])
+    }
+
+    /// An RPC2 `.rpyc` whose script slot holds `(data, statements)`.
+    fn rpyc(statements: Vec<Vec<u8>>) -> Vec<u8> {
+        let pickle = [b"\x80\x02".to_vec(), tuple(vec![b"N".to_vec(), list(statements)]), b".".to_vec()].concat();
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&pickle).unwrap();
+        let slot = encoder.finish().unwrap();
+
+        let mut out = RPC2_MAGIC.to_vec();
+        let start = (RPC2_MAGIC.len() + 24) as u32;
+        for value in [1, start, slot.len() as u32, 0, 0, 0] {
+            out.extend_from_slice(&value.to_le_bytes());
+        }
+        out.extend(slot);
+        out
+    }
+
+    #[test]
+    fn decompiles_say_menu_and_if() {
+        let menu = node(
+            "Menu",
+            vec![(
+                "items",
+                list(vec![
+                    tuple(vec![text("Yes"), text("True"), list(vec![say("You said yes.")])]),
+                    tuple(vec![text("No"), text("hungry"), list(Vec::new())]),
+                ]),
+            )],
+        );
+        let branch = node(
+            "If",
+            vec![(
+                "entries",
+                list(vec![
+                    tuple(vec![text("score > 1"), list(vec![say("High")])]),
+                    tuple(vec![text("True"), list(vec![say("Low")])]),
+                ]),
+            )],
+        );
+        let hello = node("Say", vec![("who", text("e")), ("what", text("Hello \"you\""))]);
+        let label = node("Label", vec![("name", text("start")), ("block", list(vec![hello, menu, branch]))]);
+
+        let source = decompile(&rpyc(vec![label])).unwrap();
+        assert_eq!(
+            source,
+            concat!(
+                "label start:\n",
+                "    e \"Hello \\\"you\\\"\"\n",
+                "    menu:\n",
+                "        \"Yes\":\n",
+                "            \"You said yes.\"\n",
+                "        \"No\" if hungry:\n",
+                "            pass\n",
+                "    if score > 1:\n",
+                "        \"High\"\n",
+                "    else:\n",
+                "        \"Low\"\n",
+                "\n",
+            )
+        );
+    }
+
+    #[test]
+    fn translate_block_prefixes_only_printed_lines() {
+        let python = node("Python", vec![("code", text("x = 1"))]);
+        let block = node(
+            "TranslateBlock",
+            vec![("language", text("french")), ("block", list(vec![node("EndTranslate", Vec::new()), python]))],
+        );
+        assert_eq!(decompile(&rpyc(vec![block])).unwrap(), "translate french $ x = 1\n");
+    }
+}
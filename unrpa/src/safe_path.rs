@@ -0,0 +1,101 @@
+use std::path::{Path, PathBuf};
+
+/// Characters Windows doesn't allow in file names.
+const INVALID_CHARS: &[char] = &['<', '>', ':', '"', '|', '?', '*'];
+/// Device names Windows reserves whatever the extension.
+const RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8", "COM9",
+    "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// A single path component that is valid on every platform.
+pub fn sanitize_file_name(name: &str) -> String {
+    let replaced: String = name
+        .chars()
+        .map(|c| if c.is_control() || INVALID_CHARS.contains(&c) { '_' } else { c })
+        .collect();
+    // Windows drops trailing dots and spaces, so "a." and "a" would be the same file.
+    let trimmed = replaced.trim_end_matches(['.', ' ']);
+    let stem = trimmed.split('.').next().unwrap_or_default();
+    if trimmed.is_empty() {
+        "_".to_string()
+    } else if RESERVED_NAMES.iter().any(|reserved| reserved.eq_ignore_ascii_case(stem)) {
+        format!("_{}", trimmed)
+    } else {
+        trimmed.to_string()
+    }
+}
+
+/// Relative path an entry is extracted to. Drive letters and leading slashes are dropped;
+/// names with `..` are refused since they would land outside the output folder.
+pub fn sanitize_entry_path(name: &str) -> anyhow::Result<PathBuf> {
+    let normalized = name.replace('\\', "/");
+    let mut rest = normalized.as_str();
+    if let [drive, b':', ..] = rest.as_bytes()
+        && drive.is_ascii_alphabetic()
+    {
+        rest = &rest[2..];
+    }
+
+    let mut path = PathBuf::new();
+    for component in rest.split('/') {
+        match component {
+            "" | "." => {}
+            ".." => return Err(anyhow::anyhow!("{} points outside the output folder", name)),
+            _ => path.push(sanitize_file_name(component)),
+        }
+    }
+    if path.as_os_str().is_empty() {
+        return Err(anyhow::anyhow!("{:?} has no file name", name));
+    }
+    Ok(path)
+}
+
+/// Where `name` goes below `base`; never outside it.
+pub fn entry_output_path(base: &Path, name: &str) -> anyhow::Result<PathBuf> {
+    Ok(base.join(sanitize_entry_path(name)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sanitized(name: &str) -> PathBuf {
+        sanitize_entry_path(name).unwrap()
+    }
+
+    #[test]
+    fn parent_components_are_refused() {
+        assert!(sanitize_entry_path("../../x").is_err());
+        assert!(sanitize_entry_path("images/../../x").is_err());
+        assert!(sanitize_entry_path("..\\x").is_err());
+    }
+
+    #[test]
+    fn roots_and_drives_are_dropped() {
+        assert_eq!(sanitized("C:\\evil"), PathBuf::from("evil"));
+        assert_eq!(sanitized("c:/evil/file.png"), Path::new("evil").join("file.png"));
+        assert_eq!(sanitized("/abs/path"), Path::new("abs").join("path"));
+        assert_eq!(sanitized("a/./b"), Path::new("a").join("b"));
+        assert_eq!(entry_output_path(Path::new("out"), "/abs").unwrap(), Path::new("out").join("abs"));
+    }
+
+    #[test]
+    fn empty_names_are_refused() {
+        assert!(sanitize_entry_path("").is_err());
+        assert!(sanitize_entry_path("/./").is_err());
+        assert_eq!(sanitize_file_name(""), "_");
+        assert_eq!(sanitize_file_name(". ."), "_");
+    }
+
+    #[test]
+    fn file_names_are_valid_on_windows() {
+        assert_eq!(sanitize_file_name("CON.txt"), "_CON.txt");
+        assert_eq!(sanitize_file_name("lpt1"), "_lpt1");
+        assert_eq!(sanitize_file_name("CONSOLE.txt"), "CONSOLE.txt");
+        assert_eq!(sanitize_file_name("name."), "name");
+        assert_eq!(sanitize_file_name("name "), "name");
+        assert_eq!(sanitize_file_name("a<b>:c?.png"), "a_b__c_.png");
+        assert_eq!(sanitized("dir./CON.txt"), Path::new("dir").join("_CON.txt"));
+    }
+}
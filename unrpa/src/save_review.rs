@@ -0,0 +1,252 @@
+use tracing::info;
+use crate::header::HeaderFormat;
+use crate::rpa::{RpaEditor, RpaFileEntry};
+
+/// Change kinds listed in the review, as returned by `entry_state`, with their label.
+const REVIEW_STATES: &[(&str, &str)] = &[
+    ("added", "➕ Added"),
+    ("modified", "✏️ Replaced"),
+    ("deleted", "🗑️ Deleted"),
+];
+
+pub struct SaveReviewItem {
+    pub name: String,
+    pub state: &'static str,
+    /// Size in the archive on disk; zero for added entries.
+    pub old_size: u64,
+    pub new_size: u64,
+    pub include: bool,
+}
+
+/// A save waiting for the user to confirm which pending changes go into it.
+pub struct SaveReview {
+    pub path: String,
+    pub format: &'static HeaderFormat,
+    pub key: u32,
+    pub items: Vec<SaveReviewItem>,
+}
+
+impl RpaEditor {
+    pub(crate) fn request_save(&mut self, path: &str) {
+        self.request_save_as(path, self.header_format, self.key);
+    }
+
+    /// Saves right away, or first opens the change review when that's enabled and
+    /// there is something to review.
+    pub(crate) fn request_save_as(&mut self, path: &str, format: &'static HeaderFormat, key: u32) {
+        if self.blocked_by_read_only() {
+            return;
+        }
+        let items: Vec<SaveReviewItem> = self
+            .changed_entries()
+            .into_iter()
+            .filter_map(|name| {
+                let entry = self.indexes.get(&name)?;
+                let state = self.entry_state(&name);
+                let old_size = if state == "added" {
+                    0
+                } else {
+                    entry.segments.iter().map(|segment| segment.length).sum()
+                };
+                let new_size = if state == "deleted" { 0 } else { entry.length };
+                Some(SaveReviewItem {
+                    name,
+                    state,
+                    old_size,
+                    new_size,
+                    include: true,
+                })
+            })
+            .collect();
+
+        if !self.review_before_save || items.is_empty() {
+            self.save_with_fallback(path, format, key);
+            return;
+        }
+        self.save_review = Some(SaveReview {
+            path: path.to_string(),
+            format,
+            key,
+            items,
+        });
+    }
+
+    /// Saves as if the `excluded` changes hadn't been made. Once the open archive is
+    /// rewritten the saved changes are no longer pending; only the excluded ones are.
+    fn save_excluding(&mut self, path: &str, format: &HeaderFormat, key: u32, excluded: &[String]) {
+        let stashed: Vec<(String, RpaFileEntry)> = excluded
+            .iter()
+            .filter_map(|name| Some((name.clone(), self.indexes.get(name)?.clone())))
+            .collect();
+        for (name, entry) in &stashed {
+            if self.loaded_names.contains(name) {
+                self.indexes
+                    .insert(name.clone(), RpaFileEntry::from_segments(entry.segments.clone()));
+            } else {
+                self.indexes.remove(name);
+            }
+        }
+
+        let saved = self.save_with_fallback(path, format, key);
+        let reloaded = saved && self.archive_path.as_deref() == Some(path);
+
+        let restored = !stashed.is_empty();
+        for (name, mut entry) in stashed {
+            // The entry's original bytes moved with the rewrite.
+            if reloaded && let Some(original) = self.indexes.get(&name) {
+                entry.segments = original.segments.clone();
+                if entry.data.is_none() && entry.merged.is_none() {
+                    entry.length = original.length;
+                }
+            }
+            self.indexes.insert(name, entry);
+        }
+        if reloaded && restored {
+            self.set_modified(true);
+        }
+        if !excluded.is_empty() {
+            info!("⏸️ Left {} changes out of the save", excluded.len());
+        }
+    }
+
+    pub(crate) fn show_save_review(&mut self, ui: &mut egui::Ui) {
+        let Some(review) = &mut self.save_review else {
+            return;
+        };
+
+        ui.label(format!("Saving to {}", review.path));
+        ui.horizontal(|ui| {
+            for (state, label) in REVIEW_STATES {
+                let count = review.items.iter().filter(|item| item.state == *state).count();
+                ui.label(format!("{} {}", label, count));
+            }
+        });
+        ui.horizontal(|ui| {
+            if ui.button("All").clicked() {
+                review.items.iter_mut().for_each(|item| item.include = true);
+            }
+            if ui.button("None").clicked() {
+                review.items.iter_mut().for_each(|item| item.include = false);
+            }
+        });
+        ui.separator();
+
+        egui::ScrollArea::vertical().max_height(350.0).show(ui, |ui| {
+            egui::Grid::new("save_review").num_columns(3).striped(true).show(ui, |ui| {
+                for (state, label) in REVIEW_STATES {
+                    for item in review.items.iter_mut().filter(|item| item.state == *state) {
+                        ui.checkbox(&mut item.include, &item.name);
+                        ui.label(*label);
+                        ui.label(match item.state {
+                            "added" => Self::format_bytes(item.new_size),
+                            "deleted" => Self::format_bytes(item.old_size),
+                            _ => format!(
+                                "{} → {}",
+                                Self::format_bytes(item.old_size),
+                                Self::format_bytes(item.new_size)
+                            ),
+                        });
+                        ui.end_row();
+                    }
+                }
+            });
+        });
+
+        ui.separator();
+        let included = review.items.iter().filter(|item| item.include).count();
+        if included < review.items.len() {
+            ui.label("Unchecked changes are left out of the file and stay pending in the editor.");
+        }
+        let mut confirmed = false;
+        let mut cancelled = false;
+        ui.horizontal(|ui| {
+            confirmed = ui
+                .button(format!("💾 Save {} of {} changes", included, review.items.len()))
+                .clicked();
+            cancelled = ui.button("Cancel").clicked();
+        });
+
+        if cancelled {
+            self.save_review = None;
+        } else if confirmed && let Some(review) = self.save_review.take() {
+            let excluded: Vec<String> = review
+                .items
+                .into_iter()
+                .filter(|item| !item.include)
+                .map(|item| item.name)
+                .collect();
+            self.save_excluding(&review.path, review.format, review.key, &excluded);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TestDir;
+
+    #[test]
+    fn excluded_changes_stay_pending() {
+        let dir = TestDir::new("review");
+        let path = dir.file("game.rpa");
+        let mut editor = RpaEditor {
+            auto_backup: false,
+            archive_bak_count: 0,
+            ..RpaEditor::default()
+        };
+        for name in ["a.txt", "b.txt", "c.txt"] {
+            editor.add_data(name, format!("original {}", name).into_bytes()).unwrap();
+        }
+        editor.save_rpa(&path).unwrap();
+        editor.load_rpa(&path).unwrap();
+
+        editor.add_data("a.txt", b"a resized replacement".to_vec()).unwrap();
+        editor.add_data("b.txt", b"not ready".to_vec()).unwrap();
+        let (format, key) = (editor.header_format, editor.key);
+        editor.save_excluding(&path, format, key, &["b.txt".to_string()]);
+
+        assert!(editor.modified);
+        assert_eq!(editor.changed_entries(), vec!["b.txt".to_string()]);
+        assert_eq!(editor.load_file_data("a.txt").unwrap(), b"a resized replacement");
+        assert_eq!(editor.load_file_data("b.txt").unwrap(), b"not ready");
+        assert_eq!(editor.load_file_data("c.txt").unwrap(), b"original c.txt");
+
+        editor.revert_entry("b.txt");
+        assert_eq!(editor.load_file_data("b.txt").unwrap(), b"original b.txt");
+    }
+
+    #[test]
+    fn review_stays_with_its_tab() {
+        let dir = TestDir::new("review_tabs");
+        let path_a = dir.file("a.rpa");
+        let path_b = dir.file("b.rpa");
+        let mut editor = RpaEditor {
+            auto_backup: false,
+            archive_bak_count: 0,
+            review_before_save: true,
+            ..RpaEditor::default()
+        };
+        editor.add_data("b.txt", b"original b".to_vec()).unwrap();
+        editor.save_rpa(&path_b).unwrap();
+        editor.unload_rpa().unwrap();
+        editor.add_data("a.txt", b"original a".to_vec()).unwrap();
+        editor.save_rpa(&path_a).unwrap();
+        editor.load_rpa(&path_a).unwrap();
+        editor.open_in_new_tab_with(&path_b, |editor, path| editor.load_rpa(path).unwrap());
+        editor.switch_tab(0);
+
+        editor.add_data("a.txt", b"changed a".to_vec()).unwrap();
+        editor.request_save(&path_a);
+        assert!(editor.save_review.is_some());
+
+        editor.switch_tab(1);
+        assert!(editor.save_review.is_none());
+        editor.add_data("b.txt", b"changed b".to_vec()).unwrap();
+
+        editor.switch_tab(0);
+        let review = editor.save_review.as_ref().unwrap();
+        assert_eq!(review.path, path_a);
+        assert_eq!(review.items.len(), 1);
+        assert_eq!(review.items[0].name, "a.txt");
+    }
+}
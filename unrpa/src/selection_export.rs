@@ -0,0 +1,81 @@
+use std::collections::HashSet;
+use std::path::Path;
+use tracing::info;
+use crate::rpa::RpaEditor;
+
+/// Which entries "Save Selection as RPA" writes, stored as the first element.
+pub const SELECTION_SCOPES: &[(&str, &str)] = &[
+    ("visible", "Entries shown in the file list"),
+    ("selected", "Selected entry"),
+];
+
+impl RpaEditor {
+    pub(crate) fn selection_entries(&self) -> Vec<String> {
+        let mut entries: Vec<String> = match self.selection_scope.as_str() {
+            "selected" => self.selected_file.iter().cloned().collect(),
+            _ => self
+                .get_filtered_sorted_files()
+                .into_iter()
+                .map(|(name, _)| name.clone())
+                .collect(),
+        };
+        entries.retain(|name| self.indexes.get(name).is_some_and(|entry| !entry.to_delete));
+        entries
+    }
+
+    /// Writes the chosen entries to a new archive in the open archive's format.
+    pub(crate) fn save_selection_as(&mut self, path: &Path) -> anyhow::Result<usize> {
+        let path = path.to_string_lossy().to_string();
+        if self.archive_path.as_deref() == Some(path.as_str()) {
+            return Err(anyhow::anyhow!("Choose a file other than the open archive"));
+        }
+        let entries = self.selection_entries();
+        if entries.is_empty() {
+            return Err(anyhow::anyhow!("No entries to save"));
+        }
+        let included: HashSet<&str> = entries.iter().map(String::as_str).collect();
+        let key = if self.key != 0 { self.key } else { Self::generate_key() };
+        self.save_entries_as(&path, self.header_format, key, |name| included.contains(name))?;
+
+        self.status_message = format!("Saved {} entries to {}", entries.len(), path);
+        info!("💾 {}", self.status_message);
+        Ok(entries.len())
+    }
+
+    pub(crate) fn show_save_selection_dialog(&mut self, ui: &mut egui::Ui) {
+        for (scope, label) in SELECTION_SCOPES {
+            ui.radio_value(&mut self.selection_scope, scope.to_string(), *label);
+        }
+        let entries = self.selection_entries();
+        let size: u64 = entries.iter().map(|name| self.indexes[name].length).sum();
+        ui.label(format!("{} entries, {}", entries.len(), Self::format_bytes(size)));
+        ui.label(format!("Written as {}", self.header_format.magic));
+        ui.separator();
+
+        ui.horizontal(|ui| {
+            let stem = self
+                .archive_path
+                .as_deref()
+                .and_then(|path| Path::new(path).file_stem())
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_else(|| "archive".to_string());
+            if ui.add_enabled(!entries.is_empty(), egui::Button::new("💾 Save...")).clicked()
+                && let Some(path) = rfd::FileDialog::new()
+                    .add_filter("RPA files", &["rpa"])
+                    .set_file_name(format!("{}_selection.rpa", stem))
+                    .save_file()
+            {
+                match self.save_selection_as(&path) {
+                    Ok(count) => {
+                        self.add_toast(format!("Saved {} entries as a new archive", count));
+                        self.show_save_selection_dialog = false;
+                    }
+                    Err(e) => self.add_error(format!("Save error: {}", e)),
+                }
+            }
+            if ui.button("❌ Cancel").clicked() {
+                self.show_save_selection_dialog = false;
+            }
+        });
+    }
+}
@@ -0,0 +1,268 @@
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+use crate::backup_store::default_backup_dir;
+use crate::file_styles::FileStyle;
+use crate::health::config_dir;
+use crate::rpa::{RpaEditor, BACKUP_TRIGGERS};
+use crate::temp_files::default_temp_dir;
+
+/// Theme choices, stored as the first element.
+pub const THEMES: &[(&str, &str)] = &[("system", "Follow system"), ("dark", "Dark"), ("light", "Light")];
+
+pub const DEFAULT_WINDOW_SIZE: [f32; 2] = [1500.0, 1000.0];
+
+/// Preferences kept between launches in `settings.json`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Settings {
+    pub window_size: [f32; 2],
+    pub theme: String,
+    pub auto_backup: bool,
+    pub backup_triggers: HashSet<String>,
+    pub backup_excluded_types: HashSet<String>,
+    pub persist_backups: bool,
+    pub backup_dir: PathBuf,
+    pub backup_prune_days: u32,
+    pub archive_bak_count: usize,
+    pub compression_level: u32,
+    pub reproducible_build: bool,
+    pub regenerate_prefixes: bool,
+    pub subset_fonts_on_add: bool,
+    pub validate_on_open: bool,
+    pub large_archive_mode: String,
+    pub use_mmap: bool,
+    pub review_before_save: bool,
+    pub confirm_remove: bool,
+    pub temp_dir: PathBuf,
+    pub clean_temp_on_close: bool,
+    pub log_to_file: bool,
+    pub autosave_enabled: bool,
+    pub autosave_minutes: u32,
+    pub log_level_filter: String,
+    pub filter_type: String,
+    pub sort_by: String,
+    pub sort_ascending: bool,
+    pub file_list_view: String,
+    pub preview_associations: HashMap<String, String>,
+    pub file_styles: Vec<FileStyle>,
+    pub image_fit_mode: String,
+    pub image_background: String,
+    pub image_background_color: [u8; 3],
+    pub image_compare_mode: String,
+    pub audio_loop_mode: String,
+    pub job_run_mode: String,
+    pub extract_policy: String,
+    pub extract_layout: String,
+    pub recent_files: Vec<String>,
+}
+
+pub fn settings_path() -> PathBuf {
+    config_dir().join("settings.json")
+}
+
+fn read_saved() -> anyhow::Result<serde_json::Map<String, serde_json::Value>> {
+    Ok(serde_json::from_slice(&std::fs::read(settings_path())?)?)
+}
+
+/// Window size from the last session, read before the editor exists.
+pub fn saved_window_size() -> [f32; 2] {
+    read_saved()
+        .ok()
+        .and_then(|saved| serde_json::from_value(saved.get("window_size")?.clone()).ok())
+        .unwrap_or(DEFAULT_WINDOW_SIZE)
+}
+
+pub fn theme_preference(theme: &str) -> egui::ThemePreference {
+    match theme {
+        "dark" => egui::ThemePreference::Dark,
+        "light" => egui::ThemePreference::Light,
+        _ => egui::ThemePreference::System,
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            window_size: DEFAULT_WINDOW_SIZE,
+            theme: "system".to_string(),
+            auto_backup: true,
+            backup_triggers: BACKUP_TRIGGERS.iter().map(|(trigger, _)| trigger.to_string()).collect(),
+            backup_excluded_types: HashSet::new(),
+            persist_backups: false,
+            backup_dir: default_backup_dir(),
+            backup_prune_days: 30,
+            archive_bak_count: 1,
+            compression_level: 6,
+            reproducible_build: false,
+            regenerate_prefixes: false,
+            subset_fonts_on_add: false,
+            validate_on_open: true,
+            large_archive_mode: "off".to_string(),
+            use_mmap: false,
+            review_before_save: true,
+            confirm_remove: true,
+            temp_dir: default_temp_dir(),
+            clean_temp_on_close: true,
+            log_to_file: false,
+            autosave_enabled: true,
+            autosave_minutes: 5,
+            log_level_filter: "info".to_string(),
+            filter_type: "all".to_string(),
+            sort_by: "name".to_string(),
+            sort_ascending: true,
+            file_list_view: "list".to_string(),
+            preview_associations: HashMap::new(),
+            file_styles: Vec::new(),
+            image_fit_mode: "fit".to_string(),
+            image_background: "checker".to_string(),
+            image_background_color: [40, 40, 40],
+            image_compare_mode: "side".to_string(),
+            audio_loop_mode: "off".to_string(),
+            job_run_mode: "sequential".to_string(),
+            extract_policy: "rename".to_string(),
+            extract_layout: "type".to_string(),
+            recent_files: Vec::new(),
+        }
+    }
+}
+
+impl Settings {
+    fn from_editor(editor: &RpaEditor) -> Self {
+        Self {
+            window_size: editor.window_size,
+            theme: editor.theme.clone(),
+            auto_backup: editor.auto_backup,
+            backup_triggers: editor.backup_triggers.clone(),
+            backup_excluded_types: editor.backup_excluded_types.clone(),
+            persist_backups: editor.persist_backups,
+            backup_dir: editor.backup_dir.clone(),
+            backup_prune_days: editor.backup_prune_days,
+            archive_bak_count: editor.archive_bak_count,
+            compression_level: editor.compression_level,
+            reproducible_build: editor.reproducible_build,
+            regenerate_prefixes: editor.regenerate_prefixes,
+            subset_fonts_on_add: editor.subset_fonts_on_add,
+            validate_on_open: editor.validate_on_open,
+            large_archive_mode: editor.large_archive_mode.clone(),
+            use_mmap: editor.use_mmap,
+            review_before_save: editor.review_before_save,
+            confirm_remove: editor.confirm_remove,
+            temp_dir: editor.temp_dir.clone(),
+            clean_temp_on_close: editor.clean_temp_on_close,
+            log_to_file: editor.log_to_file,
+            autosave_enabled: editor.autosave_enabled,
+            autosave_minutes: editor.autosave_minutes,
+            log_level_filter: editor.log_level_filter.clone(),
+            filter_type: editor.filter_type.clone(),
+            sort_by: editor.sort_by.clone(),
+            sort_ascending: editor.sort_ascending,
+            file_list_view: editor.file_list_view.clone(),
+            preview_associations: editor.preview_associations.clone(),
+            file_styles: editor.file_styles.clone(),
+            image_fit_mode: editor.image_fit_mode.clone(),
+            image_background: editor.image_background.clone(),
+            image_background_color: editor.image_background_color,
+            image_compare_mode: editor.image_compare_mode.clone(),
+            audio_loop_mode: editor.audio_loop_mode.clone(),
+            job_run_mode: editor.job_run_mode.clone(),
+            extract_policy: editor.extract_policy.clone(),
+            extract_layout: editor.extract_layout.clone(),
+            recent_files: editor.recent_files.clone(),
+        }
+    }
+
+    fn apply(self, editor: &mut RpaEditor) {
+        editor.window_size = self.window_size;
+        editor.theme = self.theme;
+        editor.auto_backup = self.auto_backup;
+        editor.backup_triggers = self.backup_triggers;
+        editor.backup_excluded_types = self.backup_excluded_types;
+        editor.persist_backups = self.persist_backups;
+        editor.backup_dir = self.backup_dir;
+        editor.backup_prune_days = self.backup_prune_days;
+        editor.archive_bak_count = self.archive_bak_count;
+        editor.compression_level = self.compression_level.min(9);
+        editor.reproducible_build = self.reproducible_build;
+        editor.regenerate_prefixes = self.regenerate_prefixes;
+        editor.subset_fonts_on_add = self.subset_fonts_on_add;
+        editor.validate_on_open = self.validate_on_open;
+        editor.large_archive_mode = self.large_archive_mode;
+        editor.use_mmap = self.use_mmap;
+        editor.review_before_save = self.review_before_save;
+        editor.confirm_remove = self.confirm_remove;
+        editor.temp_dir = self.temp_dir;
+        editor.clean_temp_on_close = self.clean_temp_on_close;
+        if self.log_to_file != editor.log_to_file {
+            editor.set_log_to_file(self.log_to_file);
+        }
+        editor.log_level_filter = self.log_level_filter;
+        editor.autosave_enabled = self.autosave_enabled;
+        editor.autosave_minutes = self.autosave_minutes.clamp(1, 60);
+        editor.filter_type = self.filter_type;
+        editor.sort_by = self.sort_by;
+        editor.sort_ascending = self.sort_ascending;
+        editor.file_list_view = self.file_list_view;
+        editor.preview_associations = self.preview_associations;
+        editor.file_styles = self.file_styles;
+        editor.image_fit_mode = self.image_fit_mode;
+        editor.image_background = self.image_background;
+        editor.image_background_color = self.image_background_color;
+        editor.image_compare_mode = self.image_compare_mode;
+        editor.audio_loop_mode = self.audio_loop_mode;
+        editor.job_run_mode = self.job_run_mode;
+        editor.extract_policy = self.extract_policy;
+        editor.extract_layout = self.extract_layout;
+        editor.recent_files = self.recent_files;
+    }
+}
+
+impl RpaEditor {
+    /// Applies the saved settings; keys missing from the file keep their current value.
+    pub(crate) fn load_settings(&mut self) -> anyhow::Result<()> {
+        let saved = read_saved()?;
+        let mut merged = serde_json::to_value(Settings::from_editor(self))?;
+        if let Some(current) = merged.as_object_mut() {
+            current.extend(saved);
+        }
+        let settings: Settings = serde_json::from_value(merged)?;
+        settings.apply(self);
+        info!("⚙️ Loaded settings from {}", settings_path().display());
+        Ok(())
+    }
+
+    pub(crate) fn save_settings(&self) -> anyhow::Result<()> {
+        let path = settings_path();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, serde_json::to_vec_pretty(&Settings::from_editor(self))?)?;
+        info!("💾 Saved settings to {}", path.display());
+        Ok(())
+    }
+
+    /// Puts every saved preference back to its built-in default; the recent list stays.
+    pub(crate) fn reset_settings(&mut self) {
+        let recent_files = std::mem::take(&mut self.recent_files);
+        Settings::default().apply(self);
+        self.recent_files = recent_files;
+        self.status_message = "Settings reset to defaults".to_string();
+    }
+
+    pub(crate) fn set_theme(&mut self, ctx: &egui::Context, theme: &str) {
+        self.theme = theme.to_string();
+        ctx.set_theme(theme_preference(theme));
+    }
+
+    /// Theme picker for the View menu.
+    pub(crate) fn show_theme_menu(&mut self, ui: &mut egui::Ui) {
+        ui.menu_button("Theme", |ui| {
+            for (theme, label) in THEMES {
+                if ui.radio(self.theme == *theme, *label).clicked() {
+                    self.set_theme(ui.ctx(), theme);
+                    ui.close_menu();
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,98 @@
+use crate::rpa::RpaEditor;
+
+/// Bytes read from the start of an entry to recognise its format.
+pub const SNIFF_LENGTH: u64 = 16;
+
+/// File type (as in `get_file_type`) from the magic bytes at the start of a file.
+pub fn sniff_type(head: &[u8]) -> Option<&'static str> {
+    let at = |offset: usize, magic: &[u8]| head.get(offset..offset + magic.len()) == Some(magic);
+    if at(0, b"\x89PNG") || at(0, b"\xff\xd8\xff") || at(0, b"GIF8") || (at(0, b"RIFF") && at(8, b"WEBP")) {
+        Some("images")
+    } else if at(0, b"\x1a\x45\xdf\xa3") || (at(0, b"RIFF") && at(8, b"AVI ")) {
+        Some("videos")
+    } else if at(4, b"ftyp") {
+        // MP4 containers; the M4A brand only carries sound.
+        if at(8, b"M4A ") { Some("audio") } else { Some("videos") }
+    } else if at(0, b"OggS") || at(0, b"fLaC") || at(0, b"ID3") || (at(0, b"RIFF") && at(8, b"WAVE")) {
+        Some("audio")
+    } else if head.len() >= 2 && head[0] == 0xff && head[1] & 0xe0 == 0xe0 {
+        // MPEG audio frame sync.
+        Some("audio")
+    } else if at(0, b"RENPY RPC2") || (head.first() == Some(&0x78) && matches!(head.get(1), Some(0x01 | 0x5e | 0x9c | 0xda))) {
+        // Compiled scripts, either chunked or a bare zlib stream from old Ren'Py.
+        Some("scripts")
+    } else if at(0, b"\x00\x01\x00\x00") || at(0, b"OTTO") || at(0, b"true") || at(0, b"wOFF") || at(0, b"wOF2") {
+        Some("fonts")
+    } else {
+        None
+    }
+}
+
+/// A replacement whose detected file type differs from the entry it would replace.
+#[derive(Debug)]
+pub struct TypeMismatch {
+    pub archive_name: String,
+    pub expected: &'static str,
+    pub found: &'static str,
+}
+
+impl std::fmt::Display for TypeMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} is {}, but the replacement looks like {}",
+            self.archive_name, self.expected, self.found
+        )
+    }
+}
+
+impl std::error::Error for TypeMismatch {}
+
+/// Preview mode matching a sniffed file type.
+pub fn sniffed_preview_mode(file_type: &str) -> &'static str {
+    match file_type {
+        "images" => "image",
+        "fonts" => "font",
+        "scripts" => "rpyc",
+        _ => "media",
+    }
+}
+
+impl RpaEditor {
+    /// Remembers the content type of `filename` when its extension says otherwise.
+    pub(crate) fn record_sniffed_type(&mut self, filename: &str, head: &[u8]) {
+        self.sniffed_types.remove(filename);
+        if let Some(file_type) = sniff_type(head)
+            && file_type != self.get_file_type(filename)
+        {
+            self.sniffed_types.insert(filename.to_string(), file_type);
+        }
+    }
+
+    /// Starts a job that reads the first bytes of every archived entry and records the
+    /// ones that are mislabeled or have no known extension, so opening an archive doesn't
+    /// wait on it. In large archive mode only the latter are read.
+    pub(crate) fn detect_entry_types(&mut self) {
+        self.sniffed_types.clear();
+        if self.archive_path.is_none() {
+            return;
+        }
+        let large = self.is_large_archive_mode();
+        let mut names: Vec<String> = self
+            .indexes
+            .iter()
+            .filter(|(name, entry)| {
+                !entry.to_delete
+                    && entry.in_archive()
+                    && (!large || matches!(self.get_file_type(name), "files" | "other"))
+            })
+            .map(|(name, _)| name.clone())
+            .collect();
+        if names.is_empty() {
+            return;
+        }
+        names.sort();
+        self.push_job("sniff", names, None);
+        self.start_job(self.jobs.len() - 1);
+    }
+}
@@ -0,0 +1,191 @@
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+use tracing::info;
+use crate::rpa::{same_file, RpaEditor, PREFIX_LENGTH};
+
+pub const SPLIT_MODES: &[(&str, &str)] = &[("type", "By file type"), ("size", "By size")];
+
+/// Upper bound of what one entry adds to the pickled index besides its name and
+/// prefix: the tuple, list and dict opcodes, the two offsets and the memo slots.
+const INDEX_BYTES_PER_ENTRY: u64 = 64;
+/// Upper bound of the pickle framing and the zlib header, trailer and block overhead
+/// of a small index.
+const INDEX_BYTES_BASE: u64 = 64;
+
+/// One archive produced by a split.
+pub struct SplitPart {
+    pub file_name: String,
+    pub entries: Vec<String>,
+    pub size: u64,
+}
+
+impl RpaEditor {
+    /// Bytes a part takes before any entry: the header area up to the first entry
+    /// and the fixed part of the index.
+    fn split_part_overhead(&self) -> u64 {
+        self.data_start_offset + INDEX_BYTES_BASE
+    }
+
+    /// Bytes `name` adds to a part: its data and, at most, its index record. The index
+    /// is counted uncompressed since its compressed size isn't known before writing.
+    fn split_entry_size(&self, name: &str) -> u64 {
+        let prefix = if self.regenerate_prefixes { PREFIX_LENGTH } else { 0 };
+        self.indexes[name].length + name.len() as u64 + prefix + INDEX_BYTES_PER_ENTRY
+    }
+
+    /// Which entries go into which output archive, for the current split settings.
+    /// Part sizes include the header area and the index, so a size split keeps
+    /// whole archives under the limit.
+    pub(crate) fn split_plan(&self) -> Vec<SplitPart> {
+        let mut names: Vec<&String> = self
+            .indexes
+            .iter()
+            .filter(|(_, entry)| !entry.to_delete)
+            .map(|(name, _)| name)
+            .collect();
+        names.sort();
+
+        if self.split_mode == "type" {
+            let mut groups: BTreeMap<&str, SplitPart> = BTreeMap::new();
+            for name in names {
+                let file_type = self.get_file_type(name);
+                let part = groups.entry(file_type).or_insert_with(|| SplitPart {
+                    file_name: format!("{}.rpa", file_type),
+                    entries: Vec::new(),
+                    size: self.split_part_overhead(),
+                });
+                part.entries.push(name.clone());
+                part.size += self.split_entry_size(name);
+            }
+            return groups.into_values().collect();
+        }
+
+        let stem = self
+            .archive_path
+            .as_deref()
+            .and_then(|path| Path::new(path).file_stem())
+            .map_or("archive".to_string(), |stem| stem.to_string_lossy().to_string());
+        let limit = self.split_max_mb.max(1) * 1024 * 1024;
+        let mut parts: Vec<SplitPart> = Vec::new();
+        for name in names {
+            let size = self.split_entry_size(name);
+            // An entry bigger than the limit still gets a part of its own.
+            let start_new = parts
+                .last()
+                .is_none_or(|part| !part.entries.is_empty() && part.size + size > limit);
+            if start_new {
+                parts.push(SplitPart {
+                    file_name: format!("{}{}.rpa", stem, parts.len() + 1),
+                    entries: Vec::new(),
+                    size: self.split_part_overhead(),
+                });
+            }
+            if let Some(part) = parts.last_mut() {
+                part.entries.push(name.clone());
+                part.size += size;
+            }
+        }
+        parts
+    }
+
+    /// Writes every part of the plan into `folder`; returns the number of archives.
+    /// Nothing is written if any part would replace an existing file, the open
+    /// archive included.
+    pub(crate) fn split_archive(&mut self, folder: &Path) -> anyhow::Result<usize> {
+        let plan = self.split_plan();
+        for part in &plan {
+            let path = folder.join(&part.file_name);
+            let path = path.to_string_lossy();
+            if self.archive_path.as_deref().is_some_and(|current| same_file(current, &path)) {
+                return Err(anyhow::anyhow!("{} is the open archive; pick another folder", path));
+            }
+            if Path::new(path.as_ref()).exists() {
+                return Err(anyhow::anyhow!("{} already exists; pick an empty folder", path));
+            }
+        }
+        for part in &plan {
+            let path = folder.join(&part.file_name);
+            let entries: HashSet<&str> = part.entries.iter().map(String::as_str).collect();
+            self.save_entries_as(&path.to_string_lossy(), self.header_format, self.key, |name| {
+                entries.contains(name)
+            })?;
+            info!(
+                "✂️ Wrote {} ({} files, {})",
+                path.display(),
+                part.entries.len(),
+                Self::format_bytes(part.size)
+            );
+        }
+        self.status_message = format!("Split into {} archives in {}", plan.len(), folder.display());
+        Ok(plan.len())
+    }
+
+    pub(crate) fn show_split_dialog(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            for (mode, label) in SPLIT_MODES {
+                ui.radio_value(&mut self.split_mode, mode.to_string(), *label);
+            }
+        });
+        if self.split_mode == "size" {
+            ui.horizontal(|ui| {
+                ui.label("Largest archive:");
+                ui.add(egui::DragValue::new(&mut self.split_max_mb).range(1..=1_000_000).suffix(" MB"));
+            });
+        }
+        ui.separator();
+
+        let plan = self.split_plan();
+        egui::ScrollArea::vertical().max_height(240.0).show(ui, |ui| {
+            for part in &plan {
+                ui.label(format!(
+                    "📦 {}  {} files, {}",
+                    part.file_name,
+                    part.entries.len(),
+                    Self::format_bytes(part.size)
+                ));
+            }
+        });
+        ui.separator();
+        ui.horizontal(|ui| {
+            if ui.add_enabled(!plan.is_empty(), egui::Button::new("✂️ Split...")).clicked()
+                && let Some(folder) = rfd::FileDialog::new().pick_folder()
+            {
+                match self.split_archive(&folder) {
+                    Ok(count) => {
+                        self.add_toast(format!("Wrote {} archives", count));
+                        self.show_split_dialog = false;
+                    }
+                    Err(e) => self.add_error(format!("Split error: {}", e)),
+                }
+            }
+            if ui.button("❌ Cancel").clicked() {
+                self.show_split_dialog = false;
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn size_parts_reserve_the_header_and_index() {
+        let mut editor = RpaEditor {
+            split_mode: "size".to_string(),
+            split_max_mb: 1,
+            ..Default::default()
+        };
+        // Two entries of half the limit only fit together if the overhead is ignored.
+        let half = vec![0u8; 512 * 1024];
+        editor.add_data("a.bin", half.clone()).unwrap();
+        editor.add_data("b.bin", half).unwrap();
+
+        let plan = editor.split_plan();
+        assert_eq!(plan.len(), 2);
+        for part in &plan {
+            assert!(part.size > 512 * 1024 + editor.data_start_offset);
+            assert!(part.size <= 1024 * 1024);
+        }
+    }
+}
@@ -0,0 +1,427 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use crate::archive_load::ArchiveLoad;
+use crate::archive_watch::ArchiveStamp;
+use crate::batch_replace::{BatchReplacePlan, BatchReplaceReport};
+use crate::checksums::ChecksumReport;
+use crate::diff::{ArchiveDiff, DiffLine};
+use crate::duplicates::DuplicateGroup;
+use crate::external_edit::ExternalEdit;
+use crate::extract_conflicts::{ExtractReport, PendingExtract};
+use crate::folder_add::FolderAdd;
+use crate::header::HeaderFormat;
+use crate::image_compare::ImageComparison;
+use crate::integrity::IntegrityReport;
+use crate::rpa::{BackupEntry, EncodingConversion, FilterCounts, PendingAdd, RpaEditor, RpaFileEntry, TitleCounts, DEFAULT_DATA_START};
+use crate::save_review::SaveReview;
+use crate::thumbnails::Thumbnails;
+use crate::video_probe::VideoInfo;
+use crate::watch_folder::WatchFolder;
+
+/// Archive parked in a background tab; the active archive lives in the editor fields.
+pub struct ArchiveTab {
+    version: f32,
+    key: u32,
+    header_format: &'static HeaderFormat,
+    header_line: String,
+    index_offset: u64,
+    header_subkeys: Vec<u32>,
+    indexes: HashMap<String, RpaFileEntry>,
+    archive_path: Option<String>,
+    archive_loading: Option<ArchiveLoad>,
+    modified: bool,
+    edit_generation: u64,
+    autosaved_generation: u64,
+    title_counts: Option<TitleCounts>,
+    filter_counts: Option<FilterCounts>,
+    selected_file: Option<String>,
+    search_filter: String,
+    change_filter: String,
+    loaded_names: HashSet<String>,
+    loaded_size: u64,
+    entry_warnings: HashMap<String, String>,
+    orphaned_ranges: Vec<(u64, u64)>,
+    backup_history: Vec<BackupEntry>,
+    video_info: HashMap<String, VideoInfo>,
+    sniffed_types: HashMap<String, &'static str>,
+    external_edits: Vec<ExternalEdit>,
+    watch_folder: Option<WatchFolder>,
+    archive_stamp: Option<ArchiveStamp>,
+    archive_changed_on_disk: bool,
+    pending_adds: VecDeque<PendingAdd>,
+    add_conflict: Option<PendingAdd>,
+    conflict_resolution: Option<String>,
+    conflict_apply_to_all: bool,
+    save_after_merge: bool,
+    save_review: Option<SaveReview>,
+    folder_add: Option<FolderAdd>,
+    entry_tags: HashMap<String, Vec<String>>,
+    entry_notes: HashMap<String, String>,
+    bookmarks: BTreeSet<String>,
+    duplicate_groups: Option<Vec<DuplicateGroup>>,
+    checksums: HashMap<String, String>,
+    checksum_verify: Option<(PathBuf, BTreeMap<String, String>)>,
+    checksum_report: Option<ChecksumReport>,
+    integrity_report: Option<IntegrityReport>,
+    unused_assets: Vec<String>,
+    batch_replace_plan: Option<BatchReplacePlan>,
+    batch_replace_report: Option<BatchReplaceReport>,
+    pending_extract: Option<PendingExtract>,
+    extract_report: Option<ExtractReport>,
+    archive_diff: Option<ArchiveDiff>,
+    entry_diff: Option<(String, Vec<DiffLine>)>,
+    encoding_conversions: Vec<EncodingConversion>,
+    image_comparison: Option<ImageComparison>,
+    thumbnails: Thumbnails,
+    data_start_offset: u64,
+    header_padding: String,
+}
+
+impl ArchiveTab {
+    /// Moves the active archive out of the editor, leaving it empty.
+    fn take_from(editor: &mut RpaEditor) -> Self {
+        Self {
+            version: std::mem::replace(&mut editor.version, 3.2),
+            key: std::mem::replace(&mut editor.key, 0xDEADBEEF),
+            header_format: std::mem::replace(&mut editor.header_format, HeaderFormat::standard(3.2)),
+            header_line: std::mem::take(&mut editor.header_line),
+            index_offset: std::mem::take(&mut editor.index_offset),
+            header_subkeys: std::mem::take(&mut editor.header_subkeys),
+            indexes: std::mem::take(&mut editor.indexes),
+            archive_path: editor.archive_path.take(),
+            archive_loading: editor.archive_loading.take(),
+            modified: std::mem::take(&mut editor.modified),
+            edit_generation: std::mem::take(&mut editor.edit_generation),
+            autosaved_generation: std::mem::take(&mut editor.autosaved_generation),
+            title_counts: editor.title_counts.take(),
+            filter_counts: editor.filter_counts.take(),
+            selected_file: editor.selected_file.take(),
+            search_filter: std::mem::take(&mut editor.search_filter),
+            change_filter: std::mem::replace(&mut editor.change_filter, "all".to_string()),
+            loaded_names: std::mem::take(&mut editor.loaded_names),
+            loaded_size: std::mem::take(&mut editor.loaded_size),
+            entry_warnings: std::mem::take(&mut editor.entry_warnings),
+            orphaned_ranges: std::mem::take(&mut editor.orphaned_ranges),
+            backup_history: std::mem::take(&mut editor.backup_history),
+            video_info: std::mem::take(&mut editor.video_info),
+            sniffed_types: std::mem::take(&mut editor.sniffed_types),
+            external_edits: std::mem::take(&mut editor.external_edits),
+            watch_folder: editor.watch_folder.take(),
+            archive_stamp: editor.archive_stamp.take(),
+            archive_changed_on_disk: std::mem::take(&mut editor.archive_changed_on_disk),
+            pending_adds: std::mem::take(&mut editor.pending_adds),
+            add_conflict: editor.add_conflict.take(),
+            conflict_resolution: editor.conflict_resolution.take(),
+            conflict_apply_to_all: std::mem::take(&mut editor.conflict_apply_to_all),
+            save_after_merge: std::mem::take(&mut editor.save_after_merge),
+            save_review: editor.save_review.take(),
+            folder_add: editor.folder_add.take(),
+            entry_tags: std::mem::take(&mut editor.entry_tags),
+            entry_notes: std::mem::take(&mut editor.entry_notes),
+            bookmarks: std::mem::take(&mut editor.bookmarks),
+            duplicate_groups: editor.duplicate_groups.take(),
+            checksums: std::mem::take(&mut editor.checksums),
+            checksum_verify: editor.checksum_verify.take(),
+            checksum_report: editor.checksum_report.take(),
+            integrity_report: editor.integrity_report.take(),
+            unused_assets: std::mem::take(&mut editor.unused_assets),
+            batch_replace_plan: editor.batch_replace_plan.take(),
+            batch_replace_report: editor.batch_replace_report.take(),
+            pending_extract: editor.pending_extract.take(),
+            extract_report: editor.extract_report.take(),
+            archive_diff: editor.archive_diff.take(),
+            entry_diff: editor.entry_diff.take(),
+            encoding_conversions: std::mem::take(&mut editor.encoding_conversions),
+            image_comparison: editor.image_comparison.take(),
+            thumbnails: std::mem::take(&mut editor.thumbnails),
+            data_start_offset: std::mem::replace(&mut editor.data_start_offset, DEFAULT_DATA_START),
+            header_padding: std::mem::take(&mut editor.header_padding),
+        }
+    }
+
+    /// Path shown for the tab: its archive, or the one it's loading.
+    fn shown_path(&self) -> Option<&str> {
+        self.archive_path
+            .as_deref()
+            .or(self.archive_loading.as_ref().map(|load| load.path.as_str()))
+    }
+
+    fn is_empty(&self) -> bool {
+        self.archive_path.is_none() && self.archive_loading.is_none() && self.indexes.is_empty()
+    }
+
+    fn restore_into(self, editor: &mut RpaEditor) {
+        editor.version = self.version;
+        editor.key = self.key;
+        editor.header_format = self.header_format;
+        editor.header_line = self.header_line;
+        editor.index_offset = self.index_offset;
+        editor.header_subkeys = self.header_subkeys;
+        editor.indexes = self.indexes;
+        editor.archive_path = self.archive_path;
+        editor.archive_loading = self.archive_loading;
+        editor.modified = self.modified;
+        editor.edit_generation = self.edit_generation;
+        editor.autosaved_generation = self.autosaved_generation;
+        editor.title_counts = self.title_counts;
+        editor.filter_counts = self.filter_counts;
+        editor.selected_file = self.selected_file;
+        editor.search_filter = self.search_filter;
+        editor.change_filter = self.change_filter;
+        editor.loaded_names = self.loaded_names;
+        editor.loaded_size = self.loaded_size;
+        editor.entry_warnings = self.entry_warnings;
+        editor.orphaned_ranges = self.orphaned_ranges;
+        editor.backup_history = self.backup_history;
+        editor.video_info = self.video_info;
+        editor.sniffed_types = self.sniffed_types;
+        editor.external_edits = self.external_edits;
+        editor.watch_folder = self.watch_folder;
+        editor.archive_stamp = self.archive_stamp;
+        editor.archive_changed_on_disk = self.archive_changed_on_disk;
+        editor.pending_adds = self.pending_adds;
+        editor.add_conflict = self.add_conflict;
+        editor.conflict_resolution = self.conflict_resolution;
+        editor.conflict_apply_to_all = self.conflict_apply_to_all;
+        editor.save_after_merge = self.save_after_merge;
+        editor.save_review = self.save_review;
+        editor.folder_add = self.folder_add;
+        editor.entry_tags = self.entry_tags;
+        editor.entry_notes = self.entry_notes;
+        editor.bookmarks = self.bookmarks;
+        editor.duplicate_groups = self.duplicate_groups;
+        editor.checksums = self.checksums;
+        editor.checksum_verify = self.checksum_verify;
+        editor.checksum_report = self.checksum_report;
+        editor.integrity_report = self.integrity_report;
+        editor.unused_assets = self.unused_assets;
+        editor.batch_replace_plan = self.batch_replace_plan;
+        editor.batch_replace_report = self.batch_replace_report;
+        editor.pending_extract = self.pending_extract;
+        editor.extract_report = self.extract_report;
+        editor.archive_diff = self.archive_diff;
+        editor.entry_diff = self.entry_diff;
+        editor.encoding_conversions = self.encoding_conversions;
+        editor.image_comparison = self.image_comparison;
+        editor.thumbnails = self.thumbnails;
+        editor.data_start_offset = self.data_start_offset;
+        editor.header_padding = self.header_padding;
+    }
+}
+
+fn tab_title(archive_path: Option<&str>) -> String {
+    archive_path
+        .and_then(|path| Path::new(path).file_name())
+        .map_or("Untitled".to_string(), |name| name.to_string_lossy().to_string())
+}
+
+impl RpaEditor {
+    /// Drops everything shown for the previous archive so the new one starts clean,
+    /// including confirmations that name one of its entries.
+    fn clear_tab_preview(&mut self) {
+        self.preview_data = None;
+        self.set_preview_image(None);
+        self.preview_text = None;
+        self.preview_highlight = None;
+        self.preview_animation = Vec::new();
+        self.video_thumbnail = None;
+        self.player = None;
+        self.stop_audio();
+        self.hex_match = None;
+        self.hex_search_task = None;
+        self.hex_page_key = None;
+        self.remove_to_confirm = None;
+        self.replace_type_mismatch = None;
+        self.show_frame_export_dialog = false;
+        self.frame_export_file = None;
+        self.file_to_preview = self.selected_file.clone();
+    }
+
+    /// The text and hex editors keep unsaved buffers that belong to the active archive.
+    fn can_leave_tab(&mut self) -> bool {
+        if self.editing_file.is_some() || self.hex_editing_file.is_some() {
+            self.add_warning("Save or cancel the open editor before switching archives");
+            return false;
+        }
+        true
+    }
+
+    /// External edits of the active archive and the background tabs.
+    pub(crate) fn all_external_edits(&self) -> impl Iterator<Item = &ExternalEdit> {
+        self.external_edits
+            .iter()
+            .chain(self.archive_tabs.iter().flatten().flat_map(|tab| &tab.external_edits))
+    }
+
+    /// Runs `f` with background tab `index` in the editor fields, then parks it again.
+    /// Nothing shown on screen changes.
+    pub(crate) fn with_background_tab<T>(&mut self, index: usize, f: impl FnOnce(&mut Self) -> T) -> Option<T> {
+        let tab = self.archive_tabs.get_mut(index)?.take()?;
+        let active = ArchiveTab::take_from(self);
+        tab.restore_into(self);
+        let result = f(self);
+        self.archive_tabs[index] = Some(ArchiveTab::take_from(self));
+        active.restore_into(self);
+        Some(result)
+    }
+
+    pub(crate) fn modified_background_tabs(&self) -> Vec<usize> {
+        (0..self.archive_tabs.len())
+            .filter(|&index| self.archive_tabs[index].as_ref().is_some_and(|tab| tab.modified))
+            .collect()
+    }
+
+    /// The background tab holding `path`.
+    pub(crate) fn background_tab_for(&self, path: &str) -> Option<usize> {
+        self.archive_tabs
+            .iter()
+            .position(|tab| tab.as_ref().is_some_and(|tab| tab.archive_path.as_deref() == Some(path)))
+    }
+
+    /// Whether `path` is open or being loaded in any tab.
+    pub(crate) fn is_archive_open(&self, path: &str) -> bool {
+        self.archive_path.as_deref() == Some(path)
+            || self.archive_loading.as_ref().is_some_and(|load| load.path == path)
+            || self.archive_tabs.iter().flatten().any(|tab| tab.shown_path() == Some(path))
+    }
+
+    /// The background tab waiting for load job `job`.
+    pub(crate) fn background_tab_loading(&self, job: usize) -> Option<usize> {
+        self.archive_tabs.iter().position(|tab| {
+            tab.as_ref()
+                .and_then(|tab| tab.archive_loading.as_ref())
+                .is_some_and(|load| load.job == job)
+        })
+    }
+
+    /// Whether the active tab shows nothing and isn't loading anything.
+    fn is_empty_tab(&self) -> bool {
+        self.archive_path.is_none() && self.archive_loading.is_none() && self.indexes.is_empty()
+    }
+
+    /// Drops tab `index` when it was left without an archive, e.g. after its load failed,
+    /// unless it's the last one.
+    pub(crate) fn close_tab_if_empty(&mut self, index: usize) {
+        if self.archive_tabs.len() <= 1 {
+            return;
+        }
+        if index == self.active_tab {
+            if self.is_empty_tab() {
+                self.close_active_tab();
+            }
+        } else if self.archive_tabs.get(index).is_some_and(|tab| tab.as_ref().is_some_and(ArchiveTab::is_empty)) {
+            self.archive_tabs.remove(index);
+            if index < self.active_tab {
+                self.active_tab -= 1;
+            }
+        }
+    }
+
+    pub(crate) fn switch_tab(&mut self, index: usize) {
+        if index == self.active_tab || index >= self.archive_tabs.len() || !self.can_leave_tab() {
+            return;
+        }
+        let current = ArchiveTab::take_from(self);
+        self.archive_tabs[self.active_tab] = Some(current);
+        if let Some(tab) = self.archive_tabs[index].take() {
+            tab.restore_into(self);
+        }
+        self.active_tab = index;
+        self.clear_tab_preview();
+    }
+
+    /// Loads `path` into a new tab, keeping the current archive open.
+    pub(crate) fn open_in_new_tab(&mut self, path: &str) {
+        self.open_in_new_tab_with(path, Self::open_archive);
+    }
+
+    /// Like `open_in_new_tab`, with `open` doing the loading (e.g. a recovery scan).
+    pub(crate) fn open_in_new_tab_with(&mut self, path: &str, open: impl FnOnce(&mut Self, &str)) {
+        if self.is_empty_tab() {
+            open(self, path);
+            return;
+        }
+        if !self.can_leave_tab() {
+            return;
+        }
+        let current = ArchiveTab::take_from(self);
+        self.archive_tabs[self.active_tab] = Some(current);
+        self.archive_tabs.push(None);
+        self.active_tab = self.archive_tabs.len() - 1;
+        self.clear_tab_preview();
+        open(self, path);
+        // Loading failed; go back to where the user was.
+        self.close_tab_if_empty(self.active_tab);
+    }
+
+    /// Closes the active archive and shows a neighbouring tab if there is one.
+    pub(crate) fn close_active_tab(&mut self) {
+        if let Err(e) = self.unload_rpa() {
+            self.add_error(format!("Error unloading: {}", e));
+        }
+        if self.archive_tabs.len() <= 1 {
+            return;
+        }
+        self.archive_tabs.remove(self.active_tab);
+        self.active_tab = self.active_tab.min(self.archive_tabs.len() - 1);
+        if let Some(tab) = self.archive_tabs[self.active_tab].take() {
+            tab.restore_into(self);
+        }
+        self.clear_tab_preview();
+    }
+
+    /// Strip of open archives under the menu bar.
+    pub(crate) fn show_archive_tabs(&mut self, ui: &mut egui::Ui) {
+        let mut switch_to = None;
+        let mut close = None;
+        ui.horizontal_wrapped(|ui| {
+            for (index, tab) in self.archive_tabs.iter().enumerate() {
+                let (path, loading, modified) = match tab {
+                    Some(tab) => (tab.shown_path(), tab.archive_loading.is_some(), tab.modified),
+                    None => (
+                        self.archive_path
+                            .as_deref()
+                            .or(self.archive_loading.as_ref().map(|load| load.path.as_str())),
+                        self.archive_loading.is_some(),
+                        self.modified,
+                    ),
+                };
+                let mut title = tab_title(path);
+                if loading {
+                    title.push_str(" ⏳");
+                } else if modified {
+                    title.push_str(" ●");
+                }
+                let response = ui
+                    .selectable_label(index == self.active_tab, format!("📦 {}", title))
+                    .on_hover_text(path.unwrap_or("No archive"));
+                if response.clicked() {
+                    switch_to = Some(index);
+                }
+                if self.archive_tabs.len() > 1 && ui.small_button("✖").on_hover_text("Close tab").clicked() {
+                    close = Some(index);
+                }
+                ui.separator();
+            }
+            if ui.small_button("➕").on_hover_text("Open an archive in a new tab").clicked()
+                && let Some(path) = rfd::FileDialog::new().add_filter("RPA files", &["rpa"]).pick_file()
+            {
+                self.open_in_new_tab(&path.to_string_lossy());
+            }
+        });
+
+        if let Some(index) = switch_to {
+            self.switch_tab(index);
+        }
+        if let Some(index) = close {
+            self.switch_tab(index);
+            if self.active_tab == index {
+                if self.modified {
+                    self.show_close_confirm = true;
+                } else {
+                    self.close_active_tab();
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+use crate::backup_store::sanitize;
+use crate::preview_cache::dir_size;
+use crate::rpa::RpaEditor;
+
+/// Where entries are written for playback, "Open Folder" and external editing, unless
+/// the settings point elsewhere.
+pub fn default_temp_dir() -> PathBuf {
+    std::env::temp_dir().join("rpa_editor")
+}
+
+/// Deletes everything below `dir` except the files in `keep`, then the folders left
+/// empty. Returns the bytes freed; files that can't be removed (a video still playing,
+/// say) are skipped.
+fn remove_temp_files(dir: &Path, keep: &HashSet<PathBuf>) -> u64 {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+    let mut freed = 0;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        match entry.file_type() {
+            Ok(kind) if kind.is_dir() => {
+                freed += remove_temp_files(&path, keep);
+                let _ = std::fs::remove_dir(&path);
+            }
+            _ if keep.contains(&path) => {}
+            _ => {
+                let size = entry.metadata().map_or(0, |metadata| metadata.len());
+                match std::fs::remove_file(&path) {
+                    Ok(()) => freed += size,
+                    Err(e) => warn!("⚠️ Could not remove {}: {}", path.display(), e),
+                }
+            }
+        }
+    }
+    freed
+}
+
+impl RpaEditor {
+    /// `<temp>/<archive>/<kind>/`, kind being "preview", "open", "extract" or "edit".
+    pub(crate) fn archive_temp_dir(&self, kind: &str) -> PathBuf {
+        self.temp_dir.join(sanitize(&self.archive_backup_name())).join(kind)
+    }
+
+    /// External edit copies that haven't been reimported yet, in every tab.
+    fn unimported_edit_paths(&self) -> HashSet<PathBuf> {
+        self.all_external_edits()
+            .filter(|edit| edit.changed)
+            .map(|edit| edit.path.clone())
+            .collect()
+    }
+
+    /// Removes the active archive's temp files when it's closed, if that's enabled.
+    pub(crate) fn clean_archive_temp(&mut self) {
+        if !self.clean_temp_on_close || self.archive_path.is_none() {
+            return;
+        }
+        let dir = self.temp_dir.join(sanitize(&self.archive_backup_name()));
+        let keep: HashSet<PathBuf> = self
+            .external_edits
+            .iter()
+            .filter(|edit| edit.changed)
+            .map(|edit| edit.path.clone())
+            .collect();
+        let freed = remove_temp_files(&dir, &keep);
+        let _ = std::fs::remove_dir(&dir);
+        if freed > 0 {
+            info!("🧹 Removed {} of temp files from {}", Self::format_bytes(freed), dir.display());
+        }
+    }
+
+    /// On exit everything goes except edits made in other apps that weren't reimported.
+    pub(crate) fn clean_temp_on_exit(&self) {
+        if self.clean_temp_on_close {
+            let freed = remove_temp_files(&self.temp_dir, &self.unimported_edit_paths());
+            info!("🧹 Removed {} of temp files", Self::format_bytes(freed));
+        }
+    }
+
+    /// The "Clean Temp Files" action. Copies still open for external editing are kept.
+    pub(crate) fn clean_temp_files(&mut self) -> u64 {
+        let keep: HashSet<PathBuf> = self.all_external_edits().map(|edit| edit.path.clone()).collect();
+        let freed = remove_temp_files(&self.temp_dir, &keep);
+        self.status_message = format!("Freed {} of temp files", Self::format_bytes(freed));
+        info!("🧹 {} in {}", self.status_message, self.temp_dir.display());
+        freed
+    }
+
+    pub(crate) fn temp_files_size(&self) -> u64 {
+        dir_size(&self.temp_dir)
+    }
+}
@@ -0,0 +1,79 @@
+use tracing::info;
+use crate::rpa::RpaEditor;
+
+impl RpaEditor {
+    /// Text entries that decoded as UTF-8 can be edited in place.
+    pub(crate) fn can_edit_text(&self, filename: &str) -> bool {
+        self.get_preview_mode(filename) == "text" && self.preview_text.is_some()
+    }
+
+    pub(crate) fn start_text_edit(&mut self, filename: &str) {
+        if self.blocked_by_read_only() {
+            return;
+        }
+        self.edit_buffer = self.preview_text.clone().unwrap_or_default();
+        self.editing_file = Some(filename.to_string());
+    }
+
+    /// True when the editor holds changes that haven't been applied to the entry.
+    pub(crate) fn text_edit_dirty(&self) -> bool {
+        self.editing_file.is_some() && self.preview_text.as_ref() != Some(&self.edit_buffer)
+    }
+
+    /// Writes the edited text back into the entry, which marks it modified.
+    pub(crate) fn apply_text_edit(&mut self) {
+        if self.blocked_by_read_only() {
+            return;
+        }
+        let Some(filename) = self.editing_file.clone() else {
+            return;
+        };
+        let data = self.edit_buffer.clone().into_bytes();
+
+        self.backup_before(&filename, "edit");
+        if let Err(e) = self.insert_data(&filename, data.clone()) {
+            self.add_error(format!("Can't apply the edit: {}", e));
+            return;
+        }
+        self.set_provenance(&filename, "edited in app");
+
+        self.preview_text = Some(self.edit_buffer.clone());
+        self.preview_data = Some(data);
+        self.refresh_highlight(&filename);
+        self.status_message = format!("Applied edits to {}", filename);
+    }
+
+    pub(crate) fn discard_text_edit(&mut self) {
+        if self.text_edit_dirty()
+            && let Some(filename) = &self.editing_file
+        {
+            info!("↩️ Discarded unapplied edits to {}", filename);
+        }
+        self.editing_file = None;
+        self.edit_buffer.clear();
+    }
+
+    pub(crate) fn show_text_editor(&mut self, ui: &mut egui::Ui) {
+        let filename = self.editing_file.clone().unwrap_or_default();
+        let mut layouter = |ui: &egui::Ui, text: &str, wrap_width: f32| {
+            let mut job = Self::highlight_for(&filename, text).unwrap_or_else(|| {
+                egui::text::LayoutJob::simple(
+                    text.to_string(),
+                    egui::FontId::monospace(13.0),
+                    ui.visuals().text_color(),
+                    wrap_width,
+                )
+            });
+            job.wrap.max_width = wrap_width;
+            ui.fonts(|fonts| fonts.layout_job(job))
+        };
+
+        ui.add(
+            egui::TextEdit::multiline(&mut self.edit_buffer)
+                .code_editor()
+                .desired_width(f32::INFINITY)
+                .desired_rows(30)
+                .layouter(&mut layouter),
+        );
+    }
+}
@@ -0,0 +1,266 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{Cursor, Read};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::time::Duration;
+use image::ImageFormat;
+use crate::entry_stream::EntryStream;
+use crate::preview_cache::{cache_file, store_cached};
+use crate::rpa::{RpaEditor, RpaSegment};
+
+/// Longest side of a gallery thumbnail, in pixels.
+const GALLERY_THUMB_SIZE: u32 = 128;
+
+enum ThumbnailSource {
+    Memory(Vec<u8>),
+    Archive { path: String, segments: Vec<RpaSegment> },
+}
+
+struct ThumbnailRequest {
+    generation: u64,
+    name: String,
+    source: ThumbnailSource,
+    /// Disk cache file, for entries whose content can be identified without reading it.
+    cache_path: Option<PathBuf>,
+}
+
+struct ThumbnailResult {
+    generation: u64,
+    name: String,
+    image: Result<egui::ColorImage, String>,
+}
+
+fn encode_png(image: &image::DynamicImage) -> anyhow::Result<Vec<u8>> {
+    let mut png = Vec::new();
+    image.write_to(&mut Cursor::new(&mut png), ImageFormat::Png)?;
+    Ok(png)
+}
+
+/// Renders the gallery thumbnail of an image entry into the disk cache, for the
+/// "thumbnails" job. Entries already cached are left alone.
+pub fn cache_thumbnail(data: &[u8], cache_path: &Path) -> anyhow::Result<()> {
+    if cache_path.is_file() {
+        return Ok(());
+    }
+    let thumbnail = image::load_from_memory(data)?.thumbnail(GALLERY_THUMB_SIZE, GALLERY_THUMB_SIZE);
+    store_cached(cache_path, &encode_png(&thumbnail)?);
+    Ok(())
+}
+
+fn render_thumbnail(request: &ThumbnailRequest) -> anyhow::Result<egui::ColorImage> {
+    let cached = request
+        .cache_path
+        .as_ref()
+        .filter(|path| path.is_file())
+        .and_then(|path| image::open(path).ok());
+    let thumbnail = match cached {
+        Some(thumbnail) => thumbnail,
+        None => {
+            let data = match &request.source {
+                ThumbnailSource::Memory(data) => data.clone(),
+                ThumbnailSource::Archive { path, segments } => {
+                    let mut data = Vec::new();
+                    EntryStream::from_archive(File::open(path)?, segments.clone()).read_to_end(&mut data)?;
+                    data
+                }
+            };
+            let thumbnail = image::load_from_memory(&data)?.thumbnail(GALLERY_THUMB_SIZE, GALLERY_THUMB_SIZE);
+            if let Some(path) = &request.cache_path {
+                store_cached(path, &encode_png(&thumbnail)?);
+            }
+            thumbnail
+        }
+    };
+    let rgba = thumbnail.to_rgba8();
+    Ok(egui::ColorImage::from_rgba_unmultiplied(
+        [rgba.width() as usize, rgba.height() as usize],
+        &rgba,
+    ))
+}
+
+/// Gallery thumbnails, decoded by a background worker and kept as textures.
+#[derive(Default)]
+pub struct Thumbnails {
+    textures: HashMap<String, egui::TextureHandle>,
+    pending: HashSet<String>,
+    failed: HashMap<String, String>,
+    /// Bumped when the archive changes so late results for the old one are dropped.
+    generation: u64,
+    archive_path: Option<String>,
+    requests: Option<Sender<ThumbnailRequest>>,
+    results: Option<Receiver<ThumbnailResult>>,
+}
+
+impl Thumbnails {
+    pub fn clear(&mut self) {
+        self.textures.clear();
+        self.pending.clear();
+        self.failed.clear();
+        self.generation += 1;
+    }
+
+    /// Drops the thumbnail of an entry whose content changed.
+    pub fn forget(&mut self, name: &str) {
+        self.textures.remove(name);
+        self.failed.remove(name);
+    }
+
+    fn send(&mut self, request: ThumbnailRequest) {
+        if self.requests.is_none() {
+            let (request_sender, request_receiver) = channel::<ThumbnailRequest>();
+            let (result_sender, result_receiver) = channel();
+            std::thread::spawn(move || {
+                for request in request_receiver {
+                    let image = render_thumbnail(&request).map_err(|e| e.to_string());
+                    let result = ThumbnailResult {
+                        generation: request.generation,
+                        name: request.name,
+                        image,
+                    };
+                    if result_sender.send(result).is_err() {
+                        break;
+                    }
+                }
+            });
+            self.requests = Some(request_sender);
+            self.results = Some(result_receiver);
+        }
+        self.pending.insert(request.name.clone());
+        if let Some(requests) = &self.requests {
+            let _ = requests.send(request);
+        }
+    }
+
+    fn receive(&mut self, ctx: &egui::Context) {
+        let Some(results) = &self.results else {
+            return;
+        };
+        for result in results.try_iter().collect::<Vec<_>>() {
+            if result.generation != self.generation {
+                continue;
+            }
+            self.pending.remove(&result.name);
+            match result.image {
+                Ok(image) => {
+                    let texture = ctx.load_texture(format!("thumb:{}", result.name), image, Default::default());
+                    self.textures.insert(result.name, texture);
+                }
+                Err(e) => {
+                    self.failed.insert(result.name, e);
+                }
+            }
+        }
+    }
+}
+
+impl RpaEditor {
+    pub(crate) fn thumbnail_cache_path(&self, name: &str) -> Option<PathBuf> {
+        self.archive_path
+            .as_deref()
+            .zip(self.entry_cache_hash(name))
+            .map(|(archive, hash)| cache_file("thumbnails", archive, &hash, "png"))
+    }
+
+    fn request_thumbnail(&mut self, name: &str) {
+        let Some(entry) = self.indexes.get(name) else {
+            return;
+        };
+        let source = match (&entry.data, &entry.merged, &self.archive_path) {
+            (Some(data), ..) => ThumbnailSource::Memory(data.clone()),
+            (None, Some(merged), _) => ThumbnailSource::Archive {
+                path: merged.archive.clone(),
+                segments: merged.segments.clone(),
+            },
+            (None, None, Some(path)) => ThumbnailSource::Archive {
+                path: path.clone(),
+                segments: entry.segments.clone(),
+            },
+            (None, None, None) => return,
+        };
+        let request = ThumbnailRequest {
+            generation: self.thumbnails.generation,
+            name: name.to_string(),
+            source,
+            cache_path: self.thumbnail_cache_path(name),
+        };
+        self.thumbnails.send(request);
+    }
+
+    /// Grid of thumbnails for the image entries passing the current filter. Only rows
+    /// scrolled into view are decoded.
+    pub(crate) fn show_gallery(&mut self, ui: &mut egui::Ui) {
+        if self.thumbnails.archive_path != self.archive_path {
+            self.thumbnails.clear();
+            self.thumbnails.archive_path = self.archive_path.clone();
+        }
+        self.thumbnails.receive(ui.ctx());
+
+        let images: Vec<String> = self
+            .get_filtered_sorted_files()
+            .into_iter()
+            .filter(|(name, entry)| !entry.to_delete && self.get_file_type(name) == "images")
+            .map(|(name, _)| name.clone())
+            .collect();
+        if images.is_empty() {
+            ui.label("No images match the current filter.");
+            return;
+        }
+
+        let cell = GALLERY_THUMB_SIZE as f32 + 16.0;
+        let columns = ((ui.available_width() / cell).floor() as usize).max(1);
+        let rows = images.len().div_ceil(columns);
+        let row_height = cell + ui.text_style_height(&egui::TextStyle::Small) + 8.0;
+
+        let mut clicked = None;
+        let mut wanted = Vec::new();
+        egui::ScrollArea::vertical()
+            .auto_shrink([false, false])
+            .show_rows(ui, row_height, rows, |ui, row_range| {
+                for row in row_range {
+                    ui.horizontal(|ui| {
+                        for name in images.iter().skip(row * columns).take(columns) {
+                            ui.allocate_ui(egui::vec2(cell, row_height), |ui| {
+                                ui.vertical_centered(|ui| {
+                                    let selected = self.selected_file.as_deref() == Some(name.as_str());
+                                    let size = egui::vec2(GALLERY_THUMB_SIZE as f32, GALLERY_THUMB_SIZE as f32);
+                                    let response = if let Some(texture) = self.thumbnails.textures.get(name) {
+                                        ui.add(
+                                            egui::ImageButton::new(
+                                                egui::Image::new(texture).max_size(size).fit_to_exact_size(size),
+                                            )
+                                            .selected(selected),
+                                        )
+                                    } else if let Some(error) = self.thumbnails.failed.get(name) {
+                                        ui.add_sized(size, egui::Button::new("⚠").selected(selected))
+                                            .on_hover_text(error.as_str())
+                                    } else {
+                                        if !self.thumbnails.pending.contains(name) {
+                                            wanted.push(name.clone());
+                                        }
+                                        ui.add_sized(size, egui::Spinner::new())
+                                    };
+                                    if response.on_hover_text(name.as_str()).clicked() {
+                                        clicked = Some(name.clone());
+                                    }
+                                    let label = name.rsplit('/').next().unwrap_or(name);
+                                    ui.add(egui::Label::new(egui::RichText::new(label).small()).truncate());
+                                });
+                            });
+                        }
+                    });
+                }
+            });
+
+        for name in wanted {
+            self.request_thumbnail(&name);
+        }
+        if !self.thumbnails.pending.is_empty() {
+            ui.ctx().request_repaint_after(Duration::from_millis(100));
+        }
+        if let Some(name) = clicked {
+            self.selected_file = Some(name.clone());
+            self.file_to_preview = Some(name);
+        }
+    }
+}
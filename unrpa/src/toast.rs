@@ -1,21 +1,207 @@
 use std::time::{Duration, Instant};
+use tracing::{error, warn};
+use crate::rpa::RpaEditor;
 
+/// Toast levels with their icon and background.
+pub const TOAST_LEVELS: &[(&str, &str, egui::Color32)] = &[
+    ("info", "ℹ️", egui::Color32::DARK_GREEN),
+    ("warn", "⚠️", egui::Color32::from_rgb(150, 100, 0)),
+    ("error", "❌", egui::Color32::DARK_RED),
+];
+
+/// What a toast's button does, with its label.
+pub const TOAST_ACTIONS: &[(&str, &str)] = &[
+    ("log_console", "Details"),
+    ("jobs", "Show Jobs"),
+    ("external_edits", "Review"),
+    ("watch_folder", "Show Log"),
+];
+
+/// Toasts on screen at once; older ones are only in the history.
+const MAX_VISIBLE_TOASTS: usize = 4;
+
+/// Notifications kept in the history window.
+const MAX_HISTORY: usize = 200;
+
+#[derive(Clone)]
 pub struct Toast {
     pub message: String,
+    pub level: &'static str,
+    /// One of `TOAST_ACTIONS`.
+    pub action: Option<&'static str>,
     pub created_at: Instant,
+    pub time: chrono::DateTime<chrono::Local>,
     pub duration: Duration,
 }
 
 impl Toast {
     pub fn new(message: impl Into<String>) -> Self {
+        Self::with_level("info", message)
+    }
+
+    /// Warnings and errors stay up longer so there's time to read them.
+    pub fn with_level(level: &'static str, message: impl Into<String>) -> Self {
+        let seconds = match level {
+            "error" => 10,
+            "warn" => 6,
+            _ => 3,
+        };
         Self {
             message: message.into(),
+            level,
+            action: None,
             created_at: Instant::now(),
-            duration: Duration::from_secs(3),
+            time: chrono::Local::now(),
+            duration: Duration::from_secs(seconds),
         }
     }
 
+    pub fn with_action(mut self, action: &'static str) -> Self {
+        self.action = Some(action);
+        self
+    }
+
     pub fn is_expired(&self) -> bool {
         self.created_at.elapsed() > self.duration
     }
-}
\ No newline at end of file
+
+    fn icon(&self) -> &'static str {
+        TOAST_LEVELS.iter().find(|(level, ..)| *level == self.level).map_or("", |(_, icon, _)| *icon)
+    }
+
+    fn color(&self) -> egui::Color32 {
+        TOAST_LEVELS
+            .iter()
+            .find(|(level, ..)| *level == self.level)
+            .map_or(egui::Color32::DARK_GREEN, |(.., color)| *color)
+    }
+
+    /// The message with the level icon in front, except for plain info.
+    fn text(&self) -> String {
+        if self.level == "info" {
+            self.message.clone()
+        } else {
+            format!("{} {}", self.icon(), self.message)
+        }
+    }
+}
+
+impl RpaEditor {
+    pub(crate) fn push_toast(&mut self, toast: Toast) {
+        self.toast_history.push_back(toast.clone());
+        if self.toast_history.len() > MAX_HISTORY {
+            self.toast_history.pop_front();
+        }
+        self.toasts.push(toast);
+    }
+
+    /// A warning toast, also logged.
+    pub(crate) fn add_warning(&mut self, message: impl Into<String>) {
+        let toast = Toast::with_level("warn", message);
+        warn!("⚠️ {}", toast.message);
+        self.push_toast(toast);
+    }
+
+    /// An error toast, also logged; its button opens the log console.
+    pub(crate) fn add_error(&mut self, message: impl Into<String>) {
+        let toast = Toast::with_level("error", message).with_action("log_console");
+        error!("❌ {}", toast.message);
+        self.push_toast(toast);
+    }
+
+    fn run_toast_action(&mut self, action: &str) {
+        match action {
+            "log_console" => self.open_log_console(),
+            "jobs" => self.show_jobs_dialog = true,
+            "external_edits" => self.show_external_edits = true,
+            "watch_folder" => self.show_watch_folder_dialog = true,
+            _ => {}
+        }
+    }
+
+    /// The newest toasts under the menu bar. Clicking one dismisses it.
+    pub(crate) fn show_toasts(&mut self, ctx: &egui::Context) {
+        let hidden = self.toasts.len().saturating_sub(MAX_VISIBLE_TOASTS);
+        let mut dismissed = None;
+        let mut action = None;
+        let mut show_history = false;
+        egui::TopBottomPanel::top("toasts_panel").show(ctx, |ui| {
+            ui.horizontal_wrapped(|ui| {
+                for (index, toast) in self.toasts.iter().enumerate().skip(hidden) {
+                    let label = egui::Label::new(
+                        egui::RichText::new(toast.text())
+                            .background_color(toast.color())
+                            .color(egui::Color32::WHITE)
+                            .strong(),
+                    )
+                    .sense(egui::Sense::click());
+                    if ui.add(label).on_hover_text("Click to dismiss").clicked() {
+                        dismissed = Some(index);
+                    }
+                    if let Some(id) = toast.action
+                        && let Some((_, button)) = TOAST_ACTIONS.iter().find(|(action, _)| *action == id)
+                        && ui.small_button(*button).clicked()
+                    {
+                        action = Some(id);
+                        dismissed = Some(index);
+                    }
+                }
+                if hidden > 0 && ui.link(format!("+{} more", hidden)).clicked() {
+                    show_history = true;
+                }
+            });
+        });
+
+        if let Some(index) = dismissed {
+            self.toasts.remove(index);
+        }
+        if let Some(action) = action {
+            self.run_toast_action(action);
+        }
+        if show_history {
+            self.show_notification_history = true;
+        }
+    }
+
+    /// Every toast of the session, newest first, so a message that went by can be read again.
+    pub(crate) fn show_notification_history(&mut self, ui: &mut egui::Ui) {
+        ui.set_width(560.0);
+        let mut action = None;
+        egui::ScrollArea::vertical().max_height(380.0).show(ui, |ui| {
+            if self.toast_history.is_empty() {
+                ui.label("No notifications yet.");
+            }
+            for toast in self.toast_history.iter().rev() {
+                ui.horizontal(|ui| {
+                    ui.label(egui::RichText::new(toast.time.format("%H:%M:%S").to_string()).monospace().weak());
+                    let text = egui::RichText::new(format!("{} {}", toast.icon(), toast.message));
+                    let text = match toast.level {
+                        "error" => text.color(egui::Color32::LIGHT_RED),
+                        "warn" => text.color(egui::Color32::YELLOW),
+                        _ => text,
+                    };
+                    ui.add(egui::Label::new(text).wrap());
+                    if let Some(id) = toast.action
+                        && let Some((_, button)) = TOAST_ACTIONS.iter().find(|(action, _)| *action == id)
+                        && ui.small_button(*button).clicked()
+                    {
+                        action = Some(id);
+                    }
+                });
+            }
+        });
+        if let Some(action) = action {
+            self.run_toast_action(action);
+        }
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            if ui.button("🧹 Clear").clicked() {
+                self.toast_history.clear();
+            }
+            if ui.button("Close").clicked() {
+                self.show_notification_history = false;
+            }
+        });
+    }
+}
@@ -0,0 +1,147 @@
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use zip::write::SimpleFileOptions;
+use zip::{ZipArchive, ZipWriter};
+use tracing::warn;
+use crate::rpa::RpaEditor;
+
+const MANIFEST_NAME: &str = "manifest.json";
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TranslationManifest {
+    pub archive: String,
+    pub created: chrono::DateTime<chrono::Utc>,
+    pub entries: Vec<TranslationManifestEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TranslationManifestEntry {
+    pub path: String,
+    pub kind: String,
+    pub size: u64,
+}
+
+impl RpaEditor {
+    /// Collects `tl/` scripts, fonts, and the UI images those scripts refer to.
+    fn collect_translation_kit_files(&self) -> Vec<(String, &'static str)> {
+        let mut files = Vec::new();
+        let mut script_text = String::new();
+
+        for (filename, entry) in &self.indexes {
+            if entry.to_delete {
+                continue;
+            }
+            let file_type = self.get_file_type(filename);
+            if filename.starts_with("tl/") && file_type == "scripts" {
+                if let Ok(data) = self.load_file_data(filename) {
+                    script_text.push_str(&String::from_utf8_lossy(&data));
+                    script_text.push('\n');
+                }
+                files.push((filename.clone(), "script"));
+            } else if file_type == "fonts" {
+                files.push((filename.clone(), "font"));
+            }
+        }
+
+        for (filename, entry) in &self.indexes {
+            if entry.to_delete || self.get_file_type(filename) != "images" {
+                continue;
+            }
+            let stem = Path::new(filename)
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let referenced = script_text.contains(filename.as_str())
+                || (filename.starts_with("gui/") && !stem.is_empty() && script_text.contains(&stem));
+            if referenced {
+                files.push((filename.clone(), "image"));
+            }
+        }
+
+        files.sort();
+        files
+    }
+
+    pub(crate) fn export_translation_kit(&self, zip_path: &Path) -> anyhow::Result<usize> {
+        let files = self.collect_translation_kit_files();
+        if files.is_empty() {
+            return Err(anyhow::anyhow!("No tl/ scripts or fonts found in the archive"));
+        }
+
+        let mut zip = ZipWriter::new(File::create(zip_path)?);
+        let options = SimpleFileOptions::default();
+        let mut manifest = TranslationManifest {
+            archive: self.archive_path.clone().unwrap_or_default(),
+            created: chrono::Utc::now(),
+            entries: Vec::new(),
+        };
+
+        for (filename, kind) in &files {
+            let data = self.load_file_data(filename)?;
+            zip.start_file(filename.as_str(), options)?;
+            zip.write_all(&data)?;
+            manifest.entries.push(TranslationManifestEntry {
+                path: filename.clone(),
+                kind: kind.to_string(),
+                size: data.len() as u64,
+            });
+        }
+
+        zip.start_file(MANIFEST_NAME, options)?;
+        zip.write_all(&serde_json::to_vec_pretty(&manifest)?)?;
+        zip.finish()?;
+
+        Ok(files.len())
+    }
+
+    pub(crate) fn import_translation_kit(&mut self, zip_path: &Path) -> anyhow::Result<usize> {
+        self.ensure_writable()?;
+        let mut zip = ZipArchive::new(File::open(zip_path)?)?;
+
+        let known_paths: Option<HashSet<String>> = match zip.by_name(MANIFEST_NAME) {
+            Ok(mut file) => {
+                let mut raw = Vec::new();
+                file.read_to_end(&mut raw)?;
+                let manifest: TranslationManifest = serde_json::from_slice(&raw)?;
+                Some(manifest.entries.into_iter().map(|e| e.path).collect())
+            }
+            Err(_) => None,
+        };
+
+        let mut imported = 0;
+        for i in 0..zip.len() {
+            let mut file = zip.by_index(i)?;
+            if file.is_dir() || file.name() == MANIFEST_NAME {
+                continue;
+            }
+            let Some(archive_name) = file
+                .enclosed_name()
+                .map(|p| p.to_string_lossy().replace('\\', "/"))
+            else {
+                warn!("⚠️ Skipping unsafe path in translation kit: {}", file.name());
+                continue;
+            };
+
+            // New files are only accepted under tl/, everything else must come from the manifest.
+            let expected = known_paths
+                .as_ref()
+                .is_none_or(|paths| paths.contains(&archive_name));
+            if !expected && !archive_name.starts_with("tl/") {
+                warn!("⚠️ Skipping unexpected file in translation kit: {}", archive_name);
+                continue;
+            }
+
+            let mut data = Vec::new();
+            file.read_to_end(&mut data)?;
+            self.add_data(&archive_name, data)?;
+            self.set_provenance(&archive_name, format!("translation kit: {}", zip_path.display()));
+            imported += 1;
+        }
+
+        self.status_message = format!("Imported {} files from translation kit", imported);
+        Ok(imported)
+    }
+}
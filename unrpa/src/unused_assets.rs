@@ -0,0 +1,161 @@
+use std::collections::HashSet;
+use tracing::{info, warn};
+use crate::rpa::RpaEditor;
+use crate::rpyc;
+
+/// Words that end an image name in `show`/`scene`/`hide`/`image` statements.
+const IMAGE_NAME_STOP_WORDS: &[&str] = &["at", "as", "behind", "onlayer", "with", "zorder", "expression"];
+
+/// Names scripts can use to refer to assets.
+#[derive(Default)]
+struct References {
+    /// Contents of every string literal, plus each of its `/`-separated suffixes.
+    literals: HashSet<String>,
+    /// Image names from displaying statements, plus their leading words (the tag).
+    image_names: HashSet<String>,
+    /// Every identifier, which catches `audio.<name>` and bare image tags.
+    words: HashSet<String>,
+}
+
+impl References {
+    fn add_literal(&mut self, literal: &str) {
+        let path = literal.trim().to_lowercase().replace('\\', "/");
+        let path = path.strip_prefix("game/").unwrap_or(&path);
+        let mut rest = path;
+        loop {
+            self.literals.insert(rest.to_string());
+            match rest.split_once('/') {
+                Some((_, tail)) if !tail.is_empty() => rest = tail,
+                _ => break,
+            }
+        }
+    }
+
+    fn scan(&mut self, script: &str) {
+        for line in script.lines() {
+            let mut parts = line.split('"');
+            parts.next();
+            // Odd parts sit between quotes; escaped quotes are rare enough in asset names.
+            for literal in parts.step_by(2) {
+                self.add_literal(literal);
+            }
+            for quote in ['\'', '`'] {
+                for literal in line.split(quote).skip(1).step_by(2) {
+                    self.add_literal(literal);
+                }
+            }
+
+            let lower = line.to_lowercase();
+            for word in lower.split(|c: char| !(c.is_alphanumeric() || c == '_')) {
+                if !word.is_empty() {
+                    self.words.insert(word.to_string());
+                }
+            }
+
+            let mut words = lower.split_whitespace();
+            if matches!(words.next(), Some("show" | "scene" | "hide" | "image")) {
+                let name: Vec<&str> = words
+                    .map(|w| w.trim_end_matches(':'))
+                    .take_while(|w| !w.is_empty() && !w.contains('=') && !IMAGE_NAME_STOP_WORDS.contains(w))
+                    .collect();
+                for len in 1..=name.len() {
+                    self.image_names.insert(name[..len].join(" "));
+                }
+            }
+        }
+    }
+
+    fn uses(&self, filename: &str, file_type: &str) -> bool {
+        let path = filename.to_lowercase();
+        let path = path.strip_prefix("game/").unwrap_or(&path);
+        let mut rest = path;
+        loop {
+            if self.literals.contains(rest) {
+                return true;
+            }
+            match rest.split_once('/') {
+                Some((_, tail)) => rest = tail,
+                None => break,
+            }
+        }
+
+        // Ren'Py defines images and audio from their file names without an explicit path.
+        let stem = rest.rsplit_once('.').map_or(rest, |(stem, _)| stem);
+        let name = stem.split_whitespace().collect::<Vec<_>>().join(" ");
+        match file_type {
+            "images" => {
+                self.image_names.contains(&name)
+                    || self.image_names.contains(&name.replace('_', " "))
+                    || self.literals.contains(&name)
+            }
+            "audio" => self.words.contains(&name),
+            _ => false,
+        }
+    }
+}
+
+impl RpaEditor {
+    /// Text of every script entry, decompiling `.rpyc` files that have no `.rpy` beside them.
+    fn script_sources(&self) -> Vec<String> {
+        let mut sources = Vec::new();
+        for (filename, entry) in &self.indexes {
+            let lower = filename.to_lowercase();
+            if entry.to_delete || self.get_file_type(filename) != "scripts" {
+                continue;
+            }
+            let is_compiled = lower.ends_with(".rpyc");
+            if is_compiled && self.indexes.contains_key(&filename[..filename.len() - 1]) {
+                continue;
+            }
+            let Ok(data) = self.load_file_data(filename) else {
+                continue;
+            };
+
+            if is_compiled {
+                match rpyc::decompile(&data) {
+                    Ok(source) => sources.push(source),
+                    Err(e) => warn!("⚠️ Could not decompile {} for the asset scan: {}", filename, e),
+                }
+            } else {
+                sources.push(String::from_utf8_lossy(&data).to_string());
+            }
+        }
+        sources
+    }
+
+    /// Image, audio and video entries that no script refers to, sorted by name.
+    pub(crate) fn find_unused_assets(&self) -> Vec<String> {
+        let mut references = References::default();
+        for source in self.script_sources() {
+            references.scan(&source);
+        }
+
+        let mut unused: Vec<String> = self
+            .indexes
+            .iter()
+            .filter(|(_, entry)| !entry.to_delete)
+            .filter(|(filename, _)| {
+                let file_type = self.get_file_type(filename);
+                matches!(file_type, "images" | "audio" | "videos")
+                    && !references.uses(filename, file_type)
+            })
+            .map(|(filename, _)| filename.clone())
+            .collect();
+        unused.sort();
+
+        info!("🧹 {} of {} entries look unused", unused.len(), self.indexes.len());
+        unused
+    }
+
+    pub(crate) fn remove_unused_assets(&mut self) -> usize {
+        if self.blocked_by_read_only() {
+            return 0;
+        }
+        let unused = std::mem::take(&mut self.unused_assets);
+        for filename in &unused {
+            self.remove_file(filename);
+        }
+        self.status_message = format!("Marked {} unused assets for deletion", unused.len());
+        unused.len()
+    }
+}
@@ -0,0 +1,95 @@
+use std::collections::HashMap;
+use crate::rpa::{RpaEditor, RpaSegment};
+
+/// Returns a warning when the leading bytes don't match what the extension promises.
+pub fn check_magic(filename: &str, head: &[u8]) -> Option<&'static str> {
+    let lower = filename.to_lowercase();
+    let starts = |magic: &[u8]| head.starts_with(magic);
+
+    let ok = if lower.ends_with(".png") {
+        starts(b"\x89PNG\r\n\x1a\n")
+    } else if lower.ends_with(".jpg") || lower.ends_with(".jpeg") {
+        starts(&[0xFF, 0xD8, 0xFF])
+    } else if lower.ends_with(".webp") {
+        starts(b"RIFF") && head.get(8..12) == Some(b"WEBP")
+    } else if lower.ends_with(".gif") {
+        starts(b"GIF8")
+    } else if lower.ends_with(".ogg") {
+        starts(b"OggS")
+    } else if lower.ends_with(".wav") {
+        starts(b"RIFF") && head.get(8..12) == Some(b"WAVE")
+    } else if lower.ends_with(".mp3") {
+        starts(b"ID3") || (head.len() > 1 && head[0] == 0xFF && (head[1] & 0xE0) == 0xE0)
+    } else if lower.ends_with(".flac") {
+        starts(b"fLaC")
+    } else if lower.ends_with(".webm") || lower.ends_with(".mkv") {
+        starts(&[0x1A, 0x45, 0xDF, 0xA3])
+    } else if lower.ends_with(".mp4") || lower.ends_with(".mov") {
+        head.get(4..8) == Some(b"ftyp")
+    } else if lower.ends_with(".ttf") {
+        starts(&[0x00, 0x01, 0x00, 0x00]) || starts(b"true")
+    } else if lower.ends_with(".otf") {
+        starts(b"OTTO")
+    } else {
+        true
+    };
+
+    if ok {
+        None
+    } else {
+        Some("Content doesn't match the file extension")
+    }
+}
+
+/// Checks that an entry's ranges fit in the archive, without reading them.
+fn bounds_warning(file_len: u64, segments: &[RpaSegment]) -> Option<String> {
+    for segment in segments {
+        let stored_length = segment.length.saturating_sub(segment.prefix.len() as u64);
+        if segment.prefix.len() as u64 > segment.length {
+            return Some("Prefix is longer than the entry".to_string());
+        }
+        if segment.offset + stored_length > file_len {
+            return Some(format!(
+                "Data runs past the end of the archive ({} > {})",
+                segment.offset + stored_length,
+                file_len
+            ));
+        }
+    }
+    None
+}
+
+impl RpaEditor {
+    /// Bounds checks for every archived entry. Nothing is read, so this is cheap enough
+    /// to run on every open; Tools > Deep Verify reads and decodes the entries.
+    pub(crate) fn check_entry_bounds(&self) -> anyhow::Result<HashMap<String, String>> {
+        let Some(ref archive_path) = self.archive_path else {
+            return Ok(HashMap::new());
+        };
+        let file_len = std::fs::metadata(archive_path)?.len();
+        Ok(self
+            .indexes
+            .iter()
+            .filter(|(_, entry)| entry.in_archive())
+            .filter_map(|(filename, entry)| {
+                bounds_warning(file_len, &entry.segments).map(|warning| (filename.clone(), warning))
+            })
+            .collect())
+    }
+
+    /// Queues a background job that reads every entry, checks its magic bytes and
+    /// decodes images and compiled scripts. Failures end up in the entry warnings.
+    pub(crate) fn deep_verify(&mut self) {
+        let mut entries: Vec<String> = self
+            .indexes
+            .iter()
+            .filter(|(_, entry)| !entry.to_delete)
+            .map(|(name, _)| name.clone())
+            .collect();
+        entries.sort();
+        let count = self.push_job("verify", entries, None);
+        self.jobs_running = true;
+        self.show_jobs_dialog = true;
+        self.add_toast(format!("Verifying {} entries in the background", count));
+    }
+}
@@ -0,0 +1,133 @@
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tracing::info;
+use crate::rpa::RpaEditor;
+use crate::toast::Toast;
+
+/// Parses "12.5", "1:05" or "1:02:03.5" into seconds; empty means no bound.
+pub fn parse_timestamp(text: &str) -> anyhow::Result<Option<f64>> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(None);
+    }
+    let mut seconds = 0.0;
+    for part in text.split(':') {
+        let value: f64 = part
+            .parse()
+            .map_err(|_| anyhow::anyhow!("Invalid time: {}", text))?;
+        seconds = seconds * 60.0 + value;
+    }
+    Ok(Some(seconds))
+}
+
+/// Which frames a "frames" job exports.
+#[derive(Clone, Copy)]
+pub struct FrameRange {
+    /// Keep one frame in this many.
+    pub every: u32,
+    /// Seconds; `None` is the start or end of the video.
+    pub start: Option<f64>,
+    pub end: Option<f64>,
+}
+
+impl Default for FrameRange {
+    fn default() -> Self {
+        Self { every: 1, start: None, end: None }
+    }
+}
+
+/// Where the frames of video entry `name` go below `output`.
+pub fn frames_folder(output: &Path, name: &str) -> PathBuf {
+    let stem = Path::new(name)
+        .file_stem()
+        .map_or("video".to_string(), |stem| stem.to_string_lossy().to_string());
+    output.join(format!("{}_frames", stem))
+}
+
+fn is_frame_file(name: &str) -> bool {
+    name.starts_with("frame_") && name.ends_with(".png")
+}
+
+/// Decodes the frames in `range` of `video` (a path or an ffmpeg URL) into numbered PNGs
+/// in `folder`. Frames left by an earlier export are removed first, so the folder and
+/// the returned count only hold this run's. Setting `cancel` stops ffmpeg.
+pub fn export_frames(video: &str, folder: &Path, range: FrameRange, cancel: &AtomicBool) -> anyhow::Result<usize> {
+    std::fs::create_dir_all(folder)?;
+    for item in std::fs::read_dir(folder)?.flatten() {
+        if is_frame_file(&item.file_name().to_string_lossy()) {
+            std::fs::remove_file(item.path())?;
+        }
+    }
+
+    let mut command = Command::new("ffmpeg");
+    command.arg("-y").arg("-v").arg("error");
+    if let Some(start) = range.start {
+        command.arg("-ss").arg(format!("{:.3}", start));
+    }
+    if let Some(end) = range.end {
+        command.arg("-to").arg(format!("{:.3}", end));
+    }
+    command.arg("-i").arg(video);
+    if range.every > 1 {
+        command.arg("-vf").arg(format!("select=not(mod(n\\,{}))", range.every));
+    }
+    command
+        .arg("-vsync")
+        .arg("vfr")
+        .arg(folder.join("frame_%05d.png"))
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped());
+
+    let mut child = command
+        .spawn()
+        .map_err(|e| anyhow::anyhow!("ffmpeg is not available: {}", e))?;
+    while child.try_wait()?.is_none() {
+        if cancel.load(Ordering::Relaxed) {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(anyhow::anyhow!("Cancelled"));
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "ffmpeg failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    let count = std::fs::read_dir(folder)?
+        .flatten()
+        .filter(|item| is_frame_file(&item.file_name().to_string_lossy()))
+        .count();
+    Ok(count)
+}
+
+impl RpaEditor {
+    /// Queues a job exporting frames of a video entry into `<parent>/<video name>_frames/`.
+    pub(crate) fn export_video_frames(&mut self, filename: &str, parent: &Path) -> anyhow::Result<()> {
+        let start = parse_timestamp(&self.frame_export_start)?;
+        let end = parse_timestamp(&self.frame_export_end)?;
+        if let (Some(start), Some(end)) = (start, end)
+            && end <= start
+        {
+            return Err(anyhow::anyhow!("The end time must be after the start time"));
+        }
+
+        self.push_job("frames", vec![filename.to_string()], Some(parent.to_path_buf()));
+        if let Some(job) = self.jobs.last_mut() {
+            job.frames = FrameRange {
+                every: self.frame_export_every.max(1),
+                start,
+                end,
+            };
+        }
+        self.jobs_running = true;
+        info!("🖼️ Exporting frames of {} to {}", filename, frames_folder(parent, filename).display());
+        self.push_toast(Toast::new(format!("Exporting frames of {} in the background", filename)).with_action("jobs"));
+        Ok(())
+    }
+}
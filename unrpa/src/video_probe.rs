@@ -0,0 +1,173 @@
+use std::path::Path;
+use std::process::Command;
+use crate::rpa::RpaEditor;
+
+/// Width of the thumbnail shown in the preview header.
+const THUMBNAIL_WIDTH: u32 = 240;
+
+/// Stream details reported by `ffprobe` for a video entry.
+#[derive(Clone)]
+pub struct VideoInfo {
+    pub width: u64,
+    pub height: u64,
+    pub duration: Option<f64>,
+    pub codec: String,
+}
+
+impl VideoInfo {
+    /// One-line form for the file list, e.g. "1280×720 · 0:42 · vp9".
+    pub fn summary(&self) -> String {
+        let mut parts = vec![format!("{}×{}", self.width, self.height)];
+        if let Some(duration) = self.duration {
+            let seconds = duration.round() as u64;
+            parts.push(format!("{}:{:02}", seconds / 60, seconds % 60));
+        }
+        parts.push(self.codec.clone());
+        parts.join(" · ")
+    }
+}
+
+pub fn probe_video(path: &Path) -> anyhow::Result<VideoInfo> {
+    let output = Command::new("ffprobe")
+        .arg("-v")
+        .arg("error")
+        .arg("-select_streams")
+        .arg("v:0")
+        .arg("-show_entries")
+        .arg("stream=codec_name,width,height,duration:format=duration")
+        .arg("-of")
+        .arg("json")
+        .arg(path)
+        .output()
+        .map_err(|e| anyhow::anyhow!("ffprobe is not available: {}", e))?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "ffprobe failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    let stream = json["streams"]
+        .get(0)
+        .ok_or_else(|| anyhow::anyhow!("No video stream"))?;
+    // WebM streams usually carry no duration of their own, only the container does.
+    let duration = [&stream["duration"], &json["format"]["duration"]]
+        .iter()
+        .find_map(|value| value.as_str().and_then(|s| s.parse::<f64>().ok()));
+
+    Ok(VideoInfo {
+        width: stream["width"].as_u64().unwrap_or(0),
+        height: stream["height"].as_u64().unwrap_or(0),
+        duration,
+        codec: stream["codec_name"].as_str().unwrap_or("unknown").to_string(),
+    })
+}
+
+/// Decodes the first frame, scaled down to `THUMBNAIL_WIDTH`.
+pub fn extract_thumbnail(path: &Path) -> anyhow::Result<egui::ColorImage> {
+    let output = Command::new("ffmpeg")
+        .arg("-v")
+        .arg("error")
+        .arg("-i")
+        .arg(path)
+        .arg("-frames:v")
+        .arg("1")
+        .arg("-vf")
+        .arg(format!("scale={}:-2", THUMBNAIL_WIDTH))
+        .arg("-f")
+        .arg("image2pipe")
+        .arg("-vcodec")
+        .arg("png")
+        .arg("-")
+        .output()
+        .map_err(|e| anyhow::anyhow!("ffmpeg is not available: {}", e))?;
+    if !output.status.success() || output.stdout.is_empty() {
+        return Err(anyhow::anyhow!(
+            "ffmpeg failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    let rgba = image::load_from_memory(&output.stdout)?.to_rgba8();
+    let size = [rgba.width() as usize, rgba.height() as usize];
+    Ok(egui::ColorImage::from_rgba_unmultiplied(size, &rgba))
+}
+
+/// ffmpeg URL for `length` bytes at `offset` in `path`, read in place by its `subfile` protocol.
+/// ffmpeg stops reading options at the empty `,,` pair, so commas in `path` are left alone;
+/// the `file:` prefix keeps a colon in it (a drive letter, `a:b.rpa`) from reading as a protocol.
+pub fn subfile_url(path: &str, offset: u64, length: u64) -> String {
+    format!("subfile,,start,{},end,{},,:file:{}", offset, offset + length, path)
+}
+
+impl RpaEditor {
+    /// What the video player opens for an entry. Entries stored in one piece are read
+    /// straight out of the archive, so playback starts without copying them; others are
+    /// written to the preview folder first.
+    pub(crate) fn video_source(&self, filename: &str) -> anyhow::Result<String> {
+        if let Some(archive_path) = &self.archive_path
+            && let Some(entry) = self.indexes.get(filename)
+            && entry.in_archive()
+            && let [segment] = entry.segments.as_slice()
+            && segment.prefix.is_empty()
+            && segment.length > 0
+        {
+            return Ok(subfile_url(archive_path, segment.offset, segment.length));
+        }
+        Ok(self.stream_entry_to_temp(filename)?.to_string_lossy().to_string())
+    }
+
+    /// Starts a job that probes a video entry and grabs its first frame. The details are
+    /// cached per entry; the thumbnail only for the selected video.
+    pub(crate) fn probe_video_entry(&mut self, filename: &str) {
+        let has_thumbnail = self.video_thumbnail.as_ref().is_some_and(|(name, _)| name == filename);
+        let probing = self.jobs.iter().any(|job| {
+            job.kind == "probe" && matches!(job.status.as_str(), "queued" | "running") && job.entries == [filename]
+        });
+        if probing || (has_thumbnail && self.video_info.contains_key(filename)) {
+            return;
+        }
+        let output = self.archive_temp_dir("preview");
+        self.push_job("probe", vec![filename.to_string()], Some(output));
+        self.start_job(self.jobs.len() - 1);
+    }
+
+    /// Thumbnail and stream details above the preview of the selected video.
+    pub(crate) fn show_video_header(&self, ui: &mut egui::Ui, filename: &str) {
+        let Some(info) = self.video_info.get(filename) else {
+            return;
+        };
+        ui.horizontal(|ui| {
+            if let Some((name, texture)) = &self.video_thumbnail
+                && name == filename
+            {
+                ui.add(egui::Image::new(texture).max_width(THUMBNAIL_WIDTH as f32));
+            }
+            ui.vertical(|ui| {
+                ui.label(format!("📐 {}×{}", info.width, info.height));
+                if let Some(duration) = info.duration {
+                    ui.label(format!("⏱️ {:.1}s", duration));
+                }
+                ui.label(format!("🎞️ Codec: {}", info.codec));
+            });
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn subfile_url_format() {
+        assert_eq!(
+            subfile_url("/games/demo/archive.rpa", 1024, 4096),
+            "subfile,,start,1024,end,5120,,:file:/games/demo/archive.rpa"
+        );
+        assert_eq!(
+            subfile_url("C:\\Games\\a, b\\archive.rpa", 0, 10),
+            "subfile,,start,0,end,10,,:file:C:\\Games\\a, b\\archive.rpa"
+        );
+    }
+}
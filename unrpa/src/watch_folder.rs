@@ -0,0 +1,269 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver};
+use std::time::{Duration, Instant};
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use tracing::{info, warn};
+use crate::rpa::RpaEditor;
+use crate::toast::Toast;
+
+/// Quiet time after the last change to a file before it is read, so a save that
+/// writes in several steps is picked up once, complete.
+const SETTLE_TIME: Duration = Duration::from_millis(300);
+
+/// Lines kept in the watch log.
+const LOG_LENGTH: usize = 50;
+
+/// A folder whose files replace the matching entries as soon as they change on disk.
+pub struct WatchFolder {
+    pub folder: PathBuf,
+    /// Dropping the watcher stops the notifications.
+    _watcher: RecommendedWatcher,
+    events: Receiver<notify::Result<notify::Event>>,
+    /// Changed files waiting for `SETTLE_TIME`, with their last change.
+    pending: HashMap<PathBuf, Instant>,
+    /// Most recent last.
+    pub log: Vec<String>,
+}
+
+impl WatchFolder {
+    pub fn start(folder: &Path) -> anyhow::Result<Self> {
+        let (sender, events) = channel();
+        let mut watcher = notify::recommended_watcher(move |event| {
+            let _ = sender.send(event);
+        })?;
+        watcher.watch(folder, RecursiveMode::Recursive)?;
+        Ok(Self {
+            folder: folder.to_path_buf(),
+            _watcher: watcher,
+            events,
+            pending: HashMap::new(),
+            log: Vec::new(),
+        })
+    }
+
+    fn push_log(&mut self, line: String) {
+        info!("👁 {}", line);
+        self.log.push(format!("{} {}", chrono::Local::now().format("%H:%M:%S"), line));
+        if self.log.len() > LOG_LENGTH {
+            self.log.remove(0);
+        }
+    }
+
+    /// Files whose changes have settled.
+    fn take_settled(&mut self) -> Vec<PathBuf> {
+        for event in self.events.try_iter() {
+            match event {
+                Ok(event) if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) => {
+                    for path in event.paths {
+                        self.pending.insert(path, Instant::now());
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => warn!("⚠️ Watch error: {}", e),
+            }
+        }
+        let settled: Vec<PathBuf> = self
+            .pending
+            .iter()
+            .filter(|(_, changed)| changed.elapsed() >= SETTLE_TIME)
+            .map(|(path, _)| path.clone())
+            .collect();
+        for path in &settled {
+            self.pending.remove(path);
+        }
+        settled
+    }
+}
+
+impl RpaEditor {
+    pub(crate) fn start_watch_folder(&mut self, folder: &Path) -> anyhow::Result<()> {
+        self.ensure_writable()?;
+        let mut watch = WatchFolder::start(folder)?;
+        watch.push_log(format!("Watching {}", folder.display()));
+        self.watch_folder = Some(watch);
+        self.status_message = format!("Watching {} for changes", folder.display());
+        Ok(())
+    }
+
+    /// The entry a file below the watched folder stands for: the one at its relative
+    /// path, or else the only entry sharing its file name.
+    fn watched_entry(&self, relative: &str) -> Option<String> {
+        if self.indexes.get(relative).is_some_and(|entry| !entry.to_delete) {
+            return Some(relative.to_string());
+        }
+        let basename = relative.rsplit('/').next().unwrap_or(relative);
+        let mut candidates = self.indexes.iter().filter(|(name, entry)| {
+            !entry.to_delete && name.rsplit('/').next().unwrap_or(name) == basename
+        });
+        match (candidates.next(), candidates.next()) {
+            (Some((name, _)), None) => Some(name.clone()),
+            _ => None,
+        }
+    }
+
+    /// Replaces entries whose file in the watched folder changed, then saves when
+    /// auto-save is on. Called every frame.
+    pub(crate) fn poll_watch_folder(&mut self) {
+        let Some(watch) = self.watch_folder.as_mut() else {
+            return;
+        };
+        let folder = watch.folder.clone();
+        let settled = watch.take_settled();
+        if settled.is_empty() {
+            return;
+        }
+
+        let log = self.apply_watched_files(&folder, settled);
+        if let Some(watch) = self.watch_folder.as_mut() {
+            for line in log {
+                watch.push_log(line);
+            }
+        }
+    }
+
+    /// Replaces the entries `changed` files below `folder` stand for and saves when
+    /// auto-save is on. Returns the lines for the watch log.
+    fn apply_watched_files(&mut self, folder: &Path, changed: Vec<PathBuf>) -> Vec<String> {
+        let mut log = Vec::new();
+        let mut replaced = 0;
+        for path in changed.into_iter().filter(|path| path.is_file()) {
+            let Ok(relative) = path.strip_prefix(folder) else {
+                continue;
+            };
+            let relative = relative.to_string_lossy().replace('\\', "/");
+            let Some(entry) = self.watched_entry(&relative) else {
+                log.push(format!("{}: no matching entry", relative));
+                continue;
+            };
+            // Editors often touch a file without changing it.
+            if let (Ok(new_data), Ok(old_data)) = (std::fs::read(&path), self.load_file_data(&entry))
+                && new_data == old_data
+            {
+                continue;
+            }
+            match self.replace_entry(&entry, &path.to_string_lossy(), false) {
+                Ok(()) => {
+                    self.set_provenance(&entry, format!("watch folder: {}", path.display()));
+                    if self.selected_file.as_deref() == Some(entry.as_str()) {
+                        self.preview_file(&entry);
+                    }
+                    log.push(format!("Replaced {}", entry));
+                    replaced += 1;
+                }
+                Err(e) => log.push(format!("{}: {}", relative, e)),
+            }
+        }
+
+        if replaced > 0 {
+            self.push_toast(Toast::new(format!("Live reload: replaced {} entries", replaced)).with_action("watch_folder"));
+            if self.watch_auto_save
+                && let Some(archive_path) = self.archive_path.clone()
+                && self.save_with_fallback(&archive_path, self.header_format, self.key)
+            {
+                log.push(format!("Saved {}", archive_path));
+            }
+        }
+        log
+    }
+
+    pub(crate) fn show_watch_folder_dialog(&mut self, ui: &mut egui::Ui) {
+        ui.set_width(500.0);
+        ui.label("Files saved in the folder replace the entry at the same path, or the only entry with their name.");
+        ui.separator();
+
+        match &self.watch_folder {
+            Some(watch) => {
+                ui.horizontal(|ui| {
+                    ui.label("👁 Watching");
+                    ui.monospace(watch.folder.display().to_string());
+                });
+            }
+            None => {
+                ui.horizontal(|ui| {
+                    ui.label("📂 Folder:");
+                    ui.text_edit_singleline(&mut self.watch_folder_path);
+                    if ui.button("Browse...").clicked()
+                        && let Some(folder) = rfd::FileDialog::new().pick_folder()
+                    {
+                        self.watch_folder_path = folder.to_string_lossy().to_string();
+                    }
+                });
+            }
+        }
+        ui.checkbox(&mut self.watch_auto_save, "Save the archive after each reload")
+            .on_hover_text("Lets a running game pick up the change on its next reload. Skips the change review.");
+
+        if let Some(watch) = &self.watch_folder {
+            ui.separator();
+            egui::ScrollArea::vertical()
+                .max_height(200.0)
+                .stick_to_bottom(true)
+                .show(ui, |ui| {
+                    for line in &watch.log {
+                        ui.label(egui::RichText::new(line).monospace().small());
+                    }
+                });
+        }
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            if self.watch_folder.is_some() {
+                if ui.button("⏹ Stop Watching").clicked() {
+                    self.watch_folder = None;
+                    self.status_message = "Stopped watching".to_string();
+                }
+            } else if ui
+                .add_enabled(!self.watch_folder_path.is_empty(), egui::Button::new("▶ Start Watching"))
+                .clicked()
+            {
+                let folder = PathBuf::from(&self.watch_folder_path);
+                if let Err(e) = self.start_watch_folder(&folder) {
+                    self.add_error(format!("Can't watch {}: {}", folder.display(), e));
+                }
+            }
+            if ui.button("Close").clicked() {
+                self.show_watch_folder_dialog = false;
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two auto-save rounds in a row: the second one must still find the entries the
+    /// first one moved.
+    #[test]
+    fn auto_save_twice() {
+        let dir = crate::test_dir::TestDir::new("watch");
+        let folder = dir.path().join("watched");
+        std::fs::create_dir_all(&folder).unwrap();
+        let path = dir.file("game.rpa");
+
+        let mut editor = RpaEditor {
+            auto_backup: false,
+            archive_bak_count: 0,
+            ..RpaEditor::default()
+        };
+        editor.add_data("images/a.txt", b"first".to_vec()).unwrap();
+        editor.add_data("images/b.txt", b"left alone".to_vec()).unwrap();
+        editor.save_rpa(&path).unwrap();
+        editor.load_rpa(&path).unwrap();
+        editor.watch_auto_save = true;
+
+        let changed = folder.join("a.txt");
+        for content in ["a longer second version", "third"] {
+            std::fs::write(&changed, content).unwrap();
+            let log = editor.apply_watched_files(&folder, vec![changed.clone()]);
+            assert!(log.iter().any(|line| line.starts_with("Saved")), "{:?}", log);
+            assert!(!editor.modified);
+        }
+
+        let mut reopened = RpaEditor::default();
+        reopened.load_rpa(&path).unwrap();
+        assert_eq!(reopened.load_file_data("images/a.txt").unwrap(), b"third");
+        assert_eq!(reopened.load_file_data("images/b.txt").unwrap(), b"left alone");
+    }
+}
@@ -0,0 +1,310 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use zip::write::SimpleFileOptions;
+use zip::{ZipArchive, ZipWriter};
+use tracing::warn;
+use crate::rpa::{MergedSource, RpaEditor};
+
+/// Command line switch that replays a workspace after opening the archive.
+pub const WORKSPACE_FLAG: &str = "--workspace";
+
+const WORKSPACE_NAME: &str = "workspace.json";
+const PENDING_DIR: &str = "pending/";
+
+/// Folder of the pending entries of background tab `tab`, or of the active archive.
+fn pending_dir(tab: Option<usize>) -> String {
+    match tab {
+        Some(tab) => format!("tabs/{}/{}", tab, PENDING_DIR),
+        None => PENDING_DIR.to_string(),
+    }
+}
+
+/// Fails unless every pending entry `manifest` lists is stored below `dir`.
+fn check_pending(zip: &ZipArchive<File>, manifest: &WorkspaceManifest, dir: &str) -> anyhow::Result<()> {
+    match manifest
+        .pending
+        .iter()
+        .find(|filename| zip.index_for_name(&format!("{}{}", dir, filename)).is_none())
+    {
+        Some(filename) => Err(anyhow::anyhow!("Workspace is missing the data of {}", filename)),
+        None => Ok(()),
+    }
+}
+
+/// Working state of an editing session, stored next to the pending entry data.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WorkspaceManifest {
+    pub archive: String,
+    pub archive_size: u64,
+    pub created: chrono::DateTime<chrono::Utc>,
+    pub pending: Vec<String>,
+    pub deleted: Vec<String>,
+    #[serde(default)]
+    pub selected_file: Option<String>,
+    #[serde(default)]
+    pub filter_type: String,
+    #[serde(default)]
+    pub sort_by: String,
+    #[serde(default)]
+    pub file_list_view: String,
+    #[serde(default)]
+    pub preview_associations: HashMap<String, String>,
+    #[serde(default)]
+    pub provenance: HashMap<String, String>,
+    #[serde(default)]
+    pub tags: HashMap<String, Vec<String>>,
+    #[serde(default)]
+    pub notes: HashMap<String, String>,
+    #[serde(default)]
+    pub bookmarks: Vec<String>,
+    /// The other open archives, their pending entries stored under `tabs/<n>/`.
+    #[serde(default)]
+    pub tabs: Vec<WorkspaceManifest>,
+}
+
+/// Bytes of a pending entry, as `write_workspace` stores them.
+enum PendingContent {
+    Memory(Vec<u8>),
+    Merged(MergedSource),
+}
+
+/// The manifest and pending entries of an archive, ready to be written.
+pub struct WorkspaceSnapshot {
+    manifest: WorkspaceManifest,
+    /// One per `manifest.pending` entry, in the same order.
+    contents: Vec<PendingContent>,
+    /// The contents of each `manifest.tabs` archive, likewise.
+    tab_contents: Vec<Vec<PendingContent>>,
+}
+
+fn write_pending(
+    zip: &mut ZipWriter<File>,
+    manifest: &WorkspaceManifest,
+    contents: &[PendingContent],
+    dir: &str,
+) -> anyhow::Result<usize> {
+    let options = SimpleFileOptions::default();
+    for (filename, content) in manifest.pending.iter().zip(contents) {
+        zip.start_file(format!("{}{}", dir, filename), options)?;
+        match content {
+            PendingContent::Memory(data) => zip.write_all(data)?,
+            PendingContent::Merged(source) => {
+                std::io::copy(&mut source.open()?, zip)?;
+            }
+        }
+    }
+    Ok(manifest.pending.len() + manifest.deleted.len())
+}
+
+impl WorkspaceSnapshot {
+    /// Writes the `.rpaws` (zip) file and returns the number of changes in it.
+    pub fn write(&self, workspace_path: &Path) -> anyhow::Result<usize> {
+        let mut zip = ZipWriter::new(File::create(workspace_path)?);
+        let mut count = write_pending(&mut zip, &self.manifest, &self.contents, PENDING_DIR)?;
+        for (index, (tab, contents)) in self.manifest.tabs.iter().zip(&self.tab_contents).enumerate() {
+            count += write_pending(&mut zip, tab, contents, &pending_dir(Some(index)))?;
+        }
+
+        zip.start_file(WORKSPACE_NAME, SimpleFileOptions::default())?;
+        zip.write_all(&serde_json::to_vec_pretty(&self.manifest)?)?;
+        zip.finish()?;
+        Ok(count)
+    }
+}
+
+impl RpaEditor {
+    /// Writes every open archive with its pending edits, tags, notes and bookmarks to a
+    /// `.rpaws` (zip) file. The active archive comes first; the other tabs follow it.
+    pub(crate) fn export_workspace(&mut self, workspace_path: &Path) -> anyhow::Result<usize> {
+        if self.archive_path.is_none() {
+            return Err(anyhow::anyhow!("No archive loaded"));
+        }
+        let mut snapshot = self.workspace_snapshot();
+        for index in 0..self.archive_tabs.len() {
+            if let Some(tab) = self.with_background_tab(index, |editor| editor.workspace_snapshot()) {
+                snapshot.manifest.tabs.push(tab.manifest);
+                snapshot.tab_contents.push(tab.contents);
+            }
+        }
+        snapshot.write(workspace_path)
+    }
+
+    /// `export_workspace` that also takes an untitled archive, stored with an empty
+    /// `archive`; crash recovery needs those too.
+    pub(crate) fn write_workspace(&self, workspace_path: &Path) -> anyhow::Result<usize> {
+        self.workspace_snapshot().write(workspace_path)
+    }
+
+    /// What `write_workspace` stores for the active archive, detached from the editor so
+    /// it can be written on another thread.
+    pub(crate) fn workspace_snapshot(&self) -> WorkspaceSnapshot {
+        let archive = self.archive_path.clone().unwrap_or_default();
+        let mut snapshot = WorkspaceSnapshot {
+            manifest: WorkspaceManifest {
+                archive_size: std::fs::metadata(&archive).map(|m| m.len()).unwrap_or(0),
+                archive,
+                created: chrono::Utc::now(),
+                pending: Vec::new(),
+                deleted: Vec::new(),
+                selected_file: self.selected_file.clone(),
+                filter_type: self.filter_type.clone(),
+                sort_by: self.sort_by.clone(),
+                file_list_view: self.file_list_view.clone(),
+                preview_associations: self.preview_associations.clone(),
+                provenance: HashMap::new(),
+                tags: self.entry_tags.clone(),
+                notes: self.entry_notes.clone(),
+                bookmarks: self.bookmarks.iter().cloned().collect(),
+                tabs: Vec::new(),
+            },
+            contents: Vec::new(),
+            tab_contents: Vec::new(),
+        };
+
+        let mut names: Vec<&String> = self.indexes.keys().collect();
+        names.sort();
+        for filename in names {
+            let entry = &self.indexes[filename];
+            if entry.to_delete {
+                if self.loaded_names.contains(filename) {
+                    snapshot.manifest.deleted.push(filename.clone());
+                }
+                continue;
+            }
+            let content = match (&entry.data, &entry.merged) {
+                (Some(data), _) => PendingContent::Memory(data.clone()),
+                (None, Some(merged)) => PendingContent::Merged(merged.clone()),
+                (None, None) => continue,
+            };
+            snapshot.manifest.pending.push(filename.clone());
+            snapshot.manifest.provenance.insert(filename.clone(), entry.provenance.clone());
+            snapshot.contents.push(content);
+        }
+        snapshot
+    }
+
+    /// Reads the manifest so the caller can locate the archive before importing.
+    pub(crate) fn read_workspace_manifest(workspace_path: &Path) -> anyhow::Result<WorkspaceManifest> {
+        let mut zip = ZipArchive::new(File::open(workspace_path)?)?;
+        let mut file = zip
+            .by_name(WORKSPACE_NAME)
+            .map_err(|_| anyhow::anyhow!("Not a workspace file: missing {}", WORKSPACE_NAME))?;
+        let mut raw = Vec::new();
+        file.read_to_end(&mut raw)?;
+        Ok(serde_json::from_slice(&raw)?)
+    }
+
+    /// Opens `archive_path` and replays the pending edits stored in the workspace, then
+    /// reopens the other archives it holds in new tabs. The workspace is checked before
+    /// anything is loaded.
+    pub(crate) fn import_workspace(&mut self, workspace_path: &Path, archive_path: &str) -> anyhow::Result<usize> {
+        self.ensure_writable()?;
+        let mut manifest = Self::read_workspace_manifest(workspace_path)?;
+        let mut zip = ZipArchive::new(File::open(workspace_path)?)?;
+        check_pending(&zip, &manifest, PENDING_DIR)?;
+        for (index, tab) in manifest.tabs.iter().enumerate() {
+            check_pending(&zip, tab, &pending_dir(Some(index)))?;
+        }
+
+        let tabs = std::mem::take(&mut manifest.tabs);
+        let mut applied = self.apply_workspace(&mut zip, manifest, archive_path, PENDING_DIR, workspace_path)?;
+        let active = self.active_tab;
+        for (index, tab) in tabs.into_iter().enumerate() {
+            let archive = tab.archive.clone();
+            if !archive.is_empty() && (!Path::new(&archive).is_file() || self.is_archive_open(&archive)) {
+                warn!("⚠️ Skipping workspace archive {}: missing or already open", archive);
+                continue;
+            }
+            let mut result = Ok(0);
+            self.open_in_new_tab_with(&archive, |editor, archive| {
+                result = editor.apply_workspace(&mut zip, tab, archive, &pending_dir(Some(index)), workspace_path);
+            });
+            match result {
+                Ok(count) => applied += count,
+                Err(e) => warn!("⚠️ Could not restore {} from the workspace: {}", archive, e),
+            }
+        }
+        self.switch_tab(active);
+
+        self.status_message = format!("Imported workspace with {} pending changes", applied);
+        Ok(applied)
+    }
+
+    /// Loads `archive_path` and applies one archive's part of a workspace.
+    fn apply_workspace(
+        &mut self,
+        zip: &mut ZipArchive<File>,
+        manifest: WorkspaceManifest,
+        archive_path: &str,
+        dir: &str,
+        workspace_path: &Path,
+    ) -> anyhow::Result<usize> {
+        if archive_path.is_empty() {
+            // An untitled archive: everything in it is in the workspace.
+            self.unload_rpa()?;
+        } else {
+            let archive_size = std::fs::metadata(archive_path)?.len();
+            if manifest.archive_size != 0 && manifest.archive_size != archive_size {
+                warn!(
+                    "⚠️ Archive size differs from the exported workspace ({} vs {})",
+                    archive_size, manifest.archive_size
+                );
+            }
+            self.load_rpa(archive_path)?;
+        }
+
+        let auto_backup = self.auto_backup;
+        self.auto_backup = false;
+        let mut applied = 0;
+        for filename in &manifest.pending {
+            let mut file = zip.by_name(&format!("{}{}", dir, filename))?;
+            let mut data = Vec::new();
+            file.read_to_end(&mut data)?;
+            if let Err(e) = self.add_data(filename, data) {
+                self.auto_backup = auto_backup;
+                return Err(e);
+            }
+            let source = manifest
+                .provenance
+                .get(filename)
+                .cloned()
+                .unwrap_or_else(|| format!("workspace: {}", workspace_path.display()));
+            self.set_provenance(filename, source);
+            applied += 1;
+        }
+        self.auto_backup = auto_backup;
+
+        for filename in &manifest.deleted {
+            if let Some(entry) = self.indexes.get_mut(filename) {
+                entry.to_delete = true;
+                self.set_modified(true);
+                applied += 1;
+            } else {
+                warn!("⚠️ Workspace deletes missing entry: {}", filename);
+            }
+        }
+
+        if !manifest.filter_type.is_empty() {
+            self.filter_type = manifest.filter_type;
+        }
+        if !manifest.sort_by.is_empty() {
+            self.sort_by = manifest.sort_by;
+        }
+        if !manifest.file_list_view.is_empty() {
+            self.file_list_view = manifest.file_list_view;
+        }
+        self.preview_associations.extend(manifest.preview_associations);
+        self.entry_tags.extend(manifest.tags);
+        self.entry_notes.extend(manifest.notes);
+        self.bookmarks.extend(manifest.bookmarks);
+        if let Some(selected) = manifest.selected_file
+            && self.indexes.contains_key(&selected)
+        {
+            self.file_to_preview = Some(selected);
+        }
+        Ok(applied)
+    }
+}
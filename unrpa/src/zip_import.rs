@@ -0,0 +1,45 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use zip::ZipArchive;
+use tracing::{info, warn};
+use crate::rpa::RpaEditor;
+
+/// Archive names are relative to `game/`, which mod zips usually include.
+fn archive_name(zip_path: &str) -> &str {
+    zip_path.strip_prefix("game/").unwrap_or(zip_path)
+}
+
+impl RpaEditor {
+    /// Adds every file of a zip as an entry, replacing entries with the same name.
+    pub(crate) fn import_zip(&mut self, zip_path: &Path) -> anyhow::Result<usize> {
+        self.ensure_writable()?;
+        let mut zip = ZipArchive::new(File::open(zip_path)?)?;
+
+        let mut imported = 0;
+        for i in 0..zip.len() {
+            let mut file = zip.by_index(i)?;
+            if file.is_dir() {
+                continue;
+            }
+            let Some(path) = file
+                .enclosed_name()
+                .map(|p| p.to_string_lossy().replace('\\', "/"))
+            else {
+                warn!("⚠️ Skipping unsafe path in zip: {}", file.name());
+                continue;
+            };
+            let name = archive_name(&path).to_string();
+
+            let mut data = Vec::new();
+            file.read_to_end(&mut data)?;
+            self.add_data(&name, data)?;
+            self.set_provenance(&name, format!("zip: {}", zip_path.display()));
+            imported += 1;
+        }
+
+        self.status_message = format!("Imported {} files from {}", imported, zip_path.display());
+        info!("📥 {}", self.status_message);
+        Ok(imported)
+    }
+}